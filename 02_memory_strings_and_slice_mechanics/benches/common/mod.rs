@@ -0,0 +1,13 @@
+// Helpers shared by the benchmarks (benches/common/ is not a benchmark of its own).
+
+// An xorshift64 generator with a fixed seed: every run of a benchmark gets the same inputs, so
+// timings from different runs (or before and after a change) compare.
+pub fn xorshift() -> impl FnMut() -> u64 {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    }
+}
@@ -0,0 +1,138 @@
+// The four ways concatenate_strings in main.rs joins strings, next to StrBuilder, join_with and
+// SmallString from src/concat.rs.
+// Run with: cargo bench --bench concat
+use std::fmt::Write;
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use memory_safety::concat::{self, SmallString, StrBuilder};
+
+mod common;
+
+// The chapter's example: "Hello" and "world" with a space between
+fn bench_two_words(c: &mut Criterion) {
+    let (hello, world) = (black_box("Hello"), black_box("world"));
+    let mut group = c.benchmark_group("two words");
+    group.bench_function("format!", |b| b.iter(|| format!("{hello} {world}")));
+    // `str1 + " " + &str2` needs an owned String on the left, as str1 is in main.rs
+    group.bench_function("+ operator", |b| {
+        b.iter(|| String::from(hello) + " " + world)
+    });
+    // Joined by the compiler; all that is left at run time is copying the result
+    group.bench_function("concat! + to_string", |b| {
+        b.iter(|| black_box(concat!("Hello", " ", "world")).to_string())
+    });
+    group.bench_function("[..].concat()", |b| b.iter(|| [hello, " ", world].concat()));
+    group.bench_function("StrBuilder", |b| {
+        b.iter(|| {
+            StrBuilder::new()
+                .push(hello)
+                .push_char(' ')
+                .push(world)
+                .build()
+        })
+    });
+    group.finish();
+}
+
+// Forty words of 3 to 10 bytes: long enough that the + operator has to reallocate
+fn words() -> Vec<String> {
+    let mut next = common::xorshift();
+    (0..40)
+        .map(|_| {
+            let seed = next();
+            let len = 3 + (seed % 8) as usize;
+            (0..len)
+                .map(|i| (b'a' + ((seed >> i) % 26) as u8) as char)
+                .collect()
+        })
+        .collect()
+}
+
+fn bench_many_words(c: &mut Criterion) {
+    let words = words();
+    let parts: Vec<&str> = words.iter().map(String::as_str).collect();
+    let parts = black_box(parts.as_slice());
+    let mut group = c.benchmark_group("40 words");
+    group.bench_function("format! per word", |b| {
+        b.iter(|| {
+            let mut s = String::new();
+            for part in parts {
+                s = format!("{s}{part}");
+            }
+            s
+        })
+    });
+    group.bench_function("write! into one String", |b| {
+        b.iter(|| {
+            let mut s = String::new();
+            for part in parts {
+                let _ = write!(s, "{part}");
+            }
+            s
+        })
+    });
+    group.bench_function("+ operator", |b| {
+        b.iter(|| {
+            let mut s = String::new();
+            for part in parts {
+                s = s + part;
+            }
+            s
+        })
+    });
+    group.bench_function("[..].concat()", |b| b.iter(|| parts.concat()));
+    group.bench_function("StrBuilder", |b| {
+        b.iter(|| {
+            let mut builder = StrBuilder::with_parts(parts.len());
+            builder.extend(parts.iter().copied());
+            builder.build()
+        })
+    });
+    group.finish();
+}
+
+fn bench_join(c: &mut Criterion) {
+    let nums: Vec<u32> = (0..100).map(|i| i * 7919 % 10_007).collect();
+    let nums = black_box(nums.as_slice());
+    let mut group = c.benchmark_group("join 100 numbers");
+    group.bench_function("to_string then join", |b| {
+        b.iter(|| {
+            let strings: Vec<String> = nums.iter().map(u32::to_string).collect();
+            strings.join(", ")
+        })
+    });
+    group.bench_function("join_with", |b| b.iter(|| concat::join_with(nums, ", ")));
+    group.finish();
+}
+
+// A thousand short words, each kept as its own string
+fn bench_small_strings(c: &mut Criterion) {
+    let words = words();
+    let words = black_box(words.as_slice());
+    let mut group = c.benchmark_group("1000 short strings");
+    group.bench_function("Vec<String>", |b| {
+        b.iter(|| {
+            (0..1000)
+                .map(|i| String::from(words[i % words.len()].as_str()))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("Vec<SmallString>", |b| {
+        b.iter(|| {
+            (0..1000)
+                .map(|i| SmallString::from(words[i % words.len()].as_str()))
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_two_words,
+    bench_many_words,
+    bench_join,
+    bench_small_strings
+);
+criterion_main!(benches);
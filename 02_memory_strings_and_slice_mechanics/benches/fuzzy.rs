@@ -0,0 +1,53 @@
+// "Did you mean" over a large word list: comparing the query with every word vs a BK-tree.
+// Run with: cargo bench --bench fuzzy
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use memory_safety::fuzzy::{self, BkTree};
+
+mod common;
+
+// 20,000 distinct pseudo-random words of 4 to 10 letters
+fn dictionary() -> Vec<String> {
+    let mut next = common::xorshift();
+    let mut words = std::collections::BTreeSet::new();
+    while words.len() < 20_000 {
+        let seed = next();
+        let len = 4 + (seed % 7) as usize;
+        let word: String = (0..len)
+            .map(|i| (b'a' + ((seed >> (5 * i)) % 26) as u8) as char)
+            .collect();
+        words.insert(word);
+    }
+    words.into_iter().collect()
+}
+
+fn bench_lookup(c: &mut Criterion) {
+    let words = dictionary();
+    let tree: BkTree = words.iter().collect();
+    // A dictionary word with one letter changed
+    let mut query = words[words.len() / 2].clone();
+    query.replace_range(1..2, "z");
+    let query = black_box(query.as_str());
+
+    let mut group = c.benchmark_group("20k words");
+    group.bench_function("linear scan, distance <= 1", |b| {
+        b.iter(|| {
+            words
+                .iter()
+                .filter(|word| fuzzy::levenshtein(query, word) <= 1)
+                .count()
+        })
+    });
+    group.bench_function("BK-tree, distance <= 1", |b| {
+        b.iter(|| tree.find(query, 1).len())
+    });
+    group.bench_function("BK-tree, distance <= 2", |b| {
+        b.iter(|| tree.find(query, 2).len())
+    });
+    group.bench_function("BK-tree, nearest", |b| b.iter(|| tree.nearest(query)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_lookup);
+criterion_main!(benches);
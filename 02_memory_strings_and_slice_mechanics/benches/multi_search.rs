@@ -0,0 +1,68 @@
+// Per-word search loop (what main.rs did with find_substr_pos) vs one Aho–Corasick pass.
+// Run with: cargo bench --bench multi_search
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use memory_safety::multi_search::{AhoCorasick, MatchKind};
+use memory_safety::search;
+
+mod common;
+
+#[rustfmt::skip]
+const VOCABULARY: &[&str] = &[
+    "today", "is", "a", "very", "warm", "and", "sunny", "day", "the", "dew", "on", "grass",
+    "dries", "fast", "when", "sun", "rises", "over", "hills", "people", "say", "that", "summer",
+    "arms", "farm", "river", "evening", "cool", "breeze", "morning", "light", "shadow",
+];
+
+// About 1 MB of text made of vocabulary words in a fixed pseudo-random order
+fn corpus() -> String {
+    let mut next = common::xorshift();
+    let mut text = String::new();
+    while text.len() < 1 << 20 {
+        let seed = next();
+        text.push_str(VOCABULARY[(seed % VOCABULARY.len() as u64) as usize]);
+        text.push(if seed.is_multiple_of(11) { '.' } else { ' ' });
+    }
+    text
+}
+
+// Counts every occurrence of every word, so both sides do the same amount of work
+fn per_word_loop(text: &str, words: &[String]) -> usize {
+    words
+        .iter()
+        .map(|word| search::find_overlapping(text, word).count())
+        .sum()
+}
+
+fn bench_word_lists(c: &mut Criterion) {
+    let text = corpus();
+    let five: Vec<String> = ["very", "arm", "say", "sun", "dew"]
+        .map(String::from)
+        .to_vec();
+    // Vocabulary words plus misspelled variants that never match, to grow the pattern set
+    let hundred: Vec<String> = VOCABULARY
+        .iter()
+        .flat_map(|w| [w.to_string(), format!("{w}s"), format!("x{w}")])
+        .take(100)
+        .collect();
+
+    for (name, words) in [("5 words", &five), ("100 words", &hundred)] {
+        let matcher = AhoCorasick::new(words, MatchKind::Overlapping);
+        assert_eq!(
+            matcher.find_iter(&text).count(),
+            per_word_loop(&text, words)
+        );
+        let mut group = c.benchmark_group(name);
+        group.bench_function("per-word loop", |b| {
+            b.iter(|| per_word_loop(black_box(&text), words))
+        });
+        group.bench_function("aho-corasick", |b| {
+            b.iter(|| matcher.find_iter(black_box(&text)).count())
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_word_lists);
+criterion_main!(benches);
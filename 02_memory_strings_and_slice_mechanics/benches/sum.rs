@@ -0,0 +1,66 @@
+// The one-at-a-time loops from src/sum.rs vs the lane-split chunked_sum on one million numbers.
+// Run with: cargo bench --bench sum
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use memory_safety::sum;
+
+mod common;
+
+const LEN: usize = 1_000_000;
+
+// Pseudo-random values in a fixed order, small enough that the i32 total never overflows
+fn numbers() -> Vec<i32> {
+    let mut next = common::xorshift();
+    (0..LEN).map(|_| (next() % 2001) as i32 - 1000).collect()
+}
+
+// What array_sum in main.rs used to do: no overflow check at all (wrapping in release builds)
+fn wrapping_loop(nums: &[i32]) -> i32 {
+    let mut res = 0i32;
+    for &num in nums {
+        res = res.wrapping_add(num);
+    }
+    res
+}
+
+fn bench_integers(c: &mut Criterion) {
+    let nums = numbers();
+    assert_eq!(sum::chunked_sum(&nums), Some(wrapping_loop(&nums)));
+    let mut group = c.benchmark_group("1M i32");
+    group.bench_function("wrapping loop", |b| {
+        b.iter(|| wrapping_loop(black_box(&nums)))
+    });
+    group.bench_function("checked_sum", |b| {
+        b.iter(|| sum::checked_sum(black_box(&nums)))
+    });
+    group.bench_function("saturating_sum", |b| {
+        b.iter(|| sum::saturating_sum(black_box(&nums)))
+    });
+    group.bench_function("widening_sum", |b| {
+        b.iter(|| sum::widening_sum::<i32, i64>(black_box(&nums)))
+    });
+    group.bench_function("chunked_sum", |b| {
+        b.iter(|| sum::chunked_sum(black_box(&nums)))
+    });
+    group.finish();
+}
+
+fn bench_floats(c: &mut Criterion) {
+    let nums: Vec<f64> = numbers().into_iter().map(|n| f64::from(n) / 7.0).collect();
+    let mut group = c.benchmark_group("1M f64");
+    group.bench_function("iter().sum()", |b| {
+        b.iter(|| black_box(&nums).iter().sum::<f64>())
+    });
+    group.bench_function("kahan_sum", |b| b.iter(|| sum::kahan_sum(black_box(&nums))));
+    group.bench_function("neumaier_sum", |b| {
+        b.iter(|| sum::neumaier_sum(black_box(&nums)))
+    });
+    group.bench_function("chunked_sum", |b| {
+        b.iter(|| sum::chunked_sum(black_box(&nums)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_integers, bench_floats);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+// Validating and decoding 1 MB buffers: utf8::validate and the chunked decoder next to std's
+// str::from_utf8 on the whole buffer at once.
+// Run with: cargo bench --bench utf8
+use std::hint::black_box;
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use memory_safety::utf8::{self, Mode, Utf8Decoder};
+
+const SIZE: usize = 1 << 20;
+const PACKET: usize = 1460; // A TCP segment's payload on Ethernet: chars get cut at random
+
+fn repeat_to_size(unit: &str) -> Vec<u8> {
+    unit.repeat(SIZE / unit.len() + 1).into_bytes()[..SIZE].to_vec()
+}
+
+fn bench_buffers(c: &mut Criterion) {
+    // The mixed buffer may end mid-char; every function below sees the same bytes
+    let inputs = [
+        (
+            "ASCII",
+            repeat_to_size("GET /index.html HTTP/1.1 Host: example.com "),
+        ),
+        (
+            "mixed",
+            repeat_to_size("Today is sunny. आज धूप है। 今日は晴れ 🦀 "),
+        ),
+    ];
+    for (name, bytes) in &inputs {
+        let bytes = black_box(bytes.as_slice());
+        let mut group = c.benchmark_group(*name);
+        group.throughput(Throughput::Bytes(SIZE as u64));
+        group.bench_function("std::str::from_utf8", |b| {
+            b.iter(|| std::str::from_utf8(bytes).is_ok())
+        });
+        group.bench_function("utf8::validate", |b| {
+            b.iter(|| utf8::validate(bytes).is_ok())
+        });
+        group.bench_function("Utf8Decoder, 1460-byte chunks", |b| {
+            b.iter(|| {
+                let mut decoder = Utf8Decoder::new(Mode::Lossy);
+                let mut chars = 0;
+                for chunk in bytes.chunks(PACKET) {
+                    chars += decoder.decode(chunk).map_or(0, |text| text.len());
+                }
+                chars
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_buffers);
+criterion_main!(benches);
@@ -0,0 +1,197 @@
+// textstat: counts bytes, chars, graphemes, words and lines in files or stdin, like a Unicode-aware
+// `wc`, plus the longest line, the most frequent words and which scripts the text is written in.
+// Run with: cargo run --bin textstat -- [--json] [--top N] [FILE]...
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use memory_safety::escape::Json;
+use memory_safety::stats::TextStats;
+
+const USAGE: &str = "\
+Usage: textstat [--json] [--top N] [FILE]...
+Reads standard input when no FILE is given or FILE is -.
+  --json     print JSON instead of a table
+  --top N    how many of the most frequent words to list (default 10)";
+
+struct Args {
+    help: bool,
+    json: bool,
+    top: usize,
+    paths: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args {
+        help: false,
+        json: false,
+        top: 10,
+        paths: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => parsed.help = true,
+            "--json" => parsed.json = true,
+            "--top" => {
+                let n = args.next().ok_or("--top needs a number")?;
+                parsed.top = n.parse().map_err(|_| format!("--top: not a number: {n}"))?;
+            }
+            "--" => parsed.paths.extend(args.by_ref()),
+            "-" => parsed.paths.push(arg),
+            _ if arg.starts_with('-') => return Err(format!("unknown option: {arg}")),
+            _ => parsed.paths.push(arg),
+        }
+    }
+    if parsed.paths.is_empty() {
+        parsed.paths.push("-".to_string());
+    }
+    Ok(parsed)
+}
+
+fn read_stats(path: &str) -> io::Result<TextStats> {
+    let reader: Box<dyn Read> = match path {
+        "-" => Box::new(io::stdin().lock()),
+        _ => Box::new(File::open(path)?),
+    };
+    TextStats::from_reader(reader)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("textstat: {message}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    if args.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    // Like wc: report what can be read, complain about the rest, and fail at the end
+    let mut failed = false;
+    let mut reports = Vec::new();
+    let mut total = TextStats::new();
+    for path in &args.paths {
+        match read_stats(path) {
+            Ok(stats) => {
+                total.merge(&stats);
+                reports.push((path.as_str(), stats));
+            }
+            Err(e) => {
+                eprintln!("textstat: {path}: {e}");
+                failed = true;
+            }
+        }
+    }
+
+    if args.json {
+        println!("{}", json_report(&reports, &total, args.top));
+    } else {
+        for (path, stats) in &reports {
+            print!("{}", table(path, stats, args.top));
+        }
+        if reports.len() > 1 {
+            print!("{}", table("total", &total, args.top));
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// --- TABLE OUTPUT ---
+fn table(name: &str, stats: &TextStats, top: usize) -> String {
+    let mut out = format!("== {name} ==\n");
+    let counts = [
+        ("bytes", stats.bytes),
+        ("chars", stats.chars),
+        ("graphemes", stats.graphemes),
+        ("words", stats.words),
+        ("lines", stats.lines),
+    ];
+    for (label, count) in counts {
+        let _ = writeln!(out, "{label:<14}{count}");
+    }
+    if let Some(line) = stats.longest_line {
+        let _ = writeln!(
+            out,
+            "{:<14}{} columns (line {})",
+            "longest line", line.width, line.number
+        );
+    }
+    let words = stats.top_words(top);
+    if !words.is_empty() {
+        let _ = writeln!(out, "top words");
+        for (word, count) in words {
+            let _ = writeln!(out, "  {count:>10}  {word}");
+        }
+    }
+    let scripts = stats.scripts();
+    let counted: u64 = scripts.iter().map(|&(_, count)| count).sum();
+    if counted > 0 {
+        let _ = writeln!(out, "scripts");
+        for (script, count) in scripts {
+            let share = count as f64 * 100.0 / counted as f64;
+            let _ = writeln!(out, "  {count:>10}  {share:>5.1}%  {script}");
+        }
+    }
+    out
+}
+
+// --- JSON OUTPUT ---
+// {"files": [{"file": ..., counts...}, ...], "total": {counts...}}
+fn json_report(reports: &[(&str, TextStats)], total: &TextStats, top: usize) -> String {
+    let files: Vec<String> = reports
+        .iter()
+        .map(|(path, stats)| json_stats(Some(path), stats, top))
+        .collect();
+    format!(
+        "{{\"files\": [{}], \"total\": {}}}",
+        files.join(", "),
+        json_stats(None, total, top)
+    )
+}
+
+fn json_stats(path: Option<&str>, stats: &TextStats, top: usize) -> String {
+    let mut out = String::from("{");
+    if let Some(path) = path {
+        let _ = write!(out, "\"file\": {}, ", Json(path));
+    }
+    let _ = write!(
+        out,
+        "\"bytes\": {}, \"chars\": {}, \"graphemes\": {}, \"words\": {}, \"lines\": {}, ",
+        stats.bytes, stats.chars, stats.graphemes, stats.words, stats.lines
+    );
+    match stats.longest_line {
+        Some(line) => {
+            let _ = write!(
+                out,
+                "\"longest_line\": {{\"line\": {}, \"width\": {}}}, ",
+                line.number, line.width
+            );
+        }
+        None => out.push_str("\"longest_line\": null, "),
+    }
+    let words: Vec<String> = stats
+        .top_words(top)
+        .iter()
+        .map(|(word, count)| format!("{{\"word\": {}, \"count\": {count}}}", Json(word)))
+        .collect();
+    let scripts: Vec<String> = stats
+        .scripts()
+        .iter()
+        .map(|(script, count)| format!("{}: {count}", Json(script)))
+        .collect();
+    let _ = write!(
+        out,
+        "\"top_words\": [{}], \"scripts\": {{{}}}}}",
+        words.join(", "),
+        scripts.join(", ")
+    );
+    out
+}
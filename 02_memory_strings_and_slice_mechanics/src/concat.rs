@@ -0,0 +1,308 @@
+use std::borrow::Borrow;
+use std::fmt::{self, Display, Write};
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+
+// --- STR BUILDER ---
+// `s1 + " " + &s2 + ...` reallocates whenever the next part does not fit, copying everything so
+// far each time, and format! only guesses a capacity from its literal pieces. StrBuilder collects
+// the parts first and adds up their lengths, so build() allocates once, at the exact size, and
+// copies each part once.
+#[derive(Debug, Clone, Default)]
+pub struct StrBuilder<'a> {
+    parts: Vec<Part<'a>>,
+    len: usize, // Bytes the built string will have
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Part<'a> {
+    Str(&'a str),
+    Char(char),
+}
+
+impl<'a> StrBuilder<'a> {
+    pub fn new() -> StrBuilder<'a> {
+        StrBuilder::default()
+    }
+
+    // Room for `parts` parts, so collecting them does not reallocate either
+    pub fn with_parts(parts: usize) -> StrBuilder<'a> {
+        StrBuilder {
+            parts: Vec::with_capacity(parts),
+            len: 0,
+        }
+    }
+
+    // Both push methods return the builder, so calls chain:
+    // StrBuilder::new().push("Hello").push_char(' ').push(&name).build()
+    pub fn push(&mut self, s: &'a str) -> &mut StrBuilder<'a> {
+        self.len += s.len();
+        self.parts.push(Part::Str(s));
+        self
+    }
+
+    pub fn push_char(&mut self, c: char) -> &mut StrBuilder<'a> {
+        self.len += c.len_utf8();
+        self.parts.push(Part::Char(c));
+        self
+    }
+
+    // Length in bytes of the string build() will return
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // A new string whose capacity is exactly its length
+    pub fn build(&self) -> String {
+        let mut out = String::with_capacity(self.len);
+        self.build_into(&mut out);
+        out
+    }
+
+    // Appends to an existing string, reserving the whole length up front
+    pub fn build_into(&self, out: &mut String) {
+        out.reserve(self.len);
+        for part in &self.parts {
+            match part {
+                Part::Str(s) => out.push_str(s),
+                Part::Char(c) => out.push(*c),
+            }
+        }
+    }
+}
+
+impl<'a> Extend<&'a str> for StrBuilder<'a> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, parts: I) {
+        for part in parts {
+            self.push(part);
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a str> for StrBuilder<'a> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(parts: I) -> StrBuilder<'a> {
+        let mut builder = StrBuilder::new();
+        builder.extend(parts);
+        builder
+    }
+}
+
+// --- JOINING ---
+// `[..].join(sep)` only takes strings, so numbers must become Strings first, one allocation each.
+// These take anything Display and write it straight into the output:
+// join_with([1, 2, 3], ", ") == "1, 2, 3"
+pub fn join_with<I>(items: I, sep: &str) -> String
+where
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let mut out = String::new();
+    write_joined(&mut out, items, sep).expect("writing to a String never fails");
+    out
+}
+
+// Same, into any fmt::Write: a String being built, a Formatter inside a Display impl, ...
+pub fn write_joined<W, I>(out: &mut W, items: I, sep: &str) -> fmt::Result
+where
+    W: Write + ?Sized,
+    I: IntoIterator,
+    I::Item: Display,
+{
+    let mut items = items.into_iter();
+    if let Some(first) = items.next() {
+        write!(out, "{first}")?;
+        for item in items {
+            out.write_str(sep)?;
+            write!(out, "{item}")?;
+        }
+    }
+    Ok(())
+}
+
+// --- SMALL STRINGS ---
+// A String always puts its text on the heap, even "ok". InlineString keeps up to N bytes inside
+// the value itself and only allocates when it grows past that, so a Vec of short names or tags
+// costs one allocation instead of one per string. N is at most 255 (the length is a u8).
+#[derive(Clone)]
+pub struct InlineString<const N: usize> {
+    repr: Repr<N>,
+}
+
+#[derive(Clone)]
+enum Repr<const N: usize> {
+    Inline { buf: [u8; N], len: u8 },
+    Heap(String),
+}
+
+// 23 bytes inline, as in libc++'s std::string. The value takes 32 bytes where a String takes 24,
+// which pays for itself as soon as it saves an allocation.
+pub type SmallString = InlineString<23>;
+
+impl<const N: usize> InlineString<N> {
+    pub fn new() -> InlineString<N> {
+        const {
+            assert!(
+                N <= u8::MAX as usize,
+                "InlineString holds at most 255 bytes inline"
+            )
+        };
+        InlineString {
+            repr: Repr::Inline {
+                buf: [0; N],
+                len: 0,
+            },
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.repr {
+            // Only whole strs are ever copied into buf, so this cannot fail; checking costs at
+            // most N bytes and keeps the type free of unsafe code
+            Repr::Inline { buf, len } => {
+                std::str::from_utf8(&buf[..*len as usize]).expect("inline text is UTF-8")
+            }
+            Repr::Heap(s) => s,
+        }
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        match &mut self.repr {
+            Repr::Inline { buf, len } => {
+                let (start, end) = (*len as usize, *len as usize + s.len());
+                if end <= N {
+                    buf[start..end].copy_from_slice(s.as_bytes());
+                    *len = end as u8;
+                } else {
+                    // Spill: one allocation of at least 2 * N bytes, so a string that only
+                    // just outgrew the buffer can take about N more before it reallocates
+                    let mut heap = String::with_capacity(end.max(2 * N));
+                    heap.push_str(self.as_str());
+                    heap.push_str(s);
+                    self.repr = Repr::Heap(heap);
+                }
+            }
+            Repr::Heap(heap) => heap.push_str(s),
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    // True while the text is still stored inside the value
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+
+    // Bytes it can hold before it next allocates
+    pub fn capacity(&self) -> usize {
+        match &self.repr {
+            Repr::Inline { .. } => N,
+            Repr::Heap(s) => s.capacity(),
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self.repr {
+            Repr::Inline { .. } => self.as_str().to_string(),
+            Repr::Heap(s) => s,
+        }
+    }
+}
+
+impl<const N: usize> Default for InlineString<N> {
+    fn default() -> InlineString<N> {
+        InlineString::new()
+    }
+}
+
+impl<const N: usize> Deref for InlineString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for InlineString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for InlineString<N> {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> From<&str> for InlineString<N> {
+    fn from(s: &str) -> InlineString<N> {
+        let mut out = InlineString::new();
+        out.push_str(s);
+        out
+    }
+}
+
+// Keeps the String's allocation when the text does not fit inline
+impl<const N: usize> From<String> for InlineString<N> {
+    fn from(s: String) -> InlineString<N> {
+        if s.len() <= N {
+            InlineString::from(s.as_str())
+        } else {
+            InlineString {
+                repr: Repr::Heap(s),
+            }
+        }
+    }
+}
+
+impl<const N: usize> Write for InlineString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<const N: usize> Display for InlineString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for InlineString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+// Equal when the text is, whether it is inline or on the heap
+impl<const N: usize> PartialEq for InlineString<N> {
+    fn eq(&self, other: &InlineString<N>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for InlineString<N> {}
+
+impl<const N: usize> PartialEq<str> for InlineString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for InlineString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> Hash for InlineString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
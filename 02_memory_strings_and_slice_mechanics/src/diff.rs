@@ -0,0 +1,271 @@
+use std::hash::Hash;
+use std::ops::Range;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+mod myers; // Myers' O(ND) shortest edit script, in linear space
+pub mod patch; // Unified diff: hunks with context, rendering, parsing and applying
+mod patience; // Patience diff: anchors on lines that occur once on each side
+
+pub use patch::{Hunk, Patch, PatchError};
+
+// --- DIFF ---
+// main.rs compares str_orig and str_clone with `==` and `contains`, which only say whether they
+// differ. A diff says how: the shortest list of deletions and insertions that turns the old
+// sequence into the new one, with everything else marked as kept. Text is compared as lines,
+// words, chars or graphemes; any slice of comparable items works too.
+//   let d = diff::diff("Rust", "Rust is awesome", Granularity::Word, Algorithm::Myers);
+//   d.inline(Style::Markers)   // "Rust{+ is awesome+}"
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditKind {
+    Equal,  // In both, unchanged
+    Delete, // Only in the old sequence
+    Insert, // Only in the new sequence
+}
+
+// A run of items with the same kind, as index ranges into the old and new sequences. Equal runs
+// have ranges of the same length; a Delete has an empty `new` range (where it would go), and an
+// Insert an empty `old` range. A replacement is a Delete followed by an Insert.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub kind: EditKind,
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    #[default]
+    Myers, // Fewest edits
+    Patience, // Lines unique to both sides are kept first: more readable diffs of code
+}
+
+// Edits that turn `old` into `new`. Runs alternate between Equal and changes, and within a change
+// the Delete (if any) comes before the Insert.
+pub fn diff_slices<T: Eq + Hash>(old: &[T], new: &[T], algorithm: Algorithm) -> Vec<Edit> {
+    let mut script = Script::default();
+    match algorithm {
+        Algorithm::Myers => myers::diff(old, new, &mut script),
+        Algorithm::Patience => patience::diff(old, new, &mut script),
+    }
+    script.finish(old.len(), new.len())
+}
+
+// Collects the edits as the algorithms find them, one item or run at a time, in order
+#[derive(Default)]
+pub(crate) struct Script {
+    edits: Vec<Edit>,
+    old: usize, // Old items covered so far
+    new: usize,
+}
+
+impl Script {
+    pub(crate) fn equal(&mut self, len: usize) {
+        self.push(EditKind::Equal, len, len);
+    }
+
+    pub(crate) fn delete(&mut self, len: usize) {
+        self.push(EditKind::Delete, len, 0);
+    }
+
+    pub(crate) fn insert(&mut self, len: usize) {
+        self.push(EditKind::Insert, 0, len);
+    }
+
+    fn push(&mut self, kind: EditKind, old: usize, new: usize) {
+        if old == 0 && new == 0 {
+            return;
+        }
+        let (old, new) = (self.old..self.old + old, self.new..self.new + new);
+        (self.old, self.new) = (old.end, new.end);
+        match self.edits.last_mut() {
+            Some(last) if last.kind == kind => {
+                last.old.end = old.end;
+                last.new.end = new.end;
+            }
+            _ => self.edits.push(Edit { kind, old, new }),
+        }
+    }
+
+    // Merges each stretch of interleaved deletions and insertions into one Delete and one Insert
+    fn finish(self, old_len: usize, new_len: usize) -> Vec<Edit> {
+        debug_assert_eq!((self.old, self.new), (old_len, new_len));
+        let mut edits: Vec<Edit> = Vec::with_capacity(self.edits.len());
+        let mut change: Option<(Range<usize>, Range<usize>)> = None;
+        for edit in self.edits.into_iter().chain([Edit {
+            kind: EditKind::Equal,
+            old: old_len..old_len,
+            new: new_len..new_len,
+        }]) {
+            if edit.kind != EditKind::Equal {
+                let (old, new) = change.get_or_insert((
+                    edit.old.start..edit.old.start,
+                    edit.new.start..edit.new.start,
+                ));
+                old.end = edit.old.end;
+                new.end = edit.new.end;
+                continue;
+            }
+            if let Some((old, new)) = change.take() {
+                if !old.is_empty() {
+                    let at = new.start;
+                    edits.push(Edit {
+                        kind: EditKind::Delete,
+                        old: old.clone(),
+                        new: at..at,
+                    });
+                }
+                if !new.is_empty() {
+                    let at = old.end;
+                    edits.push(Edit {
+                        kind: EditKind::Insert,
+                        old: at..at,
+                        new,
+                    });
+                }
+            }
+            if !edit.old.is_empty() {
+                edits.push(edit);
+            }
+        }
+        edits
+    }
+}
+
+// Common prefix and suffix lengths, which every algorithm keeps without searching
+pub(crate) fn common_ends<T: Eq>(old: &[T], new: &[T]) -> (usize, usize) {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    (prefix, suffix)
+}
+
+// --- TEXT ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Line,     // Each line with its '\n'
+    Word,     // Words, spaces and punctuation, on Unicode (UAX #29) word boundaries
+    Char,     // Unicode scalar values
+    Grapheme, // What a reader sees as one letter: "é" as e + U+0301 changes as a whole
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Markers, // [-deleted-]{+inserted+}, as in `git diff --word-diff`
+    Ansi,    // Deleted text red and struck through, inserted text green, for terminals
+}
+
+// Two texts cut into tokens, and the edits between the token lists. Tokens cover their text
+// completely, so each edit maps back to one contiguous slice of the old or new text.
+#[derive(Debug, Clone)]
+pub struct TextDiff<'a> {
+    old: Tokens<'a>,
+    new: Tokens<'a>,
+    edits: Vec<Edit>,
+}
+
+#[derive(Debug, Clone)]
+struct Tokens<'a> {
+    text: &'a str,
+    tokens: Vec<&'a str>,
+    starts: Vec<usize>, // Byte offset of each token, then text.len()
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str, granularity: Granularity) -> Tokens<'a> {
+        let tokens: Vec<&str> = match granularity {
+            Granularity::Line => text.split_inclusive('\n').collect(),
+            Granularity::Word => text.split_word_bounds().collect(),
+            Granularity::Char => text.split_inclusive(|_| true).collect(),
+            Granularity::Grapheme => text.graphemes(true).collect(),
+        };
+        let mut starts = Vec::with_capacity(tokens.len() + 1);
+        let mut at = 0;
+        for token in &tokens {
+            starts.push(at);
+            at += token.len();
+        }
+        starts.push(at);
+        Tokens {
+            text,
+            tokens,
+            starts,
+        }
+    }
+
+    fn slice(&self, range: &Range<usize>) -> &'a str {
+        &self.text[self.starts[range.start]..self.starts[range.end]]
+    }
+}
+
+pub fn diff<'a>(
+    old: &'a str,
+    new: &'a str,
+    granularity: Granularity,
+    algorithm: Algorithm,
+) -> TextDiff<'a> {
+    let (old, new) = (Tokens::new(old, granularity), Tokens::new(new, granularity));
+    let edits = diff_slices(&old.tokens, &new.tokens, algorithm);
+    TextDiff { old, new, edits }
+}
+
+impl<'a> TextDiff<'a> {
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    pub fn old_tokens(&self) -> &[&'a str] {
+        &self.old.tokens
+    }
+
+    pub fn new_tokens(&self) -> &[&'a str] {
+        &self.new.tokens
+    }
+
+    // Each edit as the text it covers: from the old text for Equal and Delete, the new for Insert
+    pub fn changes(&self) -> impl Iterator<Item = (EditKind, &'a str)> + '_ {
+        self.edits.iter().map(|edit| match edit.kind {
+            EditKind::Insert => (edit.kind, self.new.slice(&edit.new)),
+            _ => (edit.kind, self.old.slice(&edit.old)),
+        })
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.edits.iter().all(|edit| edit.kind == EditKind::Equal)
+    }
+
+    // Tokens deleted plus tokens inserted
+    pub fn distance(&self) -> usize {
+        let changed = |edit: &Edit| match edit.kind {
+            EditKind::Equal => 0,
+            EditKind::Delete => edit.old.len(),
+            EditKind::Insert => edit.new.len(),
+        };
+        self.edits.iter().map(changed).sum()
+    }
+
+    // Both texts in one: kept text as is, deletions and insertions marked in `style`
+    pub fn inline(&self, style: Style) -> String {
+        let (delete, insert, end_delete, end_insert) = match style {
+            Style::Markers => ("[-", "{+", "-]", "+}"),
+            Style::Ansi => ("\x1b[31;9m", "\x1b[32m", "\x1b[0m", "\x1b[0m"),
+        };
+        let mut out = String::with_capacity(self.old.text.len().max(self.new.text.len()));
+        for (kind, text) in self.changes() {
+            let (open, close) = match kind {
+                EditKind::Equal => ("", ""),
+                EditKind::Delete => (delete, end_delete),
+                EditKind::Insert => (insert, end_insert),
+            };
+            out.push_str(open);
+            out.push_str(text);
+            out.push_str(close);
+        }
+        out
+    }
+}
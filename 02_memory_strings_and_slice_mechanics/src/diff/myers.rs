@@ -0,0 +1,115 @@
+use super::{Script, common_ends};
+
+// --- MYERS ---
+// Eugene Myers, "An O(ND) Difference Algorithm and Its Variations" (1986). Think of a grid with
+// the old sequence across and the new one down: a step right deletes an item, a step down inserts
+// one, and a diagonal step (free) keeps an item both sides share. The shortest edit script is
+// the path from corner to corner with the fewest non-diagonal steps, D. Searching from both
+// corners at once finds the "middle snake" where the two searches meet; it lies on a shortest
+// path, so each half can be solved on its own. That takes O((N + M) · D) time, fast when the
+// inputs are similar, and only O(N + M) memory, where keeping every step of a one-way search
+// would take O(D²).
+
+pub(crate) fn diff<T: Eq>(old: &[T], new: &[T], script: &mut Script) {
+    let (prefix, suffix) = common_ends(old, new);
+    script.equal(prefix);
+    let (old, new) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    if old.is_empty() {
+        script.insert(new.len());
+    } else if new.is_empty() {
+        script.delete(old.len());
+    } else if let Some((x, y)) = middle_snake(old, new) {
+        diff(&old[..x], &new[..y], script);
+        diff(&old[x..], &new[y..], script);
+    } else {
+        // Not reached: the two searches always meet within (N + M + 1) / 2 steps
+        script.delete(old.len());
+        script.insert(new.len());
+    }
+    script.equal(suffix);
+}
+
+// Where to split the grid: a point on a shortest path. Diagonal k holds the points with
+// x − y = k; forward[k] is the furthest x the forward search has reached on diagonal k, and
+// backward[k] the same for the search from the far corner, counted from that corner.
+fn middle_snake<T: Eq>(old: &[T], new: &[T]) -> Option<(usize, usize)> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m + 1) / 2;
+    let offset = max; // forward[offset + k] is diagonal k
+    let width = 2 * max as usize + 2;
+    let mut forward = vec![-1isize; width];
+    let mut backward = vec![-1isize; width];
+    forward[offset as usize + 1] = 0;
+    backward[offset as usize + 1] = 0;
+    let delta = n - m; // The backward search's diagonal 0 is the forward search's diagonal delta
+    // If delta is odd the forward search meets the backward one, else the other way round
+    let front = delta % 2 != 0;
+    // Diagonals at either end that have run off the grid are skipped from then on
+    let (mut k1_start, mut k1_end, mut k2_start, mut k2_end) = (0, 0, 0, 0);
+    for d in 0..max {
+        let mut k1 = -d + k1_start;
+        while k1 <= d - k1_end {
+            let i = (offset + k1) as usize;
+            let mut x1 = if k1 == -d || (k1 != d && forward[i - 1] < forward[i + 1]) {
+                forward[i + 1] // Down from diagonal k + 1: an insertion
+            } else {
+                forward[i - 1] + 1 // Right from diagonal k - 1: a deletion
+            };
+            let mut y1 = x1 - k1;
+            while x1 < n && y1 < m && old[x1 as usize] == new[y1 as usize] {
+                x1 += 1;
+                y1 += 1;
+            }
+            forward[i] = x1;
+            if x1 > n {
+                k1_end += 2; // Off the right edge
+            } else if y1 > m {
+                k1_start += 2; // Off the bottom edge
+            } else if front {
+                let j = offset + delta - k1;
+                if (0..width as isize).contains(&j) && backward[j as usize] != -1 {
+                    // The backward search has reached past this point on the same diagonal
+                    if x1 >= n - backward[j as usize] {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k1 += 2;
+        }
+
+        let mut k2 = -d + k2_start;
+        while k2 <= d - k2_end {
+            let i = (offset + k2) as usize;
+            let mut x2 = if k2 == -d || (k2 != d && backward[i - 1] < backward[i + 1]) {
+                backward[i + 1]
+            } else {
+                backward[i - 1] + 1
+            };
+            let mut y2 = x2 - k2;
+            while x2 < n && y2 < m && old[(n - x2 - 1) as usize] == new[(m - y2 - 1) as usize] {
+                x2 += 1;
+                y2 += 1;
+            }
+            backward[i] = x2;
+            if x2 > n {
+                k2_end += 2;
+            } else if y2 > m {
+                k2_start += 2;
+            } else if !front {
+                let j = offset + delta - k2;
+                if (0..width as isize).contains(&j) && forward[j as usize] != -1 {
+                    let x1 = forward[j as usize];
+                    let y1 = x1 - (j - offset);
+                    if x1 >= n - x2 {
+                        return Some((x1 as usize, y1 as usize));
+                    }
+                }
+            }
+            k2 += 2;
+        }
+    }
+    None
+}
@@ -0,0 +1,280 @@
+use std::fmt;
+
+use super::{Algorithm, EditKind, Granularity};
+
+// --- UNIFIED DIFF ---
+// The format of `diff -u` and `git diff`: only the changed lines, each group with a few unchanged
+// lines of context around it, as hunks headed by the line numbers they cover:
+//   @@ -3,4 +3,5 @@
+//    unchanged line
+//   -deleted line
+//   +inserted line
+// A line that did not end in '\n' (the last line of a file) is followed by
+// "\ No newline at end of file", so applying the patch gives back the exact bytes.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    BadHunkHeader { line: usize }, // Patch line `line` starts with "@@" but is not a hunk header
+    BadLine { line: usize },       // Patch line `line` does not belong in the hunk it is in
+    UnexpectedEnd,                 // The patch stops before a hunk has all its lines
+    Overlap { hunk: usize },       // Hunk `hunk` (from 1) starts before the one before it ends
+    Mismatch { hunk: usize, line: usize }, // Line `line` of the old text is not what hunk `hunk` expects
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::BadHunkHeader { line } => write!(f, "invalid hunk header on line {line}"),
+            PatchError::BadLine { line } => write!(f, "unexpected line {line} in hunk"),
+            PatchError::UnexpectedEnd => write!(f, "patch ends inside a hunk"),
+            PatchError::Overlap { hunk } => write!(f, "hunk {hunk} overlaps the one before it"),
+            PatchError::Mismatch { hunk, line } => {
+                write!(f, "hunk {hunk} does not match line {line} of the text")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+// Line numbers count from 0 here; the text format counts from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: usize,
+    pub old_len: usize, // Context and deleted lines
+    pub new_start: usize,
+    pub new_len: usize,                 // Context and inserted lines
+    pub lines: Vec<(EditKind, String)>, // Each with its '\n', unless the text ended without one
+}
+
+impl Hunk {
+    fn push(&mut self, kind: EditKind, lines: &[&str]) {
+        if kind != EditKind::Insert {
+            self.old_len += lines.len();
+        }
+        if kind != EditKind::Delete {
+            self.new_len += lines.len();
+        }
+        self.lines
+            .extend(lines.iter().map(|line| (kind, line.to_string())));
+    }
+}
+
+impl fmt::Display for Hunk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // An empty range is numbered by the line before it: "-0,0" inserts at the very start
+        let range = |start: usize, len: usize| match len {
+            0 => format!("{start},0"),
+            1 => format!("{}", start + 1),
+            _ => format!("{},{len}", start + 1),
+        };
+        let (old, new) = (
+            range(self.old_start, self.old_len),
+            range(self.new_start, self.new_len),
+        );
+        writeln!(f, "@@ -{old} +{new} @@")?;
+        for (kind, line) in &self.lines {
+            let sign = match kind {
+                EditKind::Equal => ' ',
+                EditKind::Delete => '-',
+                EditKind::Insert => '+',
+            };
+            write!(f, "{sign}{line}")?;
+            if !line.ends_with('\n') {
+                write!(f, "\n\\ No newline at end of file\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Patch {
+    hunks: Vec<Hunk>,
+}
+
+impl Patch {
+    // Line diff of the two texts, with `context` unchanged lines around each change. Changes
+    // closer than 2 × context lines share a hunk, as their context would overlap.
+    pub fn new(old: &str, new: &str, algorithm: Algorithm, context: usize) -> Patch {
+        let diff = super::diff(old, new, Granularity::Line, algorithm);
+        let (old_lines, new_lines, edits) = (diff.old_tokens(), diff.new_tokens(), diff.edits());
+        let mut hunks = Vec::new();
+        let mut open: Option<Hunk> = None;
+        for (i, edit) in edits.iter().enumerate() {
+            if edit.kind == EditKind::Equal {
+                let Some(mut hunk) = open.take() else {
+                    continue; // Leading context is taken when the next change starts
+                };
+                if i + 1 == edits.len() || edit.old.len() > 2 * context {
+                    let end = edit.old.start + context.min(edit.old.len());
+                    hunk.push(EditKind::Equal, &old_lines[edit.old.start..end]);
+                    hunks.push(hunk);
+                } else {
+                    hunk.push(EditKind::Equal, &old_lines[edit.old.clone()]);
+                    open = Some(hunk);
+                }
+                continue;
+            }
+            let hunk = open.get_or_insert_with(|| {
+                // The lines before a change that starts a hunk are all unchanged
+                let before = context.min(edit.old.start);
+                let mut hunk = Hunk {
+                    old_start: edit.old.start - before,
+                    old_len: 0,
+                    new_start: edit.new.start - before,
+                    new_len: 0,
+                    lines: Vec::new(),
+                };
+                hunk.push(EditKind::Equal, &old_lines[hunk.old_start..edit.old.start]);
+                hunk
+            });
+            match edit.kind {
+                EditKind::Delete => hunk.push(edit.kind, &old_lines[edit.old.clone()]),
+                _ => hunk.push(edit.kind, &new_lines[edit.new.clone()]),
+            }
+        }
+        hunks.extend(open);
+        Patch { hunks }
+    }
+
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    // True if the two texts were equal
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    // The patch with "---" and "+++" file name headers, as `diff -u` prints it; empty if there
+    // are no changes
+    pub fn to_unified(&self, old_name: &str, new_name: &str) -> String {
+        if self.hunks.is_empty() {
+            return String::new();
+        }
+        format!("--- {old_name}\n+++ {new_name}\n{self}")
+    }
+
+    // Reads the hunks of a unified diff. Lines outside hunks (file names, `git diff` headers)
+    // are skipped. Hunks are taken as written: lines are not matched against any text until
+    // `apply`.
+    pub fn parse(text: &str) -> Result<Patch, PatchError> {
+        let mut hunks = Vec::new();
+        let mut lines = text.split_inclusive('\n').zip(1..).peekable();
+        while let Some((line, number)) = lines.next() {
+            if !line.starts_with("@@") {
+                continue;
+            }
+            let mut hunk = header(line).ok_or(PatchError::BadHunkHeader { line: number })?;
+            let (mut old_left, mut new_left) = (hunk.old_len, hunk.new_len);
+            while old_left > 0
+                || new_left > 0
+                || lines.peek().is_some_and(|(l, _)| l.starts_with('\\'))
+            {
+                let (line, number) = lines.next().ok_or(PatchError::UnexpectedEnd)?;
+                let bad = PatchError::BadLine { line: number };
+                let (kind, body) = match line.split_at_checked(1) {
+                    Some((" ", body)) => (EditKind::Equal, body),
+                    Some(("-", body)) => (EditKind::Delete, body),
+                    Some(("+", body)) => (EditKind::Insert, body),
+                    // Some tools strip the space from an empty context line
+                    _ if line == "\n" => (EditKind::Equal, line),
+                    Some(("\\", _)) => {
+                        // "\ No newline at end of file": the line before had no '\n'
+                        let (_, last) = hunk.lines.last_mut().ok_or(bad)?;
+                        if last.ends_with('\n') {
+                            last.pop();
+                        }
+                        continue;
+                    }
+                    _ => return Err(bad),
+                };
+                let (old_count, new_count) = match kind {
+                    EditKind::Equal => (1, 1),
+                    EditKind::Delete => (1, 0),
+                    EditKind::Insert => (0, 1),
+                };
+                if old_left < old_count || new_left < new_count {
+                    return Err(bad);
+                }
+                (old_left, new_left) = (old_left - old_count, new_left - new_count);
+                hunk.lines.push((kind, body.to_string()));
+            }
+            hunks.push(hunk);
+        }
+        Ok(Patch { hunks })
+    }
+
+    // The new text, from the old text and this patch. Every context and deleted line must be
+    // found exactly where the hunk says; there is no searching nearby for text that moved.
+    pub fn apply(&self, old: &str) -> Result<String, PatchError> {
+        let lines: Vec<&str> = old.split_inclusive('\n').collect();
+        let mut out = String::with_capacity(old.len());
+        let mut at = 0; // Old lines copied or consumed so far
+        for (hunk, number) in self.hunks.iter().zip(1..) {
+            if hunk.old_start < at {
+                return Err(PatchError::Overlap { hunk: number });
+            }
+            let unchanged = lines.get(at..hunk.old_start).ok_or(PatchError::Mismatch {
+                hunk: number,
+                line: lines.len() + 1,
+            })?;
+            out.extend(unchanged.iter().copied());
+            at = hunk.old_start;
+            for (kind, line) in &hunk.lines {
+                if *kind == EditKind::Insert {
+                    out.push_str(line);
+                    continue;
+                }
+                if lines.get(at) != Some(&line.as_str()) {
+                    return Err(PatchError::Mismatch {
+                        hunk: number,
+                        line: at + 1,
+                    });
+                }
+                if *kind == EditKind::Equal {
+                    out.push_str(line);
+                }
+                at += 1;
+            }
+        }
+        out.extend(lines[at..].iter().copied());
+        Ok(out)
+    }
+}
+
+// Hunks only, without file name headers
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.hunks.iter().try_for_each(|hunk| write!(f, "{hunk}"))
+    }
+}
+
+// "@@ -3,4 +3,5 @@ optional section name"
+fn header(line: &str) -> Option<Hunk> {
+    let (ranges, _) = line.strip_prefix("@@ -")?.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let ((old_start, old_len), (new_start, new_len)) = (range(old)?, range(new)?);
+    Some(Hunk {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+        lines: Vec::new(),
+    })
+}
+
+// "3,4" or "3" (one line): 1-based start, except that an empty range names the line before it
+fn range(text: &str) -> Option<(usize, usize)> {
+    let (start, len) = match text.split_once(',') {
+        Some((start, len)) => (start.parse().ok()?, len.parse().ok()?),
+        None => (text.parse().ok()?, 1),
+    };
+    let start = if len == 0 {
+        start
+    } else {
+        usize::checked_sub(start, 1)?
+    };
+    Some((start, len))
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{Script, common_ends, myers};
+
+// --- PATIENCE ---
+// Bram Cohen's patience diff. Myers finds the fewest edits, but in code the fewest edits often
+// line up the wrong lines: a lone "}" or blank line from one function matched with one from
+// another. Patience diff first keeps the items that occur exactly once on each side (in code:
+// mostly distinct lines such as function signatures), as many of them as stay in order, then
+// diffs the gaps between them the same way. A gap with no such items falls back to Myers.
+
+pub(crate) fn diff<T: Eq + Hash>(old: &[T], new: &[T], script: &mut Script) {
+    let (prefix, suffix) = common_ends(old, new);
+    script.equal(prefix);
+    let (old, new) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+    let anchors = anchors(old, new);
+    if anchors.is_empty() {
+        myers::diff(old, new, script);
+    } else {
+        let (mut i, mut j) = (0, 0);
+        for (x, y) in anchors {
+            diff(&old[i..x], &new[j..y], script);
+            script.equal(1);
+            (i, j) = (x + 1, y + 1);
+        }
+        diff(&old[i..], &new[j..], script);
+    }
+    script.equal(suffix);
+}
+
+// Positions (in old, in new) of items unique to both sides, the longest list of them that is in
+// order on both sides
+fn anchors<T: Eq + Hash>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    // Per item: (times seen, last position) in old, then in new
+    let mut seen: HashMap<&T, [(usize, usize); 2]> = HashMap::new();
+    for (i, item) in old.iter().enumerate() {
+        let entry = seen.entry(item).or_default();
+        entry[0] = (entry[0].0 + 1, i);
+    }
+    for (j, item) in new.iter().enumerate() {
+        if let Some(entry) = seen.get_mut(item) {
+            entry[1] = (entry[1].0 + 1, j);
+        }
+    }
+    let mut pairs: Vec<(usize, usize)> = seen
+        .into_values()
+        .filter(|[in_old, in_new]| in_old.0 == 1 && in_new.0 == 1)
+        .map(|[in_old, in_new]| (in_old.1, in_new.1))
+        .collect();
+    pairs.sort_unstable();
+
+    // Longest increasing run of new positions, by patience sorting: each pair goes on the
+    // leftmost pile whose top is not below it, and remembers the top of the pile to its left
+    let mut piles: Vec<usize> = Vec::new(); // Index in `pairs` of each pile's top
+    let mut previous: Vec<Option<usize>> = vec![None; pairs.len()];
+    for (p, &(_, j)) in pairs.iter().enumerate() {
+        let pile = piles.partition_point(|&top| pairs[top].1 < j);
+        if pile > 0 {
+            previous[p] = Some(piles[pile - 1]);
+        }
+        if pile == piles.len() {
+            piles.push(p);
+        } else {
+            piles[pile] = p;
+        }
+    }
+    let mut run = Vec::with_capacity(piles.len());
+    let mut at = piles.last().copied();
+    while let Some(p) = at {
+        run.push(pairs[p]);
+        at = previous[p];
+    }
+    run.reverse();
+    run
+}
@@ -0,0 +1,441 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::utf8::{self, DecodeError, Mode, Utf8Decoder};
+
+// --- ENCODINGS ---
+// Everything else in this crate takes `&str`, which is always UTF-8. Files and network peers do
+// not always send UTF-8: Windows tools write UTF-16 and old documents use single-byte code
+// pages. This module converts between those and UTF-8, in one go or chunk by chunk:
+//   let found = encoding::detect(&bytes);                          // BOM, then heuristics
+//   let text = encoding::decode(&bytes, found.encoding, Policy::Strict)?;
+//   let bytes = encoding::encode("naïve €5", Encoding::Windows1252, Policy::Replace)?;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Latin1,      // ISO-8859-1: each byte is the char with the same number, U+0000..U+00FF
+    Windows1252, // Latin-1 with printable chars (€, “ ”, —, ...) in place of the C1 controls
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Latin1 => "ISO-8859-1",
+            Encoding::Windows1252 => "windows-1252",
+        }
+    }
+
+    // The byte order mark that can start a file in this encoding (none for single-byte ones)
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            Encoding::Utf8 => b"\xEF\xBB\xBF",
+            Encoding::Utf16Le => b"\xFF\xFE",
+            Encoding::Utf16Be => b"\xFE\xFF",
+            Encoding::Latin1 | Encoding::Windows1252 => b"",
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    Strict,  // Stop at the first error
+    Replace, // U+FFFD for bytes that cannot be decoded, '?' for chars that cannot be encoded
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    Invalid { at: u64, len: usize }, // Decoding: the `len` bytes at input offset `at` are malformed
+    Truncated { at: u64 },           // Decoding: the input ends inside the char starting at `at`
+    Unmappable { at: u64, c: char }, // Encoding: `c`, at byte `at` of the text, has no code
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodingError::Invalid { at, len } => {
+                write!(f, "{len} invalid byte(s) at offset {at}")
+            }
+            EncodingError::Truncated { at } => {
+                write!(f, "input ends inside the character at offset {at}")
+            }
+            EncodingError::Unmappable { at, c } => {
+                write!(f, "{c:?} at byte {at} cannot be encoded")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+impl From<DecodeError> for EncodingError {
+    fn from(e: DecodeError) -> EncodingError {
+        match e {
+            DecodeError::Invalid { at, len } => EncodingError::Invalid { at, len },
+            DecodeError::Truncated { at } => EncodingError::Truncated { at },
+        }
+    }
+}
+
+// --- WINDOWS-1252 ---
+// Bytes 0x80..0x9F. The five holes (0x81, 0x8D, 0x8F, 0x90, 0x9D) are undefined in Microsoft's
+// table, so they are decoding errors; every other byte is the same as in Latin-1.
+#[rustfmt::skip]
+const WINDOWS_1252: [Option<char>; 32] = [
+    Some('€'), None,      Some('‚'), Some('ƒ'), Some('„'), Some('…'), Some('†'), Some('‡'),
+    Some('ˆ'), Some('‰'), Some('Š'), Some('‹'), Some('Œ'), None,      Some('Ž'), None,
+    None,      Some('‘'), Some('’'), Some('“'), Some('”'), Some('•'), Some('–'), Some('—'),
+    Some('˜'), Some('™'), Some('š'), Some('›'), Some('œ'), None,      Some('ž'), Some('Ÿ'),
+];
+
+fn windows_1252_char(b: u8) -> Option<char> {
+    match b {
+        0x80..=0x9F => WINDOWS_1252[usize::from(b - 0x80)],
+        _ => Some(char::from(b)),
+    }
+}
+
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match u32::from(c) {
+        0x00..=0x7F | 0xA0..=0xFF => u8::try_from(c).ok(),
+        _ => WINDOWS_1252
+            .iter()
+            .position(|&entry| entry == Some(c))
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+// --- DETECTION ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detected {
+    pub encoding: Encoding,
+    pub bom_len: usize, // Bytes of byte order mark at the start; decode skips them itself
+    pub certain: bool,  // Found from a BOM, not guessed
+}
+
+// Guesses the encoding of `bytes`, the start of a file or stream (a few KB is plenty):
+//   1. A byte order mark settles it.
+//   2. Text that is valid UTF-8 is UTF-8: other encodings almost never produce valid multi-byte
+//      sequences by chance, and pure ASCII is valid in all of them anyway.
+//   3. UTF-16 without a BOM shows up as zero bytes: ASCII text in UTF-16LE has a zero at every
+//      odd offset, in UTF-16BE at every even one.
+//   4. Anything else is a single-byte encoding: Windows-1252 if it uses bytes 0x80..0x9F, which
+//      are invisible control codes in Latin-1 and so almost never appear in real Latin-1 text.
+pub fn detect(bytes: &[u8]) -> Detected {
+    for encoding in [Encoding::Utf8, Encoding::Utf16Le, Encoding::Utf16Be] {
+        if bytes.starts_with(encoding.bom()) {
+            return Detected {
+                encoding,
+                bom_len: encoding.bom().len(),
+                certain: true,
+            };
+        }
+    }
+    let guess = |encoding| Detected {
+        encoding,
+        bom_len: 0,
+        certain: false,
+    };
+    let pairs = bytes.len() / 2;
+    let zeros_at = |parity| {
+        bytes
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .take(pairs)
+            .filter(|&&b| b == 0)
+            .count()
+    };
+    let (even_zeros, odd_zeros) = (zeros_at(0), zeros_at(1));
+    // A sample cut in the middle of a char is still UTF-8
+    let utf8 = matches!(
+        utf8::validate(bytes),
+        Ok(()) | Err(DecodeError::Truncated { .. })
+    );
+    if utf8 && even_zeros + odd_zeros == 0 {
+        return guess(Encoding::Utf8);
+    }
+    if pairs > 0 && odd_zeros * 10 >= pairs * 4 && even_zeros * 10 < pairs {
+        return guess(Encoding::Utf16Le);
+    }
+    if pairs > 0 && even_zeros * 10 >= pairs * 4 && odd_zeros * 10 < pairs {
+        return guess(Encoding::Utf16Be);
+    }
+    if utf8 {
+        return guess(Encoding::Utf8); // With a few NULs, but not UTF-16
+    }
+    if bytes.iter().any(|b| (0x80..=0x9F).contains(b)) {
+        guess(Encoding::Windows1252)
+    } else {
+        guess(Encoding::Latin1)
+    }
+}
+
+// --- ONE-SHOT CONVERSION ---
+// `bytes` in `encoding` as UTF-8 text, without a leading BOM. Borrows `bytes` when they are
+// already the text: valid UTF-8, or pure ASCII in a single-byte encoding.
+pub fn decode(
+    bytes: &[u8],
+    encoding: Encoding,
+    policy: Policy,
+) -> Result<Cow<'_, str>, EncodingError> {
+    let mut decoder = Decoder::new(encoding, policy);
+    let text = decoder.decode(bytes)?;
+    let end = decoder.finish()?;
+    if end.is_empty() {
+        return Ok(text);
+    }
+    Ok(Cow::Owned(text.into_owned() + end))
+}
+
+// Detects the encoding, then decodes
+pub fn decode_detected(
+    bytes: &[u8],
+    policy: Policy,
+) -> Result<(Cow<'_, str>, Encoding), EncodingError> {
+    let found = detect(bytes);
+    Ok((decode(bytes, found.encoding, policy)?, found.encoding))
+}
+
+// `text` in `encoding`, without a BOM (prepend `encoding.bom()` for one). Borrows the text's
+// own bytes for UTF-8, and for pure ASCII in a single-byte encoding.
+pub fn encode(
+    text: &str,
+    encoding: Encoding,
+    policy: Policy,
+) -> Result<Cow<'_, [u8]>, EncodingError> {
+    let single_byte = matches!(encoding, Encoding::Latin1 | Encoding::Windows1252);
+    if encoding == Encoding::Utf8 || (single_byte && text.is_ascii()) {
+        return Ok(Cow::Borrowed(text.as_bytes()));
+    }
+    let mut out = Vec::new();
+    Encoder::new(encoding, policy).encode(text, &mut out)?;
+    Ok(Cow::Owned(out))
+}
+
+// --- STREAMING ---
+// Decodes input that arrives in chunks, which may cut a char (or a UTF-16 code unit) in half.
+// Like a Utf8Decoder, which it uses for UTF-8, it keeps the cut-off start until the next chunk.
+#[derive(Debug, Clone)]
+pub struct Decoder {
+    encoding: Encoding,
+    policy: Policy,
+    utf8: Utf8Decoder,
+    offset: u64,          // Input offset of the next chunk
+    odd_byte: Option<u8>, // UTF-16: the first half of a code unit
+    lead: Option<u16>,    // UTF-16: a lead surrogate waiting for its trail
+    at_start: bool,       // Nothing decoded yet, so a U+FEFF is a BOM
+    failed: Option<EncodingError>,
+}
+
+impl Decoder {
+    pub fn new(encoding: Encoding, policy: Policy) -> Decoder {
+        let mode = match policy {
+            Policy::Strict => Mode::Strict,
+            Policy::Replace => Mode::Lossy,
+        };
+        Decoder {
+            encoding,
+            policy,
+            utf8: Utf8Decoder::new(mode),
+            offset: 0,
+            odd_byte: None,
+            lead: None,
+            at_start: true,
+            failed: None,
+        }
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub fn decode<'c>(&mut self, chunk: &'c [u8]) -> Result<Cow<'c, str>, EncodingError> {
+        if let Some(e) = &self.failed {
+            return Err(e.clone());
+        }
+        let decoded = match self.encoding {
+            Encoding::Utf8 => self.utf8.decode(chunk).map_err(EncodingError::from),
+            Encoding::Utf16Le | Encoding::Utf16Be => self.decode_utf16(chunk).map(Cow::Owned),
+            Encoding::Latin1 => Ok(single_byte(chunk, |b| Some(char::from(b)))),
+            Encoding::Windows1252 => self.decode_windows_1252(chunk),
+        };
+        self.offset += chunk.len() as u64;
+        let mut text = decoded.inspect_err(|e| self.failed = Some(e.clone()))?;
+        if self.at_start && !text.is_empty() {
+            self.at_start = false;
+            if text.starts_with('\u{FEFF}') {
+                text = match text {
+                    Cow::Borrowed(s) => Cow::Borrowed(&s[3..]),
+                    Cow::Owned(s) => Cow::Owned(s[3..].to_string()),
+                };
+            }
+        }
+        Ok(text)
+    }
+
+    // Ends the input: an error if it stopped inside a char, or U+FFFD with Policy::Replace
+    pub fn finish(&mut self) -> Result<&'static str, EncodingError> {
+        if let Some(e) = self.failed.take() {
+            return Err(e);
+        }
+        let end = self.utf8.finish()?; // Always "" unless the input is UTF-8
+        // UTF-16: a lone first byte, a lead surrogate without its trail, or a lead and a byte
+        let (odd_byte, lead) = (self.odd_byte.take(), self.lead.take());
+        if odd_byte.is_none() && lead.is_none() {
+            return Ok(end);
+        }
+        let cut = self.offset - u64::from(odd_byte.is_some()) - if lead.is_some() { 2 } else { 0 };
+        match self.policy {
+            Policy::Replace => Ok("\u{FFFD}"),
+            Policy::Strict => Err(EncodingError::Truncated { at: cut }),
+        }
+    }
+
+    fn decode_windows_1252<'c>(&mut self, chunk: &'c [u8]) -> Result<Cow<'c, str>, EncodingError> {
+        let hole = chunk.iter().position(|&b| windows_1252_char(b).is_none());
+        match (hole, self.policy) {
+            (Some(i), Policy::Strict) => Err(EncodingError::Invalid {
+                at: self.offset + i as u64,
+                len: 1,
+            }),
+            _ => Ok(single_byte(chunk, windows_1252_char)),
+        }
+    }
+
+    fn decode_utf16(&mut self, chunk: &[u8]) -> Result<String, EncodingError> {
+        let mut out = String::with_capacity(chunk.len() / 2);
+        let mut rest = chunk;
+        let mut at = self.offset; // Input offset of rest[0]
+        if let Some(first) = self.odd_byte {
+            let Some((&second, tail)) = rest.split_first() else {
+                return Ok(out);
+            };
+            self.odd_byte = None;
+            self.unit(self.code_unit(first, second), at - 1, &mut out)?;
+            rest = tail;
+            at += 1;
+        }
+        let mut pairs = rest.chunks_exact(2);
+        for pair in pairs.by_ref() {
+            self.unit(self.code_unit(pair[0], pair[1]), at, &mut out)?;
+            at += 2;
+        }
+        self.odd_byte = pairs.remainder().first().copied();
+        Ok(out)
+    }
+
+    fn code_unit(&self, first: u8, second: u8) -> u16 {
+        match self.encoding {
+            Encoding::Utf16Be => u16::from_be_bytes([first, second]),
+            _ => u16::from_le_bytes([first, second]),
+        }
+    }
+
+    // One UTF-16 code unit starting at input offset `at`
+    fn unit(&mut self, unit: u16, at: u64, out: &mut String) -> Result<(), EncodingError> {
+        if let Some(lead) = self.lead.take() {
+            if (0xDC00..0xE000).contains(&unit) {
+                let c = 0x10000 + ((u32::from(lead) - 0xD800) << 10) + (u32::from(unit) - 0xDC00);
+                out.push(char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER));
+                return Ok(());
+            }
+            // The lead has no trail: it is an error, and `unit` starts afresh
+            self.replace(at - 2, 2, out)?;
+        }
+        match unit {
+            0xD800..=0xDBFF => self.lead = Some(unit),
+            0xDC00..=0xDFFF => self.replace(at, 2, out)?,
+            _ => out.push(char::from_u32(u32::from(unit)).unwrap_or(char::REPLACEMENT_CHARACTER)),
+        }
+        Ok(())
+    }
+
+    fn replace(&self, at: u64, len: usize, out: &mut String) -> Result<(), EncodingError> {
+        match self.policy {
+            Policy::Replace => {
+                out.push(char::REPLACEMENT_CHARACTER);
+                Ok(())
+            }
+            Policy::Strict => Err(EncodingError::Invalid { at, len }),
+        }
+    }
+}
+
+// Latin-1 and Windows-1252: one char per byte, U+FFFD for a byte `map` has no char for.
+// ASCII is the same in both and in UTF-8, so an ASCII chunk is borrowed as it is.
+fn single_byte(chunk: &[u8], map: impl Fn(u8) -> Option<char>) -> Cow<'_, str> {
+    match std::str::from_utf8(chunk) {
+        Ok(ascii) if chunk.is_ascii() => Cow::Borrowed(ascii),
+        _ => Cow::Owned(
+            chunk
+                .iter()
+                .map(|&b| map(b).unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+        ),
+    }
+}
+
+// Encodes text that arrives in pieces. A &str always holds whole chars, so nothing carries
+// over between pieces except the offset, which makes errors point into the whole text.
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    encoding: Encoding,
+    policy: Policy,
+    offset: u64,
+}
+
+impl Encoder {
+    pub fn new(encoding: Encoding, policy: Policy) -> Encoder {
+        Encoder {
+            encoding,
+            policy,
+            offset: 0,
+        }
+    }
+
+    // Appends `text` in the encoding to `out`. On an error, `out` holds everything before it.
+    pub fn encode(&mut self, text: &str, out: &mut Vec<u8>) -> Result<(), EncodingError> {
+        match self.encoding {
+            Encoding::Utf8 => out.extend_from_slice(text.as_bytes()),
+            Encoding::Utf16Le => out.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+            Encoding::Utf16Be => out.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+            Encoding::Latin1 => self.single_byte(text, |c| u8::try_from(c).ok(), out)?,
+            Encoding::Windows1252 => self.single_byte(text, windows_1252_byte, out)?,
+        }
+        self.offset += text.len() as u64;
+        Ok(())
+    }
+
+    fn single_byte(
+        &self,
+        text: &str,
+        map: impl Fn(char) -> Option<u8>,
+        out: &mut Vec<u8>,
+    ) -> Result<(), EncodingError> {
+        out.reserve(text.len());
+        for (i, c) in text.char_indices() {
+            match (map(c), self.policy) {
+                (Some(b), _) => out.push(b),
+                (None, Policy::Replace) => out.push(b'?'),
+                (None, Policy::Strict) => {
+                    return Err(EncodingError::Unmappable {
+                        at: self.offset + i as u64,
+                        c,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,536 @@
+use std::borrow::Cow;
+use std::fmt::{self, Display, Write};
+
+use crate::transform;
+
+// --- ESCAPING ---
+// `println!("{}", s)` prints a user's string as it is: a quote ends a JSON string early, a `'`
+// ends a shell argument, a `<` starts an HTML tag. Each format here has a Display adapter that
+// writes the escaped text straight into the formatter (no allocation), an owned conversion, and
+// the reverse, which checks its input:
+//   println!("{}", escape::Json(name));        // "Bob \"the builder\""
+//   let arg = escape::shell_quote(path);       // Cow: only copied when quoting is needed
+//   let name = escape::unescape_json(line)?;   // Err on a malformed escape
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnescapeError {
+    Unquoted,                          // The opening or closing quote is missing
+    Unterminated,                      // The input ends inside a quote or an escape
+    BadEscape { at: usize },           // Unknown or malformed escape sequence at byte `at`
+    Unexpected { at: usize, c: char }, // `c` at byte `at` should have been escaped or quoted
+}
+
+impl fmt::Display for UnescapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnescapeError::Unquoted => write!(f, "missing quote at the start or the end"),
+            UnescapeError::Unterminated => write!(f, "input ends inside a quote or an escape"),
+            UnescapeError::BadEscape { at } => write!(f, "invalid escape sequence at byte {at}"),
+            UnescapeError::Unexpected { at, c } => {
+                write!(f, "unescaped {c:?} at byte {at}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnescapeError {}
+
+// Writes `s` with `escape` writing the replacement of each char that `special` picks out. The
+// runs of plain chars in between go out as single slices of `s`.
+fn write_escaped<W>(
+    out: &mut W,
+    s: &str,
+    special: impl Fn(char) -> bool,
+    mut escape: impl FnMut(&mut W, char) -> fmt::Result,
+) -> fmt::Result
+where
+    W: Write + ?Sized,
+{
+    let mut run_start = 0;
+    for (i, c) in s.char_indices().filter(|&(_, c)| special(c)) {
+        out.write_str(&s[run_start..i])?;
+        escape(out, c)?;
+        run_start = i + c.len_utf8();
+    }
+    out.write_str(&s[run_start..])
+}
+
+// --- JSON ---
+// A JSON string (RFC 8259), quotes included: `"` and `\` are escaped, control characters become
+// \n, \t, ... or \u00XX, everything else is written as it is (JSON text is UTF-8)
+#[derive(Debug, Clone, Copy)]
+pub struct Json<'a>(pub &'a str);
+
+impl Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        let special = |c| matches!(c, '"' | '\\') || c < ' ';
+        write_escaped(f, self.0, special, |f, c| match c {
+            '"' => f.write_str("\\\""),
+            '\\' => f.write_str("\\\\"),
+            '\n' => f.write_str("\\n"),
+            '\r' => f.write_str("\\r"),
+            '\t' => f.write_str("\\t"),
+            '\u{8}' => f.write_str("\\b"),
+            '\u{c}' => f.write_str("\\f"),
+            c => write!(f, "\\u{:04x}", c as u32),
+        })?;
+        f.write_char('"')
+    }
+}
+
+pub fn json(s: &str) -> String {
+    Json(s).to_string()
+}
+
+// The text of a JSON string literal, quotes included: "\"a\\nb\"" → "a\nb". Surrogate pairs
+// (🦀) are joined; a lone surrogate is an error, as it is no char.
+pub fn unescape_json(s: &str) -> Result<String, UnescapeError> {
+    let body = quoted_body(s, '"')?;
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.char_indices().map(|(i, c)| (i + 1, c)).peekable();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '"' => return Err(UnescapeError::Unexpected { at, c }),
+            c if c < ' ' => return Err(UnescapeError::Unexpected { at, c }),
+            '\\' => {
+                let (_, e) = chars.next().ok_or(UnescapeError::Unterminated)?;
+                let bad = UnescapeError::BadEscape { at };
+                out.push(match e {
+                    '"' | '\\' | '/' => e,
+                    'n' => '\n',
+                    'r' => '\r',
+                    't' => '\t',
+                    'b' => '\u{8}',
+                    'f' => '\u{c}',
+                    'u' => {
+                        let high = hex4(&mut chars).ok_or(bad.clone())?;
+                        let code = if (0xD800..0xDC00).contains(&high) {
+                            // A high surrogate must be followed by \u and a low one
+                            let (Some((_, '\\')), Some((_, 'u'))) = (chars.next(), chars.next())
+                            else {
+                                return Err(bad);
+                            };
+                            let low = hex4(&mut chars)
+                                .filter(|low| (0xDC00..0xE000).contains(low))
+                                .ok_or(bad.clone())?;
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        } else {
+                            high
+                        };
+                        char::from_u32(code).ok_or(bad)?
+                    }
+                    _ => return Err(bad),
+                });
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn hex4(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<u32> {
+    (0..4).try_fold(0, |code, _| Some(code * 16 + chars.next()?.1.to_digit(16)?))
+}
+
+// The text between the opening and the closing `quote` of `s`
+fn quoted_body(s: &str, quote: char) -> Result<&str, UnescapeError> {
+    s.strip_prefix(quote)
+        .and_then(|rest| rest.strip_suffix(quote))
+        .ok_or(UnescapeError::Unquoted)
+}
+
+// --- POSIX SHELL ---
+// One shell word that a POSIX shell reads back as exactly `s`: left alone when it only has
+// characters the shell treats literally, otherwise put in single quotes, inside which nothing is
+// special. A `'` cannot appear inside them, so it is written as '\'' (close, escaped quote, open).
+#[derive(Debug, Clone, Copy)]
+pub struct ShellQuoted<'a>(pub &'a str);
+
+impl Display for ShellQuoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_shell_safe(self.0) {
+            return f.write_str(self.0);
+        }
+        f.write_char('\'')?;
+        write_escaped(f, self.0, |c| c == '\'', |f, _| f.write_str("'\\''"))?;
+        f.write_char('\'')
+    }
+}
+
+// The characters Python's shlex.quote leaves unquoted: none of them means anything to the shell
+fn is_shell_safe(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"@%+=:,./_-".contains(&b))
+}
+
+pub fn shell_quote(s: &str) -> Cow<'_, str> {
+    if is_shell_safe(s) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(ShellQuoted(s).to_string())
+    }
+}
+
+// Reads one shell word the way a POSIX shell would: 'single quotes', "double quotes" and
+// backslash escapes, in any mix. Anything the shell would split on or expand ($, `, *, spaces,
+// ...) must be quoted, since running a shell is the only way to know what it would turn into.
+// Note that a command's arguments cannot contain '\0': the quoting round-trips it, a shell won't.
+pub fn shell_unquote(s: &str) -> Result<String, UnescapeError> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.char_indices();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next().ok_or(UnescapeError::Unterminated)? {
+                    (_, '\'') => break,
+                    (_, c) => out.push(c),
+                }
+            },
+            '"' => loop {
+                match chars.next().ok_or(UnescapeError::Unterminated)? {
+                    (_, '"') => break,
+                    (_, '\\') => match chars.next().ok_or(UnescapeError::Unterminated)? {
+                        (_, '\n') => {} // Line continuation
+                        (_, c @ ('$' | '`' | '"' | '\\')) => out.push(c),
+                        (_, c) => {
+                            out.push('\\');
+                            out.push(c);
+                        }
+                    },
+                    (at, c @ ('$' | '`')) => return Err(UnescapeError::Unexpected { at, c }),
+                    (_, c) => out.push(c),
+                }
+            },
+            '\\' => match chars.next().ok_or(UnescapeError::Unterminated)? {
+                (_, '\n') => {}
+                (_, c) => out.push(c),
+            },
+            c if c.is_ascii_whitespace() || "|&;<>()$`*?[#~".contains(c) => {
+                return Err(UnescapeError::Unexpected { at, c });
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+// --- CSV (RFC 4180) ---
+// A CSV field: quoted when it holds a comma, a quote or a line break, with each `"` doubled
+#[derive(Debug, Clone, Copy)]
+pub struct CsvField<'a>(pub &'a str);
+
+impl Display for CsvField<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !needs_csv_quotes(self.0) {
+            return f.write_str(self.0);
+        }
+        f.write_char('"')?;
+        write_escaped(f, self.0, |c| c == '"', |f, _| f.write_str("\"\""))?;
+        f.write_char('"')
+    }
+}
+
+fn needs_csv_quotes(s: &str) -> bool {
+    s.contains([',', '"', '\r', '\n'])
+}
+
+pub fn csv_field(s: &str) -> Cow<'_, str> {
+    if needs_csv_quotes(s) {
+        Cow::Owned(CsvField(s).to_string())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+// A whole record: the fields joined with ',' (without the line break that ends it). A record of
+// one empty field is written "" so that it is not an empty line, which readers may skip.
+#[derive(Debug, Clone, Copy)]
+pub struct CsvRecord<'a, S>(pub &'a [S]);
+
+impl<S: AsRef<str>> Display for CsvRecord<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [only] = self.0
+            && only.as_ref().is_empty()
+        {
+            return f.write_str("\"\"");
+        }
+        for (i, field) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_char(',')?;
+            }
+            write!(f, "{}", CsvField(field.as_ref()))?;
+        }
+        Ok(())
+    }
+}
+
+// The fields of one record. Quoted fields may hold line breaks; `record` is everything up to the
+// line break that ends it, without that line break.
+pub fn parse_csv_record(record: &str) -> Result<Vec<String>, UnescapeError> {
+    let mut fields = Vec::new();
+    let mut chars = record.char_indices().peekable();
+    loop {
+        let mut field = String::new();
+        if chars.next_if(|&(_, c)| c == '"').is_some() {
+            loop {
+                match chars.next().ok_or(UnescapeError::Unterminated)? {
+                    (_, '"') if chars.next_if(|&(_, c)| c == '"').is_some() => field.push('"'),
+                    (_, '"') => break,
+                    (_, c) => field.push(c),
+                }
+            }
+            // After the closing quote, only the end of the field may follow
+            if let Some(&(at, c)) = chars.peek()
+                && c != ','
+            {
+                return Err(UnescapeError::Unexpected { at, c });
+            }
+        } else {
+            while let Some(&(at, c)) = chars.peek() {
+                match c {
+                    ',' => break,
+                    '"' | '\r' | '\n' => return Err(UnescapeError::Unexpected { at, c }),
+                    c => field.push(c),
+                }
+                chars.next();
+            }
+        }
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(fields); // No ',' after this field: it was the last
+        }
+    }
+}
+
+// A single field, quoted or not
+pub fn parse_csv_field(field: &str) -> Result<String, UnescapeError> {
+    let mut fields = parse_csv_record(field)?;
+    if fields.len() > 1 {
+        let at = field.rfind(',').unwrap_or(0);
+        return Err(UnescapeError::Unexpected { at, c: ',' });
+    }
+    Ok(fields.pop().unwrap_or_default())
+}
+
+// --- HTML ---
+// Text between tags: & < > escaped. Attribute values: both quote kinds too, so the value is safe
+// inside "..." or '...'. Neither makes a string safe inside <script> or <style>, or in a URL.
+const HTML_TEXT: &[(char, &str)] = &[('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;")];
+const HTML_ATTR: &[(char, &str)] = &[
+    ('&', "&amp;"),
+    ('<', "&lt;"),
+    ('>', "&gt;"),
+    ('"', "&quot;"),
+    ('\'', "&#39;"),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlText<'a>(pub &'a str);
+
+#[derive(Debug, Clone, Copy)]
+pub struct HtmlAttr<'a>(pub &'a str);
+
+impl Display for HtmlText<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_table(f, self.0, HTML_TEXT)
+    }
+}
+
+impl Display for HtmlAttr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_table(f, self.0, HTML_ATTR)
+    }
+}
+
+fn write_table(f: &mut fmt::Formatter<'_>, s: &str, table: &[(char, &str)]) -> fmt::Result {
+    let escape_of = |c| {
+        table
+            .iter()
+            .find(|&&(from, _)| from == c)
+            .map(|&(_, to)| to)
+    };
+    write_escaped(
+        f,
+        s,
+        |c| escape_of(c).is_some(),
+        |f, c| f.write_str(escape_of(c).unwrap_or_default()),
+    )
+}
+
+pub fn html_text(s: &str) -> Cow<'_, str> {
+    transform::escape(s, HTML_TEXT)
+}
+
+pub fn html_attr(s: &str) -> Cow<'_, str> {
+    transform::escape(s, HTML_ATTR)
+}
+
+// Decodes character references: numeric ones (&#233; &#xE9;) and the named ones these escapes
+// produce, plus &apos; and &nbsp;. Like a browser, it leaves anything else as it is; numbers
+// that are no char (0, surrogates, past U+10FFFF) become U+FFFD. The full list of names
+// (over 2000) is out of scope.
+pub fn unescape_html(s: &str) -> Cow<'_, str> {
+    if !s.contains('&') {
+        return Cow::Borrowed(s);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        match decode_reference(rest) {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+// The char a reference at the start of `s` stands for, and its length in bytes
+fn decode_reference(s: &str) -> Option<(char, usize)> {
+    // Names are short ASCII; bytes, because cutting the &str at 12 could split a char
+    let end = s.bytes().take(12).position(|b| b == b';')?;
+    let name = &s[1..end];
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{A0}',
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => number.parse(),
+            };
+            // from_str_radix accepts a sign; a reference does not
+            if number.contains('+') {
+                return None;
+            }
+            let code = code.ok()?;
+            char::from_u32(code)
+                .filter(|&c| c != '\0')
+                .unwrap_or('\u{FFFD}')
+        }
+    };
+    Some((c, end + 1))
+}
+
+// --- RUST STRING LITERALS ---
+// A literal that compiles to `s`: like `{:?}`, but only escapes what it must (\\, \", control
+// characters) plus the invisible characters that reorder or hide text in an editor (bidi
+// controls, zero-width space, BOM: the "Trojan Source" characters). Everything else, accents and
+// emoji included, is written as it is.
+#[derive(Debug, Clone, Copy)]
+pub struct RustStr<'a>(pub &'a str);
+
+impl Display for RustStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('"')?;
+        let special = |c| matches!(c, '"' | '\\') || c.is_control() || is_invisible(c);
+        write_escaped(f, self.0, special, |f, c| match c {
+            '"' => f.write_str("\\\""),
+            '\\' => f.write_str("\\\\"),
+            '\n' => f.write_str("\\n"),
+            '\r' => f.write_str("\\r"),
+            '\t' => f.write_str("\\t"),
+            '\0' => f.write_str("\\0"),
+            c => write!(f, "\\u{{{:x}}}", c as u32),
+        })?;
+        f.write_char('"')
+    }
+}
+
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{61C}' // Arabic letter mark
+            | '\u{200B}' // Zero-width space
+            | '\u{200E}' | '\u{200F}' // Left-to-right and right-to-left marks
+            | '\u{202A}'..='\u{202E}' // Embeddings and overrides
+            | '\u{2066}'..='\u{2069}' // Isolates
+            | '\u{FEFF}' // Byte order mark
+    )
+}
+
+pub fn rust_str(s: &str) -> String {
+    RustStr(s).to_string()
+}
+
+// The value of a "..." literal (not a raw r"..." one), with every escape Rust has: \n \r \t \\
+// \0 \' \" \x00-\x7F, \u{...} and a backslash at the end of a line, which skips the line break
+// and the whitespace after it.
+pub fn unescape_rust(s: &str) -> Result<String, UnescapeError> {
+    let body = quoted_body(s, '"')?;
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.char_indices().map(|(i, c)| (i + 1, c)).peekable();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '"' => return Err(UnescapeError::Unexpected { at, c }),
+            // A CR must be part of CRLF (which stays in the string as it is)
+            '\r' if chars.peek().map(|&(_, c)| c) != Some('\n') => {
+                return Err(UnescapeError::Unexpected { at, c });
+            }
+            '\\' => {
+                let (_, e) = chars.next().ok_or(UnescapeError::Unterminated)?;
+                let bad = UnescapeError::BadEscape { at };
+                match e {
+                    'n' => out.push('\n'),
+                    'r' => out.push('\r'),
+                    't' => out.push('\t'),
+                    '0' => out.push('\0'),
+                    '\\' | '\'' | '"' => out.push(e),
+                    'x' => {
+                        let digits = [chars.next(), chars.next()];
+                        let [Some((_, hi)), Some((_, lo))] = digits else {
+                            return Err(bad);
+                        };
+                        let code = hi.to_digit(8).zip(lo.to_digit(16)).ok_or(bad)?;
+                        out.push(char::from(code.0 as u8 * 16 + code.1 as u8));
+                    }
+                    'u' => out.push(unicode_escape(&mut chars).ok_or(bad)?),
+                    '\n' | '\r' => {
+                        while chars
+                            .next_if(|&(_, c)| matches!(c, ' ' | '\t' | '\n' | '\r'))
+                            .is_some()
+                        {}
+                    }
+                    _ => return Err(bad),
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+// The `{1F980}` part of \u{1F980}: 1 to 6 hex digits, underscores allowed after the first
+fn unicode_escape(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<char> {
+    if chars.next()?.1 != '{' {
+        return None;
+    }
+    let (mut code, mut digits) = (0u32, 0);
+    loop {
+        match chars.next()?.1 {
+            '}' if digits > 0 => return char::from_u32(code),
+            '_' if digits > 0 => {}
+            c => {
+                code = code * 16 + c.to_digit(16)?;
+                digits += 1;
+                if digits > 6 {
+                    return None;
+                }
+            }
+        }
+    }
+}
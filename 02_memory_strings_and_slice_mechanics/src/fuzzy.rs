@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod bitap; // Approximate substring search: matches with at most k edits, one pass over the text
+pub mod bktree; // BK-tree: words indexed by edit distance, for "did you mean" lookups
+
+pub use bitap::{ApproxMatch, Bitap, BitapError};
+pub use bktree::BkTree;
+
+// --- FUZZY MATCHING ---
+// find_substr_pos in main.rs only knows "present" or "not present". These measure how far apart
+// two strings are, so a miss can come with a "did you mean". Every measure works on grapheme
+// clusters, not bytes or chars: "é" written as e + U+0301 is one letter to a reader, so
+// changing it into "e" is one edit, and "नमस्ते" is three letters, not six chars.
+
+fn graphemes(s: &str) -> Vec<&str> {
+    s.graphemes(true).collect()
+}
+
+// --- EDIT DISTANCES ---
+// Levenshtein distance: the fewest insertions, deletions and substitutions that turn `a` into
+// `b`. levenshtein("kitten", "sitting") == 3. O(|a|·|b|) time, O(|b|) memory.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    // In ASCII every byte is a grapheme, except "\r\n", which is one
+    let plain = |s: &str| s.is_ascii() && !s.contains('\r');
+    if plain(a) && plain(b) {
+        return edit_distance(a.as_bytes(), b.as_bytes());
+    }
+    edit_distance(&graphemes(a), &graphemes(b))
+}
+
+pub(crate) fn edit_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    // row[j] is the distance between the part of `a` seen so far and b[..j]
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0]; // Distance for a[..i] and b[..j], before row[j] is overwritten
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(x != y);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// Damerau–Levenshtein distance: Levenshtein plus swapping two neighbours as one edit, the most
+// common typo ("teh" → "the" is 1, not 2). This is the true distance, where a swapped pair may
+// still be edited afterwards ("ca" → "abc" is 2), not the restricted "optimal string alignment"
+// variant (3 there), which breaks the triangle inequality and so cannot drive a BK-tree.
+// Lowrance–Wagner algorithm: O(|a|·|b|) time and memory.
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let (a, b) = (graphemes(a), graphemes(b));
+    let (rows, cols) = (a.len() + 2, b.len() + 2);
+    let far = a.len() + b.len(); // More than any real distance: the border cells never win
+    // d[(i + 1) * cols + (j + 1)] is the distance between a[..i] and b[..j]
+    let mut d = vec![far; rows * cols];
+    for i in 0..=a.len() {
+        d[(i + 1) * cols + 1] = i;
+    }
+    for j in 0..=b.len() {
+        d[cols + j + 1] = j;
+    }
+    let mut last_row: HashMap<&str, usize> = HashMap::new(); // Last row of `a` holding a grapheme
+    for i in 1..=a.len() {
+        let mut last_col = 0; // Last column in this row where a[i - 1] matched
+        for j in 1..=b.len() {
+            let k = last_row.get(b[j - 1]).copied().unwrap_or(0);
+            let l = last_col;
+            let cost = if a[i - 1] == b[j - 1] {
+                last_col = j;
+                0
+            } else {
+                1
+            };
+            // Swap a[k - 1] with a[i - 1], deleting what was between them in `a` and inserting
+            // what is between them in `b`
+            let transposition = d[k * cols + l] + (i - k - 1) + 1 + (j - l - 1);
+            d[(i + 1) * cols + j + 1] = (d[i * cols + j] + cost)
+                .min(d[(i + 1) * cols + j] + 1)
+                .min(d[i * cols + j + 1] + 1)
+                .min(transposition);
+        }
+        last_row.insert(a[i - 1], i);
+    }
+    d[rows * cols - 1]
+}
+
+// --- SIMILARITY SCORES ---
+// From 0.0 (nothing in common) to 1.0 (equal); two empty strings are equal.
+
+// Jaro similarity: counts graphemes the two strings share within a window of each other's
+// position, minus half the shared ones that come in a different order. Made for short strings
+// such as names, where it ranks typos better than edit distance does.
+pub fn jaro(a: &str, b: &str) -> f64 {
+    let (a, b) = (graphemes(a), graphemes(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_taken = vec![false; b.len()];
+    let mut a_matches = Vec::new(); // Matched graphemes of `a`, in order
+    for (i, x) in a.iter().enumerate() {
+        let range = i.saturating_sub(window)..(i + window + 1).min(b.len());
+        if let Some(j) = range.into_iter().find(|&j| !b_taken[j] && b[j] == *x) {
+            b_taken[j] = true;
+            a_matches.push(*x);
+        }
+    }
+    if a_matches.is_empty() {
+        return 0.0;
+    }
+    let b_matches = b.iter().zip(&b_taken).filter(|&(_, &taken)| taken);
+    let out_of_order = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|&(x, (y, _))| x != y)
+        .count();
+    let m = a_matches.len() as f64;
+    let transpositions = out_of_order as f64 / 2.0;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions) / m) / 3.0
+}
+
+// Jaro–Winkler similarity: Jaro, raised for strings that start the same way (up to 4 graphemes
+// of common prefix, each closing a tenth of the remaining gap), since typos are rarer at the
+// start of a word. jaro_winkler("MARTHA", "MARHTA") ≈ 0.961
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let jaro = jaro(a, b);
+    let prefix = a
+        .graphemes(true)
+        .zip(b.graphemes(true))
+        .take(4)
+        .take_while(|(x, y)| x == y)
+        .count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+// N-gram similarity: the Sørensen–Dice coefficient of the two strings' n-grams (runs of `n`
+// graphemes, counted with repeats): twice the shared n-grams over the total. Word order matters
+// little, so it suits longer strings where edit distance would punish a moved word. A string
+// shorter than `n` counts as one n-gram: itself.
+pub fn ngram_similarity(a: &str, b: &str, n: usize) -> f64 {
+    assert!(n > 0, "n-grams need n > 0");
+    let (a, b) = (graphemes(a), graphemes(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let mut counts: HashMap<&[&str], (usize, usize)> = HashMap::new();
+    for gram in ngrams(&a, n) {
+        counts.entry(gram).or_default().0 += 1;
+    }
+    for gram in ngrams(&b, n) {
+        counts.entry(gram).or_default().1 += 1;
+    }
+    let shared: usize = counts.values().map(|&(in_a, in_b)| in_a.min(in_b)).sum();
+    let total: usize = counts.values().map(|&(in_a, in_b)| in_a + in_b).sum();
+    2.0 * shared as f64 / total as f64
+}
+
+fn ngrams<'a, 's>(s: &'a [&'s str], n: usize) -> impl Iterator<Item = &'a [&'s str]> {
+    let short = (!s.is_empty() && s.len() < n).then_some(s);
+    s.windows(n).chain(short)
+}
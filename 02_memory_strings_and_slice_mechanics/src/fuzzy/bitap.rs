@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+// --- BITAP (WU–MANBER) ---
+// Finds where the pattern occurs in a text with at most `max_edits` insertions, deletions or
+// substitutions, reading each grapheme of the text once. For each number of edits d it keeps one
+// u64 whose bit i says "the first i + 1 graphemes of the pattern match the text just read, with
+// at most d edits"; one grapheme of text updates all of them with a few shifts and ORs, so the
+// search costs O(text × (max_edits + 1)), whatever the pattern. The price: at most 64 graphemes
+// of pattern, one per bit.
+#[derive(Debug, Clone)]
+pub struct Bitap {
+    pattern: Vec<String>,        // Graphemes, for finding where a match starts
+    masks: HashMap<String, u64>, // Bit i set in masks[g] if pattern[i] == g
+    max_edits: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitapError {
+    EmptyPattern,                        // An empty pattern would match everywhere
+    PatternTooLong { graphemes: usize }, // More than 64 graphemes: one bit each in a u64
+}
+
+impl fmt::Display for BitapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BitapError::EmptyPattern => write!(f, "the pattern is empty"),
+            BitapError::PatternTooLong { graphemes } => {
+                write!(f, "the pattern has {graphemes} graphemes; at most 64 fit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BitapError {}
+
+// Byte offsets of a match in the text, and how many edits turn the pattern into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApproxMatch {
+    pub start: usize,
+    pub end: usize,
+    pub edits: usize,
+}
+
+impl Bitap {
+    // `max_edits` is capped at one less than the pattern length: with as many edits as the
+    // pattern has graphemes, deleting all of it would "match" anywhere
+    pub fn new(pattern: &str, max_edits: usize) -> Result<Bitap, BitapError> {
+        let pattern: Vec<String> = pattern.graphemes(true).map(str::to_string).collect();
+        if pattern.is_empty() {
+            return Err(BitapError::EmptyPattern);
+        }
+        if pattern.len() > 64 {
+            return Err(BitapError::PatternTooLong {
+                graphemes: pattern.len(),
+            });
+        }
+        let mut masks: HashMap<String, u64> = HashMap::new();
+        for (i, g) in pattern.iter().enumerate() {
+            *masks.entry(g.clone()).or_default() |= 1 << i;
+        }
+        let max_edits = max_edits.min(pattern.len() - 1);
+        Ok(Bitap {
+            pattern,
+            masks,
+            max_edits,
+        })
+    }
+
+    pub fn max_edits(&self) -> usize {
+        self.max_edits
+    }
+
+    // The first match, as find_iter would report it
+    pub fn find(&self, text: &str) -> Option<ApproxMatch> {
+        self.find_iter(text).next()
+    }
+
+    // Non-overlapping matches, left to right. Where a match ends, the ones ending in the next
+    // few graphemes are usually the same match with more or fewer edits ("wrld" in "world" ends
+    // after "worl" with 2 edits, after "world" with 1), so of the ends within `d` graphemes of
+    // the first one (d: its edits) the one with the fewest edits wins, and the earliest on a
+    // tie. Its start is the one that gives those edits with the longest match.
+    pub fn find_iter<'t>(&self, text: &'t str) -> ApproxMatches<'_, 't> {
+        ApproxMatches {
+            bitap: self,
+            graphemes: text.grapheme_indices(true).collect(),
+            next: 0,
+        }
+    }
+
+    // Reads one grapheme of text into the states (state[d]: matches with at most d edits)
+    fn step(&self, state: &mut [u64], grapheme: &str) {
+        let mask = self.masks.get(grapheme).copied().unwrap_or(0);
+        // Shifting in a 1 lets a match start at this grapheme
+        let mut before = state[0]; // state[d - 1] before this grapheme
+        state[0] = ((state[0] << 1) | 1) & mask;
+        for d in 1..state.len() {
+            let old = state[d];
+            state[d] = (((old << 1) | 1) & mask) // The grapheme matches the next pattern one
+                | before // It is an extra grapheme in the text (insertion)
+                | (before << 1) | 1 // It replaces the next pattern grapheme (substitution)
+                | (state[d - 1] << 1); // The next pattern grapheme is missing (deletion)
+            before = old;
+        }
+    }
+
+    // Edits of the best match ending just here, if there is one
+    fn ending_here(&self, state: &[u64]) -> Option<usize> {
+        let last = 1 << (self.pattern.len() - 1);
+        state.iter().position(|&bits| bits & last != 0)
+    }
+
+    // Where the match ending at grapheme `end` (exclusive) starts, no earlier than `floor`:
+    // the edit distance between the pattern and text[start..end] for every start, walking back
+    fn start_of(&self, graphemes: &[(usize, &str)], floor: usize, end: usize) -> (usize, usize) {
+        let m = self.pattern.len();
+        // column[i]: distance between the last i pattern graphemes and the text read backwards
+        let mut column: Vec<usize> = (0..=m).collect();
+        let (mut best_start, mut best_edits) = (end, m);
+        for start in (floor..end).rev() {
+            let g = graphemes[start].1;
+            let mut diagonal = column[0];
+            column[0] += 1;
+            for i in 1..=m {
+                let substitution = diagonal + usize::from(self.pattern[m - i] != g);
+                diagonal = column[i];
+                column[i] = substitution.min(column[i - 1] + 1).min(diagonal + 1);
+            }
+            if column[m] <= best_edits {
+                (best_start, best_edits) = (start, column[m]);
+            }
+        }
+        (best_start, best_edits)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApproxMatches<'b, 't> {
+    bitap: &'b Bitap,
+    graphemes: Vec<(usize, &'t str)>,
+    next: usize, // Grapheme where the search goes on: right after the last match
+}
+
+impl Iterator for ApproxMatches<'_, '_> {
+    type Item = ApproxMatch;
+
+    fn next(&mut self) -> Option<ApproxMatch> {
+        let bitap = self.bitap;
+        // Start fresh after the last match, so matches cannot overlap. Before any text, the
+        // first d pattern graphemes match with d deletions.
+        let mut state: Vec<u64> = (0..=bitap.max_edits).map(|d| (1 << d) - 1).collect();
+        let mut first: Option<(usize, usize)> = None; // (grapheme, edits) of the first end
+        let mut best: Option<(usize, usize)> = None;
+        for i in self.next..self.graphemes.len() {
+            bitap.step(&mut state, self.graphemes[i].1);
+            if let Some(edits) = bitap.ending_here(&state) {
+                first.get_or_insert((i, edits));
+                if best.is_none_or(|(_, fewest)| edits < fewest) {
+                    best = Some((i, edits));
+                }
+            }
+            if let (Some((at, edits)), Some((_, fewest))) = (first, best)
+                && (i >= at + edits || fewest == 0)
+            {
+                break;
+            }
+        }
+        let Some((last, _)) = best else {
+            self.next = self.graphemes.len();
+            return None;
+        };
+        let end = last + 1;
+        // A match with d edits spans at most pattern length + d graphemes
+        let floor = end
+            .saturating_sub(bitap.pattern.len() + bitap.max_edits)
+            .max(self.next);
+        let (start, edits) = bitap.start_of(&self.graphemes, floor, end);
+        self.next = end;
+        let (end_byte, g) = self.graphemes[last];
+        Some(ApproxMatch {
+            start: self.graphemes[start].0,
+            end: end_byte + g.len(),
+            edits,
+        })
+    }
+}
@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+
+use super::{jaro_winkler, levenshtein};
+
+// --- BK-TREE ---
+// Comparing a misspelled word against every word of a dictionary costs one edit distance per
+// word. A BK-tree (Burkhard–Keller) hangs each word under the first word already in the tree,
+// on the branch labelled with their distance. Looking for words within `r` of a query at
+// distance `d` from a node, the triangle inequality says only the branches labelled d - r to
+// d + r can hold any, so for small `r` most of the tree is never visited.
+//
+// The distance must be a metric (zero only for equal strings, symmetric, triangle inequality):
+// levenshtein and damerau_levenshtein are; the "optimal string alignment" distance is not.
+#[derive(Debug, Clone)]
+pub struct BkTree {
+    nodes: Vec<Node>, // nodes[0] is the root; children point into the same Vec
+    distance: fn(&str, &str) -> usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    word: String,
+    children: Vec<(usize, usize)>, // (distance to this word, index of the child node)
+}
+
+impl Default for BkTree {
+    fn default() -> BkTree {
+        BkTree::new()
+    }
+}
+
+impl BkTree {
+    // Words compared by Levenshtein distance over graphemes
+    pub fn new() -> BkTree {
+        BkTree::with_distance(levenshtein)
+    }
+
+    pub fn with_distance(distance: fn(&str, &str) -> usize) -> BkTree {
+        BkTree {
+            nodes: Vec::new(),
+            distance,
+        }
+    }
+
+    // Adds `word`; false if it was already there
+    pub fn insert(&mut self, word: &str) -> bool {
+        let new = self.nodes.len();
+        if new == 0 {
+            self.nodes.push(Node::new(word));
+            return true;
+        }
+        let mut at = 0;
+        loop {
+            let d = (self.distance)(word, &self.nodes[at].word);
+            if d == 0 {
+                return false;
+            }
+            match self.nodes[at]
+                .children
+                .iter()
+                .find(|&&(label, _)| label == d)
+            {
+                Some(&(_, child)) => at = child,
+                None => {
+                    self.nodes[at].children.push((d, new));
+                    self.nodes.push(Node::new(word));
+                    return true;
+                }
+            }
+        }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        !self.find(word, 0).is_empty()
+    }
+
+    // Every word within `max_distance` of `query`, closest first (then alphabetical)
+    pub fn find(&self, query: &str, max_distance: usize) -> Vec<(&str, usize)> {
+        let mut found = Vec::new();
+        let mut stack = self.root();
+        while let Some(at) = stack.pop() {
+            let node = &self.nodes[at];
+            let d = (self.distance)(query, &node.word);
+            if d <= max_distance {
+                found.push((node.word.as_str(), d));
+            }
+            let range = d.saturating_sub(max_distance)..=d + max_distance;
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|(label, _)| range.contains(label))
+                    .map(|&(_, child)| child),
+            );
+        }
+        found.sort_unstable_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+        found
+    }
+
+    // The closest word (the alphabetically first of the closest, on a tie). The search radius
+    // shrinks to the best distance found so far, so it visits far less than the whole tree.
+    pub fn nearest(&self, query: &str) -> Option<(&str, usize)> {
+        let mut best: Option<(&str, usize)> = None;
+        let mut stack = self.root();
+        while let Some(at) = stack.pop() {
+            let node = &self.nodes[at];
+            let d = (self.distance)(query, &node.word);
+            let better = match best {
+                None => true,
+                Some((word, fewest)) => (d, node.word.as_str()) < (fewest, word),
+            };
+            if better {
+                best = Some((&node.word, d));
+            }
+            let radius = best.map_or(usize::MAX, |(_, fewest)| fewest);
+            let range = d.saturating_sub(radius)..=d.saturating_add(radius);
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|(label, _)| range.contains(label))
+                    .map(|&(_, child)| child),
+            );
+        }
+        best
+    }
+
+    // "Did you mean": up to `limit` words within `max_distance` of `query`, other than `query`
+    // itself. Closest first; between words at the same distance, the one with the higher
+    // Jaro–Winkler similarity (same start, letters in the same places) comes first.
+    pub fn suggest(&self, query: &str, max_distance: usize, limit: usize) -> Vec<&str> {
+        let mut found: Vec<(&str, usize, f64)> = self
+            .find(query, max_distance)
+            .into_iter()
+            .filter(|&(_, d)| d > 0)
+            .map(|(word, d)| (word, d, jaro_winkler(query, word)))
+            .collect();
+        found.sort_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then(b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal))
+                .then(a.0.cmp(b.0))
+        });
+        found
+            .into_iter()
+            .take(limit)
+            .map(|(word, ..)| word)
+            .collect()
+    }
+
+    // Number of distinct words
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    // Where every search starts: the root, if there is one
+    fn root(&self) -> Vec<usize> {
+        if self.nodes.is_empty() {
+            Vec::new()
+        } else {
+            vec![0]
+        }
+    }
+
+    // Every word, in the order they were first inserted
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.nodes.iter().map(|node| node.word.as_str())
+    }
+}
+
+impl Node {
+    fn new(word: &str) -> Node {
+        Node {
+            word: word.to_string(),
+            children: Vec::new(),
+        }
+    }
+}
+
+impl<S: AsRef<str>> Extend<S> for BkTree {
+    fn extend<I: IntoIterator<Item = S>>(&mut self, words: I) {
+        for word in words {
+            self.insert(word.as_ref());
+        }
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for BkTree {
+    fn from_iter<I: IntoIterator<Item = S>>(words: I) -> BkTree {
+        let mut tree = BkTree::new();
+        tree.extend(words);
+        tree
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::io::{self, Read, Write};
+use std::sync::RwLock;
+
+// --- SYMBOLS ---
+// A word counter keyed by String allocates one String per distinct word, and one keyed by &str
+// ties the map to the lifetime of the text. An interner stores each distinct string once and
+// hands out a Symbol for it: a u32 that is Copy, hashes and compares in one instruction, and
+// can index a Vec directly, because symbols are numbered 0, 1, 2, ... in the order of interning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    // 0 for the first string interned, 1 for the second, ...: counts can live in a Vec
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// --- INTERNER ---
+// All strings live end to end in one String (the arena), so interning a new string allocates
+// only when the arena or the tables grow, and interning a known one never allocates.
+// Symbols only mean something to the interner that made them. S hashes the strings, as in
+// HashMap<K, V, S>; with_hasher takes any BuildHasher, which is how the tests force collisions.
+#[derive(Debug, Clone, Default)]
+pub struct Interner<S = RandomState> {
+    arena: String,
+    spans: Vec<(usize, usize)>, // Where each symbol's text is in the arena: start, end
+    // Hash of the text → its symbols. Keying by hash instead of by text means the map never
+    // holds a copy of the strings; the rare strings sharing a hash are told apart by comparing
+    lookup: HashMap<u64, Bucket>,
+    hasher: S,
+}
+
+#[derive(Debug, Clone)]
+enum Bucket {
+    One(Symbol), // Almost every bucket: no Vec, so no allocation
+    Many(Vec<Symbol>),
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    // Room for `strings` strings of `bytes` bytes in total before anything reallocates
+    pub fn with_capacity(strings: usize, bytes: usize) -> Interner {
+        Interner {
+            arena: String::with_capacity(bytes),
+            spans: Vec::with_capacity(strings),
+            lookup: HashMap::with_capacity(strings),
+            hasher: RandomState::new(),
+        }
+    }
+}
+
+impl<S: BuildHasher> Interner<S> {
+    pub fn with_hasher(hasher: S) -> Interner<S> {
+        Interner {
+            arena: String::new(),
+            spans: Vec::new(),
+            lookup: HashMap::new(),
+            hasher,
+        }
+    }
+
+    // The symbol for `s`, storing it first if it is new
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        let hash = self.hasher.hash_one(s);
+        if let Some(symbol) = self.find(hash, s) {
+            return symbol;
+        }
+        let symbol = Symbol(
+            u32::try_from(self.spans.len()).expect("an interner holds at most u32::MAX strings"),
+        );
+        let start = self.arena.len();
+        self.arena.push_str(s);
+        self.spans.push((start, self.arena.len()));
+        match self.lookup.get_mut(&hash) {
+            None => {
+                self.lookup.insert(hash, Bucket::One(symbol));
+            }
+            Some(Bucket::One(other)) => {
+                let other = *other;
+                self.lookup.insert(hash, Bucket::Many(vec![other, symbol]));
+            }
+            Some(Bucket::Many(symbols)) => symbols.push(symbol),
+        }
+        symbol
+    }
+
+    // The symbol for `s` if it has been interned; never stores anything
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.find(self.hasher.hash_one(s), s)
+    }
+
+    fn find(&self, hash: u64, s: &str) -> Option<Symbol> {
+        let candidates = match self.lookup.get(&hash)? {
+            Bucket::One(symbol) => std::slice::from_ref(symbol),
+            Bucket::Many(symbols) => symbols.as_slice(),
+        };
+        candidates
+            .iter()
+            .copied()
+            .find(|&symbol| self.resolve(symbol) == Some(s))
+    }
+
+    // The text of `symbol`; None for a symbol this interner did not make
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        let &(start, end) = self.spans.get(symbol.index())?;
+        Some(&self.arena[start..end])
+    }
+
+    // Number of distinct strings
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    // Bytes of text stored, each distinct string once
+    pub fn text_len(&self) -> usize {
+        self.arena.len()
+    }
+
+    // Every (symbol, text), in the order they were interned
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &str)> + '_ {
+        self.spans
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, end))| (Symbol(i as u32), &self.arena[start..end]))
+    }
+
+    // --- SERIALIZATION ---
+    // The table as bytes: a "STRINTERN1\n" header, the number of strings, then each string as its
+    // length and its UTF-8 bytes, all numbers u32 little-endian. Strings are written in symbol
+    // order, so symbols saved next to the table (in a counts file, say) mean the same after
+    // read_from.
+    pub fn write_to<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(MAGIC)?;
+        out.write_all(&(self.len() as u32).to_le_bytes())?;
+        for (_, s) in self.iter() {
+            let len = u32::try_from(s.len()).map_err(|_| invalid("string longer than 4 GiB"))?;
+            out.write_all(&len.to_le_bytes())?;
+            out.write_all(s.as_bytes())?;
+        }
+        out.flush()
+    }
+}
+
+impl Interner {
+    // Reads a table written by write_to. Symbols come back with the same numbers.
+    pub fn read_from<R: Read>(mut input: R) -> io::Result<Interner> {
+        let mut magic = [0; MAGIC.len()];
+        input.read_exact(&mut magic)?;
+        if magic != *MAGIC {
+            return Err(invalid("not an interner table"));
+        }
+        let count = read_u32(&mut input)? as usize;
+        // Trust the count only so far: a corrupt file must not make us reserve gigabytes
+        let mut interner = Interner::with_capacity(count.min(1 << 16), 0);
+        let mut buf = Vec::new();
+        for _ in 0..count {
+            let len = read_u32(&mut input)? as usize;
+            buf.clear();
+            input.by_ref().take(len as u64).read_to_end(&mut buf)?;
+            if buf.len() != len {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+            }
+            let s = std::str::from_utf8(&buf).map_err(|_| invalid("string is not UTF-8"))?;
+            if interner.get(s).is_some() {
+                return Err(invalid("string stored twice"));
+            }
+            interner.intern(s);
+        }
+        Ok(interner)
+    }
+}
+
+const MAGIC: &[u8; 11] = b"STRINTERN1\n";
+
+fn read_u32<R: Read>(input: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// --- THREAD-SAFE INTERNER ---
+// The same table behind a RwLock, for threads that intern at the same time: known strings only
+// take the read lock, so threads block each other only while a new string is being added. A
+// reference into the table cannot outlive the lock, so resolve copies; once the threads are done,
+// into_inner gives back a plain Interner whose resolve borrows.
+#[derive(Debug, Default)]
+pub struct SyncInterner<S = RandomState> {
+    inner: RwLock<Interner<S>>,
+}
+
+impl SyncInterner {
+    pub fn new() -> SyncInterner {
+        SyncInterner::default()
+    }
+}
+
+impl<S: BuildHasher> SyncInterner<S> {
+    pub fn intern(&self, s: &str) -> Symbol {
+        if let Some(symbol) = self.read().get(s) {
+            return symbol;
+        }
+        // Another thread may have added it between the two locks; intern checks again
+        self.inner
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .intern(s)
+    }
+
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.read().get(s)
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> Option<String> {
+        self.read().resolve(symbol).map(str::to_string)
+    }
+
+    // Calls `f` with the text of `symbol` while holding the read lock, without copying it
+    pub fn with_resolved<T>(&self, symbol: Symbol, f: impl FnOnce(Option<&str>) -> T) -> T {
+        f(self.read().resolve(symbol))
+    }
+
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    pub fn into_inner(self) -> Interner<S> {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // A panic in another thread while it held the lock (say, out of memory in intern) can at
+    // worst leave unused bytes in the arena or give one string a second symbol, so a poisoned
+    // lock is still fine to use
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Interner<S>> {
+        self.inner
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<S> From<Interner<S>> for SyncInterner<S> {
+    fn from(interner: Interner<S>) -> SyncInterner<S> {
+        SyncInterner {
+            inner: RwLock::new(interner),
+        }
+    }
+}
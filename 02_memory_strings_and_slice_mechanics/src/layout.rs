@@ -0,0 +1,389 @@
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::slicing::{display_width, truncate_width};
+
+// --- TEXT LAYOUT ---
+// main.rs prints its explanations as long single lines, and the terminal breaks them wherever the
+// width runs out, often in the middle of a word. These functions lay text out in terminal columns
+// as slicing::display_width measures them: wide characters (日, 🦀) take two, combining marks none,
+// and a grapheme is never cut in half. Results are Cow: a line that is a piece of the input comes
+// back borrowed, and only a line that needed something added (a hyphen, spaces, a prefix) is a
+// new String.
+//
+//     let options = WrapOptions::new(20);
+//     wrap("Ownership moves, borrowing lends", &options); // ["Ownership moves,", "borrowing lends"]
+
+// --- HYPHENATION ---
+// Where a word that does not fit on the rest of a line may be split. wrap() ends the line with the
+// longest first piece that fits, plus a '-' unless the piece already ends in one, and carries on
+// with the rest of the word. Any Fn(&str) -> Vec<usize> is a Hyphenator, so a dictionary or a
+// hyphenation library plugs in as a closure.
+pub trait Hyphenator {
+    // Byte offsets inside `word` where it may be split; offsets that are not char boundaries
+    // strictly inside the word are ignored
+    fn split_points(&self, word: &str) -> Vec<usize>;
+}
+
+impl<F: Fn(&str) -> Vec<usize>> Hyphenator for F {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        self(word)
+    }
+}
+
+// Splits only after the hyphens a word already has: "copy-on-write" → "copy-", "on-", "write", so
+// nothing is added. A hyphen that is not between two letters or digits ("--verbose", "x-") is no
+// split point.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HyphenSplitter;
+
+impl Hyphenator for HyphenSplitter {
+    fn split_points(&self, word: &str) -> Vec<usize> {
+        let mut points = Vec::new();
+        let mut chars = word.char_indices().peekable();
+        let mut prev = None;
+        while let Some((i, c)) = chars.next() {
+            let next = chars.peek().map(|&(_, c)| c);
+            if c == '-'
+                && prev.is_some_and(char::is_alphanumeric)
+                && next.is_some_and(char::is_alphanumeric)
+            {
+                points.push(i + 1);
+            }
+            prev = Some(c);
+        }
+        points
+    }
+}
+
+// --- OPTIONS ---
+#[derive(Clone, Copy)]
+pub struct WrapOptions<'h> {
+    pub width: usize, // Columns per line; a grapheme wider than this still gets a line to itself
+    pub break_words: bool, // Cut a word longer than a line between graphemes; false lets it stick out
+    pub hyphenator: Option<&'h dyn Hyphenator>, // Where words may be split early; None: only too-long words are cut
+}
+
+impl WrapOptions<'static> {
+    // `width` columns, words longer than a line cut, compound words split at their own hyphens
+    pub fn new(width: usize) -> WrapOptions<'static> {
+        WrapOptions {
+            width,
+            break_words: true,
+            hyphenator: Some(&HyphenSplitter),
+        }
+    }
+}
+
+// --- WRAPPING ---
+// Greedy wrapping: each line takes as many words as fit, and the whitespace where a line breaks
+// is dropped. Whitespace between words on the same line is kept as it is, one column per
+// character. '\n' always ends a line, so paragraphs and blank lines stay put, and the indentation
+// a line starts with stays on the first line wrapped from it. No-break spaces (U+00A0, U+2007,
+// U+202F) hold the words on either side together, as in "10 km".
+pub fn wrap<'a>(text: &'a str, options: &WrapOptions) -> Vec<Cow<'a, str>> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        wrap_paragraph(paragraph, options, &mut lines);
+    }
+    lines
+}
+
+// The lines of wrap() joined with '\n'; borrowed when the text already fits as it is
+pub fn fill<'a>(text: &'a str, options: &WrapOptions) -> Cow<'a, str> {
+    let lines = wrap(text, options);
+    if lines.len() == text.split('\n').count()
+        && lines
+            .iter()
+            .zip(text.split('\n'))
+            .all(|(line, paragraph)| line == paragraph)
+    {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(lines.join("\n"))
+}
+
+// Wraps one line of the input, which has no '\n' in it, pushing its lines onto `out`
+fn wrap_paragraph<'a>(paragraph: &'a str, options: &WrapOptions, out: &mut Vec<Cow<'a, str>>) {
+    let max = options.width.max(1);
+    let first = out.len();
+    // The line being filled, paragraph[start..end] taking `width` columns; None before its first word
+    let mut line: Option<(usize, usize)> = None;
+    let mut width = 0;
+    for (mut at, end) in words(paragraph) {
+        loop {
+            let word = &paragraph[at..end];
+            // Columns taken before the word: the line so far and the gap after it, or on the
+            // first line the indentation
+            let (start, used) = match line {
+                Some((start, prev)) => (start, width + columns(&paragraph[prev..at])),
+                None if out.len() == first => (0, columns(&paragraph[..at])),
+                None => (at, 0),
+            };
+            let room = max.saturating_sub(used);
+            let word_width = display_width(word);
+            if word_width <= room {
+                (line, width) = (Some((start, end)), used + word_width);
+                break;
+            }
+            if let Some((split, hyphen)) = hyphenate(word, room, options) {
+                let piece = &paragraph[start..at + split];
+                out.push(if hyphen {
+                    Cow::Owned(format!("{piece}-"))
+                } else {
+                    Cow::Borrowed(piece)
+                });
+                (line, at) = (None, at + split);
+                continue;
+            }
+            if let Some((start, prev)) = line {
+                // Try the word again at the start of a new line
+                out.push(Cow::Borrowed(&paragraph[start..prev]));
+                line = None;
+                continue;
+            }
+            // Alone on its line and still too wide: cut it, at least one grapheme per line
+            let mut cut = truncate_width(word, room).len();
+            if cut == 0 {
+                cut = word.graphemes(true).next().map_or(word.len(), str::len);
+            }
+            if !options.break_words || cut == word.len() {
+                (line, width) = (Some((start, end)), used + word_width);
+                break;
+            }
+            out.push(Cow::Borrowed(&paragraph[start..at + cut]));
+            at += cut;
+        }
+    }
+    match line {
+        Some((start, end)) => out.push(Cow::Borrowed(&paragraph[start..end])),
+        None if out.len() == first => out.push(Cow::Borrowed("")), // A blank line stays blank
+        None => {}
+    }
+}
+
+// The longest split of `word` whose first piece, with the '-' it may need, fits in `room`
+// columns: (byte offset, whether a '-' is added)
+fn hyphenate(word: &str, room: usize, options: &WrapOptions) -> Option<(usize, bool)> {
+    options
+        .hyphenator?
+        .split_points(word)
+        .into_iter()
+        .filter(|&split| 0 < split && split < word.len() && word.is_char_boundary(split))
+        .map(|split| (split, !word[..split].ends_with('-')))
+        .filter(|&(split, hyphen)| display_width(&word[..split]) + usize::from(hyphen) <= room)
+        .max_by_key(|&(split, _)| split)
+}
+
+// Whitespace a line may break at; the no-break spaces are whitespace too, but join words
+fn is_break(c: char) -> bool {
+    c.is_whitespace() && !matches!(c, '\u{A0}' | '\u{2007}' | '\u{202F}')
+}
+
+// Byte ranges of the words of `s`: the runs between breakable whitespace
+fn words(s: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match (start, is_break(c)) {
+            (Some(from), true) => {
+                words.push((from, i));
+                start = None;
+            }
+            (None, false) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        words.push((from, s.len()));
+    }
+    words
+}
+
+// Columns taken by whitespace: one per character, tabs included
+fn columns(space: &str) -> usize {
+    space.chars().count()
+}
+
+// --- JUSTIFICATION ---
+// Wraps like wrap(), then widens the gaps between words until each line is exactly `width`
+// columns, giving the leftmost gaps the spare spaces. As in print, the last line of a paragraph
+// stays ragged, and so does a line with only one word on it.
+pub fn justify<'a>(text: &'a str, options: &WrapOptions) -> Vec<Cow<'a, str>> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let from = lines.len();
+        wrap_paragraph(paragraph, options, &mut lines);
+        let last = lines.len() - 1;
+        for line in &mut lines[from..last] {
+            if let Some(spread) = spread(line, options.width) {
+                *line = Cow::Owned(spread);
+            }
+        }
+    }
+    lines
+}
+
+// `line` with its gaps widened so it takes `width` columns; None when it already does, or has
+// no gap to widen, or is too wide already
+fn spread(line: &str, width: usize) -> Option<String> {
+    if display_width(line) == width {
+        return None;
+    }
+    let words = words(line);
+    let (&(indent, _), gaps) = (words.first()?, words.len() - 1);
+    let text: usize = words
+        .iter()
+        .map(|&(from, to)| display_width(&line[from..to]))
+        .sum();
+    let spaces = width.checked_sub(columns(&line[..indent]) + text)?;
+    if gaps == 0 || spaces < gaps {
+        return None;
+    }
+    let mut out = String::with_capacity(line.len() + spaces);
+    out.push_str(&line[..indent]);
+    for (i, &(from, to)) in words.iter().enumerate() {
+        if i > 0 {
+            let gap = spaces / gaps + usize::from(i <= spaces % gaps);
+            out.extend(std::iter::repeat_n(' ', gap));
+        }
+        out.push_str(&line[from..to]);
+    }
+    Some(out)
+}
+
+// --- INDENTATION ---
+// `prefix` put in front of every line that is not blank, like Python's textwrap.indent. Blank
+// lines are left alone, so indenting never adds trailing whitespace. Borrowed when no line needs
+// the prefix.
+pub fn indent<'a>(text: &'a str, prefix: &str) -> Cow<'a, str> {
+    if prefix.is_empty() || text.split('\n').all(is_blank) {
+        return Cow::Borrowed(text);
+    }
+    let lines = text.split('\n').count();
+    let mut out = String::with_capacity(text.len() + prefix.len() * lines);
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if !is_blank(line) {
+            out.push_str(prefix);
+        }
+        out.push_str(line);
+    }
+    Cow::Owned(out)
+}
+
+// Removes the whitespace that every line that is not blank starts with, so text indented to sit
+// inside a function or a raw string literal comes out flush left. Tabs and spaces are compared
+// as they are: "\tx" and "    y" share no margin. Blank lines lose all their whitespace.
+// Borrowed when nothing changes, and for a single line, which is a slice of the input.
+pub fn dedent(text: &str) -> Cow<'_, str> {
+    let margin = text
+        .split('\n')
+        .filter(|line| !is_blank(line))
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(common_prefix)
+        .unwrap_or("");
+    if !text.contains('\n') {
+        return Cow::Borrowed(strip_margin(text, margin));
+    }
+    if margin.is_empty()
+        && text
+            .split('\n')
+            .all(|line| !is_blank(line) || line.is_empty())
+    {
+        return Cow::Borrowed(text);
+    }
+    let lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| strip_margin(line, margin))
+        .collect();
+    Cow::Owned(lines.join("\n"))
+}
+
+// A line without the margin that every line that is not blank starts with; blank lines become ""
+fn strip_margin<'a>(line: &'a str, margin: &str) -> &'a str {
+    if is_blank(line) {
+        ""
+    } else {
+        &line[margin.len()..]
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+// The longest prefix the two strings share, as a slice of the first
+fn common_prefix<'a>(a: &'a str, b: &str) -> &'a str {
+    let end = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|&((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i);
+    &a[..end]
+}
+
+// --- TABLES ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+    Center, // An odd spare column goes on the right
+}
+
+// `s` padded with spaces to `width` columns; borrowed when it is that wide already, or wider
+pub fn pad(s: &str, width: usize, align: Align) -> Cow<'_, str> {
+    let spare = width.saturating_sub(display_width(s));
+    if spare == 0 {
+        return Cow::Borrowed(s);
+    }
+    let left = match align {
+        Align::Left => 0,
+        Align::Right => spare,
+        Align::Center => spare / 2,
+    };
+    let mut out = String::with_capacity(s.len() + spare);
+    out.extend(std::iter::repeat_n(' ', left));
+    out.push_str(s);
+    out.extend(std::iter::repeat_n(' ', spare - left));
+    Cow::Owned(out)
+}
+
+// Rows of cells laid out in columns, each as wide in terminal columns as its widest cell, with
+// `gap` between them. `align` gives each column's alignment (Left for columns it does not cover),
+// a short row is padded out with empty cells, and spaces at the end of a row are dropped. Rows
+// are joined with '\n'.
+//
+//     align_columns(&[["type", "bytes"], ["&str", "16"]], &[Align::Left, Align::Right], "  ")
+//     // "type  bytes\n&str     16"
+pub fn align_columns<'r, R: AsRef<[&'r str]>>(rows: &[R], align: &[Align], gap: &str) -> String {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (column, cell) in row.as_ref().iter().enumerate() {
+            if column == widths.len() {
+                widths.push(0);
+            }
+            widths[column] = widths[column].max(display_width(cell));
+        }
+    }
+    let mut out = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        for (column, &width) in widths.iter().enumerate() {
+            if column > 0 {
+                out.push_str(gap);
+            }
+            let cell = row.as_ref().get(column).copied().unwrap_or("");
+            let align = align.get(column).copied().unwrap_or_default();
+            out.push_str(&pad(cell, width, align));
+        }
+        out.truncate(out.trim_end_matches(' ').len());
+    }
+    out
+}
@@ -0,0 +1,42 @@
+// Reusable string and slice utilities; main.rs walks through them next to the basics.
+pub mod search; // Unicode-safe substring search with byte, char and grapheme offsets
+
+pub mod multi_search; // Aho–Corasick: finds many patterns in one pass over the text
+
+pub mod num; // Number trait: overflow-checked arithmetic for every primitive number type
+
+pub mod subarray; // Subarray queries: target sums with prefix sums, sliding windows, maximum subarray
+
+pub mod sum; // Summation modes: checked, saturating, widening, compensated (Kahan) and chunked
+
+pub mod words; // Word tokenizer on Unicode (UAX #29) word boundaries, with byte offsets
+
+pub mod slicing; // Slicing by byte, char or grapheme that returns None instead of panicking
+
+pub mod stats; // Streaming text statistics: counts, longest line, top words, scripts
+
+pub mod rope; // Rope: a balanced tree of chunks with O(log n) edits and cheap clones
+
+pub mod concat; // Concatenation: StrBuilder with exact capacity, join_with over Display, InlineString
+
+pub mod normalize; // Unicode normalization (NFC/NFD/NFKC/NFKD), case folding and equivalence
+
+pub mod transform; // Copy-on-write transforms: trim, collapse, replace, escape; allocate only on change
+
+pub mod interner; // String interner: each distinct string stored once, Copy symbols, saved tables
+
+pub mod escape; // Escaping and quoting for JSON, POSIX shell, CSV, HTML and Rust literals, both ways
+
+pub mod fuzzy; // Fuzzy matching: edit distances, similarity scores, bitap search and a BK-tree
+
+pub mod regex; // Regex-lite: classes, anchors, repetition, alternation and captures in linear time
+
+pub mod utf8; // Streaming UTF-8 decoding of chunked input: strict, lossy and WTF-8, plus fast validation
+
+pub mod encoding; // Encoding detection and conversion: UTF-16LE/BE, Latin-1 and Windows-1252 to and from UTF-8
+
+pub mod diff; // Diffs of slices and text (lines, words, chars, graphemes): Myers and patience, unified patches
+
+pub mod template; // Templates compiled once, rendered many times: values, filters, if and for, errors with line and column
+
+pub mod layout; // Text layout in terminal columns: wrapping with hyphenation hooks, justify, indent, dedent, aligned tables
@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::io::Read;
+
+pub mod stream; // Searches an io::Read chunk by chunk, so large files never sit in memory whole
+
+pub use stream::StreamFindIter;
+
+// --- MATCH SEMANTICS ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Overlapping,     // Every match, overlapping ones included, ordered by end
+    LeftmostFirst,   // No overlaps; at the leftmost start the earliest listed pattern wins
+    LeftmostLongest, // No overlaps; at the leftmost start the longest pattern wins
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize, // Index into the pattern list given to AhoCorasick::new
+    pub start: usize,   // Byte offsets: haystack[start..end] is the pattern
+    pub end: usize,
+}
+
+// --- AUTOMATON ---
+// Aho–Corasick: all patterns go into one trie, and every trie state gets a failure link to the
+// longest proper suffix of its text that is also in the trie. Following failure links on a
+// mismatch finds every pattern without ever going back in the haystack. The links are then
+// folded into a full transition table, so each haystack byte costs exactly one lookup.
+type StateId = usize;
+const ROOT: StateId = 0;
+
+#[derive(Debug, Clone)]
+struct State {
+    next: Vec<(u8, StateId)>,     // Trie edges, sorted by byte
+    fail: StateId,                // Failure link
+    output_link: Option<StateId>, // Nearest state on the failure chain where a pattern ends
+    patterns: Vec<usize>,         // Patterns that end exactly here (more than one if duplicated)
+    depth: usize,                 // Length of the text this state stands for
+}
+
+impl State {
+    fn new(depth: usize) -> State {
+        State {
+            next: Vec::new(),
+            fail: ROOT,
+            output_link: None,
+            patterns: Vec::new(),
+            depth,
+        }
+    }
+
+    fn child(&self, byte: u8) -> Option<StateId> {
+        self.next
+            .binary_search_by_key(&byte, |&(b, _)| b)
+            .ok()
+            .map(|i| self.next[i].1)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    states: Vec<State>,
+    classes: [u8; 256], // Byte → column in the table; bytes used by no pattern share column 0
+    class_count: usize,
+    table: Vec<StateId>, // Next state for (state, class) at state * class_count + class
+    pattern_lens: Vec<usize>,
+    kind: MatchKind,
+}
+
+impl AhoCorasick {
+    // Compiles the patterns; their position in the list is the pattern id reported in matches.
+    // Empty patterns keep their id but never match.
+    pub fn new<I, P>(patterns: I, kind: MatchKind) -> AhoCorasick
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        let mut states = vec![State::new(0)];
+        let mut pattern_lens = Vec::new();
+        for (id, pattern) in patterns.into_iter().enumerate() {
+            let bytes = pattern.as_ref().as_bytes();
+            pattern_lens.push(bytes.len());
+            if bytes.is_empty() {
+                continue;
+            }
+            let mut state = ROOT;
+            for &byte in bytes {
+                state = match states[state].child(byte) {
+                    Some(next) => next,
+                    None => {
+                        let next = states.len();
+                        states.push(State::new(states[state].depth + 1));
+                        let edges = &mut states[state].next;
+                        let at = edges.partition_point(|&(b, _)| b < byte);
+                        edges.insert(at, (byte, next));
+                        next
+                    }
+                };
+            }
+            states[state].patterns.push(id);
+        }
+
+        // Failure links, breadth first: a state's link is built from its parent's, which is shallower
+        let mut order = vec![ROOT];
+        let mut queue: VecDeque<StateId> = states[ROOT].next.iter().map(|&(_, c)| c).collect();
+        while let Some(parent) = queue.pop_front() {
+            order.push(parent);
+            for i in 0..states[parent].next.len() {
+                let (byte, child) = states[parent].next[i];
+                let mut suffix = states[parent].fail;
+                let fail = loop {
+                    if let Some(next) = states[suffix].child(byte) {
+                        break next;
+                    }
+                    if suffix == ROOT {
+                        break ROOT;
+                    }
+                    suffix = states[suffix].fail;
+                };
+                states[child].fail = fail;
+                states[child].output_link = if states[fail].patterns.is_empty() {
+                    states[fail].output_link
+                } else {
+                    Some(fail)
+                };
+                queue.push_back(child);
+            }
+        }
+
+        // Transition table, filled in the same order so a failure state's row is always ready.
+        // UTF-8 never uses the bytes 0xC0, 0xC1 and 0xF5..=0xFF, so the classes fit in a u8.
+        let mut classes = [0u8; 256];
+        let mut class_bytes = vec![None];
+        for state in &states {
+            for &(byte, _) in &state.next {
+                if classes[byte as usize] == 0 {
+                    classes[byte as usize] = class_bytes.len() as u8;
+                    class_bytes.push(Some(byte));
+                }
+            }
+        }
+        let class_count = class_bytes.len();
+        let mut table = vec![ROOT; states.len() * class_count];
+        for &state in &order {
+            for (class, byte) in class_bytes.iter().enumerate() {
+                let child = byte.and_then(|b| states[state].child(b));
+                table[state * class_count + class] = match child {
+                    Some(child) => child,
+                    None if state == ROOT => ROOT,
+                    None => table[states[state].fail * class_count + class],
+                };
+            }
+        }
+
+        AhoCorasick {
+            states,
+            classes,
+            class_count,
+            table,
+            pattern_lens,
+            kind,
+        }
+    }
+
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.pattern_lens.len()
+    }
+
+    // --- SEARCHING ---
+    // First match according to the match kind
+    pub fn find(&self, haystack: &str) -> Option<Match> {
+        self.find_iter(haystack).next()
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.find(haystack).is_some()
+    }
+
+    // Every match in one pass over the haystack
+    pub fn find_iter<'a, 'h>(&'a self, haystack: &'h str) -> FindIter<'a, 'h> {
+        FindIter {
+            search: Search::new(self, haystack.as_bytes()),
+        }
+    }
+
+    // Same matches for a reader; offsets count bytes from the start of the stream
+    pub fn stream_find_iter<R: Read>(&self, reader: R) -> StreamFindIter<'_, R> {
+        StreamFindIter::new(self, reader)
+    }
+
+    fn next_state(&self, state: StateId, byte: u8) -> StateId {
+        self.table[state * self.class_count + self.classes[byte as usize] as usize]
+    }
+
+    // Cheap check before matches_ending; most states in ordinary text end no pattern
+    fn has_matches(&self, state: StateId) -> bool {
+        let state = &self.states[state];
+        !state.patterns.is_empty() || state.output_link.is_some()
+    }
+
+    // All patterns ending at byte offset `end` when the automaton is in `state`, longest first
+    fn matches_ending(&self, state: StateId, end: usize) -> impl Iterator<Item = Match> + '_ {
+        let mut link = Some(state);
+        std::iter::from_fn(move || {
+            let current = link?;
+            link = self.states[current].output_link;
+            Some(&self.states[current].patterns)
+        })
+        .flatten()
+        .map(move |&pattern| Match {
+            pattern,
+            start: end - self.pattern_lens[pattern],
+            end,
+        })
+    }
+
+    // Whether `a` wins over `b` under the leftmost semantics
+    fn better(&self, a: Match, b: Match) -> bool {
+        if a.start != b.start {
+            return a.start < b.start;
+        }
+        match self.kind {
+            MatchKind::LeftmostLongest => {
+                a.end > b.end || (a.end == b.end && a.pattern < b.pattern)
+            }
+            _ => a.pattern < b.pattern,
+        }
+    }
+}
+
+// --- INPUT SOURCES ---
+// Where the search reads bytes from: a slice in memory, or a buffered reader (stream.rs)
+trait Source {
+    type Error;
+    fn byte_at(&mut self, pos: usize) -> Result<Option<u8>, Self::Error>;
+    // Bytes before `pos` will not be asked for again
+    fn release(&mut self, _pos: usize) {}
+}
+
+impl Source for &[u8] {
+    type Error = Infallible;
+
+    fn byte_at(&mut self, pos: usize) -> Result<Option<u8>, Infallible> {
+        Ok(self.get(pos).copied())
+    }
+}
+
+// --- SEARCH STATE ---
+#[derive(Debug, Clone)]
+struct Search<'a, S> {
+    ac: &'a AhoCorasick,
+    source: S,
+    pos: usize,               // Next byte to read
+    state: StateId,           // Only carried between calls in overlapping mode
+    pending: VecDeque<Match>, // Overlapping matches found but not returned yet
+}
+
+impl<'a, S: Source> Search<'a, S> {
+    fn new(ac: &'a AhoCorasick, source: S) -> Search<'a, S> {
+        Search {
+            ac,
+            source,
+            pos: 0,
+            state: ROOT,
+            pending: VecDeque::new(),
+        }
+    }
+
+    fn next_match(&mut self) -> Result<Option<Match>, S::Error> {
+        match self.ac.kind {
+            MatchKind::Overlapping => self.next_overlapping(),
+            MatchKind::LeftmostFirst | MatchKind::LeftmostLongest => self.next_leftmost(),
+        }
+    }
+
+    fn next_overlapping(&mut self) -> Result<Option<Match>, S::Error> {
+        loop {
+            if let Some(found) = self.pending.pop_front() {
+                return Ok(Some(found));
+            }
+            let Some(byte) = self.source.byte_at(self.pos)? else {
+                return Ok(None);
+            };
+            self.pos += 1;
+            self.state = self.ac.next_state(self.state, byte);
+            if self.ac.has_matches(self.state) {
+                self.pending
+                    .extend(self.ac.matches_ending(self.state, self.pos));
+            }
+            self.source.release(self.pos);
+        }
+    }
+
+    // Runs from `pos` keeping the best match seen so far. A match found now may still lose to a
+    // longer or higher-priority one with the same start, so the scan only stops once every
+    // match still in progress starts after the best one; then it resumes at the best match's end.
+    fn next_leftmost(&mut self) -> Result<Option<Match>, S::Error> {
+        let ac = self.ac;
+        let mut state = ROOT;
+        let mut pos = self.pos;
+        let mut best: Option<Match> = None;
+        while let Some(byte) = self.source.byte_at(pos)? {
+            pos += 1;
+            state = ac.next_state(state, byte);
+            if ac.has_matches(state) {
+                for found in ac.matches_ending(state, pos) {
+                    if best.is_none_or(|b| ac.better(found, b)) {
+                        best = Some(found);
+                    }
+                }
+            }
+            // Anything still to come starts at or after `in_progress`
+            let in_progress = pos - ac.states[state].depth;
+            match best {
+                Some(b) if in_progress > b.start => break,
+                Some(b) => self.source.release(b.end.min(in_progress)),
+                None => self.source.release(in_progress),
+            }
+        }
+        self.pos = best.map_or(pos, |b| b.end);
+        Ok(best)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FindIter<'a, 'h> {
+    search: Search<'a, &'h [u8]>,
+}
+
+impl Iterator for FindIter<'_, '_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        match self.search.next_match() {
+            Ok(found) => found,
+            Err(never) => match never {},
+        }
+    }
+}
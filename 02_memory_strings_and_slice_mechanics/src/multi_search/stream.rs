@@ -0,0 +1,101 @@
+use std::io::{self, ErrorKind, Read};
+
+use super::{AhoCorasick, Match, Search, Source};
+
+const CHUNK: usize = 64 * 1024;
+
+// --- STREAM BUFFER ---
+// Holds the part of the input the search may still look at. Bytes are read a chunk at a time
+// and dropped once the search releases them, so memory stays around one chunk plus the longest
+// pattern no matter how big the input is.
+#[derive(Debug)]
+struct StreamBuffer<R> {
+    reader: R,
+    buf: Vec<u8>,
+    buf_start: usize, // Stream offset of buf[0]
+    eof: bool,
+}
+
+impl<R: Read> StreamBuffer<R> {
+    fn fill(&mut self) -> io::Result<()> {
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.buf.truncate(len);
+                    return Err(e);
+                }
+            }
+        };
+        self.buf.truncate(len + read);
+        self.eof = read == 0;
+        Ok(())
+    }
+}
+
+impl<R: Read> Source for StreamBuffer<R> {
+    type Error = io::Error;
+
+    fn byte_at(&mut self, pos: usize) -> io::Result<Option<u8>> {
+        while pos >= self.buf_start + self.buf.len() {
+            if self.eof {
+                return Ok(None);
+            }
+            self.fill()?;
+        }
+        Ok(Some(self.buf[pos - self.buf_start]))
+    }
+
+    // Drops released bytes in whole chunks, so the copy inside drain stays cheap
+    fn release(&mut self, pos: usize) {
+        let done = pos.saturating_sub(self.buf_start);
+        if done >= CHUNK {
+            self.buf.drain(..done);
+            self.buf_start = pos;
+        }
+    }
+}
+
+// --- STREAM ITERATOR ---
+// Yields the same matches as AhoCorasick::find_iter would for the whole input, or the first
+// read error (after which it stops)
+#[derive(Debug)]
+pub struct StreamFindIter<'a, R> {
+    search: Search<'a, StreamBuffer<R>>,
+    failed: bool,
+}
+
+impl<'a, R: Read> StreamFindIter<'a, R> {
+    pub(super) fn new(ac: &'a AhoCorasick, reader: R) -> StreamFindIter<'a, R> {
+        let buffer = StreamBuffer {
+            reader,
+            buf: Vec::new(),
+            buf_start: 0,
+            eof: false,
+        };
+        StreamFindIter {
+            search: Search::new(ac, buffer),
+            failed: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for StreamFindIter<'_, R> {
+    type Item = io::Result<Match>;
+
+    fn next(&mut self) -> Option<io::Result<Match>> {
+        if self.failed {
+            return None;
+        }
+        match self.search.next_match() {
+            Ok(found) => found.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use caseless::Caseless; // Unicode default case folding (ß → ss, Σ/ς/σ → σ, ...)
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::{IsNormalized, UnicodeNormalization};
+use unicode_script::{Script, UnicodeScript};
+
+// --- NORMALIZATION FORMS ---
+// "é" can be one code point (U+00E9) or two ('e' + U+0301 COMBINING ACUTE ACCENT). They look the
+// same and mean the same, but `==` compares code points and says they differ. Normalizing both
+// to one form first makes them equal (UAX #15).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Form {
+    Nfc,  // Composed: "é" as U+00E9. The usual form for storing and sending text
+    Nfd,  // Decomposed: "é" as 'e' + U+0301
+    Nfkc, // Composed, and compatibility characters replaced: "ﬁ" → "fi", "①" → "1", "Ａ" → "A"
+    Nfkd, // Decomposed, and compatibility characters replaced
+}
+
+// `s` in `form`; Cow::Borrowed (no copy) exactly when it already was
+pub fn normalize(s: &str, form: Form) -> Cow<'_, str> {
+    // The quick check answers most strings from a table without building anything
+    if quick_check(s, form) == IsNormalized::Yes {
+        return Cow::Borrowed(s);
+    }
+    let normalized: String = match form {
+        Form::Nfc => s.nfc().collect(),
+        Form::Nfd => s.nfd().collect(),
+        Form::Nfkc => s.nfkc().collect(),
+        Form::Nfkd => s.nfkd().collect(),
+    };
+    if normalized == s {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(normalized)
+    }
+}
+
+pub fn nfc(s: &str) -> Cow<'_, str> {
+    normalize(s, Form::Nfc)
+}
+
+pub fn nfd(s: &str) -> Cow<'_, str> {
+    normalize(s, Form::Nfd)
+}
+
+pub fn nfkc(s: &str) -> Cow<'_, str> {
+    normalize(s, Form::Nfkc)
+}
+
+pub fn nfkd(s: &str) -> Cow<'_, str> {
+    normalize(s, Form::Nfkd)
+}
+
+pub fn is_normalized(s: &str, form: Form) -> bool {
+    match form {
+        Form::Nfc => unicode_normalization::is_nfc(s),
+        Form::Nfd => unicode_normalization::is_nfd(s),
+        Form::Nfkc => unicode_normalization::is_nfkc(s),
+        Form::Nfkd => unicode_normalization::is_nfkd(s),
+    }
+}
+
+fn quick_check(s: &str, form: Form) -> IsNormalized {
+    match form {
+        Form::Nfc => unicode_normalization::is_nfc_quick(s.chars()),
+        Form::Nfd => unicode_normalization::is_nfd_quick(s.chars()),
+        Form::Nfkc => unicode_normalization::is_nfkc_quick(s.chars()),
+        Form::Nfkd => unicode_normalization::is_nfkd_quick(s.chars()),
+    }
+}
+
+// --- CASE FOLDING ---
+// Full case folding from CaseFolding.txt, for comparing text without regard to case. It is not
+// lowercasing: "ß" folds to "ss" and final "ς" to "σ", so "STRASSE" and "straße" fold the same.
+// Cow::Borrowed when nothing changes.
+pub fn case_fold(s: &str) -> Cow<'_, str> {
+    if s.chars().default_case_fold().eq(s.chars()) {
+        Cow::Borrowed(s)
+    } else {
+        Cow::Owned(s.chars().default_case_fold().collect())
+    }
+}
+
+// --- ACCENTS ---
+// Removes accents and other diacritics: "Crème Brûlée" → "Creme Brulee", "Ångström" → "Angstrom".
+// Only marks shared between scripts (Unicode script "Inherited", such as U+0300..U+036F and the
+// Arabic vowel marks) are removed. Marks that belong to one script stay, because there they are
+// letters in their own right: without them "नमस्ते" would lose its vowels.
+pub fn strip_accents(s: &str) -> Cow<'_, str> {
+    if !s.nfd().any(is_accent) {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(s.nfd().filter(|&c| !is_accent(c)).nfc().collect())
+}
+
+fn is_accent(c: char) -> bool {
+    is_combining_mark(c) && c.script() == Script::Inherited
+}
+
+// --- COMPARISON ---
+// Which differences normalized_eq and normalized_cmp ignore. Canonically equivalent strings
+// ("é" as one code point or two) are always equal; the default ignores nothing else.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Equivalence {
+    pub compatibility: bool, // Also equate compatibility characters: "ﬁ" == "fi", "①" == "1"
+    pub ignore_case: bool,   // Compare case-folded: "Straße" == "STRASSE"
+    pub ignore_accents: bool, // Drop accents first (see strip_accents): "café" == "cafe"
+}
+
+// Whether `a` and `b` are the same text under `eq`, compared without building either string
+pub fn normalized_eq(a: &str, b: &str, eq: Equivalence) -> bool {
+    key_chars(a, eq).eq(key_chars(b, eq))
+}
+
+// A total order that agrees with normalized_eq: Equal exactly when normalized_eq is true.
+// It compares code points of the decomposed text, so it suits sorting and deduplicating, not
+// alphabetical order for people (that needs a locale's collation rules).
+pub fn normalized_cmp(a: &str, b: &str, eq: Equivalence) -> Ordering {
+    key_chars(a, eq).cmp(key_chars(b, eq))
+}
+
+// The text normalized_eq compares, in NFC (NFKC with `compatibility`). Two strings are
+// normalized_eq exactly when their keys are ==, so the key can go in a HashMap or HashSet.
+pub fn comparison_key(s: &str, eq: Equivalence) -> String {
+    if eq.compatibility {
+        key_chars(s, eq).nfkc().collect()
+    } else {
+        key_chars(s, eq).nfc().collect()
+    }
+}
+
+// The decomposed text with the ignored differences removed. Case folding follows the Unicode
+// Standard's canonical (D145) and compatibility (D146) caseless matching: decompose, fold and
+// decompose again, since folding can produce characters that decompose further.
+fn key_chars(s: &str, eq: Equivalence) -> Box<dyn Iterator<Item = char> + '_> {
+    let chars: Box<dyn Iterator<Item = char> + '_> = match (eq.compatibility, eq.ignore_case) {
+        (false, false) => Box::new(s.nfd()),
+        (true, false) => Box::new(s.nfkd()),
+        (false, true) => Box::new(s.nfd().default_case_fold().nfd()),
+        (true, true) => Box::new(
+            s.nfd()
+                .default_case_fold()
+                .nfkd()
+                .default_case_fold()
+                .nfkd(),
+        ),
+    };
+    if eq.ignore_accents {
+        // Decomposing again puts the remaining marks back in canonical order, in case a removed
+        // mark (such as U+034F COMBINING GRAPHEME JOINER) was keeping two of them apart
+        Box::new(chars.filter(|&c| !is_accent(c)).nfd())
+    } else {
+        chars
+    }
+}
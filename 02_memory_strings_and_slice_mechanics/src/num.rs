@@ -0,0 +1,58 @@
+use std::fmt::Debug;
+
+// --- NUMBER TRAIT ---
+// What the generic slice algorithms need from a number: a zero, comparisons, and addition and
+// subtraction that report overflow (None) instead of panicking in debug or wrapping in release.
+// Floats count as overflowed when the result is no longer finite.
+pub trait Number: Copy + PartialOrd + Debug {
+    const ZERO: Self;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    // Clamps to the largest or smallest value instead of overflowing
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+macro_rules! integer_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            const ZERO: $t = 0;
+
+            fn checked_add(self, rhs: $t) -> Option<$t> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: $t) -> Option<$t> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn saturating_add(self, rhs: $t) -> $t {
+                <$t>::saturating_add(self, rhs)
+            }
+        }
+    )*};
+}
+
+macro_rules! float_number {
+    ($($t:ty),*) => {$(
+        impl Number for $t {
+            const ZERO: $t = 0.0;
+
+            fn checked_add(self, rhs: $t) -> Option<$t> {
+                Some(self + rhs).filter(|sum| sum.is_finite())
+            }
+
+            fn checked_sub(self, rhs: $t) -> Option<$t> {
+                Some(self - rhs).filter(|difference| difference.is_finite())
+            }
+
+            fn saturating_add(self, rhs: $t) -> $t {
+                (self + rhs).clamp(<$t>::MIN, <$t>::MAX) // NaN stays NaN
+            }
+        }
+    )*};
+}
+
+integer_number!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+float_number!(f32, f64);
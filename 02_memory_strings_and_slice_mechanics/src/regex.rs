@@ -0,0 +1,283 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+
+mod parse; // Pattern syntax → syntax tree
+mod pikevm; // Syntax tree → NFA program, and the lockstep simulation that runs it
+
+use parse::Parser;
+use pikevm::{Inst, PikeVm};
+
+// --- REGEX ---
+// find_substr_pos in main.rs only finds a literal needle. A Regex finds a pattern:
+//   literals and `.`, classes `[a-z_]` `[^0-9]` `\d` `\w` `\s` (and `\D` `\W` `\S`),
+//   anchors `^` `$` `\b` `\B`, repetition `*` `+` `?` `{n}` `{n,}` `{n,m}` (lazy with a
+//   trailing `?`), alternation `a|b`, capture groups `(...)` and plain groups `(?:...)`.
+// The pattern is compiled to a Thompson NFA and run by a Pike VM (src/regex/pikevm.rs), so
+// matching takes O(text × pattern) time for every pattern: `(a*)*b` on a million a's is as fast
+// as `b`, where a backtracking engine would never finish. Matches follow Perl's leftmost-first
+// rule. Like first_word, every match is a slice borrowed from the input; nothing is copied.
+//   let re = Regex::new(r"(\w+)@(\w+)\.com")?;
+//   let caps = re.captures("mail bob@example.com")?;   // caps.get(2) is "example"
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegexError {
+    Unexpected { at: usize, c: char }, // `c` at byte `at` makes no sense there (e.g. a stray ')')
+    UnexpectedEnd,                     // The pattern stops inside an escape, class or group
+    BadRepetition { at: usize },       // Malformed or too large `{n,m}` at byte `at`
+    NothingToRepeat { at: usize },     // `*`, `+`, `?` or `{` with nothing before it
+    UnclosedGroup { at: usize },       // The '(' at byte `at` is never closed
+    BadEscape { at: usize },           // Unknown escape at byte `at`
+    BadClass { at: usize },            // Reversed range like `[z-a]` at byte `at`
+    TooBig,                            // The compiled program would be too large
+    TooDeep { at: usize },             // Groups or repetitions nested too deeply at byte `at`
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegexError::Unexpected { at, c } => write!(f, "unexpected {c:?} at byte {at}"),
+            RegexError::UnexpectedEnd => write!(f, "pattern ends too early"),
+            RegexError::BadRepetition { at } => write!(f, "invalid repetition at byte {at}"),
+            RegexError::NothingToRepeat { at } => write!(f, "nothing to repeat at byte {at}"),
+            RegexError::UnclosedGroup { at } => {
+                write!(f, "group opened at byte {at} is not closed")
+            }
+            RegexError::BadEscape { at } => write!(f, "invalid escape sequence at byte {at}"),
+            RegexError::BadClass { at } => write!(f, "invalid class range at byte {at}"),
+            RegexError::TooBig => write!(f, "pattern is too large once compiled"),
+            RegexError::TooDeep { at } => write!(f, "pattern nests too deeply at byte {at}"),
+        }
+    }
+}
+
+impl std::error::Error for RegexError {}
+
+#[derive(Debug, Clone)]
+pub struct Regex {
+    pattern: String,
+    program: Vec<Inst>,
+    groups: usize, // Capture groups, group 0 (the whole match) included
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+        let (node, groups) = Parser::parse(pattern)?;
+        Ok(Regex {
+            pattern: pattern.to_string(),
+            program: pikevm::compile(&node)?,
+            groups,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    // Capture groups, group 0 included: "(a)(b)" has 3
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    // The leftmost match; among matches starting there, the one Perl would pick
+    pub fn find<'t>(&self, text: &'t str) -> Option<Match<'t>> {
+        self.captures(text).and_then(|caps| caps.get(0))
+    }
+
+    // Every non-overlapping match, left to right. An empty match right where the previous one
+    // ended is skipped: `a*` in "baaa" gives "" at 0 and "aaa" at 1, not another "" at 4.
+    pub fn find_iter<'r, 't>(&'r self, text: &'t str) -> FindIter<'r, 't> {
+        FindIter(self.captures_iter(text))
+    }
+
+    // The leftmost match and what each group matched in it
+    pub fn captures<'t>(&self, text: &'t str) -> Option<Captures<'t>> {
+        self.captures_iter(text).next()
+    }
+
+    pub fn captures_iter<'r, 't>(&'r self, text: &'t str) -> CapturesIter<'r, 't> {
+        CapturesIter {
+            vm: PikeVm::new(&self.program, self.groups),
+            text,
+            next_start: Some(0),
+            last_end: None,
+        }
+    }
+
+    // Replaces every match with `replacement`, where `$1` or `${1}` is what group 1 matched
+    // (nothing if it did not take part), `$0` the whole match and `$$` a '$'. Borrows `text`
+    // when nothing matches.
+    pub fn replace_all<'t>(&self, text: &'t str, replacement: &str) -> Cow<'t, str> {
+        let mut out = String::new();
+        let mut copied = 0; // text[..copied] is already in `out`
+        let mut matched = false;
+        for caps in self.captures_iter(text) {
+            let Some(m) = caps.get(0) else { continue };
+            out.push_str(&text[copied..m.start]);
+            caps.expand(replacement, &mut out);
+            copied = m.end;
+            matched = true;
+        }
+        if !matched {
+            return Cow::Borrowed(text);
+        }
+        out.push_str(&text[copied..]);
+        Cow::Owned(out)
+    }
+}
+
+impl fmt::Display for Regex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.pattern)
+    }
+}
+
+// `s` with every char that means something in a pattern escaped, so it matches literally
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\.^$*+?()[]{}|".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// --- MATCHES ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'t> {
+    text: &'t str,
+    start: usize, // Byte offsets: text[start..end] is the match
+    end: usize,
+}
+
+impl<'t> Match<'t> {
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    // Borrowed from the searched text, not from the Match, so it outlives it
+    pub fn as_str(&self) -> &'t str {
+        &self.text[self.start..self.end]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'t> {
+    text: &'t str,
+    slots: Vec<Option<usize>>, // Start and end of each group
+}
+
+impl<'t> Captures<'t> {
+    // What group `i` matched; None if it did not take part, as in `(a)|b` matching "b"
+    pub fn get(&self, i: usize) -> Option<Match<'t>> {
+        let start = (*self.slots.get(2 * i)?)?;
+        let end = (*self.slots.get(2 * i + 1)?)?;
+        Some(Match {
+            text: self.text,
+            start,
+            end,
+        })
+    }
+
+    // Capture groups, group 0 included
+    pub fn len(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    // Never true: group 0 is always there
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    // Appends `replacement` to `out` with `$n`, `${n}` and `$$` filled in
+    pub fn expand(&self, replacement: &str, out: &mut String) {
+        let mut rest = replacement;
+        while let Some(dollar) = rest.find('$') {
+            out.push_str(&rest[..dollar]);
+            rest = &rest[dollar + 1..];
+            let braced = rest.strip_prefix('{').and_then(|r| Some((r, r.find('}')?)));
+            let (digits, after) = match braced {
+                Some((inner, close)) => (&inner[..close], &inner[close + 1..]),
+                None => {
+                    let len = rest.bytes().take_while(u8::is_ascii_digit).count();
+                    (&rest[..len], &rest[len..])
+                }
+            };
+            match digits.parse::<usize>() {
+                Ok(i) => {
+                    out.push_str(self.get(i).map_or("", |m| m.as_str()));
+                    rest = after;
+                }
+                Err(_) if rest.starts_with('$') => {
+                    out.push('$');
+                    rest = &rest[1..];
+                }
+                Err(_) => out.push('$'), // Not a reference: a plain '$'
+            }
+        }
+        out.push_str(rest);
+    }
+}
+
+pub struct CapturesIter<'r, 't> {
+    vm: PikeVm<'r>, // Reused for every search, so the iterator allocates its thread lists once
+    text: &'t str,
+    next_start: Option<usize>, // None once the text is exhausted
+    last_end: Option<usize>,
+}
+
+impl<'t> Iterator for CapturesIter<'_, 't> {
+    type Item = Captures<'t>;
+
+    fn next(&mut self) -> Option<Captures<'t>> {
+        loop {
+            let start = self.next_start?;
+            let Some(slots) = self.vm.search(self.text, start) else {
+                self.next_start = None;
+                return None;
+            };
+            let (Some(from), Some(to)) = (slots[0], slots[1]) else {
+                unreachable!("group 0 is saved on every path to Match")
+            };
+            if from == to && self.last_end == Some(to) {
+                // Try again one char later
+                self.next_start = self.text[to..].chars().next().map(|c| to + c.len_utf8());
+                continue;
+            }
+            self.next_start = Some(to);
+            self.last_end = Some(to);
+            return Some(Captures {
+                text: self.text,
+                slots,
+            });
+        }
+    }
+}
+
+pub struct FindIter<'r, 't>(CapturesIter<'r, 't>);
+
+impl<'t> Iterator for FindIter<'_, 't> {
+    type Item = Match<'t>;
+
+    fn next(&mut self) -> Option<Match<'t>> {
+        self.0.next().and_then(|caps| caps.get(0))
+    }
+}
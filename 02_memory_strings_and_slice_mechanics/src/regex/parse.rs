@@ -0,0 +1,326 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use super::RegexError;
+
+// --- SYNTAX TREE ---
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Empty,
+    Char(char),
+    Any, // `.`: any char but '\n'
+    Class(Class),
+    Look(Look),
+    Concat(Vec<Node>),
+    Alternate(Vec<Node>), // Earlier branches are preferred
+    Group {
+        index: Option<usize>, // Capture group number; None for (?:...)
+        node: Box<Node>,
+    },
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>, // None: no upper bound
+        greedy: bool,     // Greedy takes as many as it can; lazy (`*?`) as few
+    },
+}
+
+// Zero-width assertions: they look at the text around a position without consuming it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Look {
+    Start,           // `^`: start of the text
+    End,             // `$`: end of the text
+    WordBoundary,    // `\b`: a word char on exactly one side
+    NotWordBoundary, // `\B`
+}
+
+// `[a-z_]`, `[^0-9]`, or a shorthand like `\d` (a class with one item)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Class {
+    pub items: Vec<ClassItem>,
+    pub negated: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ClassItem {
+    Range(char, char), // Inclusive; a single char is a range of one
+    Perl(Perl, bool),  // `\d`, `\w`, `\s`, and with `true` their negations `\D`, `\W`, `\S`
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Perl {
+    Digit, // ASCII 0-9 only: "٣" is numeric, but rarely what a pattern with \d means
+    Word,  // Unicode letters and digits, and '_'
+    Space, // Unicode whitespace
+}
+
+impl Perl {
+    pub fn matches(self, c: char) -> bool {
+        match self {
+            Perl::Digit => c.is_ascii_digit(),
+            Perl::Word => is_word(c),
+            Perl::Space => c.is_whitespace(),
+        }
+    }
+}
+
+pub(crate) fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Class {
+    pub fn matches(&self, c: char) -> bool {
+        let found = self.items.iter().any(|item| match *item {
+            ClassItem::Range(from, to) => (from..=to).contains(&c),
+            ClassItem::Perl(perl, negated) => perl.matches(c) != negated,
+        });
+        found != self.negated
+    }
+}
+
+// Bounded repetitions are expanded into copies, so their counts are capped
+const MAX_REPEAT: u32 = 1000;
+
+// Nesting limit so hostile patterns cannot blow the stack: the parser and the compiler both
+// recurse once per group and per repetition of a repetition (`a**`)
+const MAX_DEPTH: usize = 32;
+
+// --- PARSER ---
+// Recursive descent over the grammar:
+//   alternate := concat ('|' concat)*
+//   concat    := repeat*
+//   repeat    := atom ('*' | '+' | '?' | '{n}' | '{n,}' | '{n,m}')* each optionally followed by '?'
+//   atom      := char | '.' | '^' | '$' | escape | class | '(' alternate ')' | '(?:' alternate ')'
+pub(crate) struct Parser<'p> {
+    chars: Peekable<CharIndices<'p>>,
+    groups: usize, // Capture groups so far, group 0 (the whole match) included
+    depth: usize,  // Groups open around the current position
+}
+
+impl<'p> Parser<'p> {
+    // The tree, and how many capture groups it has (group 0 included)
+    pub fn parse(pattern: &'p str) -> Result<(Node, usize), RegexError> {
+        let mut parser = Parser {
+            chars: pattern.char_indices().peekable(),
+            groups: 1,
+            depth: 0,
+        };
+        let node = parser.alternate()?;
+        match parser.chars.next() {
+            None => Ok((node, parser.groups)),
+            Some((at, c)) => Err(RegexError::Unexpected { at, c }), // A ')' with no '('
+        }
+    }
+
+    fn alternate(&mut self) -> Result<Node, RegexError> {
+        let mut branches = vec![self.concat()?];
+        while self.chars.next_if(|&(_, c)| c == '|').is_some() {
+            branches.push(self.concat()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().unwrap_or(Node::Empty)
+        } else {
+            Node::Alternate(branches)
+        })
+    }
+
+    fn concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            nodes.push(self.repeat()?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap_or(Node::Empty),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn repeat(&mut self) -> Result<Node, RegexError> {
+        let mut node = self.atom()?;
+        let mut wraps = 0; // Repeat nodes around `node` so far
+        while let Some(&(at, c)) = self.chars.peek() {
+            let (min, max) = match c {
+                '*' => (0, None),
+                '+' => (1, None),
+                '?' => (0, Some(1)),
+                '{' => {
+                    self.chars.next();
+                    self.counts(at)?
+                }
+                _ => break,
+            };
+            if self.depth + wraps >= MAX_DEPTH {
+                return Err(RegexError::TooDeep { at });
+            }
+            wraps += 1;
+            if c != '{' {
+                self.chars.next();
+            }
+            let greedy = self.chars.next_if(|&(_, c)| c == '?').is_none();
+            node = Node::Repeat {
+                node: Box::new(node),
+                min,
+                max,
+                greedy,
+            };
+        }
+        Ok(node)
+    }
+
+    // The inside of `{n}`, `{n,}` or `{n,m}`, after the '{' at byte `at`
+    fn counts(&mut self, at: usize) -> Result<(u32, Option<u32>), RegexError> {
+        let bad = RegexError::BadRepetition { at };
+        let min = self.number().ok_or(bad.clone())?;
+        let max = if self.chars.next_if(|&(_, c)| c == ',').is_some() {
+            match self.chars.peek() {
+                Some(&(_, '}')) => None,
+                _ => Some(self.number().ok_or(bad.clone())?),
+            }
+        } else {
+            Some(min)
+        };
+        if self.chars.next_if(|&(_, c)| c == '}').is_none() {
+            return Err(bad);
+        }
+        if max.is_some_and(|max| max < min) || min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            return Err(bad);
+        }
+        Ok((min, max))
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let mut n: Option<u32> = None;
+        while let Some((_, c)) = self.chars.next_if(|&(_, c)| c.is_ascii_digit()) {
+            let digit = c.to_digit(10)?;
+            n = Some(n.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+        }
+        n
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexError> {
+        let (at, c) = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Look(Look::Start),
+            '$' => Node::Look(Look::End),
+            '[' => Node::Class(self.class()?),
+            '(' => self.group(at)?,
+            '\\' => self.escape()?,
+            '*' | '+' | '?' | '{' => return Err(RegexError::NothingToRepeat { at }),
+            c => Node::Char(c),
+        })
+    }
+
+    // After the '(' at byte `at`
+    fn group(&mut self, at: usize) -> Result<Node, RegexError> {
+        let index = if self.chars.next_if(|&(_, c)| c == '?').is_some() {
+            match self.chars.next() {
+                Some((_, ':')) => None,
+                Some((at, c)) => return Err(RegexError::Unexpected { at, c }),
+                None => return Err(RegexError::UnexpectedEnd),
+            }
+        } else {
+            self.groups += 1;
+            Some(self.groups - 1)
+        };
+        if self.depth >= MAX_DEPTH {
+            return Err(RegexError::TooDeep { at });
+        }
+        self.depth += 1;
+        let node = self.alternate()?;
+        self.depth -= 1;
+        if self.chars.next_if(|&(_, c)| c == ')').is_none() {
+            return Err(RegexError::UnclosedGroup { at });
+        }
+        Ok(Node::Group {
+            index,
+            node: Box::new(node),
+        })
+    }
+
+    // After a '\' outside a class
+    fn escape(&mut self) -> Result<Node, RegexError> {
+        let (at, c) = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+        Ok(match c {
+            'b' => Node::Look(Look::WordBoundary),
+            'B' => Node::Look(Look::NotWordBoundary),
+            _ => match escaped_item(at - 1, c)? {
+                ClassItem::Range(c, _) => Node::Char(c),
+                perl => Node::Class(Class {
+                    items: vec![perl],
+                    negated: false,
+                }),
+            },
+        })
+    }
+
+    // After the '['
+    fn class(&mut self) -> Result<Class, RegexError> {
+        let negated = self.chars.next_if(|&(_, c)| c == '^').is_some();
+        let mut items = Vec::new();
+        let mut first = true;
+        loop {
+            let (at, c) = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+            let item = match c {
+                ']' if !first => break,
+                '\\' => {
+                    let (at, c) = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+                    escaped_item(at - 1, c)?
+                }
+                c => ClassItem::Range(c, c),
+            };
+            first = false;
+            // A '-' between two chars makes a range; at the start or the end it is a '-'
+            let ClassItem::Range(from, _) = item else {
+                items.push(item);
+                continue;
+            };
+            let mut ahead = self.chars.clone();
+            if ahead.next_if(|&(_, c)| c == '-').is_none()
+                || matches!(ahead.peek(), Some(&(_, ']')) | None)
+            {
+                items.push(item);
+                continue;
+            }
+            self.chars.next(); // The '-'
+            let (end_at, end) = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+            let to = match end {
+                '\\' => {
+                    let (at, c) = self.chars.next().ok_or(RegexError::UnexpectedEnd)?;
+                    match escaped_item(at - 1, c)? {
+                        ClassItem::Range(to, _) => to,
+                        ClassItem::Perl(..) => return Err(RegexError::BadClass { at: end_at }),
+                    }
+                }
+                c => c,
+            };
+            if to < from {
+                return Err(RegexError::BadClass { at });
+            }
+            items.push(ClassItem::Range(from, to));
+        }
+        Ok(Class { items, negated })
+    }
+}
+
+// The meaning of `\c`, where the '\' is at byte `at`: a shorthand class, a control char, or
+// the punctuation char itself
+fn escaped_item(at: usize, c: char) -> Result<ClassItem, RegexError> {
+    Ok(match c {
+        'd' => ClassItem::Perl(Perl::Digit, false),
+        'D' => ClassItem::Perl(Perl::Digit, true),
+        'w' => ClassItem::Perl(Perl::Word, false),
+        'W' => ClassItem::Perl(Perl::Word, true),
+        's' => ClassItem::Perl(Perl::Space, false),
+        'S' => ClassItem::Perl(Perl::Space, true),
+        'n' => ClassItem::Range('\n', '\n'),
+        'r' => ClassItem::Range('\r', '\r'),
+        't' => ClassItem::Range('\t', '\t'),
+        c if c.is_ascii_punctuation() || c == ' ' => ClassItem::Range(c, c),
+        _ => return Err(RegexError::BadEscape { at }),
+    })
+}
@@ -0,0 +1,366 @@
+use super::RegexError;
+use super::parse::{Class, Look, Node, is_word};
+
+// --- PROGRAM ---
+// The pattern compiled to a Thompson NFA, written as a little program. Char, Any and Class
+// consume one char; Split and Jmp only move between states; Save records where a group starts
+// or ends. `a+b` becomes:
+//   0: Save 0   1: Char 'a'   2: Split 1, 3   3: Char 'b'   4: Save 1   5: Match
+#[derive(Debug, Clone)]
+pub(crate) enum Inst {
+    Char(char),
+    Any, // Any char but '\n'
+    Class(Class),
+    Look(Look),
+    Split(usize, usize), // Go on at both; the first has priority
+    Jmp(usize),
+    Save(usize), // Record the current position in capture slot n
+    Match,
+}
+
+// Bounded repetitions copy their body, so `(a{1000}){1000}` would be a million instructions
+const MAX_PROGRAM: usize = 100_000;
+
+pub(crate) fn compile(node: &Node) -> Result<Vec<Inst>, RegexError> {
+    let mut program = Program(Vec::new());
+    program.push(Inst::Save(0))?;
+    program.node(node)?;
+    program.push(Inst::Save(1))?;
+    program.push(Inst::Match)?;
+    Ok(program.0)
+}
+
+struct Program(Vec<Inst>);
+
+impl Program {
+    fn push(&mut self, inst: Inst) -> Result<usize, RegexError> {
+        if self.0.len() >= MAX_PROGRAM {
+            return Err(RegexError::TooBig);
+        }
+        self.0.push(inst);
+        Ok(self.0.len() - 1)
+    }
+
+    // Points the Split or Jmp at `at` to `target` (its second branch, for a Split)
+    fn patch(&mut self, at: usize, target: usize) {
+        match &mut self.0[at] {
+            Inst::Jmp(to) | Inst::Split(_, to) => *to = target,
+            _ => unreachable!("only jumps are patched"),
+        }
+    }
+
+    fn node(&mut self, node: &Node) -> Result<(), RegexError> {
+        match node {
+            Node::Empty => {}
+            Node::Char(c) => {
+                self.push(Inst::Char(*c))?;
+            }
+            Node::Any => {
+                self.push(Inst::Any)?;
+            }
+            Node::Class(class) => {
+                self.push(Inst::Class(class.clone()))?;
+            }
+            Node::Look(look) => {
+                self.push(Inst::Look(*look))?;
+            }
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.node(node)?;
+                }
+            }
+            // Split L1, next; L1: first; Jmp end; next: Split L2, next'; ...; last; end:
+            Node::Alternate(branches) => {
+                let mut jumps = Vec::new();
+                for (i, branch) in branches.iter().enumerate() {
+                    let split = if i + 1 < branches.len() {
+                        let at = self.0.len();
+                        Some(self.push(Inst::Split(at + 1, 0))?)
+                    } else {
+                        None
+                    };
+                    self.node(branch)?;
+                    if let Some(split) = split {
+                        jumps.push(self.push(Inst::Jmp(0))?);
+                        let next = self.0.len();
+                        self.patch(split, next);
+                    }
+                }
+                let end = self.0.len();
+                for jump in jumps {
+                    self.patch(jump, end);
+                }
+            }
+            Node::Group { index, node } => match index {
+                Some(i) => {
+                    self.push(Inst::Save(2 * i))?;
+                    self.node(node)?;
+                    self.push(Inst::Save(2 * i + 1))?;
+                }
+                None => self.node(node)?,
+            },
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.node(node)?;
+                }
+                match max {
+                    // loop: Split body, end; body; Jmp loop; end:
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.node(node)?;
+                        self.push(Inst::Jmp(split))?;
+                        let end = self.0.len();
+                        self.0[split] = self.split(split + 1, end, *greedy);
+                    }
+                    // Each optional copy: Split body, end; body; ... every Split jumps to the end
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0))?);
+                            self.node(node)?;
+                        }
+                        let end = self.0.len();
+                        for split in splits {
+                            self.0[split] = self.split(split + 1, end, *greedy);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Greedy prefers going into the body; lazy prefers skipping it
+    fn split(&self, body: usize, skip: usize, greedy: bool) -> Inst {
+        if greedy {
+            Inst::Split(body, skip)
+        } else {
+            Inst::Split(skip, body)
+        }
+    }
+}
+
+// --- PIKE VM ---
+// Runs every thread of the NFA in lockstep, one char of text at a time. Two threads at the same
+// instruction would do the same from then on, so only the first (higher priority) one is kept:
+// at most one thread per instruction, so the whole search is O(text × program), with no
+// backtracking and no exponential blow-up, whatever the pattern. Threads are kept in priority
+// order and a Match drops all lower-priority ones, which gives the same leftmost-first
+// answers as a backtracking engine (Perl, JavaScript, ...).
+pub(crate) struct PikeVm<'r> {
+    program: &'r [Inst],
+    current: Threads,
+    next: Threads,
+    stack: Vec<Frame>,
+}
+
+// The threads at one position: which instructions, in priority order, and each one's captures
+struct Threads {
+    order: Vec<usize>,   // Instructions that consume a char or match
+    visited: Vec<usize>, // Every instruction reached, so `seen` can be reset in O(threads)
+    seen: Vec<bool>,
+    waiting: Vec<bool>, // Splits whose second branch is still to be explored, during add
+    slots: usize,       // Two per capture group: start and end
+    captures: Vec<Option<usize>>, // `slots` entries per instruction
+}
+
+enum Frame {
+    Explore(usize),
+    Loop(usize),                   // Explore, arriving by a loop's Jmp back to its Split
+    Second(usize, usize),          // The Split at .0 goes on at .1, unless that was taken over
+    Restore(usize, Option<usize>), // Undo a Save once the branches after it are explored
+}
+
+impl Threads {
+    fn new(program: usize, slots: usize) -> Threads {
+        Threads {
+            order: Vec::with_capacity(program),
+            visited: Vec::with_capacity(program),
+            seen: vec![false; program],
+            waiting: vec![false; program],
+            slots,
+            captures: vec![None; program * slots],
+        }
+    }
+
+    fn captures(&self, pc: usize) -> &[Option<usize>] {
+        &self.captures[pc * self.slots..(pc + 1) * self.slots]
+    }
+
+    fn clear(&mut self) {
+        for &pc in &self.visited {
+            self.seen[pc] = false;
+        }
+        self.visited.clear();
+        self.order.clear();
+    }
+}
+
+impl<'r> PikeVm<'r> {
+    pub fn new(program: &'r [Inst], groups: usize) -> PikeVm<'r> {
+        PikeVm {
+            program,
+            current: Threads::new(program.len(), 2 * groups),
+            next: Threads::new(program.len(), 2 * groups),
+            stack: Vec::new(),
+        }
+    }
+
+    // Capture slots of the leftmost-first match starting at or after byte `start`
+    pub fn search(&mut self, text: &str, start: usize) -> Option<Vec<Option<usize>>> {
+        self.current.clear();
+        self.next.clear();
+        let mut matched = None;
+        let mut scratch = vec![None; self.current.slots];
+        let mut at = start;
+        loop {
+            // A new thread may start here, below every older one: an earlier start wins.
+            // Once something has matched, no later start can be leftmost.
+            if matched.is_none() {
+                scratch.fill(None);
+                let step = Step { text, at, pc: 0 };
+                add(
+                    self.program,
+                    &mut self.current,
+                    &mut self.stack,
+                    step,
+                    &mut scratch,
+                );
+            }
+            if self.current.order.is_empty() && matched.is_some() {
+                break;
+            }
+            let c = text[at..].chars().next();
+            let next_at = at + c.map_or(0, char::len_utf8);
+            for &pc in &self.current.order {
+                let consumes = match (&self.program[pc], c) {
+                    (Inst::Match, _) => {
+                        matched = Some(self.current.captures(pc).to_vec());
+                        break; // Lower-priority threads can only give worse matches
+                    }
+                    (Inst::Char(x), Some(c)) => *x == c,
+                    (Inst::Any, Some(c)) => c != '\n',
+                    (Inst::Class(class), Some(c)) => class.matches(c),
+                    _ => false,
+                };
+                if consumes {
+                    scratch.copy_from_slice(self.current.captures(pc));
+                    let step = Step {
+                        text,
+                        at: next_at,
+                        pc: pc + 1,
+                    };
+                    add(
+                        self.program,
+                        &mut self.next,
+                        &mut self.stack,
+                        step,
+                        &mut scratch,
+                    );
+                }
+            }
+            std::mem::swap(&mut self.current, &mut self.next);
+            self.next.clear();
+            if c.is_none() {
+                break;
+            }
+            at = next_at;
+        }
+        matched
+    }
+}
+
+// Where a thread goes: instruction `pc`, at byte `at` of `text`
+#[derive(Clone, Copy)]
+struct Step<'t> {
+    text: &'t str,
+    at: usize,
+    pc: usize,
+}
+
+// Adds the thread at `step` and every thread it reaches without consuming a char to `list`;
+// `captures` are its slots. Instructions already in the list are skipped: the thread there got
+// there first, so it has the higher priority.
+fn add(
+    program: &[Inst],
+    list: &mut Threads,
+    stack: &mut Vec<Frame>,
+    step: Step<'_>,
+    captures: &mut [Option<usize>],
+) {
+    stack.push(Frame::Explore(step.pc));
+    while let Some(frame) = stack.pop() {
+        let (pc, looped) = match frame {
+            Frame::Explore(pc) => (pc, false),
+            Frame::Loop(pc) => (pc, true),
+            Frame::Second(split, second) => {
+                if list.waiting[split] {
+                    list.waiting[split] = false;
+                    stack.push(Frame::Explore(second));
+                }
+                continue;
+            }
+            Frame::Restore(slot, old) => {
+                captures[slot] = old;
+                continue;
+            }
+        };
+        if list.seen[pc] {
+            // Back at a Split while its first branch is still being explored. By its own loop's
+            // Jmp, that is an iteration that matched nothing, which is dropped. Any other way
+            // in is a new pass of an enclosing loop, which a backtracking engine would try
+            // before the pending second branch, so this thread takes that branch over
+            if list.waiting[pc]
+                && !looped
+                && let Inst::Split(_, second) = program[pc]
+            {
+                list.waiting[pc] = false;
+                stack.push(Frame::Explore(second));
+            }
+            continue;
+        }
+        list.seen[pc] = true;
+        list.visited.push(pc);
+        match &program[pc] {
+            Inst::Jmp(to) if *to < pc => stack.push(Frame::Loop(*to)),
+            Inst::Jmp(to) => stack.push(Frame::Explore(*to)),
+            Inst::Split(first, second) => {
+                list.waiting[pc] = true;
+                stack.push(Frame::Second(pc, *second));
+                stack.push(Frame::Explore(*first));
+            }
+            Inst::Save(slot) => {
+                stack.push(Frame::Restore(*slot, captures[*slot]));
+                captures[*slot] = Some(step.at);
+                stack.push(Frame::Explore(pc + 1));
+            }
+            Inst::Look(look) => {
+                if holds(*look, step.text, step.at) {
+                    stack.push(Frame::Explore(pc + 1));
+                }
+            }
+            // Consumes a char or matches: the thread waits here for the next step
+            _ => {
+                list.order.push(pc);
+                let slots = list.slots;
+                list.captures[pc * slots..(pc + 1) * slots].copy_from_slice(captures);
+            }
+        }
+    }
+}
+
+fn holds(look: Look, text: &str, at: usize) -> bool {
+    let word_before = text[..at].chars().next_back().is_some_and(is_word);
+    let word_after = text[at..].chars().next().is_some_and(is_word);
+    match look {
+        Look::Start => at == 0,
+        Look::End => at == text.len(),
+        Look::WordBoundary => word_before != word_after,
+        Look::NotWordBoundary => word_before == word_after,
+    }
+}
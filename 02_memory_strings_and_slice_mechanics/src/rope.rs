@@ -0,0 +1,330 @@
+use std::fmt;
+use std::iter;
+use std::ops::Range;
+use std::sync::Arc;
+
+mod tree; // Balanced tree of text chunks: summaries, join, split and in-place insert
+
+use tree::{Info, Node};
+
+// --- ROPE ---
+// A string stored as a balanced tree of chunks of up to 1 KiB. Where `String::insert_str` and
+// `replace_range` move every byte after the edit, a rope only rebuilds one path from the root,
+// so inserting, removing and slicing are O(log n) however long the text is. Clones share all
+// their nodes until one of them is edited.
+//
+// Positions are byte offsets, like for String; char_to_byte and line_to_byte convert from the
+// other units. Like String, the edit methods panic on offsets past the end or inside a char.
+#[derive(Clone)]
+pub struct Rope {
+    root: Arc<Node>,
+}
+
+impl Rope {
+    pub fn new() -> Rope {
+        Rope {
+            root: tree::build(""),
+        }
+    }
+
+    pub fn len_bytes(&self) -> usize {
+        self.root.info().bytes
+    }
+
+    pub fn len_chars(&self) -> usize {
+        self.root.info().chars
+    }
+
+    // Lines as an editor shows them: one more than the number of '\n', so "" has one line and
+    // "a\n" has two (the second one empty)
+    pub fn len_lines(&self) -> usize {
+        self.root.info().newlines + 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len_bytes() == 0
+    }
+
+    // Height of the tree; AVL balancing keeps it below 1.44 × log2(chunks) + 2
+    pub fn depth(&self) -> usize {
+        self.root.height() as usize
+    }
+
+    // --- EDITING ---
+    pub fn insert(&mut self, at: usize, text: &str) {
+        self.assert_boundary(at);
+        if text.is_empty() || tree::insert_in_leaf(&mut self.root, at, text) {
+            return;
+        }
+        let (before, after) = tree::split(&self.root, at);
+        self.root = tree::join(tree::join(before, tree::build(text)), after);
+    }
+
+    pub fn insert_char(&mut self, at: usize, c: char) {
+        self.insert(at, c.encode_utf8(&mut [0; 4]));
+    }
+
+    pub fn remove(&mut self, range: Range<usize>) {
+        self.assert_range(&range);
+        let (before, rest) = tree::split(&self.root, range.start);
+        let (_, after) = tree::split(&rest, range.end - range.start);
+        self.root = tree::join(before, after);
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        self.insert(self.len_bytes(), text);
+    }
+
+    // Moves the text of `other` to the end; O(log n), the nodes are shared, not copied
+    pub fn append(&mut self, other: Rope) {
+        self.root = tree::join(self.root.clone(), other.root);
+    }
+
+    // Keeps [0, at) and returns [at, len) as a new rope
+    pub fn split_off(&mut self, at: usize) -> Rope {
+        self.assert_boundary(at);
+        let (before, after) = tree::split(&self.root, at);
+        self.root = before;
+        Rope { root: after }
+    }
+
+    // The text in `range` as a new rope that shares nodes with this one
+    pub fn slice(&self, range: Range<usize>) -> Rope {
+        self.assert_range(&range);
+        let (_, rest) = tree::split(&self.root, range.start);
+        let (slice, _) = tree::split(&rest, range.end - range.start);
+        Rope { root: slice }
+    }
+
+    // Line `line` including its '\n', if it has one
+    pub fn line(&self, line: usize) -> Rope {
+        let start = self.line_to_byte(line).expect("line index out of range");
+        let end = self.line_to_byte(line + 1).unwrap_or(self.len_bytes());
+        self.slice(start..end)
+    }
+
+    fn assert_boundary(&self, at: usize) {
+        assert!(
+            at <= self.len_bytes(),
+            "byte index {at} is past the end of the rope ({} bytes)",
+            self.len_bytes()
+        );
+        assert!(
+            self.is_char_boundary(at),
+            "byte index {at} is not a char boundary"
+        );
+    }
+
+    fn assert_range(&self, range: &Range<usize>) {
+        assert!(
+            range.start <= range.end,
+            "range starts at {} but ends at {}",
+            range.start,
+            range.end
+        );
+        self.assert_boundary(range.start);
+        self.assert_boundary(range.end);
+    }
+
+    // --- INDEX CONVERSIONS ---
+    // Each one walks a single path from the root, using the counts cached in the nodes.
+    // They return None for positions past the end.
+
+    pub fn is_char_boundary(&self, at: usize) -> bool {
+        if at > self.len_bytes() {
+            return false;
+        }
+        let (leaf, before) = self.find(at, |info| info.bytes);
+        leaf.is_char_boundary(at - before.bytes)
+    }
+
+    // Byte offset where char number `char_idx` starts (len_bytes for char_idx == len_chars)
+    pub fn char_to_byte(&self, char_idx: usize) -> Option<usize> {
+        if char_idx > self.len_chars() {
+            return None;
+        }
+        let (leaf, before) = self.find(char_idx, |info| info.chars);
+        let in_leaf = leaf
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(iter::once(leaf.len()))
+            .nth(char_idx - before.chars)?;
+        Some(before.bytes + in_leaf)
+    }
+
+    // Number of chars before byte offset `at`; None if `at` is inside a char
+    pub fn byte_to_char(&self, at: usize) -> Option<usize> {
+        if at > self.len_bytes() {
+            return None;
+        }
+        let (leaf, before) = self.find(at, |info| info.bytes);
+        let head = leaf.get(..at - before.bytes)?;
+        Some(before.chars + head.chars().count())
+    }
+
+    // Byte offset where line number `line` starts (0-based)
+    pub fn line_to_byte(&self, line: usize) -> Option<usize> {
+        if line == 0 {
+            return Some(0);
+        }
+        if line >= self.len_lines() {
+            return None;
+        }
+        // The line starts right after the line-th '\n'
+        let (leaf, before) = self.find(line, |info| info.newlines);
+        let newline = leaf.match_indices('\n').nth(line - before.newlines - 1)?.0;
+        Some(before.bytes + newline + 1)
+    }
+
+    // Line that byte offset `at` is on
+    pub fn byte_to_line(&self, at: usize) -> Option<usize> {
+        if at > self.len_bytes() {
+            return None;
+        }
+        let (leaf, before) = self.find(at, |info| info.bytes);
+        let head = leaf.get(..at - before.bytes)?;
+        Some(before.newlines + head.matches('\n').count())
+    }
+
+    pub fn char_to_line(&self, char_idx: usize) -> Option<usize> {
+        self.byte_to_line(self.char_to_byte(char_idx)?)
+    }
+
+    pub fn line_to_char(&self, line: usize) -> Option<usize> {
+        self.byte_to_char(self.line_to_byte(line)?)
+    }
+
+    // (line, column) of a char, both 0-based and counted in chars, as an editor's status bar
+    pub fn char_to_line_col(&self, char_idx: usize) -> Option<(usize, usize)> {
+        let line = self.char_to_line(char_idx)?;
+        Some((line, char_idx - self.line_to_char(line)?))
+    }
+
+    // Char index of (line, column); None if the line has fewer than `col` chars before its '\n'
+    pub fn line_col_to_char(&self, line: usize, col: usize) -> Option<usize> {
+        let start = self.line_to_char(line)?;
+        let end = match self.line_to_char(line + 1) {
+            Some(next) => next - 1, // The '\n' that ends this line
+            None => self.len_chars(),
+        };
+        (start + col <= end).then_some(start + col)
+    }
+
+    // The leaf containing position `target` (in the unit picked by `unit`) and the summary of
+    // all text before that leaf. Ties go left, so the end of a leaf is found in that leaf.
+    fn find(&self, target: usize, unit: impl Fn(Info) -> usize) -> (&str, Info) {
+        let mut node = &*self.root;
+        let mut before = Info::default();
+        loop {
+            match node {
+                Node::Leaf { text, .. } => return (text, before),
+                Node::Branch { left, right, .. } => {
+                    let left_info = left.info();
+                    if target <= unit(before) + unit(left_info) {
+                        node = left;
+                    } else {
+                        before = before + left_info;
+                        node = right;
+                    }
+                }
+            }
+        }
+    }
+
+    // --- ITERATORS ---
+    // The chunks the text is stored in, in order; joined together they are the whole text
+    pub fn chunks(&self) -> Chunks<'_> {
+        Chunks {
+            stack: vec![&*self.root],
+        }
+    }
+
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chunks().flat_map(str::chars)
+    }
+
+    pub fn bytes(&self) -> impl Iterator<Item = u8> + '_ {
+        self.chunks().flat_map(str::bytes)
+    }
+
+    // Every line as a rope, including its '\n'; len_lines() of them
+    pub fn lines(&self) -> impl Iterator<Item = Rope> + '_ {
+        (0..self.len_lines()).map(|line| self.line(line))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Chunks<'a> {
+    stack: Vec<&'a Node>, // Subtrees still to visit, the next one on top
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while let Some(node) = self.stack.pop() {
+            match node {
+                Node::Leaf { text, .. } if text.is_empty() => {}
+                Node::Leaf { text, .. } => return Some(text),
+                Node::Branch { left, right, .. } => {
+                    self.stack.push(right);
+                    self.stack.push(left);
+                }
+            }
+        }
+        None
+    }
+}
+
+// --- CONVERSIONS AND COMPARISONS ---
+impl Default for Rope {
+    fn default() -> Rope {
+        Rope::new()
+    }
+}
+
+impl From<&str> for Rope {
+    fn from(text: &str) -> Rope {
+        Rope {
+            root: tree::build(text),
+        }
+    }
+}
+
+impl From<String> for Rope {
+    fn from(text: String) -> Rope {
+        Rope::from(text.as_str())
+    }
+}
+
+impl fmt::Display for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chunks().try_for_each(|chunk| f.write_str(chunk))
+    }
+}
+
+impl fmt::Debug for Rope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Rope").field(&self.to_string()).finish()
+    }
+}
+
+impl PartialEq for Rope {
+    fn eq(&self, other: &Rope) -> bool {
+        self.len_bytes() == other.len_bytes() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl Eq for Rope {}
+
+impl PartialEq<str> for Rope {
+    fn eq(&self, other: &str) -> bool {
+        self.len_bytes() == other.len() && self.bytes().eq(other.bytes())
+    }
+}
+
+impl PartialEq<&str> for Rope {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
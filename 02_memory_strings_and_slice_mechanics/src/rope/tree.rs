@@ -0,0 +1,280 @@
+use std::ops::Add;
+use std::sync::Arc;
+
+// Leaves hold at most this many bytes; big enough that most of the rope's memory is text
+pub(super) const MAX_LEAF: usize = 1024;
+
+// --- SUMMARIES ---
+// Every node knows the size of its text in each unit, so a search can pick a side without
+// looking at the text: that is what makes the index conversions O(log n).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct Info {
+    pub bytes: usize,
+    pub chars: usize,
+    pub newlines: usize,
+}
+
+impl Info {
+    pub fn of(text: &str) -> Info {
+        Info {
+            bytes: text.len(),
+            chars: text.chars().count(),
+            newlines: text.bytes().filter(|&b| b == b'\n').count(),
+        }
+    }
+}
+
+impl Add for Info {
+    type Output = Info;
+
+    fn add(self, other: Info) -> Info {
+        Info {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            newlines: self.newlines + other.newlines,
+        }
+    }
+}
+
+// --- NODES ---
+// Nodes are never changed while shared: Arc::make_mut copies a node first if another rope still
+// points at it, so a clone of a rope costs one reference count and edits copy only one path.
+#[derive(Debug, Clone)]
+pub(super) enum Node {
+    Leaf {
+        text: String,
+        info: Info,
+    },
+    Branch {
+        left: Arc<Node>,
+        right: Arc<Node>,
+        info: Info,
+        height: u8, // Leaves are 0; AVL balance keeps siblings within 1 of each other
+    },
+}
+
+impl Node {
+    pub fn leaf(text: String) -> Arc<Node> {
+        let info = Info::of(&text);
+        Arc::new(Node::Leaf { text, info })
+    }
+
+    pub fn info(&self) -> Info {
+        match self {
+            Node::Leaf { info, .. } | Node::Branch { info, .. } => *info,
+        }
+    }
+
+    pub fn height(&self) -> u8 {
+        match self {
+            Node::Leaf { .. } => 0,
+            Node::Branch { height, .. } => *height,
+        }
+    }
+
+    fn children(&self) -> (&Arc<Node>, &Arc<Node>) {
+        match self {
+            Node::Branch { left, right, .. } => (left, right),
+            Node::Leaf { .. } => unreachable!("only branches are taller than their sibling"),
+        }
+    }
+}
+
+fn branch(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+    Arc::new(Node::Branch {
+        info: left.info() + right.info(),
+        height: left.height().max(right.height()) + 1,
+        left,
+        right,
+    })
+}
+
+// Balanced tree of leaves for `text`
+pub(super) fn build(text: &str) -> Arc<Node> {
+    let mut leaves = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        // Back off to a char boundary; a char is at most 4 bytes, far less than MAX_LEAF
+        let mut end = MAX_LEAF.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, tail) = rest.split_at(end);
+        leaves.push(Node::leaf(chunk.to_string()));
+        rest = tail;
+    }
+    if leaves.is_empty() {
+        return Node::leaf(String::new());
+    }
+    build_from(&leaves)
+}
+
+// Halves the list each time, so the two sides of every branch differ by at most one leaf and
+// their heights by at most one
+fn build_from(nodes: &[Arc<Node>]) -> Arc<Node> {
+    match nodes {
+        [node] => node.clone(),
+        _ => {
+            let (left, right) = nodes.split_at(nodes.len() / 2);
+            branch(build_from(left), build_from(right))
+        }
+    }
+}
+
+// --- JOIN AND SPLIT ---
+// AVL join (Blelloch, Ferizovic and Sun, "Just Join for Parallel Ordered Sets"): walk down the
+// taller tree's inner spine to a subtree of about the other tree's height, attach it there and
+// rotate on the way back up. Costs the height difference, so it is O(log n).
+pub(super) fn join(left: Arc<Node>, right: Arc<Node>) -> Arc<Node> {
+    if left.info().bytes == 0 {
+        return right;
+    }
+    if right.info().bytes == 0 {
+        return left;
+    }
+    if let (Node::Leaf { text: a, .. }, Node::Leaf { text: b, .. }) = (&*left, &*right)
+        && a.len() + b.len() <= MAX_LEAF
+    {
+        return Node::leaf(format!("{a}{b}"));
+    }
+    let (hl, hr) = (left.height(), right.height());
+    if hl > hr + 1 {
+        join_right(&left, right)
+    } else if hr > hl + 1 {
+        join_left(left, &right)
+    } else {
+        branch(left, right)
+    }
+}
+
+// `left` is at least two levels taller than `right`
+fn join_right(left: &Node, right: Arc<Node>) -> Arc<Node> {
+    let (outer, inner) = left.children();
+    let joined = if inner.height() <= right.height() + 1 {
+        let joined = branch(inner.clone(), right);
+        if joined.height() > outer.height() + 1 {
+            return rotate_left(&branch(outer.clone(), rotate_right(&joined)));
+        }
+        joined
+    } else {
+        join_right(inner, right)
+    };
+    let node = branch(outer.clone(), joined.clone());
+    if joined.height() > outer.height() + 1 {
+        rotate_left(&node)
+    } else {
+        node
+    }
+}
+
+// Mirror image of join_right
+fn join_left(left: Arc<Node>, right: &Node) -> Arc<Node> {
+    let (inner, outer) = right.children();
+    let joined = if inner.height() <= left.height() + 1 {
+        let joined = branch(left, inner.clone());
+        if joined.height() > outer.height() + 1 {
+            return rotate_right(&branch(rotate_left(&joined), outer.clone()));
+        }
+        joined
+    } else {
+        join_left(left, inner)
+    };
+    let node = branch(joined.clone(), outer.clone());
+    if joined.height() > outer.height() + 1 {
+        rotate_right(&node)
+    } else {
+        node
+    }
+}
+
+// (a, (b, c)) → ((a, b), c)
+fn rotate_left(node: &Node) -> Arc<Node> {
+    let (a, bc) = node.children();
+    let (b, c) = bc.children();
+    branch(branch(a.clone(), b.clone()), c.clone())
+}
+
+// ((a, b), c) → (a, (b, c))
+fn rotate_right(node: &Node) -> Arc<Node> {
+    let (ab, c) = node.children();
+    let (a, b) = ab.children();
+    branch(a.clone(), branch(b.clone(), c.clone()))
+}
+
+// Text before and after byte offset `at`, which must be a char boundary
+pub(super) fn split(node: &Arc<Node>, at: usize) -> (Arc<Node>, Arc<Node>) {
+    if at == 0 {
+        return (Node::leaf(String::new()), node.clone());
+    }
+    if at == node.info().bytes {
+        return (node.clone(), Node::leaf(String::new()));
+    }
+    match &**node {
+        Node::Leaf { text, .. } => (
+            Node::leaf(text[..at].to_string()),
+            Node::leaf(text[at..].to_string()),
+        ),
+        Node::Branch { left, right, .. } => {
+            let left_bytes = left.info().bytes;
+            if at <= left_bytes {
+                let (a, b) = split(left, at);
+                (a, join(b, right.clone()))
+            } else {
+                let (a, b) = split(right, at - left_bytes);
+                (join(left.clone(), a), b)
+            }
+        }
+    }
+}
+
+// --- IN-PLACE INSERT ---
+// Small inserts go straight into the leaf when it has room, so typing does not fragment the
+// rope into one-character leaves. Returns false (and changes nothing) when the leaf is full.
+pub(super) fn insert_in_leaf(node: &mut Arc<Node>, mut at: usize, text: &str) -> bool {
+    if leaf_len_at(node, at) + text.len() > MAX_LEAF {
+        return false;
+    }
+    let added = Info::of(text);
+    let mut node = node;
+    loop {
+        // Moving the reference out first lets `node` point at a child afterwards
+        let current = node;
+        match Arc::make_mut(current) {
+            Node::Leaf { text: leaf, info } => {
+                leaf.insert_str(at, text);
+                *info = *info + added;
+                return true;
+            }
+            Node::Branch {
+                left, right, info, ..
+            } => {
+                *info = *info + added;
+                let left_bytes = left.info().bytes;
+                node = if at <= left_bytes {
+                    left
+                } else {
+                    at -= left_bytes;
+                    right
+                };
+            }
+        }
+    }
+}
+
+// Length of the leaf that insert_in_leaf would pick; ties go left, as in split
+fn leaf_len_at(node: &Node, mut at: usize) -> usize {
+    let mut node = node;
+    loop {
+        match node {
+            Node::Leaf { text, .. } => return text.len(),
+            Node::Branch { left, right, .. } => {
+                if at <= left.info().bytes {
+                    node = left;
+                } else {
+                    at -= left.info().bytes;
+                    node = right;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use std::iter;
+use std::ops::Range;
+
+use caseless::Caseless; // Unicode default case folding (ß → ss, Σ/ς/σ → σ, ...)
+use unicode_segmentation::UnicodeSegmentation;
+
+pub mod horspool; // Boyer–Moore–Horspool searcher that every function below runs on
+
+pub use horspool::Searcher;
+
+// --- OFFSET UNITS ---
+// The same match can be reported in three ways. In "🦀 नमस्ते 🦀" the second crab starts at
+// byte 24, char 9 and grapheme 6 ("स्ते" is four chars but one grapheme).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Byte,     // Can be used to slice the haystack: &haystack[pos..]
+    Char,     // Unicode scalar values, what .chars() counts
+    Grapheme, // User-perceived characters; matches must also start and end on grapheme boundaries
+}
+
+// --- BYTE OFFSET SEARCH ---
+// Byte offset of the first match, or None. Never slices inside a character, so it cannot panic.
+pub fn find(haystack: &str, needle: &str) -> Option<usize> {
+    Searcher::new(needle).find(haystack)
+}
+
+// Every non-overlapping match, left to right: "aaaa" contains "aa" at 0 and 2
+pub fn find_all<'h, 'n>(haystack: &'h str, needle: &'n str) -> Matches<'h, 'n> {
+    Matches::new(haystack, needle, false)
+}
+
+// Every match, including ones that overlap: "aaaa" contains "aa" at 0, 1 and 2
+pub fn find_overlapping<'h, 'n>(haystack: &'h str, needle: &'n str) -> Matches<'h, 'n> {
+    Matches::new(haystack, needle, true)
+}
+
+#[derive(Debug, Clone)]
+pub struct Matches<'h, 'n> {
+    haystack: &'h str,
+    searcher: Searcher<'n>,
+    next_start: Option<usize>, // None once the haystack is exhausted
+    overlapping: bool,
+}
+
+impl<'h, 'n> Matches<'h, 'n> {
+    fn new(haystack: &'h str, needle: &'n str, overlapping: bool) -> Matches<'h, 'n> {
+        Matches {
+            haystack,
+            searcher: Searcher::new(needle),
+            next_start: Some(0),
+            overlapping,
+        }
+    }
+}
+
+impl Iterator for Matches<'_, '_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let start = self.next_start?;
+        let found = self.searcher.find_at(self.haystack, start);
+        let Some(pos) = found else {
+            self.next_start = None;
+            return None;
+        };
+        let needle_len = self.searcher.needle().len();
+        self.next_start = if self.overlapping || needle_len == 0 {
+            // Resume one character later; an empty needle matches once at every boundary
+            self.haystack[pos..]
+                .chars()
+                .next()
+                .map(|c| pos + c.len_utf8())
+        } else {
+            Some(pos + needle_len)
+        };
+        Some(pos)
+    }
+}
+
+// --- CHAR AND GRAPHEME OFFSETS ---
+// First match reported in the given unit
+pub fn find_in(haystack: &str, needle: &str, unit: Unit) -> Option<usize> {
+    match unit {
+        Unit::Byte => find(haystack, needle),
+        Unit::Char => find(haystack, needle).and_then(|pos| char_offset(haystack, pos)),
+        Unit::Grapheme => grapheme_matches(haystack, needle).next(),
+    }
+}
+
+// Every non-overlapping match reported in the given unit
+pub fn find_all_in(haystack: &str, needle: &str, unit: Unit) -> Vec<usize> {
+    match unit {
+        Unit::Byte => find_all(haystack, needle).collect(),
+        Unit::Char => char_offsets(haystack, find_all(haystack, needle)),
+        Unit::Grapheme => grapheme_matches(haystack, needle).collect(),
+    }
+}
+
+// Number of chars before byte offset `byte`, or None if `byte` is past the end or inside a char
+pub fn char_offset(text: &str, byte: usize) -> Option<usize> {
+    Some(text.get(..byte)?.chars().count())
+}
+
+// Number of graphemes before byte offset `byte`, or None if `byte` is past the end or inside a
+// char. An offset inside a grapheme counts the part before it: byte 1 of "e\u{301}" gives 1.
+pub fn grapheme_offset(text: &str, byte: usize) -> Option<usize> {
+    Some(text.get(..byte)?.graphemes(true).count())
+}
+
+// Converts ascending byte offsets to char offsets in one pass over the text
+fn char_offsets(text: &str, bytes: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut chars = 0;
+    let mut at = 0;
+    bytes
+        .map(|byte| {
+            chars += text[at..byte].chars().count();
+            at = byte;
+            chars
+        })
+        .collect()
+}
+
+// Grapheme indices of non-overlapping matches that cover whole graphemes: "e" is not found in
+// "e\u{301}" (é written as e + combining accent), because it would cut the accent off.
+fn grapheme_matches<'a>(haystack: &'a str, needle: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let boundaries: Vec<usize> = haystack
+        .grapheme_indices(true)
+        .map(|(i, _)| i)
+        .chain(iter::once(haystack.len()))
+        .collect();
+    let mut taken_until = 0;
+    // Overlapping candidates, so a misaligned match cannot hide an aligned one right after it
+    find_overlapping(haystack, needle).filter_map(move |start| {
+        let end = start + needle.len();
+        if start < taken_until {
+            return None;
+        }
+        let index = boundaries.binary_search(&start).ok()?;
+        boundaries.binary_search(&end).ok()?;
+        taken_until = end;
+        Some(index)
+    })
+}
+
+// --- CASE-INSENSITIVE SEARCH ---
+// Both strings are compared after Unicode case folding, so "STRASSE" finds "Straße" and "ΣΊΣΥΦΟΣ"
+// finds "σίσυφος". Folding can change the length, so matches are byte ranges in the original
+// haystack. A match must line up with whole original characters: "s" is not found inside "ß".
+pub fn find_ignore_case(haystack: &str, needle: &str) -> Option<Range<usize>> {
+    find_all_ignore_case(haystack, needle).into_iter().next()
+}
+
+pub fn find_all_ignore_case(haystack: &str, needle: &str) -> Vec<Range<usize>> {
+    let folded = Folded::new(haystack);
+    let needle = caseless::default_case_fold_str(needle);
+    let mut taken_until = 0;
+    let mut ranges = Vec::new();
+    for start in find_overlapping(&folded.text, &needle) {
+        if start < taken_until {
+            continue;
+        }
+        if let Some(range) = folded.original_range(start, start + needle.len()) {
+            taken_until = start + needle.len();
+            ranges.push(range);
+        }
+    }
+    ranges
+}
+
+// The case-folded haystack plus, for every original char boundary, where it ended up
+struct Folded {
+    text: String,
+    folded_at: Vec<usize>, // Folded byte offset of each original boundary (ascending)
+    original_at: Vec<usize>, // The matching original byte offset
+}
+
+impl Folded {
+    fn new(original: &str) -> Folded {
+        let mut text = String::with_capacity(original.len());
+        let mut folded_at = Vec::with_capacity(original.len() + 1);
+        let mut original_at = Vec::with_capacity(original.len() + 1);
+        for (i, c) in original.char_indices() {
+            folded_at.push(text.len());
+            original_at.push(i);
+            text.extend(iter::once(c).default_case_fold());
+        }
+        folded_at.push(text.len());
+        original_at.push(original.len());
+        Folded {
+            text,
+            folded_at,
+            original_at,
+        }
+    }
+
+    // Maps a folded range back, or None if it starts or ends inside one char's folding
+    fn original_range(&self, start: usize, end: usize) -> Option<Range<usize>> {
+        let first = self.folded_at.binary_search(&start).ok()?;
+        let last = self.folded_at.binary_search(&end).ok()?;
+        Some(self.original_at[first]..self.original_at[last])
+    }
+}
@@ -0,0 +1,53 @@
+// --- BOYER–MOORE–HORSPOOL ---
+// Compares the needle against the window from its last byte backwards. On a mismatch the
+// window jumps ahead by how far the window's last byte sits from the end of the needle, so on
+// typical text most haystack bytes are never looked at (sub-linear on average).
+//
+// Searching bytes is safe for UTF-8: a valid needle starts with a lead byte, which can never
+// equal a continuation byte, so every match starts and ends on a character boundary.
+#[derive(Debug, Clone)]
+pub struct Searcher<'n> {
+    needle: &'n str,
+    skip: [usize; 256], // How far the window may move when its last byte is this value
+}
+
+impl<'n> Searcher<'n> {
+    // Builds the skip table once, so one needle can be searched in many haystacks
+    pub fn new(needle: &'n str) -> Searcher<'n> {
+        let bytes = needle.as_bytes();
+        let mut skip = [bytes.len().max(1); 256];
+        if let Some((_, init)) = bytes.split_last() {
+            for (i, &b) in init.iter().enumerate() {
+                skip[b as usize] = bytes.len() - 1 - i;
+            }
+        }
+        Searcher { needle, skip }
+    }
+
+    pub fn needle(&self) -> &'n str {
+        self.needle
+    }
+
+    // Byte offset of the first match in `haystack` at or after byte `start`.
+    // An empty needle matches at `start` itself, like str::find("").
+    pub fn find_at(&self, haystack: &str, start: usize) -> Option<usize> {
+        let hay = haystack.as_bytes();
+        let needle = self.needle.as_bytes();
+        let Some((&last, init)) = needle.split_last() else {
+            return (start <= hay.len()).then_some(start);
+        };
+        let mut pos = start;
+        while pos + needle.len() <= hay.len() {
+            let window_last = hay[pos + needle.len() - 1];
+            if window_last == last && &hay[pos..pos + init.len()] == init {
+                return Some(pos);
+            }
+            pos += self.skip[window_last as usize];
+        }
+        None
+    }
+
+    pub fn find(&self, haystack: &str) -> Option<usize> {
+        self.find_at(haystack, 0)
+    }
+}
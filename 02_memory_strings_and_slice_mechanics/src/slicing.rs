@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+use std::iter;
+use std::ops::{Bound, RangeBounds};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr; // Terminal column widths from Unicode's East Asian Width data
+
+// --- SAFE SLICING ---
+// `&s[a..b]` panics when a or b is past the end or inside a character. These return None instead,
+// and take any range form: 2..5, ..3, 4.., ..=1 or .. (everything).
+
+// Byte offsets, like `&s[range]`; None unless both ends are char boundaries inside the string
+pub fn get_byte_range<R: RangeBounds<usize>>(s: &str, range: R) -> Option<&str> {
+    let (start, end) = bounds(range)?;
+    s.get(start..end.unwrap_or(s.len()))
+}
+
+// Char offsets: slice_chars("🦀🦀🦀", 1..2) == Some("🦀")
+pub fn slice_chars<R: RangeBounds<usize>>(s: &str, range: R) -> Option<&str> {
+    let boundaries = s.char_indices().map(|(i, _)| i);
+    slice_at(s, boundaries, range)
+}
+
+// Grapheme offsets, so no accent, conjunct or emoji sequence is ever cut in half:
+// slice_graphemes("नमस्ते", 2..3) == Some("स्ते")
+pub fn slice_graphemes<R: RangeBounds<usize>>(s: &str, range: R) -> Option<&str> {
+    let boundaries = s.grapheme_indices(true).map(|(i, _)| i);
+    slice_at(s, boundaries, range)
+}
+
+// The first `n` graphemes, or the whole string if it has fewer
+pub fn truncate_graphemes(s: &str, n: usize) -> &str {
+    match s.grapheme_indices(true).nth(n) {
+        Some((end, _)) => &s[..end],
+        None => s,
+    }
+}
+
+// Resolves a range to a start and an end (None: to the end of the string) counted in units.
+// None if a bound overflows usize, which no string can reach anyway.
+fn bounds<R: RangeBounds<usize>>(range: R) -> Option<(usize, Option<usize>)> {
+    let start = match range.start_bound() {
+        Bound::Included(&i) => i,
+        Bound::Excluded(&i) => i.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&i) => Some(i.checked_add(1)?),
+        Bound::Excluded(&i) => Some(i),
+        Bound::Unbounded => None,
+    };
+    Some((start, end))
+}
+
+// Slices from the start-th to the end-th unit, given the byte offset where each unit starts
+fn slice_at<R>(s: &str, boundaries: impl Iterator<Item = usize>, range: R) -> Option<&str>
+where
+    R: RangeBounds<usize>,
+{
+    let (start, end) = bounds(range)?;
+    if end.is_some_and(|end| end < start) {
+        return None;
+    }
+    // Unit starts plus the end of the string, so the last unit has an end too
+    let mut offsets = boundaries.chain(iter::once(s.len()));
+    let from = offsets.nth(start)?;
+    let to = match end {
+        None => s.len(),
+        Some(end) if end == start => from,
+        Some(end) => offsets.nth(end - start - 1)?,
+    };
+    Some(&s[from..to])
+}
+
+// --- DISPLAY WIDTH ---
+// How many terminal columns a string takes: East Asian wide characters (日, 한) and emoji take
+// two, combining marks and zero-width characters none. Whole grapheme clusters are measured, so
+// "👨‍👩‍👧" (three emoji joined by zero-width joiners) and "🇯🇵" (two flag letters) count as 2.
+pub fn display_width(s: &str) -> usize {
+    s.graphemes(true).map(UnicodeWidthStr::width).sum()
+}
+
+// The longest prefix of whole graphemes that fits in `max_width` columns
+pub fn truncate_width(s: &str, max_width: usize) -> &str {
+    let mut width = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > max_width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+// Cuts to `max_width` columns and marks the cut with `ellipsis` ("…" is one column); strings that
+// already fit come back borrowed. The result never exceeds `max_width`, even when a wide
+// character has to go and leaves a column free.
+pub fn truncate_with_ellipsis<'a>(s: &'a str, max_width: usize, ellipsis: &str) -> Cow<'a, str> {
+    if display_width(s) <= max_width {
+        return Cow::Borrowed(s);
+    }
+    let ellipsis = truncate_width(ellipsis, max_width);
+    let kept = truncate_width(s, max_width - display_width(ellipsis));
+    Cow::Owned(format!("{kept}{ellipsis}"))
+}
@@ -0,0 +1,258 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read};
+
+use unicode_script::UnicodeScript; // Script property of every char (Latin, Devanagari, Han, ...)
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::words::{self, WordKind};
+
+// Short lines are counted in batches of about this size
+const BATCH: usize = 64 * 1024;
+// Lines longer than this are cut into pieces at a space, or failing that before the last word,
+// so one huge line cannot fill memory
+const PIECE: usize = 1 << 20;
+
+// --- STATISTICS ---
+// Counts for one input, built piece by piece: memory depends on the number of distinct words,
+// not on the size of the input.
+#[derive(Debug, Clone, Default)]
+pub struct TextStats {
+    pub bytes: u64,
+    pub chars: u64, // Invalid UTF-8 counts as one U+FFFD per bad sequence
+    pub graphemes: u64,
+    pub words: u64, // UAX #29 words and numbers, see words.rs
+    pub lines: u64, // Like str::lines: a last line without '\n' still counts
+    pub longest_line: Option<LongestLine>,
+    word_counts: HashMap<String, u64>, // Lowercased words (not numbers)
+    script_counts: HashMap<&'static str, u64>,
+    line_width: usize, // Columns of the line read so far
+    line_open: bool,   // Some of the current line has been read
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LongestLine {
+    pub number: u64,  // 1-based
+    pub width: usize, // Terminal columns without the line ending, see slicing::display_width
+}
+
+impl TextStats {
+    pub fn new() -> TextStats {
+        TextStats::default()
+    }
+
+    // Reads everything from `reader`; a multi-GB file is fine, it never sits in memory whole
+    pub fn from_reader<R: Read>(reader: R) -> io::Result<TextStats> {
+        let mut stats = TextStats::new();
+        let mut reader = BufReader::new(reader);
+        let mut buf = Vec::new();
+        let mut scanned = 0; // buf[..scanned] has no safe place to cut
+        loop {
+            // Up to the next line end, or until buf holds a whole number of pieces
+            let limit = (PIECE - buf.len() % PIECE) as u64;
+            let read = (&mut reader).take(limit).read_until(b'\n', &mut buf)?;
+            if read == 0 {
+                stats.add_bytes(&buf);
+                break;
+            }
+            if buf.ends_with(b"\n") {
+                if buf.len() >= BATCH {
+                    stats.add_bytes(&buf);
+                    buf.clear();
+                    scanned = 0;
+                }
+                continue;
+            }
+            // A long line: hand over everything up to the last safe cut. Without one (a
+            // megabyte with no spaces), everything but the last word, which waits for the rest
+            // of itself; buf never holds much more than a piece.
+            let cut = safe_cut(&buf, scanned).or_else(|| {
+                // The read stops at a whole piece, so a shorter buf ends the line
+                (buf.len() % PIECE == 0).then(|| forced_cut(&buf))
+            });
+            if let Some(cut) = cut {
+                stats.add_bytes(&buf[..cut]);
+                buf.drain(..cut);
+            }
+            scanned = buf.len();
+        }
+        stats.end_line();
+        Ok(stats)
+    }
+
+    // Stats for text already in memory
+    pub fn from_text(text: &str) -> TextStats {
+        let mut stats = TextStats::new();
+        stats.bytes = text.len() as u64;
+        stats.count(text);
+        stats.end_line();
+        stats
+    }
+
+    // Adds another input's counts, for a total over several files. The longest line keeps the
+    // line number from the input it came from.
+    pub fn merge(&mut self, other: &TextStats) {
+        self.bytes += other.bytes;
+        self.chars += other.chars;
+        self.graphemes += other.graphemes;
+        self.words += other.words;
+        self.lines += other.lines;
+        if let Some(line) = other.longest_line
+            && self.longest_line.is_none_or(|own| line.width > own.width)
+        {
+            self.longest_line = Some(line);
+        }
+        for (word, count) in &other.word_counts {
+            *self.word_counts.entry(word.clone()).or_insert(0) += count;
+        }
+        for (&script, count) in &other.script_counts {
+            *self.script_counts.entry(script).or_insert(0) += count;
+        }
+    }
+
+    // The `n` most frequent words, most frequent first; ties in alphabetical order
+    pub fn top_words(&self, n: usize) -> Vec<(&str, u64)> {
+        let mut top: Vec<(&str, u64)> = self
+            .word_counts
+            .iter()
+            .map(|(word, &count)| (word.as_str(), count))
+            .collect();
+        top.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        top.truncate(n);
+        top
+    }
+
+    // Non-whitespace chars per Unicode script, most common first. Digits and punctuation shared
+    // by many scripts are "Common"; combining marks that take their base's script are "Inherited".
+    pub fn scripts(&self) -> Vec<(&'static str, u64)> {
+        let mut scripts: Vec<(&'static str, u64)> =
+            self.script_counts.iter().map(|(&s, &c)| (s, c)).collect();
+        scripts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        scripts
+    }
+
+    fn add_bytes(&mut self, piece: &[u8]) {
+        self.bytes += piece.len() as u64;
+        self.count(&String::from_utf8_lossy(piece));
+    }
+
+    // `text` must end at a line end, at a cut from safe_cut, or at the end of the input
+    fn count(&mut self, text: &str) {
+        self.chars += text.chars().count() as u64;
+        // Graphemes and line widths in one pass; "\r\n" is a single grapheme
+        for grapheme in text.graphemes(true) {
+            self.graphemes += 1;
+            self.line_open = true;
+            if grapheme.ends_with('\n') {
+                self.end_line();
+            } else {
+                self.line_width += grapheme.width(); // Same sum as slicing::display_width
+            }
+        }
+        for word in words::words(text) {
+            self.words += 1;
+            if word.kind != WordKind::Word {
+                continue;
+            }
+            // Most words are lowercase already, so only allocate for new or capitalized ones
+            let lower = if word.text.chars().any(char::is_uppercase) {
+                Cow::Owned(word.text.to_lowercase())
+            } else {
+                Cow::Borrowed(word.text)
+            };
+            match self.word_counts.get_mut(lower.as_ref()) {
+                Some(count) => *count += 1,
+                None => {
+                    self.word_counts.insert(lower.into_owned(), 1);
+                }
+            }
+        }
+        // Scripts come in runs, so count a run at a time instead of a map update per char
+        let mut run: Option<(&'static str, u64)> = None;
+        for c in text.chars().filter(|c| !c.is_whitespace()) {
+            let script = match c {
+                'a'..='z' | 'A'..='Z' => "Latin",
+                _ if c.is_ascii() => "Common",
+                _ => c.script().full_name(),
+            };
+            match &mut run {
+                Some((current, count)) if *current == script => *count += 1,
+                _ => {
+                    self.add_script_run(run);
+                    run = Some((script, 1));
+                }
+            }
+        }
+        self.add_script_run(run);
+    }
+
+    fn add_script_run(&mut self, run: Option<(&'static str, u64)>) {
+        if let Some((script, count)) = run {
+            *self.script_counts.entry(script).or_insert(0) += count;
+        }
+    }
+
+    fn end_line(&mut self) {
+        if !self.line_open {
+            return;
+        }
+        self.lines += 1;
+        if self
+            .longest_line
+            .is_none_or(|line| self.line_width > line.width)
+        {
+            self.longest_line = Some(LongestLine {
+                number: self.lines,
+                width: self.line_width,
+            });
+        }
+        self.line_width = 0;
+        self.line_open = false;
+    }
+}
+
+// Where a piece can be cut without changing any count: right after a line end, or after an ASCII
+// space or tab that is followed by an ASCII byte. That is always a char, grapheme and word
+// boundary (a space followed by a combining mark would be one grapheme, hence the ASCII check).
+fn safe_cut(buf: &[u8], from: usize) -> Option<usize> {
+    (from.max(1)..buf.len()).rev().find(|&i| match buf[i - 1] {
+        b'\n' => true,
+        b' ' | b'\t' => buf[i].is_ascii(),
+        _ => false,
+    })
+}
+
+// Where to cut a full piece that has no safe cut: before its last UAX #29 word segment, which is
+// also a char and grapheme boundary, so the counts stay exact. Only a single word longer than a
+// piece is cut inside, before its last grapheme, and then counts as one word per piece.
+fn forced_cut(buf: &[u8]) -> usize {
+    // A char the read cut short waits for the rest of its bytes
+    let lead = (buf.len().saturating_sub(4)..buf.len())
+        .rev()
+        .find(|&i| buf[i] & 0xC0 != 0x80);
+    let complete = match lead.map(|i| (i, buf[i].leading_ones() as usize)) {
+        Some((i, len @ 2..=4)) if len > buf.len() - i => i,
+        _ => buf.len(),
+    };
+    // Only the valid UTF-8 at the end is segmented; invalid bytes end at a char boundary anyway
+    let Some(chunk) = buf[..complete].utf8_chunks().last() else {
+        return complete;
+    };
+    if !chunk.invalid().is_empty() {
+        return complete;
+    }
+    let text = chunk.valid();
+    let start = complete - text.len();
+    let last_word = text
+        .split_word_bound_indices()
+        .next_back()
+        .map_or(0, |(i, _)| i);
+    if last_word > 0 || start > 0 {
+        return start + last_word;
+    }
+    match text.grapheme_indices(true).next_back() {
+        Some((i, _)) if i > 0 => i,
+        _ => complete, // One grapheme a piece long: cut it after all
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::num::Number;
+
+// --- ERRORS ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubarrayError {
+    Overflow { index: usize }, // Adding nums[index] to the running sum overflowed
+    Negative { index: usize }, // A sliding-window query got a negative number at nums[index]
+}
+
+impl fmt::Display for SubarrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubarrayError::Overflow { index } => {
+                write!(f, "running sum overflowed at index {index}")
+            }
+            SubarrayError::Negative { index } => write!(
+                f,
+                "sliding windows need non-negative numbers, found one at index {index}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SubarrayError {}
+
+// --- TARGET SUM WITH PREFIX SUMS ---
+// prefix(j) = nums[0] + ... + nums[j - 1], so nums[i..j] sums to target exactly when
+// prefix(i) = prefix(j) - target. Remembering each prefix value in a HashMap answers that in O(1),
+// which makes these O(n) and correct for negative numbers too. Floats are not Hash + Eq, so
+// these queries are for integers only (exact float equality would not be reliable anyway).
+
+// Longest subarray summing to target; among equally long ones, the one starting first
+pub fn longest_with_sum<T>(nums: &[T], target: T) -> Result<Option<Range<usize>>, SubarrayError>
+where
+    T: Number + Eq + Hash,
+{
+    let mut first_seen = HashMap::from([(T::ZERO, 0)]); // prefix value → earliest index
+    let mut best: Option<Range<usize>> = None;
+    let mut prefix = T::ZERO;
+    for (index, &num) in nums.iter().enumerate() {
+        prefix = add(prefix, num, index)?;
+        let end = index + 1;
+        // If prefix - target does not fit in T, no prefix can be equal to it
+        if let Some(wanted) = prefix.checked_sub(target)
+            && let Some(&start) = first_seen.get(&wanted)
+            && best.as_ref().is_none_or(|b| end - start > b.len())
+        {
+            best = Some(start..end);
+        }
+        first_seen.entry(prefix).or_insert(end);
+    }
+    Ok(best)
+}
+
+// Shortest non-empty subarray summing to target; among equally short ones, the one starting first
+pub fn shortest_with_sum<T>(nums: &[T], target: T) -> Result<Option<Range<usize>>, SubarrayError>
+where
+    T: Number + Eq + Hash,
+{
+    let mut last_seen = HashMap::from([(T::ZERO, 0)]); // prefix value → latest index
+    let mut best: Option<Range<usize>> = None;
+    let mut prefix = T::ZERO;
+    for (index, &num) in nums.iter().enumerate() {
+        prefix = add(prefix, num, index)?;
+        let end = index + 1;
+        if let Some(wanted) = prefix.checked_sub(target)
+            && let Some(&start) = last_seen.get(&wanted)
+            && best.as_ref().is_none_or(|b| end - start < b.len())
+        {
+            best = Some(start..end);
+        }
+        last_seen.insert(prefix, end);
+    }
+    Ok(best)
+}
+
+// Every subarray summing to target, ordered by end, then start. There can be O(n²) of them
+// ([0, 0, 0, ...] with target 0); count_with_sum counts them in O(n) without listing them.
+pub fn all_with_sum<T>(nums: &[T], target: T) -> Result<Vec<Range<usize>>, SubarrayError>
+where
+    T: Number + Eq + Hash,
+{
+    let mut seen: HashMap<T, Vec<usize>> = HashMap::from([(T::ZERO, vec![0])]);
+    let mut found = Vec::new();
+    let mut prefix = T::ZERO;
+    for (index, &num) in nums.iter().enumerate() {
+        prefix = add(prefix, num, index)?;
+        let end = index + 1;
+        if let Some(wanted) = prefix.checked_sub(target)
+            && let Some(starts) = seen.get(&wanted)
+        {
+            found.extend(starts.iter().map(|&start| start..end));
+        }
+        seen.entry(prefix).or_default().push(end);
+    }
+    Ok(found)
+}
+
+pub fn count_with_sum<T>(nums: &[T], target: T) -> Result<usize, SubarrayError>
+where
+    T: Number + Eq + Hash,
+{
+    let mut seen = HashMap::from([(T::ZERO, 1usize)]);
+    let mut count = 0;
+    let mut prefix = T::ZERO;
+    for (index, &num) in nums.iter().enumerate() {
+        prefix = add(prefix, num, index)?;
+        if let Some(wanted) = prefix.checked_sub(target) {
+            count += seen.get(&wanted).copied().unwrap_or(0);
+        }
+        *seen.entry(prefix).or_default() += 1;
+    }
+    Ok(count)
+}
+
+// --- SLIDING WINDOWS (NON-NEGATIVE INPUT) ---
+// With no negative numbers, growing a window never lowers its sum and shrinking never raises it,
+// so two indices that only move forward are enough: O(n) time, O(1) memory, works for floats.
+// A negative number breaks that, so on reaching one these return SubarrayError::Negative
+// instead of a wrong answer.
+
+// First subarray (smallest end) summing to exactly target
+pub fn window_with_sum<T: Number>(
+    nums: &[T],
+    target: T,
+) -> Result<Option<Range<usize>>, SubarrayError> {
+    let mut window = Window::new(nums);
+    for index in 0..nums.len() {
+        window.push(index)?;
+        while window.exceeds(target) {
+            window.pop();
+        }
+        if window.sum == target && !window.is_empty() {
+            return Ok(Some(window.range()));
+        }
+    }
+    Ok(None)
+}
+
+// Longest subarray whose sum is at most limit
+pub fn longest_window_at_most<T: Number>(
+    nums: &[T],
+    limit: T,
+) -> Result<Option<Range<usize>>, SubarrayError> {
+    let mut window = Window::new(nums);
+    let mut best: Option<Range<usize>> = None;
+    for index in 0..nums.len() {
+        window.push(index)?;
+        while window.exceeds(limit) {
+            window.pop();
+        }
+        if !window.is_empty() && best.as_ref().is_none_or(|b| window.len() > b.len()) {
+            best = Some(window.range());
+        }
+    }
+    Ok(best)
+}
+
+// Shortest subarray whose sum is at least target
+pub fn shortest_window_at_least<T: Number>(
+    nums: &[T],
+    target: T,
+) -> Result<Option<Range<usize>>, SubarrayError> {
+    let mut window = Window::new(nums);
+    let mut best: Option<Range<usize>> = None;
+    for index in 0..nums.len() {
+        window.push(index)?;
+        while window.reaches(target) {
+            if best.as_ref().is_none_or(|b| window.len() < b.len()) {
+                best = Some(window.range());
+            }
+            window.pop();
+        }
+    }
+    Ok(best)
+}
+
+// The window nums[start..end] and its sum. If adding the newest number would overflow, it waits
+// in `pending` (the true sum is then above T's maximum, so above any bound) until numbers leaving
+// on the left make room. That way a window never fails just because its sum got too big.
+struct Window<'a, T> {
+    nums: &'a [T],
+    start: usize,
+    end: usize,
+    sum: T, // Sum of nums[start..end], minus the pending number if there is one
+    pending: Option<T>,
+}
+
+impl<'a, T: Number> Window<'a, T> {
+    fn new(nums: &'a [T]) -> Window<'a, T> {
+        Window {
+            nums,
+            start: 0,
+            end: 0,
+            sum: T::ZERO,
+            pending: None,
+        }
+    }
+
+    // Grows the window by nums[index], which must be the number right after it
+    fn push(&mut self, index: usize) -> Result<(), SubarrayError> {
+        let num = self.nums[index];
+        if num < T::ZERO {
+            return Err(SubarrayError::Negative { index });
+        }
+        self.end = index + 1;
+        self.pending = Some(num);
+        self.add_pending();
+        Ok(())
+    }
+
+    fn pop(&mut self) {
+        let num = self.nums[self.start];
+        self.start += 1;
+        if self.pending.is_some() && self.len() == 1 {
+            self.sum = T::ZERO; // Only the pending number is left
+        } else if self.is_empty() {
+            self.sum = T::ZERO; // Also drops float rounding left over from the additions
+        } else {
+            // Cannot underflow: num is part of the sum
+            self.sum = self.sum.checked_sub(num).unwrap_or(T::ZERO);
+        }
+        self.add_pending();
+    }
+
+    fn add_pending(&mut self) {
+        if let Some(num) = self.pending
+            && let Some(sum) = self.sum.checked_add(num)
+        {
+            self.sum = sum;
+            self.pending = None;
+        }
+    }
+
+    fn exceeds(&self, bound: T) -> bool {
+        !self.is_empty() && (self.pending.is_some() || self.sum > bound)
+    }
+
+    fn reaches(&self, bound: T) -> bool {
+        !self.is_empty() && (self.pending.is_some() || self.sum >= bound)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+// --- MAXIMUM SUBARRAY (KADANE) ---
+// Largest sum of any non-empty subarray, with its range; None for an empty slice.
+// Walking left to right, the best run ending here either extends the best run ending one step
+// earlier or starts fresh; a run whose sum is not positive only drags what follows down.
+pub fn max_subarray<T: Number>(nums: &[T]) -> Result<Option<(Range<usize>, T)>, SubarrayError> {
+    let Some(&first) = nums.first() else {
+        return Ok(None);
+    };
+    let mut best = (0..1, first);
+    let (mut run_start, mut run_sum) = (0, first);
+    for (index, &num) in nums.iter().enumerate().skip(1) {
+        if run_sum > T::ZERO {
+            run_sum = add(run_sum, num, index)?;
+        } else {
+            (run_start, run_sum) = (index, num);
+        }
+        if run_sum > best.1 {
+            best = (run_start..index + 1, run_sum);
+        }
+    }
+    Ok(Some(best))
+}
+
+fn add<T: Number>(sum: T, num: T, index: usize) -> Result<T, SubarrayError> {
+    sum.checked_add(num)
+        .ok_or(SubarrayError::Overflow { index })
+}
@@ -0,0 +1,163 @@
+use std::ops::{Add, Sub};
+
+use crate::num::Number;
+
+// --- SIMPLE MODES ---
+// `for num in nums { res += num }` panics on overflow in debug builds and silently wraps around in
+// release builds. These make the choice explicit. Both work left to right, so checked_sum gives
+// up at the first partial sum that overflows, even if later negative numbers would have brought
+// the total back in range; widening_sum or chunked_sum see the true total.
+
+// None as soon as the running sum overflows (or, for floats, stops being finite)
+pub fn checked_sum<T: Number>(nums: &[T]) -> Option<T> {
+    nums.iter()
+        .try_fold(T::ZERO, |sum, &num| sum.checked_add(num))
+}
+
+// Sticks at the type's maximum or minimum instead of overflowing
+pub fn saturating_sum<T: Number>(nums: &[T]) -> T {
+    nums.iter()
+        .fold(T::ZERO, |sum, &num| sum.saturating_add(num))
+}
+
+// Sums in a wider type chosen by the caller: `let total: Option<i64> = widening_sum(&[i32::MAX; 3])`.
+// i32 values in an i64 only overflow past 2^32 of them, and i64 values in an i128 never do.
+pub fn widening_sum<T, W>(nums: &[T]) -> Option<W>
+where
+    T: Copy,
+    W: Number + From<T>,
+{
+    nums.iter()
+        .try_fold(W::ZERO, |sum, &num| sum.checked_add(W::from(num)))
+}
+
+// --- COMPENSATED FLOAT SUMMATION ---
+// Adding a small float to a large one rounds away the small one's low bits, and over many additions
+// the lost bits add up: summing 0.1 ten million times with plain `+` gives 999999.9998389754.
+// Compensated summation keeps the rounding error of every addition in a second variable and adds
+// it back at the end.
+pub trait Float: Number + Add<Output = Self> + Sub<Output = Self> {
+    fn abs(self) -> Self;
+}
+
+macro_rules! float {
+    ($($t:ty),*) => {$(
+        impl Float for $t {
+            fn abs(self) -> $t {
+                <$t>::abs(self)
+            }
+        }
+    )*};
+}
+
+float!(f32, f64);
+
+// Kahan: subtracts the error of the previous step from the next number before adding it
+pub fn kahan_sum<F: Float>(nums: &[F]) -> F {
+    let (mut sum, mut error) = (F::ZERO, F::ZERO);
+    for &num in nums {
+        let corrected = num - error;
+        let next = sum + corrected;
+        error = (next - sum) - corrected; // What the addition lost, with the opposite sign
+        sum = next;
+    }
+    sum
+}
+
+// Neumaier: like Kahan, but also right when the next number is larger than the running sum
+// (Kahan gives 0.0 for [1.0, 1e100, 1.0, -1e100], Neumaier the correct 2.0)
+pub fn neumaier_sum<F: Float>(nums: &[F]) -> F {
+    let (mut sum, mut error) = (F::ZERO, F::ZERO);
+    for &num in nums {
+        let next = sum + num;
+        error = if sum.abs() >= num.abs() {
+            error + ((sum - next) + num)
+        } else {
+            error + ((num - next) + sum)
+        };
+        sum = next;
+    }
+    sum + error
+}
+
+// --- CHUNKED ACCUMULATION ---
+// A checked loop has a branch after every addition, and a float loop must add in exactly the
+// written order, so neither can use SIMD. chunked_sum adds into LANES independent accumulators
+// of a wider type (which the compiler turns into vector instructions) over blocks small enough
+// that the accumulators cannot overflow, and only checks once per block.
+pub trait Summable: Number {
+    type Acc: Number + Add<Output = Self::Acc>; // Holds the sum of a whole block without overflowing
+    fn widen(self) -> Self::Acc;
+    fn narrow(acc: Self::Acc) -> Option<Self>; // None if the total does not fit back into Self
+}
+
+macro_rules! summable_int {
+    ($($t:ty => $acc:ty),*) => {$(
+        impl Summable for $t {
+            type Acc = $acc;
+
+            fn widen(self) -> $acc {
+                self as $acc
+            }
+
+            fn narrow(acc: $acc) -> Option<$t> {
+                <$t>::try_from(acc).ok()
+            }
+        }
+    )*};
+}
+
+summable_int!(
+    i8 => i64, i16 => i64, i32 => i64, i64 => i128, isize => i128,
+    u8 => u64, u16 => u64, u32 => u64, u64 => u128, usize => u128
+);
+
+impl Summable for f32 {
+    type Acc = f64; // f32 values summed in f64 are also more accurate
+
+    fn widen(self) -> f64 {
+        self as f64
+    }
+
+    fn narrow(acc: f64) -> Option<f32> {
+        Some(acc as f32).filter(|sum| sum.is_finite())
+    }
+}
+
+impl Summable for f64 {
+    type Acc = f64;
+
+    fn widen(self) -> f64 {
+        self
+    }
+
+    fn narrow(acc: f64) -> Option<f64> {
+        Some(acc).filter(|sum| sum.is_finite())
+    }
+}
+
+const LANES: usize = 8;
+// Each lane adds at most BLOCK / LANES = 8192 numbers, far below 2^32 (i32 into i64)
+const BLOCK: usize = 1 << 16;
+
+// Integers: the exact total if it fits in T, otherwise None. Floats: the lanes are added in a
+// different order than left to right, so the result is rounded differently (f32 gains precision
+// from its f64 lanes); None if it is not finite.
+pub fn chunked_sum<T: Summable>(nums: &[T]) -> Option<T> {
+    let mut total = T::Acc::ZERO;
+    for block in nums.chunks(BLOCK) {
+        let mut lanes = [T::Acc::ZERO; LANES];
+        let mut chunks = block.chunks_exact(LANES);
+        for chunk in &mut chunks {
+            for (lane, &num) in lanes.iter_mut().zip(chunk) {
+                *lane = *lane + num.widen();
+            }
+        }
+        let mut block_sum = lanes.into_iter().fold(T::Acc::ZERO, |a, b| a + b);
+        for &num in chunks.remainder() {
+            block_sum = block_sum + num.widen();
+        }
+        total = total.checked_add(block_sum)?;
+    }
+    T::narrow(total)
+}
@@ -0,0 +1,430 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::slicing;
+
+mod parse; // Template source → syntax tree, with the position of every tag and value
+
+use parse::{Base, Expr, Filter, Node};
+
+// --- TEMPLATES ---
+// my_function in main.rs builds its message with format!, so changing the message means
+// recompiling. A Template is the same idea read at run time, from a config file say:
+//   {{ name }}                      a value from the context; {{ user.name }} for a field
+//   {{ name | trim | upper }}       filters: upper, lower, trim, length, truncate(n),
+//                                   default("text"), join(", ")
+//   {% if admin %}…{% else %}…{% endif %}   {% if not admin %} also works
+//   {% for item in items %}…{% endfor %}    with loop.index (from 1), loop.first, loop.last
+//   {# a comment #}                 dropped; a '-' inside any delimiter ({{- ... -}}) trims the
+//                                   whitespace on that side
+// The source is parsed once by Template::new; render then only walks the syntax tree, and
+// values are borrowed from the Context, not copied. Errors give the line and column in the
+// template, both for malformed templates and for values missing when rendering.
+//   let t = Template::new("Hello, {{ name | upper }}!")?;
+//   t.render(&Context::new().with("name", "ferris"))?   // "Hello, FERRIS!"
+
+// Line and column in the template source, both from 1; columns count chars
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+pub(crate) fn position(source: &str, at: usize) -> Position {
+    let before = &source[..at];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Position {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    // Compiling
+    UnclosedTag { at: Position }, // A "{{", "{%" or "{#" with no closing delimiter
+    UnclosedBlock { at: Position, block: &'static str }, // {% if %} or {% for %} with no end tag
+    TooDeep { at: Position, block: &'static str }, // A block inside too many others
+    UnexpectedTag { at: Position, tag: String }, // Unknown tag, or else / endif / endfor out of place
+    Unexpected { at: Position, found: String },  // Something that makes no sense inside a tag
+    UnknownFilter { at: Position, name: String },
+    BadArgument { at: Position, filter: String }, // Missing or wrong kind of argument
+    // Rendering
+    Undefined { at: Position, name: String }, // No such value in the context
+    NotText { at: Position, name: String },   // A list or map where text is needed
+    NotList { at: Position, name: String },   // {% for %} over something that is not a list
+}
+
+impl TemplateError {
+    pub fn position(&self) -> Position {
+        match self {
+            TemplateError::UnclosedTag { at }
+            | TemplateError::UnclosedBlock { at, .. }
+            | TemplateError::TooDeep { at, .. }
+            | TemplateError::UnexpectedTag { at, .. }
+            | TemplateError::Unexpected { at, .. }
+            | TemplateError::UnknownFilter { at, .. }
+            | TemplateError::BadArgument { at, .. }
+            | TemplateError::Undefined { at, .. }
+            | TemplateError::NotText { at, .. }
+            | TemplateError::NotList { at, .. } => *at,
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.position())?;
+        match self {
+            TemplateError::UnclosedTag { .. } => write!(f, "tag is never closed"),
+            TemplateError::UnclosedBlock { block, .. } => {
+                write!(f, "{{% {block} %}} has no {{% end{block} %}}")
+            }
+            TemplateError::TooDeep { block, .. } => {
+                write!(f, "{{% {block} %}} is nested too deeply")
+            }
+            TemplateError::UnexpectedTag { tag, .. } => write!(f, "unexpected tag `{tag}`"),
+            TemplateError::Unexpected { found, .. } => write!(f, "unexpected {found}"),
+            TemplateError::UnknownFilter { name, .. } => write!(f, "unknown filter `{name}`"),
+            TemplateError::BadArgument { filter, .. } => {
+                write!(f, "invalid argument for filter `{filter}`")
+            }
+            TemplateError::Undefined { name, .. } => write!(f, "`{name}` is not defined"),
+            TemplateError::NotText { name, .. } => write!(f, "`{name}` is not text"),
+            TemplateError::NotList { name, .. } => write!(f, "`{name}` is not a list"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+// --- VALUES ---
+// What a template can show. Strings may borrow from the caller: a Context built from &str
+// values renders without copying them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value<'a> {
+    Str(Cow<'a, str>),
+    Int(i64),
+    Bool(bool),
+    List(Vec<Value<'a>>),
+    Map(BTreeMap<&'a str, Value<'a>>), // Fields, for {{ user.name }}
+}
+
+impl<'a> Value<'a> {
+    pub fn map(fields: impl IntoIterator<Item = (&'a str, Value<'a>)>) -> Value<'a> {
+        Value::Map(fields.into_iter().collect())
+    }
+
+    // What {% if %} tests: false, 0, "" and empty lists and maps are false
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Str(s) => !s.is_empty(),
+            Value::Int(n) => *n != 0,
+            Value::Bool(b) => *b,
+            Value::List(items) => !items.is_empty(),
+            Value::Map(fields) => !fields.is_empty(),
+        }
+    }
+
+    // Strings as they are, numbers and booleans written out; None for lists and maps
+    fn text(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::Str(s) => Some(Cow::Borrowed(s)),
+            Value::Int(n) => Some(Cow::Owned(n.to_string())),
+            Value::Bool(b) => Some(Cow::Borrowed(if *b { "true" } else { "false" })),
+            Value::List(_) | Value::Map(_) => None,
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Value<'a> {
+    fn from(s: &'a str) -> Value<'a> {
+        Value::Str(Cow::Borrowed(s))
+    }
+}
+
+impl<'a> From<&'a String> for Value<'a> {
+    fn from(s: &'a String) -> Value<'a> {
+        Value::Str(Cow::Borrowed(s))
+    }
+}
+
+impl From<String> for Value<'_> {
+    fn from(s: String) -> Self {
+        Value::Str(Cow::Owned(s))
+    }
+}
+
+impl From<i64> for Value<'_> {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+impl From<i32> for Value<'_> {
+    fn from(n: i32) -> Self {
+        Value::Int(n.into())
+    }
+}
+
+impl From<bool> for Value<'_> {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl<'a, T: Into<Value<'a>>> From<Vec<T>> for Value<'a> {
+    fn from(items: Vec<T>) -> Value<'a> {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+// The named values a template is rendered with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context<'a> {
+    values: HashMap<&'a str, Value<'a>>,
+}
+
+impl<'a> Context<'a> {
+    pub fn new() -> Context<'a> {
+        Context::default()
+    }
+
+    pub fn with(mut self, name: &'a str, value: impl Into<Value<'a>>) -> Context<'a> {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: &'a str, value: impl Into<Value<'a>>) {
+        self.values.insert(name, value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value<'a>> {
+        self.values.get(name)
+    }
+}
+
+// --- TEMPLATE ---
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    source: String,
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn new(source: &str) -> Result<Template, TemplateError> {
+        Ok(Template {
+            nodes: parse::parse(source)?,
+            source: source.to_string(),
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn render(&self, context: &Context) -> Result<String, TemplateError> {
+        let mut out = String::with_capacity(self.source.len());
+        self.render_to(context, &mut out)?;
+        Ok(out)
+    }
+
+    // Appends to `out`, so one buffer can serve many renders. On error, `out` keeps whatever
+    // was rendered before the failing tag.
+    pub fn render_to(&self, context: &Context, out: &mut String) -> Result<(), TemplateError> {
+        let mut renderer = Renderer {
+            template: self,
+            context,
+            scopes: Vec::new(),
+        };
+        renderer.nodes(&self.nodes, out)
+    }
+}
+
+// --- RENDERING ---
+struct Renderer<'t, 'v, 'a> {
+    template: &'t Template,
+    context: &'v Context<'a>,
+    scopes: Vec<Scope<'t, 'v, 'a>>, // One per enclosing {% for %}, innermost last
+}
+
+struct Scope<'t, 'v, 'a> {
+    var: &'t str,
+    item: &'v Value<'a>,
+    index: usize,
+    len: usize,
+}
+
+impl<'t, 'v, 'a> Renderer<'t, 'v, 'a> {
+    fn nodes(&mut self, nodes: &'t [Node], out: &mut String) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(range) => out.push_str(&self.template.source[range.clone()]),
+                Node::Print(expr) => {
+                    let value = self.eval(expr)?;
+                    let text = value.text().ok_or_else(|| self.not_text(expr))?;
+                    out.push_str(&text);
+                }
+                Node::If {
+                    negated,
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    // A missing value is false rather than an error, for optional settings
+                    let truthy = match self.eval(condition) {
+                        Ok(value) => value.is_truthy(),
+                        Err(TemplateError::Undefined { .. }) => false,
+                        Err(e) => return Err(e),
+                    };
+                    self.nodes(if truthy != *negated { then } else { otherwise }, out)?;
+                }
+                Node::For { var, list, body } => {
+                    let Base::Path(path) = &list.base else {
+                        return Err(self.not_list(list)); // The parser only allows names
+                    };
+                    let items = match self.path(path) {
+                        Some(Cow::Borrowed(Value::List(items))) => items,
+                        Some(_) => return Err(self.not_list(list)),
+                        None => return Err(self.undefined(list)),
+                    };
+                    for (index, item) in items.iter().enumerate() {
+                        self.scopes.push(Scope {
+                            var,
+                            item,
+                            index,
+                            len: items.len(),
+                        });
+                        let done = self.nodes(body, out);
+                        self.scopes.pop();
+                        done?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn path(&self, path: &[String]) -> Option<Cow<'v, Value<'a>>> {
+        let (first, fields) = path.split_first()?;
+        let mut value = match self.scopes.iter().rev().find(|s| s.var == first) {
+            Some(scope) => Cow::Borrowed(scope.item),
+            None if first == "loop" && !self.scopes.is_empty() => {
+                let scope = self.scopes.last()?;
+                let (field, rest) = fields.split_first()?;
+                let value = match field.as_str() {
+                    "index" => Value::Int(scope.index as i64 + 1),
+                    "first" => Value::Bool(scope.index == 0),
+                    "last" => Value::Bool(scope.index + 1 == scope.len),
+                    _ => return None,
+                };
+                return rest.is_empty().then_some(Cow::Owned(value));
+            }
+            None => Cow::Borrowed(self.context.get(first)?),
+        };
+        for field in fields {
+            value = match value {
+                Cow::Borrowed(Value::Map(map)) => Cow::Borrowed(map.get(field.as_str())?),
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Cow<'v, Value<'a>>, TemplateError> {
+        let mut value: Option<Cow<'v, Value<'a>>> = match &expr.base {
+            Base::Path(path) => self.path(path),
+            Base::Str(s) => Some(Cow::Owned(Value::Str(Cow::Owned(s.clone())))),
+            Base::Int(n) => Some(Cow::Owned(Value::Int(*n))),
+        };
+        for (_, filter) in &expr.filters {
+            if let Filter::Default(fallback) = filter {
+                if value
+                    .as_ref()
+                    .is_none_or(|v| matches!(&**v, Value::Str(s) if s.is_empty()))
+                {
+                    value = Some(Cow::Owned(Value::Str(Cow::Owned(fallback.clone()))));
+                }
+                continue;
+            }
+            let current = value.ok_or_else(|| self.undefined(expr))?;
+            value = Some(Cow::Owned(self.filter(expr, filter, &current)?));
+        }
+        value.ok_or_else(|| self.undefined(expr))
+    }
+
+    fn filter(
+        &self,
+        expr: &Expr,
+        filter: &Filter,
+        value: &Value<'a>,
+    ) -> Result<Value<'a>, TemplateError> {
+        let text = || value.text().ok_or_else(|| self.not_text(expr));
+        let value = match filter {
+            Filter::Upper => Value::from(text()?.to_uppercase()),
+            Filter::Lower => Value::from(text()?.to_lowercase()),
+            Filter::Trim => Value::from(text()?.trim().to_string()),
+            Filter::Truncate(width) => {
+                Value::from(slicing::truncate_with_ellipsis(&text()?, *width, "…").into_owned())
+            }
+            Filter::Length => match value {
+                Value::List(items) => Value::Int(items.len() as i64),
+                Value::Map(fields) => Value::Int(fields.len() as i64),
+                _ => Value::Int(text()?.graphemes(true).count() as i64),
+            },
+            Filter::Join(separator) => match value {
+                Value::List(items) => {
+                    let mut joined = String::new();
+                    for (i, item) in items.iter().enumerate() {
+                        if i > 0 {
+                            joined.push_str(separator);
+                        }
+                        joined.push_str(&item.text().ok_or_else(|| self.not_text(expr))?);
+                    }
+                    Value::from(joined)
+                }
+                _ => return Err(self.not_list(expr)),
+            },
+            Filter::Default(_) => value.clone(), // Handled by eval, as it also takes missing values
+        };
+        Ok(value)
+    }
+
+    fn name(&self, expr: &Expr) -> &'t str {
+        &self.template.source[expr.span.clone()]
+    }
+
+    fn at(&self, offset: usize) -> Position {
+        position(&self.template.source, offset)
+    }
+
+    fn undefined(&self, expr: &Expr) -> TemplateError {
+        TemplateError::Undefined {
+            at: self.at(expr.span.start),
+            name: self.name(expr).to_string(),
+        }
+    }
+
+    fn not_text(&self, expr: &Expr) -> TemplateError {
+        TemplateError::NotText {
+            at: self.at(expr.span.start),
+            name: self.name(expr).to_string(),
+        }
+    }
+
+    fn not_list(&self, expr: &Expr) -> TemplateError {
+        TemplateError::NotList {
+            at: self.at(expr.span.start),
+            name: self.name(expr).to_string(),
+        }
+    }
+}
@@ -0,0 +1,504 @@
+use std::ops::Range;
+
+use super::{TemplateError, position};
+
+// --- SYNTAX TREE ---
+// Text is kept as byte ranges into the template source, which the Template owns: compiling
+// copies nothing, and rendering writes the ranges out as they are.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    Text(Range<usize>),
+    Print(Expr), // {{ expr }}
+    If {
+        negated: bool, // {% if not expr %}
+        condition: Expr,
+        then: Vec<Node>,
+        otherwise: Vec<Node>, // After {% else %}
+    },
+    For {
+        var: String,
+        list: Expr,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Expr {
+    pub span: Range<usize>, // The base value in the source, for error messages
+    pub base: Base,
+    pub filters: Vec<(usize, Filter)>, // With the byte offset of each filter's name
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Base {
+    Path(Vec<String>), // user.name → ["user", "name"]
+    Str(String),
+    Int(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Length,          // Graphemes in a string, items in a list
+    Truncate(usize), // At most n columns, "…" included
+    Default(String), // For a missing value or an empty string
+    Join(String),    // The items of a list, with a separator
+}
+
+// --- TAGS ---
+// The template cut at its delimiters: text, {{ expressions }} and {% tags %}, with
+// {# comments #} dropped. Each inner range excludes the delimiters and any '-' trim marker.
+#[derive(Debug, Clone)]
+enum Piece {
+    Text(Range<usize>),
+    Print(Range<usize>),
+    Tag(Range<usize>),
+}
+
+fn pieces(source: &str) -> Result<Vec<Piece>, TemplateError> {
+    let mut pieces = Vec::new();
+    let mut text_start = 0;
+    let mut trim_next = false; // The last tag ended with '-': trim the whitespace after it
+    let mut at = 0;
+    while let Some(found) = source[at..].find('{') {
+        let open = at + found;
+        let close = match source.as_bytes().get(open + 1) {
+            Some(b'{') => "}}",
+            Some(b'%') => "%}",
+            Some(b'#') => "#}",
+            _ => {
+                at = open + 1;
+                continue;
+            }
+        };
+        let trim_before = source[open + 2..].starts_with('-');
+        let inner_start = open + 2 + usize::from(trim_before);
+        // Lazily: position counts lines from the start, which per tag would be quadratic
+        let end =
+            closing(source, inner_start, close).ok_or_else(|| TemplateError::UnclosedTag {
+                at: position(source, open),
+            })?;
+        let trim_after = end > inner_start && source[..end].ends_with('-');
+        let inner = inner_start..end - usize::from(trim_after);
+
+        let (mut start, mut end_text) = (text_start, open);
+        if trim_next {
+            start = end_text - source[start..end_text].trim_start().len();
+        }
+        if trim_before {
+            end_text = start + source[start..end_text].trim_end().len();
+        }
+        if start < end_text {
+            pieces.push(Piece::Text(start..end_text));
+        }
+        match close {
+            "}}" => pieces.push(Piece::Print(inner)),
+            "%}" => pieces.push(Piece::Tag(inner)),
+            _ => {} // A comment
+        }
+        trim_next = trim_after;
+        at = end + close.len();
+        text_start = at;
+    }
+    let mut start = text_start;
+    if trim_next {
+        start = source.len() - source[start..].trim_start().len();
+    }
+    if start < source.len() {
+        pieces.push(Piece::Text(start..source.len()));
+    }
+    Ok(pieces)
+}
+
+// Where `close` ends the tag, skipping over string literals, which may contain it
+fn closing(source: &str, from: usize, close: &str) -> Option<usize> {
+    let mut in_string = false;
+    let mut chars = source[from..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => in_string = !in_string,
+            '\\' if in_string => {
+                chars.next();
+            }
+            _ if !in_string && source[from + i..].starts_with(close) => return Some(from + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+// --- TOKENS ---
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'s> {
+    Ident(&'s str),
+    Str(String),
+    Int(i64),
+    Pipe,
+    Dot,
+    Open,  // (
+    Close, // )
+}
+
+fn tokens<'s>(
+    source: &'s str,
+    range: Range<usize>,
+) -> Result<Vec<(usize, Token<'s>)>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut chars = source[range.clone()].char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let at = range.start + i;
+        let token = match c {
+            _ if c.is_whitespace() => continue,
+            '|' => Token::Pipe,
+            '.' => Token::Dot,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, c @ ('"' | '\\'))) => value.push(c),
+                            _ => return Err(unexpected(source, at, "escape in string")),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(unexpected(source, at, "end of tag inside a string")),
+                    }
+                }
+                Token::Str(value)
+            }
+            _ if c.is_ascii_digit() || c == '-' => {
+                let mut end = i + c.len_utf8();
+                while let Some((j, c)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = j + c.len_utf8();
+                }
+                let digits = &source[at..range.start + end];
+                let value = digits.parse().map_err(|_| unexpected(source, at, digits))?;
+                Token::Int(value)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let mut end = i + c.len_utf8();
+                while let Some((j, c)) = chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_') {
+                    end = j + c.len_utf8();
+                }
+                Token::Ident(&source[at..range.start + end])
+            }
+            _ => return Err(unexpected(source, at, &c.to_string())),
+        };
+        tokens.push((at, token));
+    }
+    Ok(tokens)
+}
+
+fn unexpected(source: &str, at: usize, found: &str) -> TemplateError {
+    TemplateError::Unexpected {
+        at: position(source, at),
+        found: found.to_string(),
+    }
+}
+
+// --- PARSER ---
+pub(crate) fn parse(source: &str) -> Result<Vec<Node>, TemplateError> {
+    let mut parser = Parser {
+        source,
+        pieces: pieces(source)?,
+        next: 0,
+        depth: 0,
+    };
+    let (nodes, end) = parser.nodes()?;
+    match end {
+        None => Ok(nodes),
+        Some((at, tag)) => Err(parser.unexpected_tag(at, tag)),
+    }
+}
+
+// Nesting limit so hostile templates cannot blow the stack: parsing and rendering both recurse
+// once per {% if %} or {% for %} block
+const MAX_DEPTH: usize = 32;
+
+// An else / endif / endfor tag that ends a run of nodes, with its byte offset
+type EndTag<'s> = Option<(usize, &'s str)>;
+
+struct Parser<'s> {
+    source: &'s str,
+    pieces: Vec<Piece>,
+    next: usize,
+    depth: usize, // Blocks open around the current piece
+}
+
+impl<'s> Parser<'s> {
+    // Nodes up to the end of the template or up to an end tag, returned for the caller to check
+    fn nodes(&mut self) -> Result<(Vec<Node>, EndTag<'s>), TemplateError> {
+        let mut nodes = Vec::new();
+        while let Some(piece) = self.pieces.get(self.next).cloned() {
+            self.next += 1;
+            match piece {
+                Piece::Text(range) => nodes.push(Node::Text(range)),
+                Piece::Print(inner) => {
+                    let mut tokens = Tokens::new(self.source, inner)?;
+                    nodes.push(Node::Print(tokens.expr()?));
+                    tokens.end()?;
+                }
+                Piece::Tag(inner) => {
+                    let mut tokens = Tokens::new(self.source, inner.clone())?;
+                    let (at, name) = tokens.ident("a tag name")?;
+                    match name {
+                        "if" => {
+                            let negated = tokens.keyword("not");
+                            let condition = tokens.expr()?;
+                            tokens.end()?;
+                            nodes.push(self.if_block(at, negated, condition)?);
+                        }
+                        "for" => {
+                            let (_, var) = tokens.ident("a loop variable")?;
+                            if !tokens.keyword("in") {
+                                return Err(tokens.unexpected("`in`"));
+                            }
+                            let list = tokens.expr()?;
+                            tokens.end()?;
+                            // Loops borrow their list from the context, so it has to be a name
+                            if !matches!(list.base, Base::Path(_)) || !list.filters.is_empty() {
+                                return Err(TemplateError::NotList {
+                                    at: position(self.source, list.span.start),
+                                    name: self.source[list.span.start..inner.end]
+                                        .trim()
+                                        .to_string(),
+                                });
+                            }
+                            let (body, end) = self.block(at, "for")?;
+                            match end {
+                                Some((_, "endfor")) => {}
+                                Some((end_at, tag)) => return Err(self.unexpected_tag(end_at, tag)),
+                                None => return Err(self.unclosed(at, "for")),
+                            }
+                            nodes.push(Node::For {
+                                var: var.to_string(),
+                                list,
+                                body,
+                            });
+                        }
+                        "else" | "endif" | "endfor" => {
+                            tokens.end()?;
+                            return Ok((nodes, Some((at, name))));
+                        }
+                        _ => return Err(self.unexpected_tag(at, name)),
+                    }
+                }
+            }
+        }
+        Ok((nodes, None))
+    }
+
+    fn if_block(
+        &mut self,
+        at: usize,
+        negated: bool,
+        condition: Expr,
+    ) -> Result<Node, TemplateError> {
+        let (then, mut end) = self.block(at, "if")?;
+        let mut otherwise = Vec::new();
+        if let Some((_, "else")) = end {
+            (otherwise, end) = self.block(at, "if")?;
+        }
+        match end {
+            Some((_, "endif")) => Ok(Node::If {
+                negated,
+                condition,
+                then,
+                otherwise,
+            }),
+            Some((end_at, tag)) => Err(self.unexpected_tag(end_at, tag)),
+            None => Err(self.unclosed(at, "if")),
+        }
+    }
+
+    // The nodes inside the block whose tag name is at byte `at`
+    fn block(
+        &mut self,
+        at: usize,
+        block: &'static str,
+    ) -> Result<(Vec<Node>, EndTag<'s>), TemplateError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(TemplateError::TooDeep {
+                at: position(self.source, at),
+                block,
+            });
+        }
+        self.depth += 1;
+        let nodes = self.nodes();
+        self.depth -= 1;
+        nodes
+    }
+
+    fn unexpected_tag(&self, at: usize, tag: &str) -> TemplateError {
+        TemplateError::UnexpectedTag {
+            at: position(self.source, at),
+            tag: tag.to_string(),
+        }
+    }
+
+    fn unclosed(&self, at: usize, block: &'static str) -> TemplateError {
+        TemplateError::UnclosedBlock {
+            at: position(self.source, at),
+            block,
+        }
+    }
+}
+
+// The tokens of one tag or expression
+struct Tokens<'s> {
+    source: &'s str,
+    tokens: Vec<(usize, Token<'s>)>,
+    next: usize,
+    end: usize, // Where the tag's content ends, for "expected ..." at the end
+}
+
+impl<'s> Tokens<'s> {
+    fn new(source: &'s str, range: Range<usize>) -> Result<Tokens<'s>, TemplateError> {
+        Ok(Tokens {
+            source,
+            end: range.end,
+            tokens: tokens(source, range)?,
+            next: 0,
+        })
+    }
+
+    fn peek(&self) -> Option<&(usize, Token<'s>)> {
+        self.tokens.get(self.next)
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.peek().is_some_and(|(_, t)| t == token);
+        self.next += usize::from(found);
+        found
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        self.eat(&Token::Ident(word))
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<(usize, &'s str), TemplateError> {
+        match self.peek() {
+            Some(&(at, Token::Ident(name))) => {
+                self.next += 1;
+                Ok((at, name))
+            }
+            _ => Err(self.unexpected(expected)),
+        }
+    }
+
+    // An error at the next token, or at the end of the tag if there is none
+    fn unexpected(&self, expected: &str) -> TemplateError {
+        let (at, found) = match self.peek() {
+            Some((at, _)) => {
+                let next = self
+                    .tokens
+                    .get(self.next + 1)
+                    .map_or(self.end, |(end, _)| *end);
+                (*at, self.source[*at..next].trim_end())
+            }
+            None => (self.end, ""),
+        };
+        let found = if found.is_empty() {
+            format!("end of tag, expected {expected}")
+        } else {
+            format!("{found:?}, expected {expected}")
+        };
+        TemplateError::Unexpected {
+            at: position(self.source, at),
+            found,
+        }
+    }
+
+    fn end(&self) -> Result<(), TemplateError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected("the end of the tag")),
+        }
+    }
+
+    // value ( "|" filter )*
+    fn expr(&mut self) -> Result<Expr, TemplateError> {
+        let Some((start, token)) = self.peek().cloned() else {
+            return Err(self.unexpected("a value"));
+        };
+        self.next += 1;
+        let base = match token {
+            Token::Ident(name) => {
+                let mut path = vec![name.to_string()];
+                while self.eat(&Token::Dot) {
+                    path.push(self.ident("a field name")?.1.to_string());
+                }
+                Base::Path(path)
+            }
+            Token::Str(value) => Base::Str(value),
+            Token::Int(value) => Base::Int(value),
+            _ => {
+                self.next -= 1;
+                return Err(self.unexpected("a value"));
+            }
+        };
+        let end = self.peek().map_or(self.end, |(at, _)| *at);
+        let span = start..start + self.source[start..end].trim_end().len();
+        let mut filters = Vec::new();
+        while self.eat(&Token::Pipe) {
+            let (at, name) = self.ident("a filter name")?;
+            filters.push((at, self.filter(at, name)?));
+        }
+        Ok(Expr {
+            span,
+            base,
+            filters,
+        })
+    }
+
+    fn filter(&mut self, at: usize, name: &str) -> Result<Filter, TemplateError> {
+        let filter = match name {
+            "upper" => Filter::Upper,
+            "lower" => Filter::Lower,
+            "trim" => Filter::Trim,
+            "length" => Filter::Length,
+            "truncate" => match self.argument(at, name)? {
+                Token::Int(n) if n >= 0 => Filter::Truncate(n as usize),
+                _ => return Err(self.bad_argument(at, name)),
+            },
+            "default" | "join" => match self.argument(at, name)? {
+                Token::Str(s) if name == "default" => Filter::Default(s),
+                Token::Str(s) => Filter::Join(s),
+                _ => return Err(self.bad_argument(at, name)),
+            },
+            _ => {
+                return Err(TemplateError::UnknownFilter {
+                    at: position(self.source, at),
+                    name: name.to_string(),
+                });
+            }
+        };
+        Ok(filter)
+    }
+
+    // "(" argument ")"
+    fn argument(&mut self, at: usize, filter: &str) -> Result<Token<'s>, TemplateError> {
+        match (self.eat(&Token::Open), self.peek().cloned()) {
+            (true, Some((_, token))) if !matches!(token, Token::Close) => {
+                self.next += 1;
+                if !self.eat(&Token::Close) {
+                    return Err(self.unexpected("`)`"));
+                }
+                Ok(token)
+            }
+            _ => Err(self.bad_argument(at, filter)),
+        }
+    }
+
+    fn bad_argument(&self, at: usize, filter: &str) -> TemplateError {
+        TemplateError::BadArgument {
+            at: position(self.source, at),
+            filter: filter.to_string(),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::borrow::Cow;
+
+// --- COPY-ON-WRITE TRANSFORMS ---
+// takes_and_gives_back in main.rs moves a String in only to hand it back, and my_function builds
+// a new String with format! every time. These take &str, so the caller keeps its text, and return
+// Cow: Cow::Borrowed points into the input when nothing had to change, Cow::Owned is a new String
+// made only when something did. Most text needs no change, so most calls allocate nothing.
+
+// Leading and trailing whitespace removed. Always borrowed: the result is a slice of the input
+pub fn trim(s: &str) -> Cow<'_, str> {
+    Cow::Borrowed(s.trim())
+}
+
+// Trims, then turns every run of whitespace (spaces, tabs, newlines, Unicode spaces) into one
+// ' ': "  a \t b\n" → "a b". Allocates only when a run inside the text has to change.
+pub fn collapse_whitespace(s: &str) -> Cow<'_, str> {
+    let s = s.trim();
+    // Up to `first` the text is already collapsed: single ' ' between words
+    let mut prev_space = false;
+    let first = s.char_indices().find(|&(_, c)| {
+        let needs_change = c.is_whitespace() && (c != ' ' || prev_space);
+        prev_space = c.is_whitespace();
+        needs_change
+    });
+    let Some((first, _)) = first else {
+        return Cow::Borrowed(s);
+    };
+    // `first` is the second character of a run, or a tab/newline/other space after a word
+    let mut out = String::with_capacity(s.len());
+    out.push_str(s[..first].trim_end());
+    for word in s[first..].split_whitespace() {
+        out.push(' ');
+        out.push_str(word);
+    }
+    Cow::Owned(out)
+}
+
+// Every `from` replaced by `to`, like str::replace; borrowed when `from` does not occur
+pub fn replace<'a>(s: &'a str, from: &str, to: &str) -> Cow<'a, str> {
+    if from.is_empty() || from == to {
+        return Cow::Borrowed(s);
+    }
+    let mut matches = s.match_indices(from).peekable();
+    if matches.peek().is_none() {
+        return Cow::Borrowed(s);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut last = 0;
+    for (at, _) in matches {
+        out.push_str(&s[last..at]);
+        out.push_str(to);
+        last = at + from.len();
+    }
+    out.push_str(&s[last..]);
+    Cow::Owned(out)
+}
+
+// Each char found in `escapes` replaced by its escape sequence; borrowed when there is none:
+// escape("a<b", &[('<', "&lt;"), ('>', "&gt;")]) == "a&lt;b"
+pub fn escape<'a>(s: &'a str, escapes: &[(char, &str)]) -> Cow<'a, str> {
+    let escape_of = |c: char| {
+        escapes
+            .iter()
+            .find(|&&(from, _)| from == c)
+            .map(|&(_, to)| to)
+    };
+    let Some(first) = s.find(|c| escape_of(c).is_some()) else {
+        return Cow::Borrowed(s);
+    };
+    // Room for a few escapes, so short escaped strings are allocated once
+    let mut out = String::with_capacity(s.len() + 16);
+    out.push_str(&s[..first]);
+    for c in s[first..].chars() {
+        match escape_of(c) {
+            Some(escaped) => out.push_str(escaped),
+            None => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+// Chars matching `remove` taken out: remove_chars(s, char::is_control) drops control codes.
+// Borrowed when there are none.
+pub fn remove_chars(s: &str, remove: impl Fn(char) -> bool) -> Cow<'_, str> {
+    let Some(first) = s.find(&remove) else {
+        return Cow::Borrowed(s);
+    };
+    let mut out = String::with_capacity(s.len());
+    out.push_str(&s[..first]);
+    out.extend(s[first..].chars().filter(|&c| !remove(c)));
+    Cow::Owned(out)
+}
+
+// --- CHAINING ---
+// Applies the next transform to the result of the last one, without losing what the Cow saves:
+//   let clean = transform::chain(transform::trim(input), transform::collapse_whitespace);
+// A borrowed input stays borrowed while nothing changes. An owned one is passed on as it is, and
+// when the transform returns a slice of it (trim, say) the String is cut down in place instead
+// of copied, so only transforms that change the text ever allocate.
+pub fn chain<'a, F>(s: Cow<'a, str>, transform: F) -> Cow<'a, str>
+where
+    F: for<'b> FnOnce(&'b str) -> Cow<'b, str>,
+{
+    match s {
+        Cow::Borrowed(s) => transform(s),
+        Cow::Owned(mut owned) => {
+            let (start, end) = match transform(&owned) {
+                Cow::Owned(changed) => return Cow::Owned(changed),
+                Cow::Borrowed(slice) => match slice_range(&owned, slice) {
+                    Some(range) => range,
+                    // Borrowed from somewhere else (a string literal, say)
+                    None => return Cow::Owned(slice.to_string()),
+                },
+            };
+            owned.truncate(end);
+            owned.drain(..start);
+            Cow::Owned(owned)
+        }
+    }
+}
+
+// Where `slice` lies inside `s`, as byte offsets, if it is a part of it
+fn slice_range(s: &str, slice: &str) -> Option<(usize, usize)> {
+    let base = s.as_ptr() as usize;
+    let start = (slice.as_ptr() as usize).checked_sub(base)?;
+    let end = start + slice.len();
+    (end <= s.len()).then_some((start, end))
+}
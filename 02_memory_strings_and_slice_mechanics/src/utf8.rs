@@ -0,0 +1,388 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+// --- STREAMING UTF-8 ---
+// `"नमस्ते".bytes()` and `.chars()` work because the whole string is in memory and known to be
+// UTF-8. Data from a socket arrives in chunks that ignore character boundaries: the 3 bytes of
+// "न" can be split across two reads, and nothing guarantees the bytes are UTF-8 at all. A
+// Utf8Decoder keeps the unfinished end of one chunk until the next one completes it:
+//   let mut decoder = Utf8Decoder::new(Mode::Strict);
+//   for chunk in packets {
+//       for c in decoder.decode(&chunk)?.chars() { ... }  // Borrows the chunk when it can
+//   }
+//   decoder.finish()?;                                    // Err if the stream stopped mid-char
+
+const REPLACEMENT: char = '\u{FFFD}';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Strict, // The first invalid sequence is an error; the decoder stops there
+    Lossy,  // Each invalid sequence becomes one U+FFFD, as in String::from_utf8_lossy
+    Wtf8,   // Strict, but lone surrogates (U+D800..U+DFFF) are accepted, as in Windows file names
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Invalid { at: u64, len: usize }, // The `len` bytes at stream offset `at` are not UTF-8
+    Truncated { at: u64 },           // The stream ends inside the sequence that starts at `at`
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Invalid { at, len } => {
+                write!(f, "invalid UTF-8 sequence of {len} byte(s) at offset {at}")
+            }
+            DecodeError::Truncated { at } => {
+                write!(f, "stream ends inside the UTF-8 sequence at offset {at}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+// --- SEQUENCES ---
+enum Sequence {
+    Valid(usize),   // A whole sequence of this many bytes
+    Incomplete,     // The bytes run out before the sequence is complete
+    Invalid(usize), // This many bytes start a sequence that cannot be completed: one U+FFFD
+}
+
+// The sequence at the start of `bytes` (not empty), following Unicode's table of well-formed
+// byte sequences. An invalid one is as long as its longest valid prefix (at least 1), which is
+// also what std replaces with one U+FFFD. With `surrogates`, ED A0..BF xx (U+D800..U+DFFF) is
+// accepted too.
+fn sequence(bytes: &[u8], surrogates: bool) -> Sequence {
+    let (width, second) = match bytes[0] {
+        0x00..=0x7F => return Sequence::Valid(1),
+        0xC2..=0xDF => (2, 0x80..=0xBF),
+        0xE0 => (3, 0xA0..=0xBF), // No overlong forms: U+0800 and up
+        0xED if !surrogates => (3, 0x80..=0x9F),
+        0xE1..=0xEF => (3, 0x80..=0xBF),
+        0xF0 => (4, 0x90..=0xBF), // U+10000 and up
+        0xF1..=0xF3 => (4, 0x80..=0xBF),
+        0xF4 => (4, 0x80..=0x8F),         // Up to U+10FFFF
+        _ => return Sequence::Invalid(1), // A continuation byte, or never used (C0, C1, F5..FF)
+    };
+    for k in 1..width {
+        let Some(&b) = bytes.get(k) else {
+            return Sequence::Incomplete;
+        };
+        let range = if k == 1 { second.clone() } else { 0x80..=0xBF };
+        if !range.contains(&b) {
+            return Sequence::Invalid(k);
+        }
+    }
+    Sequence::Valid(width)
+}
+
+// How many bytes at the end of `bytes` are the start of a sequence that does not fit
+fn cut_off(bytes: &[u8]) -> usize {
+    for k in 1..=bytes.len().min(3) {
+        let width = match bytes[bytes.len() - k] {
+            0x80..=0xBF => continue, // A continuation byte: the lead is further back
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xFF => 4,
+            _ => 1,
+        };
+        return if width > k { k } else { 0 };
+    }
+    0
+}
+
+// ED A0..BF xx: the three bytes of a surrogate, only valid in WTF-8
+fn surrogate(bytes: &[u8]) -> Option<u16> {
+    match *bytes {
+        [0xED, b1 @ 0xA0..=0xBF, b2, ..] => {
+            Some(0xD000 | (u16::from(b1 & 0x3F) << 6) | u16::from(b2 & 0x3F))
+        }
+        _ => None,
+    }
+}
+
+// --- VALIDATION ---
+// Checks a whole buffer. Runs of ASCII, most of a typical HTTP header or JSON document, are
+// skipped 32 bytes at a time: the block is read as four u64 words, and if no byte of their OR
+// has its top bit set, all 32 bytes are ASCII. Other bytes go through `sequence` one by one.
+pub fn validate(bytes: &[u8]) -> Result<(), DecodeError> {
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] < 0x80 {
+            let ascii_blocks = bytes[i..]
+                .chunks_exact(32)
+                .take_while(|block| {
+                    // Always 8 bytes, so the fallback (not ASCII) is never used
+                    let word = |k: usize| {
+                        u64::from_ne_bytes(block[8 * k..8 * k + 8].try_into().unwrap_or([0x80; 8]))
+                    };
+                    (word(0) | word(1) | word(2) | word(3)) & 0x8080_8080_8080_8080 == 0
+                })
+                .count();
+            i += 32 * ascii_blocks;
+            while bytes.get(i).is_some_and(u8::is_ascii) {
+                i += 1;
+            }
+            continue;
+        }
+        match sequence(&bytes[i..], false) {
+            Sequence::Valid(width) => i += width,
+            Sequence::Incomplete => return Err(DecodeError::Truncated { at: i as u64 }),
+            Sequence::Invalid(len) => return Err(DecodeError::Invalid { at: i as u64, len }),
+        }
+    }
+    Ok(())
+}
+
+// --- DECODER ---
+#[derive(Debug, Clone)]
+pub struct Utf8Decoder {
+    mode: Mode,
+    pending: [u8; 4], // The start of a sequence the last chunk cut off
+    pending_len: usize,
+    offset: u64,                 // Stream offset of the next chunk
+    after_lead: bool,            // WTF-8: the last thing decoded was a lead surrogate
+    failed: Option<DecodeError>, // Strict and WTF-8 stop at the first error
+}
+
+// What one chunk decodes to, in order
+enum Piece<'c> {
+    Text(&'c str), // Valid UTF-8, straight from the chunk
+    Char(char),    // A char split across chunks, or U+FFFD in place of invalid bytes
+    Surrogate(u16),
+}
+
+impl Utf8Decoder {
+    pub fn new(mode: Mode) -> Utf8Decoder {
+        Utf8Decoder {
+            mode,
+            pending: [0; 4],
+            pending_len: 0,
+            offset: 0,
+            after_lead: false,
+            failed: None,
+        }
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    // Bytes fed in so far
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    // The text of `chunk`, after whatever the previous chunk left unfinished. A chunk that
+    // starts and ends on char boundaries and is valid is borrowed, not copied. In WTF-8 mode a
+    // lone surrogate comes out as U+FFFD, since a str cannot hold it; decode_utf16 keeps it.
+    pub fn decode<'c>(&mut self, chunk: &'c [u8]) -> Result<Cow<'c, str>, DecodeError> {
+        let mut text = Cow::Borrowed("");
+        self.run(chunk, &mut |piece| match piece {
+            Piece::Text(s) if text.is_empty() => text = Cow::Borrowed(s),
+            Piece::Text(s) => text.to_mut().push_str(s),
+            Piece::Char(c) => text.to_mut().push(c),
+            Piece::Surrogate(_) => text.to_mut().push(REPLACEMENT),
+        })?;
+        Ok(text)
+    }
+
+    // Appends the UTF-16 code units of `chunk` to `out`. In WTF-8 mode lone surrogates are
+    // kept, so UTF-16 that went through WTF-8 comes back exactly as it was.
+    pub fn decode_utf16(&mut self, chunk: &[u8], out: &mut Vec<u16>) -> Result<(), DecodeError> {
+        self.run(chunk, &mut |piece| match piece {
+            Piece::Text(s) => out.extend(s.encode_utf16()),
+            Piece::Char(c) => out.extend(c.encode_utf16(&mut [0; 2]).iter()),
+            Piece::Surrogate(unit) => out.push(unit),
+        })
+    }
+
+    // Ends the stream. A sequence the last chunk left unfinished is an error, or one U+FFFD in
+    // lossy mode. The decoder can then start on a new stream.
+    pub fn finish(&mut self) -> Result<&'static str, DecodeError> {
+        if let Some(e) = self.failed.take() {
+            return Err(e);
+        }
+        let pending = std::mem::take(&mut self.pending_len);
+        self.after_lead = false;
+        if pending == 0 {
+            return Ok("");
+        }
+        let at = self.offset - pending as u64;
+        match self.mode {
+            Mode::Lossy => Ok("\u{FFFD}"),
+            Mode::Strict | Mode::Wtf8 => Err(DecodeError::Truncated { at }),
+        }
+    }
+
+    fn run<'c>(
+        &mut self,
+        chunk: &'c [u8],
+        emit: &mut impl FnMut(Piece<'c>),
+    ) -> Result<(), DecodeError> {
+        if let Some(e) = &self.failed {
+            return Err(e.clone());
+        }
+        let mut rest = chunk;
+        if self.pending_len > 0 {
+            // Finish the sequence the last chunk cut off, with at most 3 bytes of this one
+            let held = self.pending_len;
+            let take = chunk.len().min(4 - held);
+            let mut joined = self.pending;
+            joined[held..held + take].copy_from_slice(&chunk[..take]);
+            match sequence(&joined[..held + take], self.mode == Mode::Wtf8) {
+                Sequence::Incomplete => {
+                    // `take` is the whole chunk: a longer one would have completed the sequence
+                    self.pending = joined;
+                    self.pending_len += take;
+                    self.offset += take as u64;
+                    return Ok(());
+                }
+                Sequence::Valid(width) => {
+                    self.emit_sequence(&joined[..width], self.offset - held as u64, emit)?;
+                    rest = &chunk[width - held..];
+                }
+                Sequence::Invalid(len) => {
+                    self.invalid(self.offset - held as u64, len, emit)?;
+                    rest = &chunk[len - held..];
+                }
+            }
+            self.pending_len = 0;
+        }
+        let base = self.offset + (chunk.len() - rest.len()) as u64; // Stream offset of rest[0]
+        let mut at = 0; // rest[..at] is decoded
+        // Most chunks are valid text, maybe with a char cut off at the end: std checks those in
+        // one fast pass. Only chunks with invalid bytes or surrogates take the loop below.
+        if let Ok(text) = std::str::from_utf8(&rest[..rest.len() - cut_off(rest)]) {
+            if !text.is_empty() {
+                emit(Piece::Text(text));
+                self.after_lead = false;
+            }
+            at = text.len();
+        }
+        // std finds the valid runs; the bytes after each one are handled here. The search starts
+        // over after them, as a surrogate is one sequence here and three invalid bytes to std.
+        while let Some(piece) = rest[at..].utf8_chunks().next() {
+            if !piece.valid().is_empty() {
+                emit(Piece::Text(piece.valid()));
+                self.after_lead = false;
+            }
+            at += piece.valid().len();
+            if piece.invalid().is_empty() {
+                break; // The end of the chunk
+            }
+            match sequence(&rest[at..], self.mode == Mode::Wtf8) {
+                Sequence::Incomplete => {
+                    // Cut off by the end of the chunk: wait for the next one
+                    let tail = &rest[at..];
+                    self.pending[..tail.len()].copy_from_slice(tail);
+                    self.pending_len = tail.len();
+                    break;
+                }
+                Sequence::Valid(width) => {
+                    self.emit_sequence(&rest[at..at + width], base + at as u64, emit)?;
+                    at += width;
+                }
+                Sequence::Invalid(len) => {
+                    self.invalid(base + at as u64, len, emit)?;
+                    at += len;
+                }
+            }
+        }
+        self.offset += chunk.len() as u64;
+        Ok(())
+    }
+
+    // A whole sequence starting at stream offset `at`: a char, or in WTF-8 mode a surrogate
+    fn emit_sequence<'c>(
+        &mut self,
+        bytes: &[u8],
+        at: u64,
+        emit: &mut impl FnMut(Piece<'c>),
+    ) -> Result<(), DecodeError> {
+        if let Some(unit) = surrogate(bytes) {
+            let lead = unit < 0xDC00;
+            // A lead followed by a trail is a pair, which WTF-8 writes as one 4-byte sequence
+            if !lead && self.after_lead {
+                return self.invalid(at, bytes.len(), emit);
+            }
+            emit(Piece::Surrogate(unit));
+            self.after_lead = lead;
+            return Ok(());
+        }
+        let c = std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .unwrap_or(REPLACEMENT);
+        emit(Piece::Char(c));
+        self.after_lead = false;
+        Ok(())
+    }
+
+    fn invalid<'c>(
+        &mut self,
+        at: u64,
+        len: usize,
+        emit: &mut impl FnMut(Piece<'c>),
+    ) -> Result<(), DecodeError> {
+        self.after_lead = false;
+        match self.mode {
+            Mode::Lossy => {
+                emit(Piece::Char(REPLACEMENT));
+                Ok(())
+            }
+            Mode::Strict | Mode::Wtf8 => {
+                let e = DecodeError::Invalid { at, len };
+                self.failed = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+}
+
+// --- GRAPHEMES ---
+// A grapheme can span chunks even when every char arrives whole: "e" at the end of one chunk
+// and U+0301 (combining acute) at the start of the next are one "é". The last grapheme of each
+// chunk is held back until the next chunk shows whether it goes on.
+#[derive(Debug, Clone)]
+pub struct GraphemeDecoder {
+    decoder: Utf8Decoder,
+    text: String,   // Decoded text that has not been handed out yet, or just was
+    emitted: usize, // text[..emitted] went out with the last push
+}
+
+impl GraphemeDecoder {
+    pub fn new(mode: Mode) -> GraphemeDecoder {
+        GraphemeDecoder {
+            decoder: Utf8Decoder::new(mode),
+            text: String::new(),
+            emitted: 0,
+        }
+    }
+
+    // The graphemes that `chunk` completes
+    pub fn push(&mut self, chunk: &[u8]) -> Result<impl Iterator<Item = &str>, DecodeError> {
+        self.text.drain(..self.emitted);
+        self.emitted = 0;
+        let decoded = self.decoder.decode(chunk)?;
+        self.text.push_str(&decoded);
+        // Boundaries before the last grapheme are final: what comes next cannot move them
+        self.emitted = self
+            .text
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(start, _)| start);
+        Ok(self.text[..self.emitted].graphemes(true))
+    }
+
+    // Ends the stream: the graphemes still held back
+    pub fn finish(&mut self) -> Result<impl Iterator<Item = &str>, DecodeError> {
+        self.text.drain(..self.emitted);
+        self.text.push_str(self.decoder.finish()?);
+        self.emitted = self.text.len();
+        Ok(self.text.graphemes(true))
+    }
+}
@@ -0,0 +1,201 @@
+use unicode_segmentation::{UWordBoundIndices, UnicodeSegmentation};
+
+// --- OPTIONS ---
+// Which tokens come out of the tokenizer. Word boundaries follow Unicode's UAX #29 rules: spaces
+// of every kind (tabs, newlines, no-break spaces) separate words, and so does punctuation, but
+// "don't", "e.g" and "3.14" each stay one token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WordOptions {
+    pub punctuation: bool,        // Also yield punctuation, symbols and emoji
+    pub numbers: bool,            // Also yield numbers ("42", "3.14", "1,000")
+    pub split_contractions: bool, // "don't" → "don" + "'t", "we're" → "we" + "'re"
+}
+
+impl Default for WordOptions {
+    fn default() -> WordOptions {
+        WordOptions {
+            punctuation: false,
+            numbers: true,
+            split_contractions: false,
+        }
+    }
+}
+
+// --- TOKENS ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordKind {
+    Word,        // Contains at least one letter ("hello", "3rd", "नमस्ते")
+    Number,      // Digits and the separators UAX #29 keeps inside numbers
+    Punctuation, // Anything else that is not whitespace
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Word<'a> {
+    pub text: &'a str, // Borrowed from the input, never copied
+    pub start: usize,  // Byte offset: input[start..start + text.len()] == text
+    pub kind: WordKind,
+}
+
+impl Word<'_> {
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+}
+
+// --- QUERIES ---
+// Words with the default options: letters and numbers, no punctuation
+pub fn words(text: &str) -> Words<'_> {
+    words_with(text, WordOptions::default())
+}
+
+pub fn words_with(text: &str, options: WordOptions) -> Words<'_> {
+    Words {
+        bounds: text.split_word_bound_indices(),
+        options,
+        front: None,
+        back: None,
+    }
+}
+
+// first_word from main.rs, but for any whitespace and punctuation: "Hello,\tworld" → "Hello"
+pub fn first_word(text: &str) -> Option<&str> {
+    words(text).next().map(|w| w.text)
+}
+
+// Zero-based, so nth_word(text, 0) == first_word(text)
+pub fn nth_word(text: &str, n: usize) -> Option<&str> {
+    words(text).nth(n).map(|w| w.text)
+}
+
+// Found from the end, without walking the words before it
+pub fn last_word(text: &str) -> Option<&str> {
+    words(text).next_back().map(|w| w.text)
+}
+
+pub fn word_count(text: &str) -> usize {
+    words(text).count()
+}
+
+// --- ITERATOR ---
+// UAX #29 splits the text into segments, including runs of whitespace; segments that the options
+// rule out are skipped and, if asked for, contractions are split into pieces. Works from both ends.
+#[derive(Debug, Clone)]
+pub struct Words<'a> {
+    bounds: UWordBoundIndices<'a>,
+    options: WordOptions,
+    front: Option<Pieces<'a>>, // Pieces of the segment being read from the front
+    back: Option<Pieces<'a>>,  // ... and from the back
+}
+
+impl<'a> Words<'a> {
+    fn pieces(&self, (start, text): (usize, &'a str)) -> Option<Pieces<'a>> {
+        let kind = classify(text)?;
+        let wanted = match kind {
+            WordKind::Word => true,
+            WordKind::Number => self.options.numbers,
+            WordKind::Punctuation => self.options.punctuation,
+        };
+        wanted.then_some(Pieces {
+            text,
+            start,
+            kind,
+            split: self.options.split_contractions && kind == WordKind::Word,
+        })
+    }
+}
+
+impl<'a> Iterator for Words<'a> {
+    type Item = Word<'a>;
+
+    fn next(&mut self) -> Option<Word<'a>> {
+        loop {
+            if let Some(word) = self.front.as_mut().and_then(Pieces::next) {
+                return Some(word);
+            }
+            match self.bounds.next() {
+                Some(segment) => self.front = self.pieces(segment),
+                // Both ends met inside one segment: finish what the back left over
+                None => return self.back.as_mut().and_then(Pieces::next),
+            }
+        }
+    }
+}
+
+impl DoubleEndedIterator for Words<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(word) = self.back.as_mut().and_then(Pieces::next_back) {
+                return Some(word);
+            }
+            match self.bounds.next_back() {
+                Some(segment) => self.back = self.pieces(segment),
+                None => return self.front.as_mut().and_then(Pieces::next_back),
+            }
+        }
+    }
+}
+
+// None for whitespace, which is never a word
+fn classify(text: &str) -> Option<WordKind> {
+    if text.chars().all(char::is_whitespace) {
+        None
+    } else if text.chars().any(char::is_alphabetic) {
+        Some(WordKind::Word)
+    } else if text.chars().any(char::is_numeric) {
+        Some(WordKind::Number)
+    } else {
+        Some(WordKind::Punctuation)
+    }
+}
+
+// One segment, or its contraction pieces: each piece after the first starts at an apostrophe.
+// UAX #29 only keeps an apostrophe inside a word when letters surround it, so no piece is empty.
+#[derive(Debug, Clone)]
+struct Pieces<'a> {
+    text: &'a str, // What is left of the segment
+    start: usize,
+    kind: WordKind,
+    split: bool,
+}
+
+impl<'a> Pieces<'a> {
+    fn is_apostrophe(c: char) -> bool {
+        c == '\'' || c == '’'
+    }
+
+    fn take(&mut self, at: usize, from_back: bool) -> Word<'a> {
+        let (head, tail) = self.text.split_at(at);
+        let (piece, start) = if from_back {
+            self.text = head;
+            (tail, self.start + at)
+        } else {
+            self.text = tail;
+            self.start += at;
+            (head, self.start - at)
+        };
+        Word {
+            text: piece,
+            start,
+            kind: self.kind,
+        }
+    }
+
+    fn next(&mut self) -> Option<Word<'a>> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let mut rest = self.text.char_indices().skip(1);
+        let apostrophe = rest.find(|&(_, c)| self.split && Pieces::is_apostrophe(c));
+        let at = apostrophe.map_or(self.text.len(), |(i, _)| i);
+        Some(self.take(at, false))
+    }
+
+    fn next_back(&mut self) -> Option<Word<'a>> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let found = self.split.then(|| self.text.rfind(Pieces::is_apostrophe));
+        let at = found.flatten().unwrap_or(0);
+        Some(self.take(at, true))
+    }
+}
@@ -0,0 +1,51 @@
+// Helpers shared by the test files (tests/common/ is not a test of its own). Each test file
+// compiles its own copy and uses only some of them.
+#![allow(dead_code)]
+
+use std::io::{self, Read};
+
+use proptest::prelude::*;
+
+// Strings made of up to `max_pieces - 1` pieces picked from `pieces`. Each test file lists the
+// pieces its module finds tricky (multi-byte chars, combining marks, delimiters, escapes...), so
+// they meet each other far more often than they would in arbitrary strings.
+pub fn concat_of(
+    pieces: &'static [&'static str],
+    max_pieces: usize,
+) -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(pieces), 0..max_pieces)
+        .prop_map(|parts| parts.concat())
+}
+
+// A reader that hands over its bytes in reads of the given sizes (cycling), like a socket, so
+// reads end anywhere: inside a character, between "\r" and "\n", ...
+pub struct Trickle<'a> {
+    bytes: &'a [u8],
+    sizes: Vec<usize>,
+    reads: usize,
+}
+
+impl Trickle<'_> {
+    pub fn new(bytes: &[u8], sizes: Vec<usize>) -> Trickle<'_> {
+        assert!(
+            sizes.iter().all(|&size| size > 0),
+            "a read of 0 bytes means the end"
+        );
+        Trickle {
+            bytes,
+            sizes,
+            reads: 0,
+        }
+    }
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.sizes[self.reads % self.sizes.len()];
+        self.reads += 1;
+        let n = size.min(buf.len()).min(self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
@@ -0,0 +1,146 @@
+// StrBuilder's planned length is the length it builds, and InlineString holds the same text as a
+// String would, whether it is still inline or has spilled to the heap.
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use memory_safety::concat::{self, InlineString, SmallString, StrBuilder};
+use proptest::prelude::*;
+
+mod common;
+
+// --- STR BUILDER ---
+#[test]
+fn builds_at_the_planned_length() {
+    let name = String::from("Ferris");
+    let mut builder = StrBuilder::new();
+    builder
+        .push("Hello")
+        .push_char(' ')
+        .push(&name)
+        .push_char('🦀');
+    assert_eq!(builder.len(), 16);
+    let built = builder.build();
+    assert_eq!(built, "Hello Ferris🦀");
+    assert_eq!(built.len(), builder.len());
+    assert_eq!(built.capacity(), built.len()); // Allocated once, at the exact size
+
+    // build_into reserves the whole length before copying
+    let mut out = String::from(">> ");
+    builder.build_into(&mut out);
+    assert_eq!(out, ">> Hello Ferris🦀");
+    assert!(out.capacity() >= out.len());
+
+    let empty = StrBuilder::with_parts(4);
+    assert!(empty.is_empty());
+    assert_eq!(empty.build().capacity(), 0); // No allocation at all
+    let collected: StrBuilder = ["नम", "", "स्ते"].into_iter().collect();
+    assert_eq!(collected.len(), "नमस्ते".len());
+    assert_eq!(collected.build(), "नमस्ते");
+}
+
+// --- JOINING ---
+#[test]
+fn joins_anything_display() {
+    assert_eq!(concat::join_with([1, 2, 3], ", "), "1, 2, 3");
+    assert_eq!(concat::join_with(["only"], ", "), "only");
+    assert_eq!(concat::join_with(Vec::<u8>::new(), ", "), "");
+    assert_eq!(concat::join_with(std::iter::empty::<&str>(), "-"), "");
+    assert_eq!(concat::join_with(['a', 'é'], ""), "aé");
+    let mut out = String::from("[");
+    concat::write_joined(&mut out, [0.5, 1.25], "; ").unwrap();
+    out.push(']');
+    assert_eq!(out, "[0.5; 1.25]");
+}
+
+// --- INLINE STRING ---
+#[test]
+fn spills_exactly_past_n_bytes() {
+    let mut s = InlineString::<8>::new();
+    s.push_str("1234567");
+    s.push('8');
+    assert!(s.is_inline());
+    assert_eq!((s.len(), s.capacity()), (8, 8));
+    s.push('9');
+    assert!(!s.is_inline());
+    assert_eq!(s, "123456789");
+    assert!(s.capacity() >= 16); // Room for as much again
+
+    // A multi-byte char that would end exactly at N fits, one byte more spills
+    let mut fits = InlineString::<8>::from("abcd");
+    fits.push('🦀');
+    assert!(fits.is_inline() && fits == "abcd🦀");
+    let mut spills = InlineString::<8>::from("abcde");
+    spills.push('🦀');
+    assert!(!spills.is_inline() && spills == "abcde🦀");
+    // A big push at once gets a buffer of its size
+    let mut big = InlineString::<4>::from("ab");
+    big.push_str(&"x".repeat(100));
+    assert_eq!(big.len(), 102);
+    assert!(big.capacity() >= 102);
+}
+
+#[test]
+fn from_string_keeps_the_allocation() {
+    let mut long = String::with_capacity(64);
+    long.push_str("longer than twenty-three bytes");
+    let ptr = long.as_ptr();
+    let s = SmallString::from(long);
+    assert!(!s.is_inline());
+    assert_eq!((s.as_ptr(), s.capacity()), (ptr, 64));
+    let back = s.into_string();
+    assert_eq!(back.as_ptr(), ptr);
+
+    // Short enough to go inline: the String is dropped
+    let s = SmallString::from(String::from("short"));
+    assert!(s.is_inline());
+    assert_eq!(s.into_string(), "short");
+}
+
+#[test]
+fn behaves_like_str() {
+    let inline = SmallString::from("tag");
+    let mut spilled = InlineString::<2>::from("ta");
+    spilled.push('g');
+    assert!(inline.is_inline() && !spilled.is_inline());
+    assert_eq!(inline, "tag");
+    assert_eq!(spilled, "tag");
+    // Hashed like the str, so a set of them can be searched with a &str
+    let set: HashSet<InlineString<2>> = [spilled].into();
+    assert!(set.contains("tag"));
+    assert_eq!(
+        format!("{inline}|{inline:?}|{}", inline.to_uppercase()),
+        "tag|\"tag\"|TAG"
+    );
+    let mut written = SmallString::default();
+    let (n, c) = (1, 'é');
+    write!(written, "{n}-{c}").unwrap();
+    assert_eq!(written, "1-é");
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["", "a", "é", "€", "🦀", "नमस्ते", "abcdefgh"];
+
+proptest! {
+    #[test]
+    fn builder_matches_concat(parts in prop::collection::vec(common::concat_of(PIECES, 4), 0..8)) {
+        let builder: StrBuilder = parts.iter().map(String::as_str).collect();
+        let built = builder.build();
+        prop_assert_eq!(&built, &parts.concat());
+        prop_assert_eq!(built.len(), builder.len());
+        prop_assert_eq!(built.capacity(), built.len());
+    }
+
+    #[test]
+    fn inline_string_matches_string(parts in prop::collection::vec(common::concat_of(PIECES, 3), 0..8)) {
+        let mut inline = InlineString::<12>::new();
+        let mut string = String::new();
+        for part in &parts {
+            inline.push_str(part);
+            string.push_str(part);
+            prop_assert_eq!(inline.as_str(), string.as_str());
+            prop_assert_eq!(inline.is_inline(), string.len() <= 12);
+        }
+        prop_assert_eq!(inline.into_string(), string);
+    }
+}
@@ -0,0 +1,259 @@
+// Every edit script must turn the old sequence into the new one; Myers' must also be as short as
+// the longest common subsequence allows; a unified patch must survive printing, parsing and
+// applying with the exact bytes of the new text.
+use memory_safety::diff::{self, Algorithm, Edit, EditKind, Granularity, Patch, PatchError, Style};
+use proptest::prelude::*;
+
+// Rebuilds the new sequence from the old one and the edits, checking that the ranges line up
+fn replay<T: Clone + PartialEq + std::fmt::Debug>(old: &[T], new: &[T], edits: &[Edit]) -> Vec<T> {
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for edit in edits {
+        assert_eq!((edit.old.start, edit.new.start), (i, j), "{edits:?}");
+        match edit.kind {
+            EditKind::Equal => {
+                assert_eq!(old[edit.old.clone()], new[edit.new.clone()]);
+                out.extend_from_slice(&old[edit.old.clone()]);
+            }
+            EditKind::Delete => assert!(edit.new.is_empty() && !edit.old.is_empty()),
+            EditKind::Insert => {
+                assert!(edit.old.is_empty() && !edit.new.is_empty());
+                out.extend_from_slice(&new[edit.new.clone()]);
+            }
+        }
+        (i, j) = (edit.old.end, edit.new.end);
+    }
+    assert_eq!((i, j), (old.len(), new.len()));
+    out
+}
+
+fn changed(edits: &[Edit]) -> usize {
+    edits
+        .iter()
+        .map(|edit| match edit.kind {
+            EditKind::Equal => 0,
+            EditKind::Delete => edit.old.len(),
+            EditKind::Insert => edit.new.len(),
+        })
+        .sum()
+}
+
+// Fewest deletions plus insertions, from the longest common subsequence: n + m − 2 · LCS
+fn shortest(old: &[u8], new: &[u8]) -> usize {
+    let mut row = vec![0; new.len() + 1];
+    for a in old {
+        let mut diagonal = 0;
+        for (j, b) in new.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == b {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    old.len() + new.len() - 2 * row[new.len()]
+}
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn finds_shortest_scripts() {
+    // The example from Myers' paper: D = 5
+    let (old, new) = (b"ABCABBA", b"CBABAC");
+    let edits = diff::diff_slices(old, new, Algorithm::Myers);
+    assert_eq!(changed(&edits), 5);
+    assert_eq!(replay(old, new, &edits), new);
+
+    let edits = diff::diff_slices(&[1, 2, 3, 4], &[1, 9, 4, 5], Algorithm::Myers);
+    #[rustfmt::skip]
+    assert_eq!(edits, [
+        Edit { kind: EditKind::Equal, old: 0..1, new: 0..1 },
+        Edit { kind: EditKind::Delete, old: 1..3, new: 1..1 },
+        Edit { kind: EditKind::Insert, old: 3..3, new: 1..2 },
+        Edit { kind: EditKind::Equal, old: 3..4, new: 2..3 },
+        Edit { kind: EditKind::Insert, old: 4..4, new: 3..4 },
+    ]);
+    assert!(diff::diff_slices::<u8>(&[], &[], Algorithm::Myers).is_empty());
+}
+
+#[test]
+fn diffs_text_at_each_granularity() {
+    let (old, new) = ("Rust", "Rust is an awesome language");
+    let d = diff::diff(old, new, Granularity::Word, Algorithm::Myers);
+    assert_eq!(d.inline(Style::Markers), "Rust{+ is an awesome language+}");
+
+    let d = diff::diff(
+        "the quick fox",
+        "the slow fox",
+        Granularity::Word,
+        Algorithm::Myers,
+    );
+    assert_eq!(d.inline(Style::Markers), "the [-quick-]{+slow+} fox");
+    assert_eq!(d.distance(), 2);
+    assert_eq!(
+        d.inline(Style::Ansi),
+        "the \x1b[31;9mquick\x1b[0m\x1b[32mslow\x1b[0m fox"
+    );
+
+    // Chars split "é" written as e + U+0301; graphemes keep it whole
+    let (old, new) = ("cafe\u{301}", "cafe");
+    let chars = diff::diff(old, new, Granularity::Char, Algorithm::Myers);
+    assert_eq!(chars.inline(Style::Markers), "cafe[-\u{301}-]");
+    let graphemes = diff::diff(old, new, Granularity::Grapheme, Algorithm::Myers);
+    assert_eq!(graphemes.inline(Style::Markers), "caf[-e\u{301}-]{+e+}");
+
+    let lines = diff::diff("a\nb\nc", "a\nB\nc", Granularity::Line, Algorithm::Myers);
+    assert_eq!(
+        lines.changes().collect::<Vec<_>>(),
+        [
+            (EditKind::Equal, "a\n"),
+            (EditKind::Delete, "b\n"),
+            (EditKind::Insert, "B\n"),
+            (EditKind::Equal, "c"),
+        ]
+    );
+    assert!(diff::diff("same", "same", Granularity::Char, Algorithm::Patience).is_unchanged());
+}
+
+#[test]
+fn patience_keeps_unique_lines_together() {
+    // Two functions swapped. Myers' fewest edits pair up the two signatures and the two `y();`
+    // lines, rewriting both functions; patience keeps the unique `fn f1() {` line, so f1 stays
+    // whole and f0 is shown moved below it
+    let old = "fn f0() {\n    y();\n}\n\nfn f1() {\n    y();\n    x();\n}\n";
+    let new = "fn f1() {\n    y();\n    x();\n}\n\nfn f0() {\n    y();\n}\n";
+    let kept = |algorithm| {
+        let d = diff::diff(old, new, Granularity::Line, algorithm);
+        d.changes()
+            .filter(|(kind, _)| *kind == EditKind::Equal)
+            .map(|(_, text)| text)
+            .collect::<String>()
+    };
+    assert!(!kept(Algorithm::Myers).contains("fn f1() {\n"));
+    assert_eq!(
+        kept(Algorithm::Patience),
+        "fn f1() {\n    y();\n    x();\n}\n"
+    );
+    assert_eq!(Patch::new(old, new, Algorithm::Myers, 0).hunks().len(), 4);
+    assert_eq!(
+        Patch::new(old, new, Algorithm::Patience, 0).hunks().len(),
+        2
+    );
+
+    // An item repeated on either side is no anchor: "x" is in `new` twice
+    let edits = diff::diff_slices(&["x", "a"], &["a", "x", "x"], Algorithm::Patience);
+    assert_eq!(changed(&edits), 3);
+}
+
+#[test]
+fn renders_unified_diffs() {
+    let old = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten";
+    let new = "one\nTWO\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\neleven\n";
+    let patch = Patch::new(old, new, Algorithm::Myers, 1);
+    assert_eq!(
+        patch.to_unified("a/numbers.txt", "b/numbers.txt"),
+        "--- a/numbers.txt\n+++ b/numbers.txt\n\
+         @@ -1,3 +1,3 @@\n one\n-two\n+TWO\n three\n\
+         @@ -9,2 +9,3 @@\n nine\n-ten\n\\ No newline at end of file\n+ten\n+eleven\n"
+    );
+    // 7 unchanged lines apart: with 4 lines of context on each side the two changes share a hunk
+    assert_eq!(Patch::new(old, new, Algorithm::Myers, 3).hunks().len(), 2);
+    assert_eq!(Patch::new(old, new, Algorithm::Myers, 4).hunks().len(), 1);
+    assert_eq!(
+        Patch::new(old, old, Algorithm::Myers, 3).to_unified("a", "b"),
+        ""
+    );
+    // Inserting into an empty text is numbered from line 0
+    let patch = Patch::new("", "hello\n", Algorithm::Myers, 3);
+    assert_eq!(patch.to_string(), "@@ -0,0 +1 @@\n+hello\n");
+    assert_eq!(patch.apply(""), Ok("hello\n".to_string()));
+}
+
+#[test]
+fn parses_and_applies_patches() {
+    let text = "diff --git a/f b/f\nindex 1234..5678 100644\n--- a/f\n+++ b/f\n\
+                @@ -1,3 +1,3 @@ fn main\n a\n-b\n+B\n\n";
+    let patch = Patch::parse(text).unwrap();
+    assert_eq!(patch.hunks().len(), 1);
+    assert_eq!(patch.apply("a\nb\n\nd\n"), Ok("a\nB\n\nd\n".to_string()));
+    assert_eq!(
+        patch.apply("a\nc\n\n"),
+        Err(PatchError::Mismatch { hunk: 1, line: 2 })
+    );
+    assert_eq!(
+        patch.apply("a\n"),
+        Err(PatchError::Mismatch { hunk: 1, line: 2 })
+    );
+
+    assert_eq!(
+        Patch::parse("@@ -1 +1 @@\n-a\n"),
+        Err(PatchError::UnexpectedEnd)
+    );
+    assert_eq!(
+        Patch::parse("@@ -1 +1 @@\n-a\n-b\n"),
+        Err(PatchError::BadLine { line: 3 })
+    );
+    assert_eq!(
+        Patch::parse("--- a\n@@ -x +1 @@\n"),
+        Err(PatchError::BadHunkHeader { line: 2 })
+    );
+    let overlapping = Patch::parse("@@ -1,2 +1,2 @@\n a\n-b\n+c\n@@ -2 +2 @@\n-b\n+d\n").unwrap();
+    assert_eq!(
+        overlapping.apply("a\nb\n"),
+        Err(PatchError::Overlap { hunk: 2 })
+    );
+    assert_eq!(
+        PatchError::Mismatch { hunk: 2, line: 7 }.to_string(),
+        "hunk 2 does not match line 7 of the text"
+    );
+}
+
+// --- ROUND TRIPS ---
+fn algorithm() -> impl Strategy<Value = Algorithm> {
+    prop::sample::select(vec![Algorithm::Myers, Algorithm::Patience])
+}
+
+// Lines from a small set, so the two texts share many of them, sometimes without a final '\n'
+fn lines() -> impl Strategy<Value = String> {
+    let line = prop::sample::select(vec!["a\n", "b\n", "c\n", "}\n", "\n", "fn x() {\n", "é\n"]);
+    (
+        prop::collection::vec(line, 0..16),
+        prop::option::of("end|\n"),
+    )
+        .prop_map(|(lines, end)| lines.concat() + end.as_deref().unwrap_or(""))
+}
+
+proptest! {
+    #[test]
+    fn myers_is_shortest(old in prop::collection::vec(0..4u8, 0..24), new in prop::collection::vec(0..4u8, 0..24)) {
+        let edits = diff::diff_slices(&old, &new, Algorithm::Myers);
+        prop_assert_eq!(replay(&old, &new, &edits), new.clone());
+        prop_assert_eq!(changed(&edits), shortest(&old, &new));
+        let patience = diff::diff_slices(&old, &new, Algorithm::Patience);
+        prop_assert_eq!(replay(&old, &new, &patience), new.clone());
+    }
+
+    #[test]
+    fn text_changes_rebuild_both_texts(old in "[ab é\u{301}\n]{0,20}", new in "[ab é\u{301}\n]{0,20}", algorithm in algorithm()) {
+        for granularity in [Granularity::Line, Granularity::Word, Granularity::Char, Granularity::Grapheme] {
+            let d = diff::diff(&old, &new, granularity, algorithm);
+            let (mut was, mut is) = (String::new(), String::new());
+            for (kind, text) in d.changes() {
+                if kind != EditKind::Insert { was.push_str(text); }
+                if kind != EditKind::Delete { is.push_str(text); }
+            }
+            prop_assert_eq!((&was, &is), (&old, &new));
+        }
+    }
+
+    #[test]
+    fn patches_round_trip(old in lines(), new in lines(), algorithm in algorithm(), context in 0..4usize) {
+        let patch = Patch::new(&old, &new, algorithm, context);
+        prop_assert_eq!(patch.apply(&old), Ok(new.clone()));
+        let printed = patch.to_unified("a/file", "b/file");
+        let parsed = Patch::parse(&printed).unwrap();
+        prop_assert_eq!(&parsed, &patch);
+        prop_assert_eq!(parsed.apply(&old), Ok(new.clone()));
+    }
+}
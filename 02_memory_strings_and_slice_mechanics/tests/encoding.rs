@@ -0,0 +1,232 @@
+// Known byte sequences for each encoding, detection on typical inputs, and properties: encoding
+// then decoding gives the text back, and decoding in chunks matches decoding in one go.
+use std::borrow::Cow;
+
+use memory_safety::encoding::{self, Decoder, Encoder, Encoding, EncodingError, Policy};
+use proptest::prelude::*;
+
+mod common;
+
+const ALL: [Encoding; 5] = [
+    Encoding::Utf8,
+    Encoding::Utf16Le,
+    Encoding::Utf16Be,
+    Encoding::Latin1,
+    Encoding::Windows1252,
+];
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn decodes_each_encoding() {
+    fn decode(bytes: &[u8], encoding: Encoding) -> Cow<'_, str> {
+        encoding::decode(bytes, encoding, Policy::Strict).unwrap()
+    }
+    assert_eq!(decode(b"caf\xC3\xA9", Encoding::Utf8), "café");
+    assert_eq!(
+        decode(b"c\x00a\x00f\x00\xE9\x00", Encoding::Utf16Le),
+        "café"
+    );
+    assert_eq!(
+        decode(b"\x00c\x00a\x00f\x00\xE9", Encoding::Utf16Be),
+        "café"
+    );
+    assert_eq!(decode(b"\x3E\xD8\x80\xDD", Encoding::Utf16Le), "🦀"); // A surrogate pair
+    assert_eq!(decode(b"caf\xE9", Encoding::Latin1), "café");
+    assert_eq!(
+        decode(b"\x93caf\xE9\x94 \x80", Encoding::Windows1252),
+        "“café” €"
+    );
+    assert_eq!(decode(b"\x93\x80", Encoding::Latin1), "\u{93}\u{80}"); // C1 controls in Latin-1
+    // A BOM is not part of the text
+    assert_eq!(decode(b"\xEF\xBB\xBFhi", Encoding::Utf8), "hi");
+    assert_eq!(decode(b"\xFE\xFF\x00h\x00i", Encoding::Utf16Be), "hi");
+    // Already the text: borrowed
+    assert!(matches!(
+        decode(b"plain", Encoding::Windows1252),
+        Cow::Borrowed("plain")
+    ));
+    assert!(matches!(
+        decode("né".as_bytes(), Encoding::Utf8),
+        Cow::Borrowed("né")
+    ));
+}
+
+#[test]
+fn windows_1252_table() {
+    // Every defined byte of 0x80..0x9F, in order, and the five holes
+    let defined = "€‚ƒ„…†‡ˆ‰Š‹ŒŽ‘’“”•–—˜™š›œžŸ";
+    let bytes: Vec<u8> = (0x80..=0x9F)
+        .filter(|b| ![0x81, 0x8D, 0x8F, 0x90, 0x9D].contains(b))
+        .collect();
+    assert_eq!(
+        encoding::decode(&bytes, Encoding::Windows1252, Policy::Strict).unwrap(),
+        defined
+    );
+    assert_eq!(
+        encoding::encode(defined, Encoding::Windows1252, Policy::Strict).unwrap(),
+        bytes
+    );
+    assert_eq!(
+        encoding::decode(b"ab\x81", Encoding::Windows1252, Policy::Strict),
+        Err(EncodingError::Invalid { at: 2, len: 1 })
+    );
+    assert_eq!(
+        encoding::decode(b"ab\x81", Encoding::Windows1252, Policy::Replace).unwrap(),
+        "ab\u{FFFD}"
+    );
+    // Above 0x9F it is Latin-1
+    let high: Vec<u8> = (0xA0..=0xFF).collect();
+    let latin1 = encoding::decode(&high, Encoding::Latin1, Policy::Strict).unwrap();
+    assert_eq!(
+        encoding::decode(&high, Encoding::Windows1252, Policy::Strict).unwrap(),
+        latin1
+    );
+}
+
+#[test]
+fn reports_errors_by_offset() {
+    // UTF-16: a trail surrogate with no lead, a lead with no trail, a cut-off code unit
+    assert_eq!(
+        encoding::decode(b"a\x00\x00\xDCb\x00", Encoding::Utf16Le, Policy::Strict),
+        Err(EncodingError::Invalid { at: 2, len: 2 })
+    );
+    assert_eq!(
+        encoding::decode(b"a\x00\x3D\xD8b\x00", Encoding::Utf16Le, Policy::Replace).unwrap(),
+        "a\u{FFFD}b"
+    );
+    assert_eq!(
+        encoding::decode(b"a\x00\x3D\xD8\x80", Encoding::Utf16Le, Policy::Strict),
+        Err(EncodingError::Truncated { at: 2 })
+    );
+    assert_eq!(
+        encoding::decode(b"a\x00b", Encoding::Utf16Le, Policy::Replace).unwrap(),
+        "a\u{FFFD}"
+    );
+    assert_eq!(
+        encoding::decode(b"ok\xFF", Encoding::Utf8, Policy::Strict),
+        Err(EncodingError::Invalid { at: 2, len: 1 })
+    );
+    // Encoding: chars the target has no code for
+    assert_eq!(
+        encoding::encode("née 🦀", Encoding::Latin1, Policy::Strict),
+        Err(EncodingError::Unmappable { at: 5, c: '🦀' })
+    );
+    assert_eq!(
+        encoding::encode("€ 🦀", Encoding::Windows1252, Policy::Replace).unwrap(),
+        &b"\x80 ?"[..]
+    );
+    assert_eq!(
+        encoding::encode("€", Encoding::Latin1, Policy::Replace).unwrap(),
+        &b"?"[..]
+    );
+    assert_eq!(
+        EncodingError::Unmappable { at: 5, c: '🦀' }.to_string(),
+        "'🦀' at byte 5 cannot be encoded"
+    );
+}
+
+#[test]
+fn detects_encodings() {
+    let detect = |bytes: &[u8]| {
+        let found = encoding::detect(bytes);
+        (found.encoding, found.bom_len, found.certain)
+    };
+    assert_eq!(detect(b"\xEF\xBB\xBFhi"), (Encoding::Utf8, 3, true));
+    assert_eq!(detect(b"\xFF\xFEh\x00"), (Encoding::Utf16Le, 2, true));
+    assert_eq!(detect(b"\xFE\xFF\x00h"), (Encoding::Utf16Be, 2, true));
+    assert_eq!(detect("plain ASCII".as_bytes()), (Encoding::Utf8, 0, false));
+    assert_eq!(detect("नमस्ते".as_bytes()), (Encoding::Utf8, 0, false));
+    assert_eq!(detect(&"नमस्ते".as_bytes()[..4]), (Encoding::Utf8, 0, false)); // Cut mid-char
+    let utf16 = |text: &str, encoding| {
+        encoding::encode(text, encoding, Policy::Strict)
+            .unwrap()
+            .into_owned()
+    };
+    assert_eq!(
+        detect(&utf16("Hello, world", Encoding::Utf16Le)),
+        (Encoding::Utf16Le, 0, false)
+    );
+    assert_eq!(
+        detect(&utf16("Hello, world", Encoding::Utf16Be)),
+        (Encoding::Utf16Be, 0, false)
+    );
+    assert_eq!(detect(b"\x93quoted\x94"), (Encoding::Windows1252, 0, false));
+    assert_eq!(detect(b"caf\xE9 au lait"), (Encoding::Latin1, 0, false));
+
+    let (text, found) = encoding::decode_detected(b"\xFF\xFEh\x00i\x00", Policy::Strict).unwrap();
+    assert_eq!((text.as_ref(), found), ("hi", Encoding::Utf16Le));
+}
+
+#[test]
+fn streams_in_chunks() {
+    // "🦀" in UTF-16BE is D8 3E DD 80: cut inside the first unit, then between the two
+    let bytes = b"\xFE\xFF\xD8\x3E\xDD\x80\x00!";
+    let mut decoder = Decoder::new(Encoding::Utf16Be, Policy::Strict);
+    let mut text = String::new();
+    for chunk in [&bytes[..1], &bytes[1..3], &bytes[3..4], &bytes[4..]] {
+        text.push_str(&decoder.decode(chunk).unwrap());
+    }
+    text.push_str(decoder.finish().unwrap());
+    assert_eq!(text, "🦀!");
+
+    let mut encoder = Encoder::new(Encoding::Latin1, Policy::Strict);
+    let mut out = Vec::new();
+    encoder.encode("café, ", &mut out).unwrap();
+    assert_eq!(
+        encoder.encode("naïve €", &mut out),
+        Err(EncodingError::Unmappable { at: 14, c: '€' })
+    );
+    assert_eq!(out, b"caf\xE9, na\xEFve ");
+}
+
+// --- ROUND TRIPS ---
+const PIECES: &[&str] = &["a", " ", "é", "ÿ", "€", "“", "Ÿ", "न", "🦀", "\u{FEFF}"];
+
+fn text() -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, 12)
+}
+
+fn split<'b>(bytes: &'b [u8], cuts: &[usize]) -> Vec<&'b [u8]> {
+    let mut cuts: Vec<usize> = cuts.iter().map(|c| c % (bytes.len() + 1)).collect();
+    cuts.sort_unstable();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for cut in cuts.into_iter().chain([bytes.len()]) {
+        chunks.push(&bytes[start..cut]);
+        start = cut;
+    }
+    chunks
+}
+
+proptest! {
+    // Every encodable text comes back; an unencodable one fails at its first unencodable char
+    #[test]
+    fn encode_then_decode_round_trips(text in text(), encoding in prop::sample::select(ALL.to_vec())) {
+        // A leading U+FEFF would be read back as a BOM
+        let text = text.trim_start_matches('\u{FEFF}');
+        match encoding::encode(text, encoding, Policy::Strict) {
+            Ok(bytes) => {
+                let decoded = encoding::decode(&bytes, encoding, Policy::Strict).unwrap();
+                prop_assert_eq!(decoded, text);
+            }
+            Err(EncodingError::Unmappable { at, c }) => {
+                prop_assert_eq!(text[at as usize..].chars().next(), Some(c));
+                let replaced = encoding::encode(text, encoding, Policy::Replace).unwrap();
+                prop_assert!(replaced.contains(&b'?'));
+            }
+            Err(e) => prop_assert!(false, "{}", e),
+        }
+    }
+
+    #[test]
+    fn chunks_decode_like_the_whole(bytes in prop::collection::vec(any::<u8>(), 0..40), cuts in prop::collection::vec(any::<usize>(), 0..5), encoding in prop::sample::select(ALL.to_vec()), replace in any::<bool>()) {
+        let policy = if replace { Policy::Replace } else { Policy::Strict };
+        let whole = encoding::decode(&bytes, encoding, policy).map(Cow::into_owned);
+        let mut decoder = Decoder::new(encoding, policy);
+        let chunked = split(&bytes, &cuts)
+            .into_iter()
+            .try_fold(String::new(), |text, chunk| Ok(text + &decoder.decode(chunk)?))
+            .and_then(|text| Ok(text + decoder.finish()?));
+        prop_assert_eq!(chunked, whole);
+    }
+}
@@ -0,0 +1,276 @@
+// Every escape must come back unchanged through its unescape, for any text, including the samples
+// string_slicing in main.rs walks through. Known answers pin the exact output of each format, and
+// shell quoting is checked against a real shell.
+use std::borrow::Cow;
+
+use memory_safety::escape::{
+    self, CsvField, CsvRecord, HtmlAttr, HtmlText, Json, RustStr, ShellQuoted, UnescapeError,
+};
+use proptest::prelude::*;
+
+mod common;
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn json_escapes_quotes_backslashes_and_controls() {
+    assert_eq!(escape::json("Hello, world!"), "\"Hello, world!\"");
+    assert_eq!(escape::json("say \"hi\"\\"), r#""say \"hi\"\\""#);
+    assert_eq!(escape::json("a\nb\t\u{8}\u{1}"), r#""a\nb\t\b\u0001""#);
+    assert_eq!(escape::json("नमस्ते 🦀"), "\"नमस्ते 🦀\"");
+
+    assert_eq!(escape::unescape_json(r#""é\/🦀""#).unwrap(), "é/🦀");
+    assert_eq!(escape::unescape_json("hi"), Err(UnescapeError::Unquoted));
+    assert_eq!(escape::unescape_json(r#""\"#), Err(UnescapeError::Unquoted)); // One quote only
+    assert_eq!(
+        escape::unescape_json(r#""\""#),
+        Err(UnescapeError::Unterminated)
+    );
+    assert_eq!(
+        escape::unescape_json(r#""a"b""#),
+        Err(UnescapeError::Unexpected { at: 2, c: '"' })
+    );
+    assert_eq!(
+        escape::unescape_json("\"a\nb\""),
+        Err(UnescapeError::Unexpected { at: 2, c: '\n' })
+    );
+    assert_eq!(
+        escape::unescape_json(r#""\x41""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+    // Lone surrogates
+    assert_eq!(
+        escape::unescape_json(r#""\ud83e""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+    assert_eq!(
+        escape::unescape_json(r#""\udd80""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+}
+
+#[test]
+fn shell_quoting_only_quotes_when_needed() {
+    assert!(matches!(
+        escape::shell_quote("src/main.rs"),
+        Cow::Borrowed("src/main.rs")
+    ));
+    assert_eq!(escape::shell_quote(""), "''");
+    assert_eq!(escape::shell_quote("it's"), r"'it'\''s'");
+    assert_eq!(escape::shell_quote("$HOME *"), "'$HOME *'");
+    assert_eq!(escape::shell_quote("🦀"), "'🦀'");
+
+    assert_eq!(
+        escape::shell_unquote(r#"a'b c'"d\"\$"\ e"#).unwrap(),
+        "ab cd\"$ e"
+    );
+    assert_eq!(escape::shell_unquote(r#""\n""#).unwrap(), r"\n"); // Kept inside "..."
+    assert_eq!(
+        escape::shell_unquote("'abc"),
+        Err(UnescapeError::Unterminated)
+    );
+    assert_eq!(
+        escape::shell_unquote("a b"),
+        Err(UnescapeError::Unexpected { at: 1, c: ' ' })
+    );
+    assert_eq!(
+        escape::shell_unquote("\"$HOME\""),
+        Err(UnescapeError::Unexpected { at: 1, c: '$' })
+    );
+    assert_eq!(
+        escape::shell_unquote("*.rs"),
+        Err(UnescapeError::Unexpected { at: 0, c: '*' })
+    );
+}
+
+#[test]
+fn csv_follows_rfc_4180() {
+    assert!(matches!(escape::csv_field("plain text"), Cow::Borrowed(_)));
+    assert_eq!(escape::csv_field("Hello, world!"), "\"Hello, world!\"");
+    assert_eq!(escape::csv_field("6\" ruler"), "\"6\"\" ruler\"");
+    assert_eq!(
+        CsvRecord(&["a", "b,c", "", "line\nbreak"]).to_string(),
+        "a,\"b,c\",,\"line\nbreak\""
+    );
+    assert_eq!(CsvRecord(&[""]).to_string(), "\"\"");
+
+    assert_eq!(
+        escape::parse_csv_record("a,\"b,\"\"c\"\"\",,").unwrap(),
+        ["a", "b,\"c\"", "", ""]
+    );
+    assert_eq!(escape::parse_csv_field("\"\"").unwrap(), "");
+    assert_eq!(
+        escape::parse_csv_field("a,b"),
+        Err(UnescapeError::Unexpected { at: 1, c: ',' })
+    );
+    assert_eq!(
+        escape::parse_csv_record("a\"b"),
+        Err(UnescapeError::Unexpected { at: 1, c: '"' })
+    );
+    assert_eq!(
+        escape::parse_csv_record("\"a\"b"),
+        Err(UnescapeError::Unexpected { at: 3, c: 'b' })
+    );
+    assert_eq!(
+        escape::parse_csv_record("\"a"),
+        Err(UnescapeError::Unterminated)
+    );
+}
+
+#[test]
+fn html_escapes_text_and_attributes() {
+    assert_eq!(
+        escape::html_text("a < b && \"c\""),
+        "a &lt; b &amp;&amp; \"c\""
+    );
+    assert_eq!(escape::html_attr("it's \"x\""), "it&#39;s &quot;x&quot;");
+    assert!(matches!(escape::html_attr("नमस्ते"), Cow::Borrowed(_)));
+
+    assert_eq!(
+        escape::unescape_html("&lt;b&gt; &#233;&#xE9;&#X1F980; &apos;&nbsp;"),
+        "<b> éé🦀 '\u{A0}"
+    );
+    // Anything else is left alone, like a browser would
+    assert_eq!(
+        escape::unescape_html("AT&T &copy &bogus; &#+1; &"),
+        "AT&T &copy &bogus; &#+1; &"
+    );
+    assert_eq!(
+        escape::unescape_html("&#0;&#xD800;&#x110000;"),
+        "\u{FFFD}\u{FFFD}\u{FFFD}"
+    );
+    assert!(matches!(
+        escape::unescape_html("no references"),
+        Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn rust_literals_escape_controls_and_invisible_characters() {
+    assert_eq!(escape::rust_str("Hello, world!"), "\"Hello, world!\"");
+    assert_eq!(
+        escape::rust_str("a\"b\\\n\0\u{7f}"),
+        r#""a\"b\\\n\0\u{7f}""#
+    );
+    // Bidi overrides and zero-width characters would hide what the literal really holds
+    assert_eq!(
+        escape::rust_str("\u{202E}abc\u{200B}\u{FEFF}"),
+        r#""\u{202e}abc\u{200b}\u{feff}""#
+    );
+    assert_eq!(escape::rust_str("नमस्ते 🦀 é"), "\"नमस्ते 🦀 é\"");
+
+    // Literals from this file, read back the way rustc reads them
+    assert_eq!(
+        escape::unescape_rust(r#""\x41\u{1F980}\u{1_f600}\'""#).unwrap(),
+        "\x41\u{1F980}\u{1_f600}\'"
+    );
+    assert_eq!(
+        escape::unescape_rust("\"a\\\n    b\"").unwrap(),
+        "a\
+    b"
+    );
+    assert_eq!(
+        escape::unescape_rust(r#""\x80""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+    assert_eq!(
+        escape::unescape_rust(r#""\u{D800}""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+    assert_eq!(
+        escape::unescape_rust(r#""\u{1234567}""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+    assert_eq!(
+        escape::unescape_rust(r#""\q""#),
+        Err(UnescapeError::BadEscape { at: 1 })
+    );
+    assert_eq!(
+        escape::unescape_rust("\"a\rb\""),
+        Err(UnescapeError::Unexpected { at: 2, c: '\r' })
+    );
+}
+
+// The quoting as a shell reads it: printf gets back exactly the original argument
+#[cfg(unix)]
+#[test]
+fn a_real_shell_reads_quoted_words_back() {
+    use std::process::Command;
+
+    let samples = [
+        "नमस्ते",
+        "🦀🦀🦀🦀🦀",
+        "Hello, world!",
+        "it's",
+        "",
+        "$(echo hi) `x` *",
+        "a\nb\\c\"d",
+        " ~#!",
+    ];
+    for sample in samples {
+        let quoted = escape::shell_quote(sample);
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("printf %s {quoted}"))
+            .output()
+            .expect("sh runs");
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            sample,
+            "{quoted}"
+        );
+    }
+}
+
+// --- ROUND TRIPS ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "नमस्ते", "🦀", "Hello, world!", "é", "a", " ", "\"", "'", "\\", ",", "\n", "\r", "\t", "\0",
+    "\u{1}", "\u{7f}", "\u{202E}", "\u{200B}", "\u{FEFF}", "<", ">", "&", "&amp;", "&#39;", "$",
+    "`", "*", "~", "{", "}", "x41", "u{41}",
+];
+
+fn text() -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, 12)
+}
+
+proptest! {
+    #[test]
+    fn every_escape_round_trips(s in text()) {
+        prop_assert_eq!(escape::unescape_json(&escape::json(&s)), Ok(s.clone()));
+        prop_assert_eq!(escape::unescape_rust(&escape::rust_str(&s)), Ok(s.clone()));
+        prop_assert_eq!(escape::shell_unquote(&escape::shell_quote(&s)), Ok(s.clone()));
+        prop_assert_eq!(escape::parse_csv_field(&escape::csv_field(&s)), Ok(s.clone()));
+        let (text, attr) = (escape::html_text(&s), escape::html_attr(&s));
+        prop_assert_eq!(escape::unescape_html(&text), s.as_str());
+        prop_assert_eq!(escape::unescape_html(&attr), s.as_str());
+    }
+
+    // A record needs at least one field: an empty line is one empty field
+    #[test]
+    fn csv_records_round_trip(fields in prop::collection::vec(text(), 1..5)) {
+        prop_assert_eq!(escape::parse_csv_record(&CsvRecord(&fields).to_string()), Ok(fields));
+    }
+
+    // The Display adapters write exactly what the owned conversions return
+    #[test]
+    fn adapters_match_owned_conversions(s in text()) {
+        prop_assert_eq!(Json(&s).to_string(), escape::json(&s));
+        prop_assert_eq!(RustStr(&s).to_string(), escape::rust_str(&s));
+        prop_assert_eq!(ShellQuoted(&s).to_string(), escape::shell_quote(&s));
+        prop_assert_eq!(CsvField(&s).to_string(), escape::csv_field(&s));
+        prop_assert_eq!(HtmlText(&s).to_string(), escape::html_text(&s));
+        prop_assert_eq!(HtmlAttr(&s).to_string(), escape::html_attr(&s));
+    }
+
+    // Escaped text never contains what the format treats as special
+    #[test]
+    fn escaped_text_has_no_raw_specials(s in text()) {
+        let json = escape::json(&s);
+        prop_assert!(!json.chars().any(|c| c < ' '));
+        let rust = escape::rust_str(&s);
+        let hidden = ['\u{202E}', '\u{200B}', '\u{FEFF}', '\r', '\0'];
+        prop_assert!(!rust.contains(hidden));
+        let html = escape::html_attr(&s);
+        prop_assert!(!html.contains(['<', '>', '"', '\'']));
+    }
+}
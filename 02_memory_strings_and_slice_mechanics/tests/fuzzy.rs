@@ -0,0 +1,203 @@
+// Known answers from the literature, and properties checked against plain implementations: the
+// distances are metrics, bitap finds what a full dynamic-programming scan finds, and the BK-tree
+// returns what comparing against every word returns.
+use memory_safety::fuzzy::{self, ApproxMatch, Bitap, BitapError, BkTree};
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+mod common;
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn edit_distances() {
+    assert_eq!(fuzzy::levenshtein("kitten", "sitting"), 3);
+    assert_eq!(fuzzy::levenshtein("", "abc"), 3);
+    assert_eq!(fuzzy::damerau_levenshtein("teh", "the"), 1);
+    assert_eq!(fuzzy::levenshtein("teh", "the"), 2);
+    // True Damerau–Levenshtein: swap, then insert between (optimal string alignment says 3)
+    assert_eq!(fuzzy::damerau_levenshtein("ca", "abc"), 2);
+    // Graphemes, not chars: é written with a combining accent is one letter
+    assert_eq!(fuzzy::levenshtein("cafe\u{301}", "cafe"), 1);
+    assert_eq!(fuzzy::levenshtein("नमस्ते", "नमस्कार"), 2);
+    assert_eq!(fuzzy::damerau_levenshtein("🦀🦀a", "🦀a🦀"), 1);
+}
+
+#[test]
+fn similarity_scores() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-3;
+    assert!(close(fuzzy::jaro("MARTHA", "MARHTA"), 0.944));
+    assert!(close(fuzzy::jaro_winkler("MARTHA", "MARHTA"), 0.961));
+    assert!(close(fuzzy::jaro_winkler("DWAYNE", "DUANE"), 0.840));
+    assert!(close(fuzzy::jaro_winkler("DIXON", "DICKSONX"), 0.813));
+    assert_eq!(fuzzy::jaro("abc", "xyz"), 0.0);
+    assert_eq!(fuzzy::jaro("", ""), 1.0);
+    assert_eq!(fuzzy::jaro("a", ""), 0.0);
+    // Bigrams of "night" and "nacht" share only "ht"
+    assert!(close(fuzzy::ngram_similarity("night", "nacht", 2), 0.25));
+    assert_eq!(fuzzy::ngram_similarity("a", "a", 3), 1.0); // Shorter than n: one n-gram
+    assert_eq!(fuzzy::ngram_similarity("", "", 2), 1.0);
+}
+
+#[test]
+fn bitap_finds_approximate_matches() {
+    let text = "Hello, world! wonderful world, wrold";
+    let bitap = Bitap::new("wrld", 1).unwrap();
+    let found: Vec<&str> = bitap
+        .find_iter(text)
+        .map(|m| &text[m.start..m.end])
+        .collect();
+    assert_eq!(found, ["world", "world", "wrold"]);
+
+    let text = "Today is a very warm and suny day.";
+    let m = Bitap::new("sunny", 2).unwrap().find(text);
+    assert_eq!(
+        m,
+        Some(ApproxMatch {
+            start: 25,
+            end: 29,
+            edits: 1
+        })
+    );
+    // Offsets are bytes on grapheme boundaries
+    let text = "🦀 नमस्ते 🦀";
+    let m = Bitap::new("नमस्ते", 0).unwrap().find(text).unwrap();
+    assert_eq!(&text[m.start..m.end], "नमस्ते");
+    // "नमस" is a prefix of "नमस्ते" char by char, but its last grapheme is स, not स्ते
+    assert_eq!(Bitap::new("नमस", 0).unwrap().find("नमस्ते"), None);
+
+    assert_eq!(Bitap::new("", 1).unwrap_err(), BitapError::EmptyPattern);
+    let long = "a".repeat(65);
+    assert_eq!(
+        Bitap::new(&long, 1).unwrap_err(),
+        BitapError::PatternTooLong { graphemes: 65 }
+    );
+    assert_eq!(Bitap::new("ab", 5).unwrap().max_edits(), 1);
+}
+
+#[test]
+fn bk_tree_suggestions() {
+    let words = ["Today", "is", "a", "very", "warm", "and", "sunny", "day"];
+    let tree: BkTree = words.iter().collect();
+    assert_eq!(tree.len(), words.len());
+    assert_eq!(tree.suggest("say", 2, 3), ["day", "a"]);
+    assert_eq!(tree.suggest("arm", 1, 3), ["warm"]);
+    assert_eq!(tree.nearest("sun"), Some(("sunny", 2)));
+    assert_eq!(tree.find("dew", 1), []);
+    assert!(tree.contains("warm") && !tree.contains("war"));
+
+    let mut tree = BkTree::with_distance(fuzzy::damerau_levenshtein);
+    assert!(tree.insert("the"));
+    assert!(!tree.insert("the"));
+    assert_eq!(tree.nearest("teh"), Some(("the", 1)));
+    assert_eq!(BkTree::new().nearest("x"), None);
+}
+
+// --- AGAINST PLAIN IMPLEMENTATIONS ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "b", "c", "ab", "\r", "\n", "e\u{301}", "न", "स्ते", "🦀"];
+
+fn text(max: usize) -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, max)
+}
+
+fn graphemes(s: &str) -> Vec<&str> {
+    s.graphemes(true).collect()
+}
+
+// Textbook full-matrix Levenshtein
+fn levenshtein_matrix(a: &[&str], b: &[&str]) -> usize {
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j - 1] + cost)
+                .min(d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1);
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+// Fewest edits of `pattern` against any substring of `text` (Sellers): the first row is all
+// zeros, so a match may start anywhere
+fn best_substring_edits(pattern: &[&str], text: &[&str]) -> usize {
+    let mut row = vec![0; text.len() + 1];
+    for (i, p) in pattern.iter().enumerate() {
+        let mut next = vec![i + 1; text.len() + 1];
+        for j in 1..=text.len() {
+            let cost = usize::from(*p != text[j - 1]);
+            next[j] = (row[j - 1] + cost).min(row[j] + 1).min(next[j - 1] + 1);
+        }
+        row = next;
+    }
+    row.into_iter().min().unwrap_or(0)
+}
+
+proptest! {
+    #[test]
+    fn distances_are_metrics(a in text(7), b in text(7), c in text(7)) {
+        let (ga, gb) = (graphemes(&a), graphemes(&b));
+        let lev = fuzzy::levenshtein(&a, &b);
+        prop_assert_eq!(lev, levenshtein_matrix(&ga, &gb));
+        for distance in [fuzzy::levenshtein, fuzzy::damerau_levenshtein] {
+            let ab = distance(&a, &b);
+            prop_assert_eq!(ab, distance(&b, &a));
+            prop_assert_eq!(ab == 0, a == b);
+            prop_assert!(distance(&a, &c) <= ab + distance(&b, &c));
+            prop_assert!(ab <= ga.len().max(gb.len()));
+        }
+        // A swap is one edit for Damerau and at most two for Levenshtein
+        let dl = fuzzy::damerau_levenshtein(&a, &b);
+        prop_assert!(dl <= lev && lev <= 2 * dl);
+    }
+
+    #[test]
+    fn similarities_are_between_0_and_1(a in text(7), b in text(7)) {
+        for score in [fuzzy::jaro(&a, &b), fuzzy::jaro_winkler(&a, &b), fuzzy::ngram_similarity(&a, &b, 2)] {
+            prop_assert!((0.0..=1.0).contains(&score), "{}", score);
+        }
+        prop_assert!(fuzzy::jaro_winkler(&a, &b) >= fuzzy::jaro(&a, &b));
+        prop_assert_eq!(fuzzy::jaro(&a, &a), 1.0);
+        prop_assert_eq!(fuzzy::ngram_similarity(&a, &a, 3), 1.0);
+        prop_assert_eq!(fuzzy::ngram_similarity(&a, &b, 2), fuzzy::ngram_similarity(&b, &a, 2));
+    }
+
+    // Every match is real, and bitap misses nothing a full scan finds
+    #[test]
+    fn bitap_agrees_with_a_full_scan(pattern in text(6), text in text(20), max_edits in 0..3usize) {
+        let Ok(bitap) = Bitap::new(&pattern, max_edits) else {
+            prop_assert!(pattern.is_empty());
+            return Ok(());
+        };
+        let matches: Vec<ApproxMatch> = bitap.find_iter(&text).collect();
+        let mut last_end = 0;
+        for m in &matches {
+            prop_assert!(m.start >= last_end && m.start < m.end);
+            prop_assert!(m.edits <= bitap.max_edits());
+            prop_assert_eq!(m.edits, fuzzy::levenshtein(&pattern, &text[m.start..m.end]));
+            last_end = m.end;
+        }
+        let best = best_substring_edits(&graphemes(&pattern), &graphemes(&text));
+        prop_assert_eq!(!matches.is_empty(), best <= bitap.max_edits());
+    }
+
+    #[test]
+    fn bk_tree_matches_a_linear_scan(words in prop::collection::vec(text(6), 0..30), query in text(6), max in 0..4usize) {
+        let tree: BkTree = words.iter().collect();
+        let mut expected: Vec<(&str, usize)> = tree
+            .iter()
+            .map(|word| (word, fuzzy::levenshtein(&query, word)))
+            .filter(|&(_, d)| d <= max)
+            .collect();
+        expected.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(b.0)));
+        prop_assert_eq!(tree.find(&query, max), expected);
+        let nearest = words.iter().map(|word| fuzzy::levenshtein(&query, word)).min();
+        prop_assert_eq!(tree.nearest(&query).map(|(_, d)| d), nearest);
+    }
+}
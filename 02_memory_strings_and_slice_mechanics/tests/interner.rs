@@ -0,0 +1,193 @@
+// Every string gets one symbol and resolves back to itself, even when strings share a hash or
+// threads intern at the same time, and a saved table reads back with the same symbols or fails.
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::io;
+use std::thread;
+
+use memory_safety::interner::{Interner, Symbol, SyncInterner};
+use proptest::prelude::*;
+
+mod common;
+
+// Hashes a string to its length, so every string of the same length lands in one bucket
+#[derive(Default)]
+struct ByLength(u64);
+
+impl Hasher for ByLength {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len() as u64;
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn colliding() -> Interner<BuildHasherDefault<ByLength>> {
+    Interner::with_hasher(BuildHasherDefault::default())
+}
+
+// A table as write_to lays it out, with any bytes for the strings
+fn table(strings: &[&[u8]]) -> Vec<u8> {
+    let mut bytes = b"STRINTERN1\n".to_vec();
+    bytes.extend((strings.len() as u32).to_le_bytes());
+    for s in strings {
+        bytes.extend((s.len() as u32).to_le_bytes());
+        bytes.extend(*s);
+    }
+    bytes
+}
+
+fn read_error(bytes: &[u8]) -> io::Error {
+    Interner::read_from(bytes).unwrap_err()
+}
+
+// --- INTERNER ---
+#[test]
+fn numbers_symbols_in_order_of_interning() {
+    let mut interner = Interner::new();
+    let words = ["Blue", "Yellow", "Blue", "", "🦀", "Yellow"];
+    let symbols: Vec<usize> = words.iter().map(|w| interner.intern(w).index()).collect();
+    assert_eq!(symbols, [0, 1, 0, 2, 3, 1]);
+    assert_eq!(interner.len(), 4);
+    assert_eq!(interner.text_len(), "BlueYellow🦀".len()); // Each string once
+    let blue = interner.get("Blue").unwrap();
+    assert_eq!(interner.resolve(blue), Some("Blue"));
+    assert_eq!(interner.get("Green"), None);
+    assert_eq!(interner.len(), 4); // get never stores
+    // A symbol from a bigger interner is None here, not a panic
+    let mut other = Interner::new();
+    let last = ["a", "b", "c", "d", "e"].map(|w| other.intern(w))[4];
+    assert_eq!(interner.resolve(last), None);
+}
+
+#[test]
+fn tells_apart_strings_that_share_a_hash() {
+    let mut interner = colliding();
+    // "ab", "ba", "é" and "cd" are two bytes each and share a bucket; "a" and "b" share another
+    let words = ["ab", "ba", "é", "a", "cd", "ab", "é", "b"];
+    let symbols: Vec<usize> = words.iter().map(|w| interner.intern(w).index()).collect();
+    assert_eq!(symbols, [0, 1, 2, 3, 4, 0, 2, 5]);
+    for (symbol, word) in interner.iter() {
+        assert_eq!(interner.get(word), Some(symbol));
+        assert_eq!(interner.resolve(symbol), Some(word));
+    }
+    // Same hash as four stored strings, but not one of them
+    assert_eq!(interner.get("zz"), None);
+    assert_eq!(interner.get("c"), None);
+    assert_eq!(interner.len(), 6);
+}
+
+// --- SERIALIZATION ---
+#[test]
+fn round_trips_with_the_same_symbols() {
+    let mut interner = Interner::new();
+    for word in ["Blue", "", "नमस्ते", "e\u{301}", "Blue", "🦀"] {
+        interner.intern(word);
+    }
+    let mut bytes = Vec::new();
+    interner.write_to(&mut bytes).unwrap();
+    assert_eq!(
+        bytes,
+        table(&[
+            b"Blue",
+            b"",
+            "नमस्ते".as_bytes(),
+            "e\u{301}".as_bytes(),
+            "🦀".as_bytes()
+        ])
+    );
+    let read = Interner::read_from(bytes.as_slice()).unwrap();
+    assert!(read.iter().eq(interner.iter()));
+    assert_eq!(read.get("🦀"), interner.get("🦀"));
+
+    let mut bytes = Vec::new();
+    Interner::new().write_to(&mut bytes).unwrap();
+    assert!(Interner::read_from(bytes.as_slice()).unwrap().is_empty());
+}
+
+#[test]
+fn rejects_bad_tables() {
+    let error = read_error(b"STRINTERN2\n\0\0\0\0");
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "not an interner table");
+    let error = read_error(&table(&[b"ok", b"caf\xe9"])); // Latin-1, not UTF-8
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "string is not UTF-8");
+    let error = read_error(&table(&[b"a", b"b", b"a"]));
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "string stored twice");
+    // Cut anywhere (header, a length, a string), a table is an error, never a shorter table
+    let bytes = table(&[b"Blue", "🦀".as_bytes()]);
+    for end in 0..bytes.len() {
+        assert_eq!(
+            read_error(&bytes[..end]).kind(),
+            io::ErrorKind::UnexpectedEof,
+            "cut at {end}"
+        );
+    }
+    // A count far beyond the data fails on the data, without reserving room for the count
+    let mut bytes = table(&[b"a"]);
+    bytes[11..15].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(read_error(&bytes).kind(), io::ErrorKind::UnexpectedEof);
+}
+
+// --- THREAD-SAFE INTERNER ---
+#[test]
+fn threads_agree_on_every_symbol() {
+    const THREADS: usize = 8;
+    let words: Vec<String> = (0..500).map(|i| format!("word{i}")).collect();
+    let interner = SyncInterner::new();
+    // Each thread interns every word, starting at a different place, so they race on new words
+    let seen: Vec<HashMap<String, Symbol>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let (interner, words) = (&interner, &words);
+                scope.spawn(move || {
+                    let start = t * words.len() / THREADS;
+                    let order = words[start..].iter().chain(&words[..start]);
+                    order.map(|w| (w.clone(), interner.intern(w))).collect()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(interner.len(), words.len()); // No word was stored twice
+    for map in &seen[1..] {
+        assert_eq!(map, &seen[0]);
+    }
+    for (word, &symbol) in &seen[0] {
+        assert_eq!(interner.resolve(symbol).as_deref(), Some(word.as_str()));
+        assert!(interner.with_resolved(symbol, |s| s == Some(word.as_str())));
+    }
+    let interner = interner.into_inner();
+    for (word, &symbol) in &seen[0] {
+        assert_eq!(interner.get(word), Some(symbol));
+    }
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "b", "ab", "é", "e\u{301}", "🦀", "न", "\0", " "];
+
+proptest! {
+    #[test]
+    fn same_string_same_symbol(words in prop::collection::vec(common::concat_of(PIECES, 4), 0..40)) {
+        // The colliding interner puts most of these in Many buckets; both must agree throughout
+        let mut interner = Interner::new();
+        let mut collide = colliding();
+        let mut first: HashMap<&str, usize> = HashMap::new();
+        for word in &words {
+            let next = first.len();
+            let expected = *first.entry(word).or_insert(next);
+            prop_assert_eq!(interner.intern(word).index(), expected);
+            prop_assert_eq!(collide.intern(word).index(), expected);
+        }
+        prop_assert!(interner.iter().eq(collide.iter()));
+        let mut bytes = Vec::new();
+        collide.write_to(&mut bytes).unwrap();
+        let read = Interner::read_from(bytes.as_slice()).unwrap();
+        prop_assert!(read.iter().eq(interner.iter()));
+    }
+}
@@ -0,0 +1,203 @@
+// Wrapped lines fit their width in terminal columns and keep every word of the text; justified
+// lines fill it exactly; dedent undoes indent.
+use std::borrow::Cow;
+
+use memory_safety::layout::{self, Align, WrapOptions};
+use memory_safety::slicing::display_width;
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+// --- WRAPPING ---
+#[test]
+fn wraps_in_terminal_columns() {
+    let options = WrapOptions::new(20);
+    let lines = layout::wrap("Ownership moves, borrowing lends", &options);
+    assert_eq!(lines, ["Ownership moves,", "borrowing lends"]);
+    assert!(lines.iter().all(|line| matches!(line, Cow::Borrowed(_))));
+
+    // Wide characters take two columns and are never cut in half; "é" as e + U+0301 takes one
+    let cases = [
+        ("日本語のテキスト", 10, vec!["日本語のテ", "キスト"]),
+        ("🦀🦀🦀", 5, vec!["🦀🦀", "🦀"]),
+        (
+            "cafe\u{301} cafe\u{301}",
+            5,
+            vec!["cafe\u{301}", "cafe\u{301}"],
+        ),
+        ("日", 1, vec!["日"]), // Wider than the line, and still gets one
+        // Indentation stays on the first line; '\n' and blank lines stay put
+        (
+            "  indented text here\n\nnext",
+            10,
+            vec!["  indented", "text here", "", "next"],
+        ),
+        ("10\u{A0}km away", 6, vec!["10\u{A0}km", "away"]), // No-break space
+        ("trailing   \n", 20, vec!["trailing", ""]),
+    ];
+    for (text, width, expected) in cases {
+        assert_eq!(
+            layout::wrap(text, &WrapOptions::new(width)),
+            expected,
+            "{text}"
+        );
+    }
+}
+
+#[test]
+fn splits_long_and_hyphenated_words() {
+    // Compound words split after their own hyphens, so nothing is added
+    let lines = layout::wrap("use copy-on-write strings", &WrapOptions::new(12));
+    assert_eq!(lines, ["use copy-on-", "write", "strings"]);
+
+    // A hyphenation hook adds the '-' itself
+    let dictionary = |word: &str| match word {
+        "ownership" => vec![3, 5], // own-er-ship
+        _ => Vec::new(),
+    };
+    let options = WrapOptions {
+        hyphenator: Some(&dictionary),
+        ..WrapOptions::new(10)
+    };
+    let lines = layout::wrap("rust ownership rules", &options);
+    assert_eq!(lines, ["rust own-", "ership", "rules"]);
+    assert!(matches!(lines[0], Cow::Owned(_)));
+    assert!(matches!(lines[1], Cow::Borrowed(_)));
+
+    let long = "a supercalifragilistic b";
+    let options = WrapOptions {
+        hyphenator: None,
+        ..WrapOptions::new(8)
+    };
+    assert_eq!(
+        layout::wrap(long, &options),
+        ["a", "supercal", "ifragili", "stic b"]
+    );
+    let options = WrapOptions {
+        break_words: false,
+        ..options
+    };
+    assert_eq!(
+        layout::wrap(long, &options),
+        ["a", "supercalifragilistic", "b"]
+    );
+}
+
+#[test]
+fn fills_and_justifies() {
+    let text = "short\nlines";
+    assert!(matches!(
+        layout::fill(text, &WrapOptions::new(10)),
+        Cow::Borrowed(_)
+    ));
+    assert_eq!(
+        layout::fill("one two three", &WrapOptions::new(7)),
+        "one two\nthree"
+    );
+
+    let cases = [
+        (
+            "the quick brown fox jumps",
+            11,
+            vec!["the   quick", "brown   fox", "jumps"],
+        ),
+        ("a bb c ddd", 7, vec!["a  bb c", "ddd"]), // The spare space goes to the left gap
+        ("a b c d e f g", 9, vec!["a b c d e", "f g"]), // Already full: left as it is
+        ("one\nparagraph each", 9, vec!["one", "paragraph", "each"]),
+    ];
+    for (text, width, expected) in cases {
+        assert_eq!(
+            layout::justify(text, &WrapOptions::new(width)),
+            expected,
+            "{text}"
+        );
+    }
+}
+
+// --- INDENTATION ---
+#[test]
+fn indents_and_dedents() {
+    let code = "fn main() {\n    body();\n\n}";
+    let indented = layout::indent(code, "    ");
+    assert_eq!(indented, "    fn main() {\n        body();\n\n    }");
+    assert_eq!(layout::dedent(&indented), code);
+    assert!(matches!(layout::indent(code, ""), Cow::Borrowed(_)));
+    assert!(matches!(layout::indent("\n  \n", "> "), Cow::Borrowed(_)));
+
+    assert_eq!(layout::dedent("    a\n      b\n\n    c"), "a\n  b\n\nc");
+    assert_eq!(layout::dedent("  a\n   \n  b"), "a\n\nb"); // Blank lines lose their spaces
+    assert!(matches!(
+        layout::dedent("\tx\n    y"),
+        Cow::Borrowed("\tx\n    y")
+    )); // No common margin
+    assert!(matches!(
+        layout::dedent("   one line"),
+        Cow::Borrowed("one line")
+    ));
+}
+
+// --- TABLES ---
+#[test]
+fn aligns_columns() {
+    let rows = [
+        ["type", "bytes"],
+        ["&str", "16"],
+        ["String", "24"],
+        ["日本", "?"],
+    ];
+    assert_eq!(
+        layout::align_columns(&rows, &[Align::Left, Align::Right], "  "),
+        "type    bytes\n&str       16\nString     24\n日本        ?"
+    );
+    // Rows of different lengths; columns without an alignment are Left
+    let rows = vec![vec!["name", "lang"], vec!["Ferris", "Rust"], vec!["x"]];
+    assert_eq!(
+        layout::align_columns(&rows, &[Align::Center], " | "),
+        " name  | lang\nFerris | Rust\n  x    |"
+    );
+    assert_eq!(layout::align_columns::<[&str; 0]>(&[], &[], " "), "");
+
+    assert_eq!(layout::pad("ab", 5, Align::Center), " ab  ");
+    assert_eq!(layout::pad("🦀", 4, Align::Right), "  🦀");
+    assert!(matches!(
+        layout::pad("abc", 2, Align::Right),
+        Cow::Borrowed("abc")
+    ));
+}
+
+// --- PROPERTIES ---
+proptest! {
+    // Every line fits, unless all it holds after its indentation is one grapheme too wide to fit,
+    // and the words come out in order with nothing lost or added
+    #[test]
+    fn wrapped_lines_fit_and_keep_the_words(text in "[a-z日🦀 \n-]{0,60}", width in 1..20usize) {
+        let lines = layout::wrap(&text, &WrapOptions::new(width));
+        for line in &lines {
+            prop_assert!(
+                display_width(line) <= width || line.trim_start().graphemes(true).count() == 1,
+                "{:?} is wider than {}", line, width
+            );
+        }
+        prop_assert!(lines.len() >= text.split('\n').count());
+        let words: String = lines.concat().split_whitespace().collect();
+        prop_assert_eq!(words, text.split_whitespace().collect::<String>());
+    }
+
+    // Every line of a paragraph but the last is exactly as wide as the width, if it has a gap
+    #[test]
+    fn justified_lines_are_full(text in "[a-z ]{0,80}", width in 4..30usize) {
+        let lines = layout::justify(&text, &WrapOptions::new(width));
+        for line in &lines[..lines.len() - 1] {
+            if line.split_whitespace().count() > 1 {
+                prop_assert_eq!(display_width(line), width, "{:?}", line);
+            }
+        }
+        let words: String = lines.concat().split_whitespace().collect();
+        prop_assert_eq!(words, text.split_whitespace().collect::<String>());
+    }
+
+    #[test]
+    fn dedent_undoes_indent(text in "[ \tab\n]{0,40}", prefix in "[ \t]{1,4}") {
+        let indented = layout::indent(&text, &prefix);
+        prop_assert_eq!(layout::dedent(&indented), layout::dedent(&text));
+    }
+}
@@ -0,0 +1,187 @@
+// The automaton finds exactly what trying every pattern at every byte finds, under each match
+// kind, whether the haystack is a &str or a reader that hands it over in arbitrary pieces.
+use std::io::{self, Read};
+
+use memory_safety::multi_search::{AhoCorasick, Match, MatchKind};
+use proptest::prelude::*;
+
+mod common;
+
+use common::Trickle;
+
+const KINDS: [MatchKind; 3] = [
+    MatchKind::Overlapping,
+    MatchKind::LeftmostFirst,
+    MatchKind::LeftmostLongest,
+];
+
+// (pattern, start, end) for every match, easier to read in assertions than Match structs
+fn found(ac: &AhoCorasick, haystack: &str) -> Vec<(usize, usize, usize)> {
+    ac.find_iter(haystack)
+        .map(|m| (m.pattern, m.start, m.end))
+        .collect()
+}
+
+// What the automaton should find: every pattern tried at every byte, then the match kind's rules
+fn naive(patterns: &[String], haystack: &str, kind: MatchKind) -> Vec<Match> {
+    let hay = haystack.as_bytes();
+    let at = |start: usize| {
+        patterns
+            .iter()
+            .enumerate()
+            .filter(move |(_, p)| !p.is_empty() && hay[start..].starts_with(p.as_bytes()))
+            .map(move |(pattern, p)| Match {
+                pattern,
+                start,
+                end: start + p.len(),
+            })
+    };
+    if kind == MatchKind::Overlapping {
+        let mut all: Vec<Match> = (0..hay.len()).flat_map(at).collect();
+        all.sort_by_key(|m| (m.end, m.start, m.pattern)); // By end, longest first
+        return all;
+    }
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start < hay.len() {
+        let best = at(start).min_by_key(|m| match kind {
+            MatchKind::LeftmostLongest => (usize::MAX - m.end, m.pattern),
+            _ => (0, m.pattern),
+        });
+        match best {
+            Some(m) => {
+                matches.push(m);
+                start = m.end;
+            }
+            None => start += 1,
+        }
+    }
+    matches
+}
+
+fn streamed(ac: &AhoCorasick, reader: impl Read) -> Vec<Match> {
+    ac.stream_find_iter(reader).map(Result::unwrap).collect()
+}
+
+// --- MATCH KINDS ---
+#[test]
+fn match_kinds_pick_different_winners() {
+    let patterns = ["sun", "sunny", "sunday", "days"];
+    let text = "sunny sundays";
+    let first = AhoCorasick::new(patterns, MatchKind::LeftmostFirst);
+    assert_eq!(first.kind(), MatchKind::LeftmostFirst);
+    assert_eq!(found(&first, text), [(0, 0, 3), (0, 6, 9), (3, 9, 13)]);
+    let longest = AhoCorasick::new(patterns, MatchKind::LeftmostLongest);
+    assert_eq!(found(&longest, text), [(1, 0, 5), (2, 6, 12)]);
+    let overlapping = AhoCorasick::new(patterns, MatchKind::Overlapping);
+    assert_eq!(
+        found(&overlapping, text),
+        [(0, 0, 3), (1, 0, 5), (0, 6, 9), (2, 6, 12), (3, 9, 13)]
+    );
+
+    // Priority only breaks ties between matches that start together; the leftmost start wins
+    let first = AhoCorasick::new(["bcd", "abcd", "abc"], MatchKind::LeftmostFirst);
+    assert_eq!(found(&first, "abcd"), [(1, 0, 4)]);
+    let first = AhoCorasick::new(["abc", "abcd"], MatchKind::LeftmostFirst);
+    assert_eq!(found(&first, "abcd"), [(0, 0, 3)]);
+    // Among equally long matches, the earlier listed pattern wins
+    let longest = AhoCorasick::new(["ab", "ab", "a"], MatchKind::LeftmostLongest);
+    assert_eq!(found(&longest, "aab"), [(2, 0, 1), (0, 1, 3)]);
+}
+
+#[test]
+fn finds_non_ascii_patterns() {
+    let patterns = ["🦀", "नमस्ते", "é", "e\u{301}"];
+    let text = "🦀 नमस्ते, café, cafe\u{301} 🦀";
+    for kind in KINDS {
+        let ac = AhoCorasick::new(patterns, kind);
+        let matches: Vec<&str> = ac
+            .find_iter(text)
+            .map(|m| &text[m.start..m.end]) // Always char boundaries, so slicing cannot panic
+            .collect();
+        assert_eq!(matches, ["🦀", "नमस्ते", "é", "e\u{301}", "🦀"]);
+    }
+    // "è" and "ê" start with the same byte as "é", and "स्त" is "स्ते" cut short
+    let ac = AhoCorasick::new(["é", "स्ते"], MatchKind::Overlapping);
+    assert!(!ac.is_match("èê स्त"));
+}
+
+#[test]
+fn empty_patterns_never_match() {
+    for kind in KINDS {
+        let ac = AhoCorasick::new(["", "b", ""], kind);
+        assert_eq!(ac.pattern_count(), 3); // Ids stay the position in the list
+        assert_eq!(found(&ac, "abc"), [(1, 1, 2)]);
+        assert_eq!(found(&ac, ""), []);
+        assert!(!AhoCorasick::new([""], kind).is_match("abc"));
+        assert!(!AhoCorasick::new(Vec::<&str>::new(), kind).is_match("abc"));
+    }
+}
+
+// --- STREAMING ---
+#[test]
+fn stream_matches_straddle_chunk_boundaries() {
+    // The reader fills 64 KiB chunks; put matches across the first two chunk ends
+    const CHUNK: usize = 64 * 1024;
+    let mut text = "x".repeat(3 * CHUNK);
+    text.replace_range(CHUNK - 3..CHUNK + 3, "needle");
+    text.replace_range(2 * CHUNK - 2..2 * CHUNK + 2, "🦀");
+    text.push_str("needl"); // A match cut off by the end of the input is no match
+    let patterns = ["needle", "🦀", "xneedlex"];
+    for kind in KINDS {
+        let ac = AhoCorasick::new(patterns, kind);
+        let expected: Vec<Match> = ac.find_iter(&text).collect();
+        assert!(expected.iter().any(|m| m.start < CHUNK && m.end > CHUNK));
+        assert!(
+            expected
+                .iter()
+                .any(|m| m.start < 2 * CHUNK && m.end > 2 * CHUNK)
+        );
+        assert_eq!(streamed(&ac, text.as_bytes()), expected);
+        let trickle = Trickle::new(text.as_bytes(), vec![1, 7, CHUNK - 1]);
+        assert_eq!(streamed(&ac, trickle), expected);
+    }
+}
+
+#[test]
+fn stream_stops_at_the_first_read_error() {
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk on fire"))
+        }
+    }
+    let ac = AhoCorasick::new(["a"], MatchKind::LeftmostFirst);
+    let mut iter = ac.stream_find_iter(Failing);
+    assert_eq!(
+        iter.next().unwrap().unwrap_err().to_string(),
+        "disk on fire"
+    );
+    assert!(iter.next().is_none());
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "b", "ab", "ba", "aab", "é", "e\u{301}", "🦀", "न", "स्ते", " "];
+
+fn text(max_pieces: usize) -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, max_pieces)
+}
+
+proptest! {
+    #[test]
+    fn agrees_with_naive_search(
+        patterns in prop::collection::vec(text(4), 0..6),
+        haystack in text(24),
+        kind in prop::sample::select(KINDS.to_vec()),
+        sizes in prop::collection::vec(1usize..9, 1..5),
+    ) {
+        let ac = AhoCorasick::new(&patterns, kind);
+        let expected = naive(&patterns, &haystack, kind);
+        let found: Vec<Match> = ac.find_iter(&haystack).collect();
+        prop_assert_eq!(&found, &expected);
+        // Reads of a few bytes put chunk boundaries everywhere, inside characters too
+        let trickle = Trickle::new(haystack.as_bytes(), sizes);
+        prop_assert_eq!(streamed(&ac, trickle), expected);
+    }
+}
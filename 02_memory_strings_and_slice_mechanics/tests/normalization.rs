@@ -0,0 +1,297 @@
+// Conformance tests driven by Unicode's NormalizationTest.txt (a sample in tests/data; the
+// complete file with --ignored), plus case folding, accent stripping and a property test tying
+// normalized_cmp to normalized_eq.
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use memory_safety::normalize::{self, Equivalence, Form};
+use proptest::prelude::*;
+
+mod common;
+
+// A sample in the official format, checked on every run
+const SAMPLE: &str = include_str!("data/NormalizationTest.txt");
+// The complete UCD file (about 19,000 lines) is not in the repo; download it here to run
+// `cargo test --test normalization -- --ignored`
+const COMPLETE: &str = "tests/data/ucd/NormalizationTest.txt";
+
+// One test line: source, NFC, NFD, NFKC, NFKD
+struct Case {
+    line: usize,
+    columns: [String; 5],
+}
+
+// Lines of @Part0, @Part1, ... in order, comments and headers skipped
+fn parts(data: &str) -> Vec<Vec<Case>> {
+    let mut parts: Vec<Vec<Case>> = Vec::new();
+    for (i, line) in data.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.starts_with("@Part") {
+            parts.push(Vec::new());
+        } else if !line.is_empty() {
+            let columns: Vec<String> = line.split(';').take(5).map(decode).collect();
+            let columns = columns.try_into().expect("five columns");
+            let part = parts.last_mut().expect("a line before the first @Part");
+            part.push(Case {
+                line: i + 1,
+                columns,
+            });
+        }
+    }
+    parts
+}
+
+// "0044 0307" → "D\u{307}"
+fn decode(column: &str) -> String {
+    column
+        .split_whitespace()
+        .map(|hex| u32::from_str_radix(hex, 16).expect("hex code point"))
+        .map(|cp| char::from_u32(cp).expect("a Unicode scalar value"))
+        .collect()
+}
+
+// --- CONFORMANCE ---
+fn check_invariants(parts: &[Vec<Case>]) {
+    assert!(
+        parts.iter().any(|part| !part.is_empty()),
+        "no test lines in the data file"
+    );
+    for Case { line, columns } in parts.iter().flatten() {
+        let [c1, c2, c3, c4, c5] = columns;
+        let check = |form: Form, expected: &str, sources: &[&String]| {
+            for source in sources {
+                let normalized = normalize::normalize(source, form);
+                assert_eq!(normalized, expected, "line {line}: {form:?} of {source:?}");
+                // Borrowed exactly when the input was already normalized
+                let borrowed = matches!(normalized, Cow::Borrowed(_));
+                assert_eq!(borrowed, *source == expected, "line {line}: {form:?} Cow");
+            }
+            assert!(
+                normalize::is_normalized(expected, form),
+                "line {line}: {form:?}"
+            );
+        };
+        check(Form::Nfc, c2, &[c1, c2, c3]);
+        check(Form::Nfc, c4, &[c4, c5]);
+        check(Form::Nfd, c3, &[c1, c2, c3]);
+        check(Form::Nfd, c5, &[c4, c5]);
+        check(Form::Nfkc, c4, &[c1, c2, c3, c4, c5]);
+        check(Form::Nfkd, c5, &[c1, c2, c3, c4, c5]);
+    }
+}
+
+// The same lines seen through the comparison API: columns 1-3 are canonically equivalent, and
+// all five are compatibility equivalent
+fn check_equivalence(parts: &[Vec<Case>]) {
+    let canonical = Equivalence::default();
+    let compatible = Equivalence {
+        compatibility: true,
+        ..Equivalence::default()
+    };
+    for Case { line, columns } in parts.iter().flatten() {
+        let [c1, c2, c3, c4, c5] = columns;
+        for other in [c2, c3] {
+            assert!(
+                normalize::normalized_eq(c1, other, canonical),
+                "line {line}"
+            );
+            assert_eq!(
+                normalize::normalized_cmp(c1, other, canonical),
+                Ordering::Equal
+            );
+        }
+        assert_eq!(normalize::comparison_key(c1, canonical), *c2, "line {line}");
+        for other in [c2, c3, c4, c5] {
+            assert!(
+                normalize::normalized_eq(c1, other, compatible),
+                "line {line}"
+            );
+        }
+        assert_eq!(
+            normalize::comparison_key(c1, compatible),
+            *c4,
+            "line {line}"
+        );
+        if c1 != c4 && !normalize::normalized_eq(c1, c4, canonical) {
+            assert_ne!(
+                normalize::normalized_cmp(c1, c4, canonical),
+                Ordering::Equal
+            );
+        }
+    }
+}
+
+// Part 1 lists every character that changes under some form, so every other character must
+// come out of all four forms unchanged. Only meaningful with the complete file.
+fn check_unlisted_unchanged(part1: &[Case]) {
+    let listed: HashSet<char> = part1
+        .iter()
+        .filter_map(|case| case.columns[0].chars().next())
+        .collect();
+    let mut buf = [0; 4];
+    for c in (0..=0x10FFFF).filter_map(char::from_u32) {
+        if listed.contains(&c) {
+            continue;
+        }
+        let s = &*c.encode_utf8(&mut buf);
+        for form in [Form::Nfc, Form::Nfd, Form::Nfkc, Form::Nfkd] {
+            assert_eq!(
+                normalize::normalize(s, form),
+                s,
+                "U+{:04X} {form:?}",
+                c as u32
+            );
+        }
+    }
+}
+
+#[test]
+fn sample_holds_the_conformance_invariants() {
+    let parts = parts(SAMPLE);
+    // Every test line of the sample was parsed, so a truncated or mangled file cannot pass
+    let lines: Vec<usize> = parts.iter().map(Vec::len).collect();
+    assert_eq!(lines, [62, 84], "test lines in each part of the sample");
+    check_invariants(&parts);
+    check_equivalence(&parts);
+}
+
+#[test]
+#[ignore = "needs the complete NormalizationTest.txt in tests/data/ucd"]
+fn complete_data_holds_the_conformance_invariants() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(COMPLETE);
+    let data = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {e}. Download https://www.unicode.org/Public/17.0.0/ucd/NormalizationTest.txt \
+             there to run the complete conformance test",
+            path.display()
+        )
+    });
+    let parts = parts(&data);
+    let part1 = parts.get(1).map_or(0, Vec::len);
+    assert!(
+        part1 > 10_000,
+        "{} has {part1} lines in Part 1, the complete file has over 10,000",
+        path.display()
+    );
+    check_invariants(&parts);
+    check_equivalence(&parts);
+    check_unlisted_unchanged(&parts[1]);
+}
+
+// --- CASE FOLDING AND ACCENTS ---
+#[test]
+fn case_folding_is_full_folding() {
+    assert_eq!(normalize::case_fold("Straße"), "strasse");
+    assert_eq!(normalize::case_fold("ΣΊΣΥΦΟΣ"), "σίσυφοσ"); // Final sigma too
+    assert_eq!(normalize::case_fold("ﬁ"), "fi");
+    assert_eq!(normalize::case_fold("İ"), "i\u{307}"); // Not Turkish-specific: keeps the dot
+    assert!(matches!(
+        normalize::case_fold("already folded"),
+        Cow::Borrowed(_)
+    ));
+}
+
+#[test]
+fn ignore_case_equates_what_folding_does() {
+    let ignore_case = Equivalence {
+        ignore_case: true,
+        ..Equivalence::default()
+    };
+    assert!(normalize::normalized_eq("STRASSE", "straße", ignore_case));
+    assert!(normalize::normalized_eq(
+        "ὈΔΥΣΣΕΎΣ",
+        "ὀδυσσεύς",
+        ignore_case
+    ));
+    // Canonical equivalence still holds: precomposed capital vs decomposed small letter
+    assert!(normalize::normalized_eq(
+        "\u{C9}COLE",
+        "e\u{301}cole",
+        ignore_case
+    ));
+    // U+0345 COMBINING GREEK YPOGEGRAMMENI folds to ι only after decomposition (D145)
+    assert!(normalize::normalized_eq(
+        "\u{1FB3}",
+        "\u{3B1}\u{3B9}",
+        ignore_case
+    ));
+    // Folding keeps fullwidth letters fullwidth: "Ａ" → "ａ", which only compatibility maps to "a"
+    assert!(!normalize::normalized_eq("Ａｂｃ", "ABC", ignore_case));
+    let compatible = Equivalence {
+        compatibility: true,
+        ..ignore_case
+    };
+    assert!(normalize::normalized_eq("Ａｂｃ", "ABC", compatible));
+}
+
+#[test]
+fn accents_are_stripped_but_script_marks_kept() {
+    assert_eq!(normalize::strip_accents("Crème Brûlée"), "Creme Brulee");
+    assert_eq!(normalize::strip_accents("Ångström"), "Angstrom");
+    assert_eq!(normalize::strip_accents("e\u{301}"), "e");
+    assert_eq!(normalize::strip_accents("Việt Nam"), "Viet Nam");
+    // Devanagari vowel signs and virama are not accents
+    assert!(matches!(
+        normalize::strip_accents("नमस्ते"),
+        Cow::Borrowed("नमस्ते")
+    ));
+    assert!(matches!(
+        normalize::strip_accents("plain"),
+        Cow::Borrowed(_)
+    ));
+
+    let loose = Equivalence {
+        ignore_case: true,
+        ignore_accents: true,
+        ..Equivalence::default()
+    };
+    assert!(normalize::normalized_eq("Café", "CAFE", loose));
+    assert!(normalize::normalized_eq("résumé", "Resume", loose));
+    assert!(!normalize::normalized_eq("résumé", "resumes", loose));
+    assert_eq!(normalize::comparison_key("Crème", loose), "creme");
+}
+
+// --- ORDERING ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "a", "A", "e", "E", "\u{E9}", "\u{C9}", "\u{301}", "\u{300}", "\u{323}", "\u{34F}", "ß", "ss",
+    "SS", "ﬁ", "fi", "Σ", "ς", "σ", "가", "\u{1100}", "\u{1161}", "①", "1", "न", "\u{94D}",
+];
+
+fn text() -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, 8)
+}
+
+fn equivalence() -> impl Strategy<Value = Equivalence> {
+    any::<(bool, bool, bool)>().prop_map(|(compatibility, ignore_case, ignore_accents)| {
+        Equivalence {
+            compatibility,
+            ignore_case,
+            ignore_accents,
+        }
+    })
+}
+
+proptest! {
+    // normalized_cmp is a total order whose Equal is normalized_eq, and keys agree with both
+    #[test]
+    fn cmp_eq_and_key_agree(a in text(), b in text(), c in text(), eq in equivalence()) {
+        let ab = normalize::normalized_cmp(&a, &b, eq);
+        prop_assert_eq!(ab == Ordering::Equal, normalize::normalized_eq(&a, &b, eq));
+        prop_assert_eq!(normalize::normalized_cmp(&b, &a, eq), ab.reverse());
+        prop_assert_eq!(
+            normalize::comparison_key(&a, eq) == normalize::comparison_key(&b, eq),
+            ab == Ordering::Equal
+        );
+        let bc = normalize::normalized_cmp(&b, &c, eq);
+        if ab == bc {
+            prop_assert_eq!(normalize::normalized_cmp(&a, &c, eq), ab);
+        }
+        // A string is equivalent to its own key, and to its normal forms
+        prop_assert!(normalize::normalized_eq(&a, &normalize::comparison_key(&a, eq), eq));
+        prop_assert!(normalize::normalized_eq(&a, &normalize::nfd(&a), eq));
+    }
+}
@@ -0,0 +1,385 @@
+// Known answers, patterns that make backtracking engines explode, and two references: escaped
+// literals must find what search::find_all finds, and random patterns must match what a plain
+// backtracking matcher (the textbook definition of leftmost-first) matches, captures included.
+use std::borrow::Cow;
+
+use memory_safety::regex::{self, Regex, RegexError};
+use memory_safety::search;
+use proptest::prelude::*;
+
+mod common;
+
+fn found<'t>(pattern: &str, text: &'t str) -> Vec<&'t str> {
+    let re = Regex::new(pattern).unwrap();
+    re.find_iter(text).map(|m| m.as_str()).collect()
+}
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn finds_patterns() {
+    let text = "Today is a very warm and sunny day.";
+    assert_eq!(found(r"\b\w*ay\b", text), ["Today", "day"]);
+    assert_eq!(found(r"s\w+y", text), ["sunny"]);
+    assert_eq!(found("[aeiou]{2}", "queue see"), ["ue", "ue", "ee"]);
+    assert_eq!(
+        found(r"\d+(?:\.\d+)?", "pi is 3.14, e is 2.7, 42."),
+        ["3.14", "2.7", "42"]
+    );
+    assert_eq!(found("^a|b$", "abab"), ["a", "b"]);
+    assert_eq!(
+        found("colou?r", "color colour colouur"),
+        ["color", "colour"]
+    );
+    assert_eq!(found("a{2,3}", "aaaaaaa"), ["aaa", "aaa"]);
+    assert_eq!(found("<.+>", "<a><b>"), ["<a><b>"]);
+    assert_eq!(found("<.+?>", "<a><b>"), ["<a>", "<b>"]);
+    assert_eq!(found("[^ ]+", "π ≈ 3.14 🦀"), ["π", "≈", "3.14", "🦀"]);
+    assert_eq!(found(".", "a\nb"), ["a", "b"]);
+    assert_eq!(found(r"[\w-]+", "well-known x_y"), ["well-known", "x_y"]);
+    // Leftmost-first, like Perl: the first alternative that leads to a match wins
+    assert_eq!(found("sam|samwise", "samwise"), ["sam"]);
+    assert_eq!(found("samwise|sam", "samwise"), ["samwise"]);
+    // Empty matches: one at every position, but never right where the previous match ended
+    assert_eq!(found("a*", "baaa"), ["", "aaa"]);
+    assert_eq!(found("", "né"), ["", "", ""]);
+
+    let re = Regex::new(r"\d+").unwrap();
+    assert!(re.is_match("abc123") && !re.is_match("abc"));
+    let m = re.find("abc123def").unwrap();
+    assert_eq!((m.start(), m.end(), m.range()), (3, 6, 3..6));
+    assert_eq!(re.as_str(), r"\d+");
+}
+
+#[test]
+fn captures_groups() {
+    let re = Regex::new(r"(\w+)@(\w+)\.(?:com|org)").unwrap();
+    let caps = re.captures("mail bob@example.com now").unwrap();
+    assert_eq!(caps.len(), 3);
+    assert_eq!(caps.get(0).unwrap().as_str(), "bob@example.com");
+    assert_eq!(caps.get(1).unwrap().as_str(), "bob");
+    assert_eq!(caps.get(2).unwrap().range(), 9..16);
+    assert_eq!(caps.get(3), None);
+
+    // A group that takes no part in the match captures nothing
+    let caps = Regex::new("(a)|(b)").unwrap().captures("b").unwrap();
+    assert_eq!(caps.get(1), None);
+    assert_eq!(caps.get(2).unwrap().as_str(), "b");
+    // In a loop, a group keeps its last iteration
+    let caps = Regex::new("(?:(a)|(b))+").unwrap().captures("ab").unwrap();
+    assert_eq!(caps.get(1).unwrap().as_str(), "a");
+    assert_eq!(caps.get(2).unwrap().as_str(), "b");
+
+    // The slices outlive the Regex and the Captures: they borrow from the text
+    let text = String::from("key=value");
+    let value = {
+        let re = Regex::new("=(.*)").unwrap();
+        re.captures(&text)
+            .and_then(|caps| caps.get(1))
+            .unwrap()
+            .as_str()
+    };
+    assert_eq!(value, "value");
+}
+
+#[test]
+fn replaces_matches() {
+    let re = Regex::new(r"(\w+)@(\w+)").unwrap();
+    let text = "bob@home, ann@work";
+    assert_eq!(re.replace_all(text, "$2:$1"), "home:bob, work:ann");
+    assert_eq!(
+        re.replace_all(text, "${1}_$0"),
+        "bob_bob@home, ann_ann@work"
+    );
+    assert_eq!(re.replace_all(text, "$$1 $9 $x"), "$1  $x, $1  $x");
+    // Nothing matched: the input comes back borrowed
+    assert!(matches!(
+        re.replace_all("no mail", "x"),
+        Cow::Borrowed("no mail")
+    ));
+    assert_eq!(Regex::new("x*").unwrap().replace_all("abc", "-"), "-a-b-c-");
+}
+
+#[test]
+fn rejects_malformed_patterns() {
+    let err = |pattern| Regex::new(pattern).unwrap_err();
+    assert_eq!(err("a)"), RegexError::Unexpected { at: 1, c: ')' });
+    assert_eq!(err("(ab"), RegexError::UnclosedGroup { at: 0 });
+    assert_eq!(err("*a"), RegexError::NothingToRepeat { at: 0 });
+    assert_eq!(err("a{3,1}"), RegexError::BadRepetition { at: 1 });
+    assert_eq!(err("a{2"), RegexError::BadRepetition { at: 1 });
+    assert_eq!(err("a{1001}"), RegexError::BadRepetition { at: 1 });
+    assert_eq!(err(r"\q"), RegexError::BadEscape { at: 0 });
+    assert_eq!(err("[z-a]"), RegexError::BadClass { at: 1 });
+    assert_eq!(err("[ab"), RegexError::UnexpectedEnd);
+    assert_eq!(err("(?=a)"), RegexError::Unexpected { at: 2, c: '=' });
+    assert_eq!(err("(?:a{1000}){1000}"), RegexError::TooBig);
+    // Deep nesting is an error, not a stack overflow, for groups of both kinds and stacked repeats
+    let nest = |open: &str, n, close: &str| open.repeat(n) + "a" + &close.repeat(n);
+    let deep = |open, close| Regex::new(&nest(open, 200_000, close)).unwrap_err();
+    assert_eq!(deep("(", ")"), RegexError::TooDeep { at: 32 });
+    assert_eq!(deep("(?:", ")"), RegexError::TooDeep { at: 96 });
+    assert_eq!(deep("", "*"), RegexError::TooDeep { at: 33 });
+    assert_eq!(
+        RegexError::TooDeep { at: 32 }.to_string(),
+        "pattern nests too deeply at byte 32"
+    );
+    assert_eq!(found(&nest("(", 32, ")"), "bab"), ["a"]); // Up to the limit is fine
+    assert_eq!(found(&nest("", 32, "*"), "baa"), ["", "aa"]);
+    // A ']' first in a class and a '-' at either end are literal
+    assert_eq!(found("[]-]+", "a]-]b"), ["]-]"]);
+    assert_eq!(found("[-a]+", "b-a-"), ["-a-"]);
+}
+
+// --- LINEAR TIME ---
+// Each of these takes exponential time in a backtracking engine. Here they take as many steps
+// as the text has chars, times the size of the pattern.
+#[test]
+fn pathological_patterns_run_in_linear_time() {
+    let text = "a".repeat(20_000);
+    assert!(!Regex::new("(a*)*b").unwrap().is_match(&text));
+    assert!(!Regex::new("(a|aa)+b").unwrap().is_match(&text));
+    assert!(!Regex::new("(?:a?){30}a{30}b").unwrap().is_match(&text));
+    let caps = Regex::new("^(a+)+$").unwrap().captures(&text).unwrap();
+    assert_eq!(caps.get(1).unwrap().as_str().len(), 20_000);
+}
+
+// --- AGAINST REFERENCES ---
+#[derive(Debug, Clone)]
+enum Pat {
+    Char(char),
+    Any,
+    Class(Vec<char>, bool), // The chars, and whether the class is negated
+    Start,
+    End,
+    Concat(Vec<Pat>),
+    Alternate(Vec<Pat>),
+    Group(usize, Box<Pat>), // Capture group number; 0 until numbered
+    Repeat(Box<Pat>, u32, Option<u32>, bool),
+}
+
+const ALPHABET: &[char] = &['a', 'b', 'é', '\n'];
+
+fn pat() -> impl Strategy<Value = Pat> {
+    let leaf = prop_oneof![
+        4 => prop::sample::select(&ALPHABET[..3]).prop_map(Pat::Char),
+        1 => Just(Pat::Any),
+        1 => (prop::sample::subsequence(ALPHABET.to_vec(), 1..3), any::<bool>())
+            .prop_map(|(chars, negated)| Pat::Class(chars, negated)),
+        1 => Just(Pat::Start),
+        1 => Just(Pat::End),
+    ];
+    leaf.prop_recursive(4, 16, 3, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 2..4).prop_map(Pat::Concat),
+            prop::collection::vec(inner.clone(), 2..3).prop_map(Pat::Alternate),
+            inner.clone().prop_map(|p| Pat::Group(0, Box::new(p))),
+            (inner, 0..3u32, prop::option::of(0..3u32), any::<bool>()).prop_map(
+                |(p, min, extra, greedy)| Pat::Repeat(
+                    Box::new(p),
+                    min,
+                    extra.map(|e| min + e),
+                    greedy
+                )
+            ),
+        ]
+    })
+}
+
+// Numbers the groups in the order their '(' appears, as the parser does
+fn number(p: &mut Pat, next: &mut usize) {
+    match p {
+        Pat::Group(i, inner) => {
+            *i = *next;
+            *next += 1;
+            number(inner, next);
+        }
+        Pat::Concat(ps) | Pat::Alternate(ps) => ps.iter_mut().for_each(|p| number(p, next)),
+        Pat::Repeat(inner, ..) => number(inner, next),
+        _ => {}
+    }
+}
+
+fn render(p: &Pat) -> String {
+    let escaped = |c: char| {
+        if c == '\n' {
+            r"\n".to_string()
+        } else {
+            c.to_string()
+        }
+    };
+    match p {
+        Pat::Char(c) => escaped(*c),
+        Pat::Any => ".".to_string(),
+        Pat::Class(chars, negated) => {
+            let chars: String = chars.iter().map(|&c| escaped(c)).collect();
+            format!("[{}{chars}]", if *negated { "^" } else { "" })
+        }
+        Pat::Start => "^".to_string(),
+        Pat::End => "$".to_string(),
+        Pat::Concat(ps) => ps.iter().map(|p| format!("(?:{})", render(p))).collect(),
+        Pat::Alternate(ps) => ps.iter().map(render).collect::<Vec<_>>().join("|"),
+        Pat::Group(_, p) => format!("({})", render(p)),
+        Pat::Repeat(p, min, max, greedy) => {
+            let counts = match max {
+                Some(max) => format!("{{{min},{max}}}"),
+                None => format!("{{{min},}}"),
+            };
+            format!(
+                "(?:{}){counts}{}",
+                render(p),
+                if *greedy { "" } else { "?" }
+            )
+        }
+    }
+}
+
+type Slots = Vec<Option<usize>>;
+
+// Tries every way `p` can match at `at`, in priority order, calling `k` with where each one
+// ends until `k` accepts. A loop iteration that matches nothing is rejected, so `(?:a*)*`
+// cannot spin forever.
+fn backtrack(
+    p: &Pat,
+    text: &str,
+    at: usize,
+    slots: &mut Slots,
+    k: &mut dyn FnMut(usize, &mut Slots) -> bool,
+) -> bool {
+    let next = text[at..].chars().next();
+    match p {
+        Pat::Char(c) => next == Some(*c) && k(at + c.len_utf8(), slots),
+        Pat::Any => {
+            next.is_some_and(|c| c != '\n') && k(at + next.map_or(0, char::len_utf8), slots)
+        }
+        Pat::Class(chars, negated) => match next {
+            Some(c) if chars.contains(&c) != *negated => k(at + c.len_utf8(), slots),
+            _ => false,
+        },
+        Pat::Start => at == 0 && k(at, slots),
+        Pat::End => at == text.len() && k(at, slots),
+        Pat::Concat(ps) => concat(ps, text, at, slots, k),
+        Pat::Alternate(ps) => ps.iter().any(|p| backtrack(p, text, at, slots, k)),
+        Pat::Group(i, p) => {
+            let old = (slots[2 * i], slots[2 * i + 1]);
+            slots[2 * i] = Some(at);
+            let mut close = |end, slots: &mut Slots| {
+                let before = slots[2 * i + 1];
+                slots[2 * i + 1] = Some(end);
+                k(end, slots) || {
+                    slots[2 * i + 1] = before;
+                    false
+                }
+            };
+            backtrack(p, text, at, slots, &mut close) || {
+                (slots[2 * i], slots[2 * i + 1]) = old;
+                false
+            }
+        }
+        Pat::Repeat(p, min, max, greedy) => repeat(p, *min, *max, *greedy, text, at, slots, k),
+    }
+}
+
+fn concat(
+    ps: &[Pat],
+    text: &str,
+    at: usize,
+    slots: &mut Slots,
+    k: &mut dyn FnMut(usize, &mut Slots) -> bool,
+) -> bool {
+    match ps.split_first() {
+        None => k(at, slots),
+        Some((first, rest)) => backtrack(first, text, at, slots, &mut |at, slots| {
+            concat(rest, text, at, slots, k)
+        }),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn repeat(
+    p: &Pat,
+    min: u32,
+    max: Option<u32>,
+    greedy: bool,
+    text: &str,
+    at: usize,
+    slots: &mut Slots,
+    k: &mut dyn FnMut(usize, &mut Slots) -> bool,
+) -> bool {
+    if min > 0 {
+        let mut more = |end, slots: &mut Slots| {
+            repeat(p, min - 1, max.map(|m| m - 1), greedy, text, end, slots, k)
+        };
+        return backtrack(p, text, at, slots, &mut more);
+    }
+    if max == Some(0) {
+        return k(at, slots);
+    }
+    // Bounded: each optional copy may match nothing. Unbounded: an iteration must move on.
+    let moved = |end: usize| max.is_some() || end != at;
+    let rest = max.map(|m| m - 1);
+    if greedy {
+        let mut more =
+            |end, slots: &mut Slots| moved(end) && repeat(p, 0, rest, greedy, text, end, slots, k);
+        backtrack(p, text, at, slots, &mut more) || k(at, slots)
+    } else {
+        k(at, slots)
+            || backtrack(p, text, at, slots, &mut |end, slots: &mut Slots| {
+                moved(end) && repeat(p, 0, rest, greedy, text, end, slots, k)
+            })
+    }
+}
+
+// The leftmost-first match and its captures
+fn reference(p: &Pat, groups: usize, text: &str) -> Option<Slots> {
+    let starts = text.char_indices().map(|(i, _)| i).chain([text.len()]);
+    for start in starts {
+        let mut slots = vec![None; 2 * groups];
+        let mut found = None;
+        let mut accept = |end, slots: &mut Slots| {
+            let mut slots = slots.clone();
+            (slots[0], slots[1]) = (Some(start), Some(end));
+            found = Some(slots);
+            true
+        };
+        if backtrack(p, text, start, &mut slots, &mut accept) {
+            return found;
+        }
+    }
+    None
+}
+
+fn text(max: usize) -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(ALPHABET.to_vec()), 0..max)
+        .prop_map(|chars| chars.into_iter().collect())
+}
+
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "b", "ab", ".", "*", "(", "[", "\\", "$", "é", "🦀"];
+
+proptest! {
+    #[test]
+    fn escaped_literals_match_like_search(needle in common::concat_of(PIECES, 3), haystack in common::concat_of(PIECES, 12)) {
+        let re = Regex::new(&regex::escape(&needle)).unwrap();
+        let starts: Vec<usize> = re.find_iter(&haystack).map(|m| m.start()).collect();
+        let expected: Vec<usize> = search::find_all(&haystack, &needle).collect();
+        prop_assert_eq!(starts, expected);
+        for m in re.find_iter(&haystack) {
+            prop_assert_eq!(m.as_str(), needle.as_str());
+        }
+    }
+
+    #[test]
+    fn matches_like_a_backtracking_engine(mut p in pat(), text in text(8)) {
+        let mut groups = 1;
+        number(&mut p, &mut groups);
+        let pattern = render(&p);
+        let re = Regex::new(&pattern).unwrap();
+        let expected = reference(&p, groups, &text);
+        let caps = re.captures(&text);
+        let got = caps.map(|caps| (0..groups).map(|i| caps.get(i).map(|m| m.range())).collect::<Vec<_>>());
+        let expected = expected.map(|slots| {
+            slots.chunks(2).map(|s| Some(s[0]?..s[1]?)).collect::<Vec<_>>()
+        });
+        prop_assert_eq!(got, expected, "pattern {:?}", pattern);
+    }
+}
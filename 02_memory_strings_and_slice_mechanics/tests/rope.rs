@@ -0,0 +1,168 @@
+// Property tests: a Rope and a String get the same edits, and must hold the same text afterwards.
+// Texts run to several KiB so that edits cross chunk boundaries and the tree is rebalanced.
+use memory_safety::rope::Rope;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+mod common;
+
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "a", "Z", " ", "\n", "\r\n", "é", "e\u{301}", "न", "स्ते", "🦀", "👩\u{200d}💻", "日本",
+    "a line\n", "",
+];
+
+fn text(max_pieces: usize) -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, max_pieces)
+}
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Insert(Index, String),
+    Remove(Index, Index),
+    SplitAndAppend(Index), // split_off then append: must give the same text back
+}
+
+fn edit() -> impl Strategy<Value = Edit> {
+    prop_oneof![
+        3 => (any::<Index>(), text(8)).prop_map(|(at, s)| Edit::Insert(at, s)),
+        1 => (any::<Index>(), text(1500)).prop_map(|(at, s)| Edit::Insert(at, s)),
+        3 => (any::<Index>(), any::<Index>()).prop_map(|(a, b)| Edit::Remove(a, b)),
+        1 => any::<Index>().prop_map(Edit::SplitAndAppend),
+    ]
+}
+
+// A char boundary of `s`, picked by `index`
+fn boundary(s: &str, index: &Index) -> usize {
+    let boundaries: Vec<usize> = (0..=s.len()).filter(|&i| s.is_char_boundary(i)).collect();
+    boundaries[index.index(boundaries.len())]
+}
+
+// The lines an editor shows: split after every '\n', plus the empty line after a final '\n'
+fn editor_lines(s: &str) -> Vec<String> {
+    let mut lines: Vec<String> = s.split_inclusive('\n').map(String::from).collect();
+    if s.is_empty() || s.ends_with('\n') {
+        lines.push(String::new());
+    }
+    lines
+}
+
+fn assert_same(rope: &Rope, s: &str) {
+    assert_eq!(rope, s);
+    assert_eq!(rope.len_bytes(), s.len());
+    assert_eq!(rope.len_chars(), s.chars().count());
+    assert_eq!(rope.len_lines(), s.matches('\n').count() + 1);
+    assert_eq!(rope.chunks().collect::<String>(), s);
+    // AVL bound on the height, with every byte as a possible chunk
+    let bound = 1.45 * ((s.len() + 2) as f64).log2() + 1.0;
+    assert!((rope.depth() as f64) <= bound, "depth {}", rope.depth());
+}
+
+// Applies `edit` to both, checking the halves of a split on the way
+fn apply(rope: &mut Rope, s: &mut String, edit: Edit) {
+    match edit {
+        Edit::Insert(at, text) => {
+            let at = boundary(s, &at);
+            rope.insert(at, &text);
+            s.insert_str(at, &text);
+        }
+        Edit::Remove(a, b) => {
+            let (a, b) = (boundary(s, &a), boundary(s, &b));
+            let range = a.min(b)..a.max(b);
+            rope.remove(range.clone());
+            s.replace_range(range, "");
+        }
+        Edit::SplitAndAppend(at) => {
+            let at = boundary(s, &at);
+            let tail = rope.split_off(at);
+            assert_same(rope, &s[..at]);
+            assert_same(&tail, &s[at..]);
+            rope.append(tail);
+        }
+    }
+}
+
+proptest! {
+    // --- EDITS ---
+    #[test]
+    fn edits_match_string(initial in text(3000), edits in prop::collection::vec(edit(), 1..30)) {
+        let mut rope = Rope::from(initial.as_str());
+        let mut s = initial;
+        for edit in edits {
+            apply(&mut rope, &mut s, edit);
+            assert_same(&rope, &s);
+        }
+    }
+
+    // Clones share nodes; editing one must never show through in the other
+    #[test]
+    fn clones_keep_their_text(initial in text(3000), edits in prop::collection::vec(edit(), 1..10)) {
+        let mut rope = Rope::from(initial.as_str());
+        let snapshot = rope.clone();
+        let mut s = initial.clone();
+        for edit in edits {
+            apply(&mut rope, &mut s, edit);
+        }
+        assert_same(&rope, &s);
+        assert_same(&snapshot, &initial);
+    }
+
+    #[test]
+    fn slices_match_str(s in text(3000), a in any::<Index>(), b in any::<Index>()) {
+        let rope = Rope::from(s.as_str());
+        let (a, b) = (boundary(&s, &a), boundary(&s, &b));
+        let (a, b) = (a.min(b), a.max(b));
+        assert_same(&rope.slice(a..b), &s[a..b]);
+    }
+
+    // --- INDEX CONVERSIONS ---
+    #[test]
+    fn byte_conversions_match_str(s in text(3000), at in any::<Index>()) {
+        let rope = Rope::from(s.as_str());
+        let at = at.index(s.len() + 1);
+        let head = s.get(..at);
+        prop_assert_eq!(rope.is_char_boundary(at), head.is_some());
+        prop_assert_eq!(rope.byte_to_char(at), head.map(|h| h.chars().count()));
+        prop_assert_eq!(rope.byte_to_line(at), head.map(|h| h.matches('\n').count()));
+        prop_assert_eq!(rope.byte_to_char(s.len() + 1), None);
+    }
+
+    #[test]
+    fn char_and_line_conversions_match_str(s in text(3000), c in any::<Index>(), l in any::<Index>()) {
+        let rope = Rope::from(s.as_str());
+        let char_starts: Vec<usize> = s.char_indices().map(|(i, _)| i).chain([s.len()]).collect();
+        let char_idx = c.index(char_starts.len());
+        prop_assert_eq!(rope.char_to_byte(char_idx), Some(char_starts[char_idx]));
+        prop_assert_eq!(rope.char_to_byte(char_starts.len()), None);
+
+        let lines = editor_lines(&s);
+        let line = l.index(lines.len());
+        let line_start: usize = lines[..line].iter().map(String::len).sum();
+        prop_assert_eq!(rope.line_to_byte(line), Some(line_start));
+        prop_assert_eq!(rope.line_to_byte(lines.len()), None);
+        prop_assert_eq!(rope.line(line).to_string(), lines[line].clone());
+
+        // (line, column) round trip, and the column limit at the end of the line
+        let (at_line, col) = rope.char_to_line_col(char_idx).unwrap();
+        prop_assert_eq!(rope.line_col_to_char(at_line, col), Some(char_idx));
+        let width = lines[line].trim_end_matches('\n').chars().count();
+        prop_assert!(rope.line_col_to_char(line, width).is_some());
+        prop_assert_eq!(rope.line_col_to_char(line, width + 1), None);
+    }
+
+    // --- ITERATORS ---
+    #[test]
+    fn iterators_match_str(s in text(3000)) {
+        let rope = Rope::from(s.as_str());
+        prop_assert!(rope.chars().eq(s.chars()));
+        prop_assert!(rope.bytes().eq(s.bytes()));
+        let lines: Vec<String> = rope.lines().map(|line| line.to_string()).collect();
+        prop_assert_eq!(lines, editor_lines(&s));
+    }
+}
+
+#[test]
+#[should_panic(expected = "not a char boundary")]
+fn insert_inside_a_char_panics() {
+    Rope::from("🦀").insert(1, "x");
+}
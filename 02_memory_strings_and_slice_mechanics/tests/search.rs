@@ -0,0 +1,146 @@
+// Every search finds what str::find / str::match_indices find, never slices inside a character,
+// and reports the same match consistently in bytes, chars and graphemes.
+use memory_safety::search::{self, Searcher, Unit};
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+mod common;
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn finds_first_match_or_none() {
+    // The sentence main.rs searches; a missing word is None, not an index that looks real
+    let text = "Today is a very warm and sunny day.";
+    let found: Vec<Option<usize>> = ["very", "arm", "say", "sun", "dew"]
+        .iter()
+        .map(|word| search::find(text, word))
+        .collect();
+    assert_eq!(found, [Some(11), Some(17), None, Some(25), None]);
+    // Slicing at every byte used to panic on these
+    assert_eq!(search::find("नमस्ते", "स्ते"), Some(6));
+    assert_eq!(search::find("🦀 crab", "crab"), Some(5));
+    assert_eq!(search::find("🦀", "a"), None);
+    assert_eq!(search::find("abc", ""), Some(0));
+    assert_eq!(search::find("", "a"), None);
+    assert_eq!(search::find("ab", "abc"), None);
+}
+
+#[test]
+fn horspool_skips_and_resumes() {
+    // The last byte of the window decides the skip: "x" is not in the needle, so jump by 4
+    let searcher = Searcher::new("abcd");
+    assert_eq!(searcher.needle(), "abcd");
+    assert_eq!(searcher.find("xxxxxxxabcd"), Some(7));
+    // A last byte that is also inside the needle only moves the window to line them up
+    assert_eq!(Searcher::new("abab").find("aababab"), Some(1));
+    assert_eq!(Searcher::new("aab").find("aaaab"), Some(2));
+    // One searcher, many haystacks and start offsets
+    let searcher = Searcher::new("🦀");
+    assert_eq!(searcher.find_at("🦀 🦀", 0), Some(0));
+    assert_eq!(searcher.find_at("🦀 🦀", 1), Some(5));
+    assert_eq!(searcher.find_at("🦀 🦀", 6), None);
+    // An empty needle matches at the start, as long as the start is in the haystack
+    let empty = Searcher::new("");
+    assert_eq!(empty.find_at("ab", 2), Some(2));
+    assert_eq!(empty.find_at("ab", 3), None);
+}
+
+#[test]
+fn finds_all_and_overlapping() {
+    let all: Vec<usize> = search::find_all("aaaa", "aa").collect();
+    assert_eq!(all, [0, 2]);
+    let overlapping: Vec<usize> = search::find_overlapping("aaaa", "aa").collect();
+    assert_eq!(overlapping, [0, 1, 2]);
+    // An empty needle matches once at every char boundary
+    let empty: Vec<usize> = search::find_all("é🦀", "").collect();
+    assert_eq!(empty, [0, 2, 6]);
+}
+
+#[test]
+fn reports_matches_in_every_unit() {
+    let text = "🦀 नमस्ते 🦀";
+    assert_eq!(search::find_all_in(text, "🦀", Unit::Byte), [0, 24]);
+    assert_eq!(search::find_all_in(text, "🦀", Unit::Char), [0, 9]);
+    assert_eq!(search::find_all_in(text, "🦀", Unit::Grapheme), [0, 6]);
+    assert_eq!(search::find_in(text, "🦀", Unit::Char), Some(0));
+    // A match that cuts a grapheme apart is not a grapheme match
+    assert_eq!(search::find_in("cafe\u{301}", "e", Unit::Char), Some(3));
+    assert_eq!(search::find_in("cafe\u{301}", "e", Unit::Grapheme), None);
+    assert_eq!(
+        search::find_in("cafe\u{301}", "e\u{301}", Unit::Grapheme),
+        Some(3)
+    );
+    assert_eq!(
+        search::find_all_ignore_case("Hauptstraße STRASSE", "strasse"),
+        [5..12, 13..20]
+    );
+    assert_eq!(search::find_ignore_case("ß", "s"), None);
+}
+
+#[test]
+fn converts_byte_offsets() {
+    let text = "🦀 नमस्ते";
+    assert_eq!(search::char_offset(text, 0), Some(0));
+    assert_eq!(search::char_offset(text, 5), Some(2));
+    assert_eq!(search::char_offset(text, text.len()), Some(8));
+    assert_eq!(search::grapheme_offset(text, text.len()), Some(5));
+    // Inside a char or past the end is None, not a panic
+    assert_eq!(search::char_offset(text, 1), None);
+    assert_eq!(search::char_offset(text, text.len() + 1), None);
+    assert_eq!(search::grapheme_offset(text, 7), None);
+    assert_eq!(search::grapheme_offset(text, usize::MAX), None);
+    // Inside a grapheme but on a char boundary counts the part before it
+    assert_eq!(search::grapheme_offset("e\u{301}", 1), Some(1));
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "b", "ab", "ba", "é", "e\u{301}", "\u{301}", "ß", "S", "स्ते", "🦀", " "];
+
+fn text(max_pieces: usize) -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, max_pieces)
+}
+
+proptest! {
+    #[test]
+    fn agrees_with_std(haystack in text(16), needle in text(3)) {
+        prop_assert_eq!(search::find(&haystack, &needle), haystack.find(&needle));
+        let all: Vec<usize> = search::find_all(&haystack, &needle).collect();
+        let expected: Vec<usize> = haystack.match_indices(&needle).map(|(i, _)| i).collect();
+        prop_assert_eq!(all, expected);
+        // Overlapping matches are every boundary where the needle starts
+        let overlapping: Vec<usize> = search::find_overlapping(&haystack, &needle).collect();
+        let expected: Vec<usize> = (0..=haystack.len())
+            .filter(|&i| haystack.get(i..).is_some_and(|rest| rest.starts_with(&needle)))
+            .collect();
+        prop_assert_eq!(overlapping, expected);
+    }
+
+    #[test]
+    fn units_agree(haystack in text(16), needle in text(3)) {
+        let bytes = search::find_all_in(&haystack, &needle, Unit::Byte);
+        let chars: Vec<usize> = bytes
+            .iter()
+            .map(|&b| search::char_offset(&haystack, b).unwrap())
+            .collect();
+        prop_assert_eq!(search::find_all_in(&haystack, &needle, Unit::Char), chars);
+        // Grapheme matches start on a grapheme boundary where the needle starts
+        let boundaries: Vec<usize> = haystack
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .chain([haystack.len()])
+            .collect();
+        for g in search::find_all_in(&haystack, &needle, Unit::Grapheme) {
+            prop_assert!(haystack[boundaries[g]..].starts_with(&needle));
+            prop_assert_eq!(search::grapheme_offset(&haystack, boundaries[g]), Some(g));
+        }
+    }
+
+    #[test]
+    fn offsets_never_panic(text in text(8), byte in 0usize..40) {
+        let chars = search::char_offset(&text, byte);
+        prop_assert_eq!(chars.is_some(), text.is_char_boundary(byte));
+        prop_assert_eq!(chars, text.get(..byte).map(|head| head.chars().count()));
+        prop_assert_eq!(search::grapheme_offset(&text, byte).is_some(), chars.is_some());
+    }
+}
@@ -0,0 +1,118 @@
+// Slicing by bytes, chars or graphemes returns None where `&s[a..b]` would panic, and width-based
+// truncation never goes past the width it was given, whatever mix of wide and zero-width text.
+use std::borrow::Cow;
+use std::ops::Bound;
+
+use memory_safety::slicing;
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+mod common;
+
+// --- SAFE SLICING ---
+#[test]
+fn out_of_range_or_inside_a_char_is_none() {
+    let s = "héllo"; // é is bytes 1..3
+    assert_eq!(slicing::get_byte_range(s, 0..3), Some("hé"));
+    assert_eq!(slicing::get_byte_range(s, ..=0), Some("h"));
+    assert_eq!(slicing::get_byte_range(s, ..), Some(s));
+    assert_eq!(slicing::get_byte_range(s, 0..2), None);
+    assert_eq!(slicing::get_byte_range(s, 2..), None);
+    assert_eq!(slicing::get_byte_range(s, 6..), Some(""));
+    assert_eq!(slicing::get_byte_range(s, 7..), None);
+    let backwards = (Bound::Included(3), Bound::Excluded(1));
+    assert_eq!(slicing::get_byte_range(s, backwards), None);
+    assert_eq!(slicing::get_byte_range(s, ..=usize::MAX), None);
+    let after_max = (Bound::Excluded(usize::MAX), Bound::Unbounded);
+    assert_eq!(slicing::get_byte_range(s, after_max), None);
+}
+
+#[test]
+fn slices_chars_and_graphemes() {
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", 1..2), Some("🦀"));
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", 3..), Some(""));
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", 3..3), Some(""));
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", 4..), None);
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", 0..4), None);
+    let backwards = (Bound::Included(2), Bound::Excluded(1));
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", backwards), None);
+    assert_eq!(slicing::slice_chars("🦀🦀🦀", ..=usize::MAX), None);
+    // The accent is a char of its own, but not a grapheme of its own
+    assert_eq!(slicing::slice_chars("e\u{301}x", 1..), Some("\u{301}x"));
+    assert_eq!(slicing::slice_graphemes("e\u{301}x", ..1), Some("e\u{301}"));
+    assert_eq!(slicing::slice_graphemes("नमस्ते", 2..3), Some("स्ते"));
+    assert_eq!(slicing::slice_graphemes("नमस्ते", 2..=3), None);
+    assert_eq!(slicing::truncate_graphemes("नमस्ते", 2), "नम");
+    assert_eq!(slicing::truncate_graphemes("नमस्ते", 9), "नमस्ते");
+}
+
+// --- DISPLAY WIDTH ---
+#[test]
+fn measures_terminal_columns() {
+    assert_eq!(slicing::display_width("日本語"), 6);
+    assert_eq!(slicing::display_width("한국어 text"), 11);
+    assert_eq!(slicing::display_width("ｆｕｌｌ"), 8); // Fullwidth Latin
+    assert_eq!(slicing::display_width("e\u{301}"), 1);
+    assert_eq!(slicing::display_width("a\u{200B}b"), 2);
+    assert_eq!(slicing::display_width("👨‍👩‍👧🇯🇵"), 4);
+    // A wide character that does not fit is left out whole
+    assert_eq!(slicing::truncate_width("日本語", 5), "日本");
+    assert_eq!(slicing::truncate_width("日本語", 1), "");
+    assert_eq!(slicing::truncate_width("a日", 2), "a");
+}
+
+#[test]
+fn ellipsis_never_exceeds_the_width() {
+    assert_eq!(
+        slicing::truncate_with_ellipsis("short", 5, "…"),
+        Cow::Borrowed("short")
+    );
+    assert_eq!(
+        slicing::truncate_with_ellipsis("日本語テキスト", 5, "…"),
+        "日本…"
+    );
+    // Dropping 語 frees two columns, but only one was needed for the ellipsis
+    assert_eq!(slicing::truncate_with_ellipsis("日本語", 4, "…"), "日…");
+    // Narrower than the ellipsis: the ellipsis itself is cut, and nothing of the text is left
+    assert_eq!(slicing::truncate_with_ellipsis("abcdef", 2, "..."), "..");
+    assert_eq!(slicing::truncate_with_ellipsis("abcdef", 0, "..."), "");
+    assert_eq!(slicing::truncate_with_ellipsis("abcdef", 3, "。。"), "a。");
+    assert_eq!(slicing::truncate_with_ellipsis("日本語", 1, "。"), "");
+    assert_eq!(slicing::truncate_with_ellipsis("abc", 2, ""), "ab");
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "é", "e\u{301}", "日", "한", "🦀", "👨‍👩‍👧", "🇯🇵", "\u{200B}", "स्ते", " "];
+
+proptest! {
+    #[test]
+    fn slices_agree_with_std(s in common::concat_of(PIECES, 10), a in 0usize..40, b in 0usize..40) {
+        prop_assert_eq!(slicing::get_byte_range(&s, a..b), s.get(a..b));
+        let chars: Vec<char> = s.chars().collect();
+        let expected = (a <= b && b <= chars.len()).then(|| chars[a..b].iter().collect::<String>());
+        prop_assert_eq!(slicing::slice_chars(&s, a..b).map(String::from), expected);
+        let graphemes: Vec<&str> = s.graphemes(true).collect();
+        let expected = (a <= b && b <= graphemes.len()).then(|| graphemes[a..b].concat());
+        prop_assert_eq!(slicing::slice_graphemes(&s, a..b).map(String::from), expected);
+    }
+
+    #[test]
+    fn truncation_fits(s in common::concat_of(PIECES, 10), max_width in 0usize..12, ellipsis in prop::sample::select(vec!["…", "...", "。", ""])) {
+        let cut = slicing::truncate_width(&s, max_width);
+        prop_assert!(s.starts_with(cut) && slicing::display_width(cut) <= max_width);
+        let marked = slicing::truncate_with_ellipsis(&s, max_width, ellipsis);
+        prop_assert!(slicing::display_width(&marked) <= max_width);
+        match marked {
+            Cow::Borrowed(kept) => prop_assert_eq!(kept, s.as_str()),
+            Cow::Owned(marked) => {
+                // Whole graphemes of the text, then as much of the ellipsis as fits
+                prop_assert!(slicing::display_width(&s) > max_width);
+                let shown = slicing::truncate_width(ellipsis, max_width);
+                let kept = marked.strip_suffix(shown).unwrap();
+                prop_assert!(s.starts_with(kept));
+                prop_assert_eq!(kept, slicing::truncate_width(&s, max_width - slicing::display_width(shown)));
+            }
+        }
+    }
+}
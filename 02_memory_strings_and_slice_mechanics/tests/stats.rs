@@ -0,0 +1,240 @@
+// Reading a stream gives the same stats as counting the whole text at once, however the reads
+// and the 1 MiB pieces cut it: inside a character, a grapheme, a "\r\n" or a word. A line with
+// nowhere to cut is still read in bounded memory.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use memory_safety::stats::{LongestLine, TextStats};
+use proptest::prelude::*;
+
+mod common;
+
+use common::Trickle;
+
+const PIECE: usize = 1 << 20;
+
+// --- MEMORY MEASURING ---
+// Live heap bytes per thread, and the most there have been, because the test harness runs tests
+// on several threads at once
+struct Measuring;
+
+thread_local! {
+    static LIVE: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+fn grow(by: usize) {
+    let _ = LIVE.try_with(|live| {
+        live.set(live.get() + by);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(live.get())));
+    });
+}
+
+fn shrink(by: usize) {
+    // Memory allocated on another thread and freed on this one could take it below zero
+    let _ = LIVE.try_with(|live| live.set(live.get().saturating_sub(by)));
+}
+
+unsafe impl GlobalAlloc for Measuring {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        grow(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        shrink(layout.size());
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        grow(new_size.saturating_sub(layout.size()));
+        shrink(layout.size().saturating_sub(new_size));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Measuring = Measuring;
+
+// The result of `f` and the most heap it had allocated at once, beyond what was live before
+fn peak_during<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(before));
+    let result = f();
+    (result, PEAK.with(Cell::get) - before)
+}
+
+// Everything TextStats exposes except the byte count, which lossy decoding changes
+type Summary<'a> = (
+    u64,
+    u64,
+    u64,
+    u64,
+    Option<LongestLine>,
+    Vec<(&'a str, u64)>,
+    Vec<(&'static str, u64)>,
+);
+
+fn summary(stats: &TextStats) -> Summary<'_> {
+    (
+        stats.chars,
+        stats.graphemes,
+        stats.words,
+        stats.lines,
+        stats.longest_line,
+        stats.top_words(usize::MAX),
+        stats.scripts(),
+    )
+}
+
+// Streams `text` through reads of the given sizes and checks it against from_text
+fn assert_streams_like_text(text: &str, sizes: Vec<usize>) {
+    let whole = TextStats::from_text(text);
+    let streamed = TextStats::from_reader(Trickle::new(text.as_bytes(), sizes)).unwrap();
+    assert_eq!(streamed.bytes, whole.bytes);
+    assert_eq!(summary(&streamed), summary(&whole));
+}
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn counts_one_text() {
+    let stats = TextStats::from_text("Hello, world!\r\nनमस्ते दुनिया\nhello 日本語 e\u{301}");
+    assert_eq!(stats.lines, 3);
+    assert_eq!(stats.words, 9); // One word per ideograph
+    assert_eq!(stats.chars, 41);
+    assert_eq!(stats.graphemes, 33);
+    assert_eq!(
+        stats.longest_line,
+        Some(LongestLine {
+            number: 3,
+            width: 14
+        })
+    );
+    assert_eq!(
+        stats.top_words(3),
+        [("hello", 2), ("e\u{301}", 1), ("world", 1)]
+    );
+    assert_eq!(
+        stats.scripts(),
+        [
+            ("Latin", 16),
+            ("Devanagari", 12),
+            ("Han", 3),
+            ("Common", 2),
+            ("Inherited", 1)
+        ]
+    );
+    let empty = TextStats::from_text("");
+    assert_eq!((empty.lines, empty.longest_line), (0, None));
+}
+
+#[test]
+fn invalid_utf8_counts_as_replacement_chars() {
+    let bytes = b"caf\xC3 ok\xFF\xFE\n\xE6\x97";
+    let stats = TextStats::from_reader(&bytes[..]).unwrap();
+    assert_eq!(stats.bytes, bytes.len() as u64);
+    // One U+FFFD per bad sequence: "\xC3", "\xFF", "\xFE" and the cut-off "\xE6\x97"
+    assert_eq!(stats.chars, 3 + 1 + 3 + 2 + 1 + 1);
+    assert_eq!(stats.lines, 2);
+    assert_eq!(stats.top_words(5), [("caf", 1), ("ok", 1)]);
+    let lossy = String::from_utf8_lossy(bytes);
+    assert_eq!(summary(&stats), summary(&TextStats::from_text(&lossy)));
+}
+
+// --- STREAMING ---
+#[test]
+fn reads_that_split_characters() {
+    let text = "🦀 नमस्ते\r\nwörld e\u{301} 日本語\n\nend";
+    for size in 1..8 {
+        assert_streams_like_text(text, vec![size]);
+    }
+    assert_streams_like_text(text, vec![3, 1, 4, 1, 5, 9, 2, 6]);
+}
+
+#[test]
+fn batches_of_short_lines() {
+    // Far more than one 64 KiB batch, with reads that never line up with the batches
+    let text = "héllo wörld 🦀\r\n".repeat(10_000);
+    assert_streams_like_text(&text, vec![4_099]);
+}
+
+#[test]
+fn long_lines_cut_into_pieces() {
+    // Spaces followed by ASCII: cut at a space inside each 1 MiB piece
+    let spaced = "日本 a\u{301} 🦀 ".repeat(PIECE / 7);
+    assert!(spaced.len() > 2 * PIECE);
+    assert_streams_like_text(&format!("{spaced}\nshort\n"), vec![65_537]);
+    // Spaces only before a combining mark or a non-ASCII char: no safe cut, held whole
+    let unsafe_cuts = " \u{301}日".repeat(PIECE / 5);
+    assert!(unsafe_cuts.len() > PIECE);
+    assert_streams_like_text(&unsafe_cuts, vec![65_536]);
+    // No spaces at all, with a piece boundary inside a crab
+    let crabs = format!("x{}", "🦀".repeat(PIECE / 4 + 1));
+    assert!(!crabs.is_char_boundary(PIECE));
+    assert_streams_like_text(&crabs, vec![PIECE]);
+}
+
+#[test]
+fn lines_without_breaks_use_bounded_memory() {
+    // No space or newline anywhere, but a word boundary at every ideograph: cut before the last
+    // word, so every count is still exact
+    let ideographs = format!("x{}", "日本語".repeat(4 * PIECE / 9));
+    let trickle = Trickle::new(ideographs.as_bytes(), vec![65_536]);
+    let (streamed, peak) = peak_during(|| TextStats::from_reader(trickle));
+    assert!(peak < 2 * PIECE, "{peak} bytes for a 4 MiB line");
+    let streamed = streamed.unwrap();
+    let whole = TextStats::from_text(&ideographs);
+    assert_eq!(streamed.bytes, whole.bytes);
+    assert_eq!(summary(&streamed), summary(&whole));
+
+    // One 4 MiB word: cut before its last grapheme, so it counts as one word per piece
+    let word = "a\u{301}".repeat(4 * PIECE / 3);
+    let trickle = Trickle::new(word.as_bytes(), vec![65_536, 3]);
+    let (streamed, peak) = peak_during(|| TextStats::from_reader(trickle));
+    // The buffer, and the piece-long word the word counts keep
+    assert!(peak < 3 * PIECE, "{peak} bytes for a 4 MiB word");
+    let streamed = streamed.unwrap();
+    let whole = TextStats::from_text(&word);
+    assert_eq!(
+        (
+            streamed.bytes,
+            streamed.chars,
+            streamed.graphemes,
+            streamed.lines
+        ),
+        (whole.bytes, whole.chars, whole.graphemes, whole.lines)
+    );
+    assert_eq!(streamed.longest_line, whole.longest_line);
+    assert_eq!((whole.words, streamed.words), (1, 5));
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "a", "Word", " ", "\t", "\n", "\r\n", "\r", "é", "e\u{301}", "\u{301}", "日本", "🦀", "👨‍👩‍👧", "नमस्ते", "3.14", "don't",
+];
+
+const BAD_BYTES: &[&[u8]] = &[b"\xFF", b"\xC3", b"\xE6\x97", b"\xED\xA0\x80"];
+
+proptest! {
+    #[test]
+    fn any_reads_give_the_same_stats(text in common::concat_of(PIECES, 40), sizes in prop::collection::vec(1usize..12, 1..6)) {
+        let whole = TextStats::from_text(&text);
+        let streamed = TextStats::from_reader(Trickle::new(text.as_bytes(), sizes)).unwrap();
+        prop_assert_eq!(streamed.bytes, whole.bytes);
+        prop_assert_eq!(summary(&streamed), summary(&whole));
+    }
+
+    #[test]
+    fn invalid_bytes_stream_like_lossy_text(parts in prop::collection::vec(prop_oneof![
+        prop::sample::select(PIECES).prop_map(|p| p.as_bytes().to_vec()),
+        prop::sample::select(BAD_BYTES).prop_map(<[u8]>::to_vec),
+    ], 0..30), sizes in prop::collection::vec(1usize..12, 1..6)) {
+        let bytes = parts.concat();
+        let lossy = String::from_utf8_lossy(&bytes);
+        let whole = TextStats::from_text(&lossy);
+        let streamed = TextStats::from_reader(Trickle::new(&bytes, sizes)).unwrap();
+        prop_assert_eq!(streamed.bytes, bytes.len() as u64);
+        prop_assert_eq!(summary(&streamed), summary(&whole));
+    }
+}
@@ -0,0 +1,214 @@
+// Every query returns what checking every subarray returns, ties included, and reports overflow
+// as an error instead of wrapping or panicking.
+use std::ops::Range;
+
+use memory_safety::subarray::{self, SubarrayError};
+use proptest::prelude::*;
+
+// Every non-empty range of nums, by end, then start, with its exact sum
+fn ranges(nums: &[i64]) -> Vec<(Range<usize>, i128)> {
+    (1..=nums.len())
+        .flat_map(|end| (0..end).map(move |start| start..end))
+        .map(|range| {
+            let sum = nums[range.clone()].iter().map(|&n| n as i128).sum();
+            (range, sum)
+        })
+        .collect()
+}
+
+fn widen(nums: &[u8]) -> Vec<i64> {
+    nums.iter().map(|&n| n as i64).collect()
+}
+
+// --- PREFIX SUMS ---
+#[test]
+fn handles_negative_numbers_and_zero_targets() {
+    let nums = [3, -2, 5, -1, 2, -4, 6, 1];
+    assert_eq!(subarray::longest_with_sum(&nums, 6), Ok(Some(1..7)));
+    assert_eq!(subarray::shortest_with_sum(&nums, 6), Ok(Some(6..7)));
+    assert_eq!(subarray::count_with_sum(&nums, 6), Ok(4));
+    // Zero: sums that cancel out, and single zeros
+    let nums = [1, -1, 0, 2, -2];
+    assert_eq!(subarray::longest_with_sum(&nums, 0), Ok(Some(0..5)));
+    assert_eq!(subarray::shortest_with_sum(&nums, 0), Ok(Some(2..3)));
+    assert_eq!(
+        subarray::all_with_sum(&nums, 0),
+        Ok(vec![0..2, 0..3, 2..3, 0..5, 2..5, 3..5])
+    );
+    assert_eq!(subarray::count_with_sum(&[0; 4], 0), Ok(10)); // Every one of the 4·5/2 ranges
+    assert_eq!(subarray::longest_with_sum(&[-3, -4], -7), Ok(Some(0..2)));
+    assert_eq!(subarray::longest_with_sum::<i32>(&[], 0), Ok(None));
+    assert_eq!(subarray::shortest_with_sum(&[1, 2], 0), Ok(None)); // The empty range does not count
+}
+
+#[test]
+fn distinguishes_found_missing_and_overflow() {
+    // What main.rs's find_subarray passes on: Ok(Some) to slice with, Ok(None), or the error
+    let nums = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+    let Ok(Some(range)) = subarray::longest_with_sum(&nums, 18) else {
+        panic!("no subarray with sum 18");
+    };
+    assert_eq!(&nums[range], [3, 4, 5, 6]);
+    assert_eq!(subarray::longest_with_sum(&nums, 100), Ok(None));
+    assert_eq!(
+        subarray::longest_with_sum(&[i32::MAX, 1, -5], -5),
+        Err(SubarrayError::Overflow { index: 1 })
+    );
+    assert_eq!(
+        SubarrayError::Overflow { index: 1 }.to_string(),
+        "running sum overflowed at index 1"
+    );
+}
+
+#[test]
+fn prefix_sums_near_the_limits() {
+    // The running sum reaches i64::MAX and comes back down without overflowing
+    let nums = [i64::MAX - 1, 1, -1, 1];
+    assert_eq!(subarray::longest_with_sum(&nums, i64::MAX), Ok(Some(0..4)));
+    assert_eq!(subarray::count_with_sum(&nums, 1), Ok(3));
+    // prefix - target does not fit in i64, so nothing can match, and nothing panics
+    assert_eq!(subarray::longest_with_sum(&[i64::MAX], i64::MIN), Ok(None));
+    assert_eq!(subarray::count_with_sum(&[i64::MAX], i64::MIN), Ok(0));
+    assert_eq!(
+        subarray::shortest_with_sum(&[i64::MIN], i64::MIN),
+        Ok(Some(0..1))
+    );
+    for result in [
+        subarray::all_with_sum(&[i64::MIN, -1], 0).map(|all| all.len()),
+        subarray::count_with_sum(&[i64::MIN, -1], 0),
+        subarray::shortest_with_sum(&[i64::MIN, -1], 0).map(|_| 0),
+    ] {
+        assert_eq!(result, Err(SubarrayError::Overflow { index: 1 }));
+    }
+}
+
+// --- SLIDING WINDOWS ---
+#[test]
+fn windows_wait_out_an_overflowing_sum() {
+    // 100 + 200 does not fit in a u8; the 200 waits until the 100 has left the window
+    assert_eq!(
+        subarray::window_with_sum(&[100u8, 200, 50], 250),
+        Ok(Some(1..3))
+    );
+    assert_eq!(
+        subarray::longest_window_at_most(&[1u8, 1, 254, 2], 255),
+        Ok(Some(0..2))
+    );
+    assert_eq!(
+        subarray::longest_window_at_most(&[255u8, 255, 1, 1], 255),
+        Ok(Some(2..4))
+    );
+    // A sum above u8::MAX reaches every bound
+    assert_eq!(
+        subarray::shortest_window_at_least(&[250u8, 10, 250], 255),
+        Ok(Some(0..2))
+    );
+    assert_eq!(
+        subarray::shortest_window_at_least(&[200u8; 3], 255),
+        Ok(Some(0..2))
+    );
+    assert_eq!(subarray::longest_window_at_most(&[200u8; 3], 100), Ok(None));
+}
+
+#[test]
+fn windows_reject_negative_numbers() {
+    let nums = [2, 0, -1, 3];
+    assert_eq!(
+        subarray::window_with_sum(&nums, 5),
+        Err(SubarrayError::Negative { index: 2 })
+    );
+    assert_eq!(
+        subarray::longest_window_at_most(&nums, 5),
+        Err(SubarrayError::Negative { index: 2 })
+    );
+    // Found before reaching the negative number, so no error
+    assert_eq!(subarray::window_with_sum(&nums, 2), Ok(Some(0..1)));
+    // Floats work too
+    assert_eq!(
+        subarray::shortest_window_at_least(&[0.5, 1.5, 2.0, 0.25], 2.0),
+        Ok(Some(2..3))
+    );
+}
+
+// --- KADANE ---
+#[test]
+fn max_subarray_ties_and_limits() {
+    assert_eq!(
+        subarray::max_subarray(&[3, -2, 5, -1, 2, -4, 6, 1]),
+        Ok(Some((0..8, 10)))
+    );
+    // Ties go to the range that ends first, then to the shortest one
+    assert_eq!(subarray::max_subarray(&[2, -2, 2]), Ok(Some((0..1, 2))));
+    assert_eq!(subarray::max_subarray(&[0, 3, 0]), Ok(Some((1..2, 3))));
+    assert_eq!(subarray::max_subarray(&[-3, -1, -1]), Ok(Some((1..2, -1))));
+    assert_eq!(subarray::max_subarray::<i32>(&[]), Ok(None));
+    assert_eq!(
+        subarray::max_subarray(&[i64::MIN]),
+        Ok(Some((0..1, i64::MIN)))
+    );
+    assert_eq!(
+        subarray::max_subarray(&[i64::MAX, 1]),
+        Err(SubarrayError::Overflow { index: 1 })
+    );
+    // A run that is not positive is dropped, not added to
+    assert_eq!(
+        subarray::max_subarray(&[i64::MIN, i64::MIN]),
+        Ok(Some((0..1, i64::MIN)))
+    );
+}
+
+// --- PROPERTIES ---
+proptest! {
+    #[test]
+    fn prefix_sums_match_brute_force(nums in prop::collection::vec(-4i64..5, 0..12), target in -6i64..7) {
+        let all = ranges(&nums);
+        let hits: Vec<Range<usize>> = all
+            .iter()
+            .filter(|(_, sum)| *sum == target as i128)
+            .map(|(range, _)| range.clone())
+            .collect();
+        prop_assert_eq!(subarray::all_with_sum(&nums, target), Ok(hits.clone()));
+        prop_assert_eq!(subarray::count_with_sum(&nums, target), Ok(hits.len()));
+        let longest = hits.iter().min_by_key(|r| (usize::MAX - r.len(), r.start)).cloned();
+        prop_assert_eq!(subarray::longest_with_sum(&nums, target), Ok(longest));
+        let shortest = hits.iter().min_by_key(|r| (r.len(), r.start)).cloned();
+        prop_assert_eq!(subarray::shortest_with_sum(&nums, target), Ok(shortest));
+    }
+
+    #[test]
+    fn windows_match_brute_force(nums in prop::collection::vec(any::<u8>(), 0..10), bound: u8) {
+        // u8 sums overflow all the time, so the pending branch runs in most cases
+        let all = ranges(&widen(&nums));
+        let bound_wide = bound as i128;
+        let exact = all.iter().find(|(_, sum)| *sum == bound_wide).map(|(r, _)| r.clone());
+        prop_assert_eq!(subarray::window_with_sum(&nums, bound), Ok(exact));
+        let mut at_most: Option<Range<usize>> = None;
+        let mut at_least: Option<Range<usize>> = None;
+        for (range, sum) in &all {
+            if *sum <= bound_wide && at_most.as_ref().is_none_or(|b| range.len() > b.len()) {
+                at_most = Some(range.clone());
+            }
+            if *sum >= bound_wide && at_least.as_ref().is_none_or(|b| range.len() < b.len()) {
+                at_least = Some(range.clone());
+            }
+        }
+        prop_assert_eq!(subarray::longest_window_at_most(&nums, bound), Ok(at_most));
+        prop_assert_eq!(subarray::shortest_window_at_least(&nums, bound), Ok(at_least));
+    }
+
+    #[test]
+    fn kadane_matches_brute_force(nums in prop::collection::vec(-5i64..6, 0..12)) {
+        let all = ranges(&nums);
+        let best = all.iter().map(|(_, sum)| *sum).max();
+        let expected = best.map(|best| {
+            // The range Kadane settles on: first to end, then shortest
+            let (range, _) = all
+                .iter()
+                .filter(|(_, sum)| *sum == best)
+                .min_by_key(|(r, _)| (r.end, usize::MAX - r.start))
+                .unwrap();
+            (range.clone(), best as i64)
+        });
+        prop_assert_eq!(subarray::max_subarray(&nums), Ok(expected));
+    }
+}
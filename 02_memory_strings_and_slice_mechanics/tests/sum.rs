@@ -0,0 +1,124 @@
+// Integer sums are exact or None, never wrapped, whatever the lane and block layout; compensated
+// float sums stay within their error bounds where plain `+` drifts.
+use memory_safety::sum;
+use proptest::prelude::*;
+
+// --- INTEGERS ---
+#[test]
+fn checked_and_saturating() {
+    let big = [i32::MAX, 1, -2];
+    assert_eq!(sum::checked_sum(&big), None); // Gives up at the first overflow
+    assert_eq!(sum::saturating_sum(&big), i32::MAX - 2);
+    assert_eq!(sum::checked_sum::<u8>(&[]), Some(0));
+    assert_eq!(sum::saturating_sum(&[-100i8, -100, 50]), -78);
+    assert_eq!(sum::checked_sum(&[f64::MAX, f64::MAX]), None);
+    assert_eq!(sum::saturating_sum(&[f64::MAX, f64::MAX]), f64::MAX);
+}
+
+#[test]
+fn widening_picks_the_accumulator() {
+    assert_eq!(
+        sum::widening_sum::<i32, i64>(&[i32::MAX; 3]),
+        Some(3 * i32::MAX as i64)
+    );
+    assert_eq!(
+        sum::widening_sum::<i64, i128>(&[i64::MIN; 4]),
+        Some(4 * i64::MIN as i128)
+    );
+    // Too narrow an accumulator still overflows, and says so
+    assert_eq!(sum::widening_sum::<u8, u16>(&[255; 257]), Some(65_535));
+    assert_eq!(sum::widening_sum::<u8, u16>(&[255; 258]), None);
+    assert_eq!(
+        sum::widening_sum::<f32, f64>(&[f32::MAX, f32::MAX]),
+        Some(2.0 * f32::MAX as f64)
+    );
+}
+
+#[test]
+fn chunked_sees_the_true_total() {
+    // An overflow on the way that cancels out later is fine
+    assert_eq!(sum::chunked_sum(&[i32::MAX, 1, -2]), Some(i32::MAX - 1));
+    assert_eq!(sum::chunked_sum(&[127i8, 127, -128, -126]), Some(0));
+    assert_eq!(
+        sum::chunked_sum(&[i64::MAX, i64::MAX, i64::MIN, i64::MIN]),
+        Some(-2)
+    );
+    assert_eq!(sum::chunked_sum(&[u64::MAX, 1]), None);
+    assert_eq!(sum::chunked_sum::<u8>(&[]), Some(0));
+    // Several blocks of 65536, each with a remainder that does not fill the 8 lanes
+    let many = vec![u32::MAX; 3 * 65_536 + 5];
+    assert_eq!(sum::chunked_sum(&many), None);
+    let alternating: Vec<i32> = (0..2 * 65_536 + 3)
+        .map(|i| if i % 2 == 0 { i32::MAX } else { i32::MIN })
+        .collect();
+    assert_eq!(sum::chunked_sum(&alternating), Some(i32::MAX - 65_537));
+    assert_eq!(sum::chunked_sum(&[f64::MAX, f64::MAX]), None);
+}
+
+// --- FLOATS ---
+#[test]
+fn compensated_sums_do_not_drift() {
+    let tenths = vec![0.1f64; 1_000_000];
+    let plain: f64 = tenths.iter().sum();
+    assert_eq!(plain, 100_000.00000133288);
+    assert_eq!(sum::kahan_sum(&tenths), 100_000.0);
+    assert_eq!(sum::neumaier_sum(&tenths), 100_000.0);
+
+    let mixed = [1.0, 1e100, 1.0, -1e100];
+    assert_eq!(sum::kahan_sum(&mixed), 0.0);
+    assert_eq!(sum::neumaier_sum(&mixed), 2.0);
+    assert_eq!(sum::kahan_sum::<f32>(&[]), 0.0);
+
+    // f32 lanes are f64, so a million f32 tenths come out far closer than adding in f32
+    let tenths = vec![0.1f32; 1_000_000];
+    let plain: f32 = tenths.iter().sum();
+    assert!((plain - 100_000.0).abs() > 100.0, "{plain}");
+    let chunked = sum::chunked_sum(&tenths).unwrap();
+    assert!((chunked - 100_000.0).abs() < 0.01, "{chunked}");
+    assert!((sum::kahan_sum(&tenths) - 100_000.0).abs() < 0.01);
+}
+
+// --- PROPERTIES ---
+// m · 2^e for the float tests: sums of these are exact in i128 units of 2^-20
+fn dyadic() -> impl Strategy<Value = (i32, i32)> {
+    (any::<i32>(), -20..20)
+}
+
+proptest! {
+    #[test]
+    fn integer_modes_agree(nums in prop::collection::vec(any::<i32>(), 0..200)) {
+        let exact: i64 = nums.iter().map(|&n| n as i64).sum();
+        let fits = i32::try_from(exact).ok();
+        prop_assert_eq!(sum::chunked_sum(&nums), fits);
+        prop_assert_eq!(sum::widening_sum::<i32, i64>(&nums), Some(exact));
+        // checked_sum may give up earlier, but never returns a wrong total
+        if let Some(total) = sum::checked_sum(&nums) {
+            prop_assert_eq!(Some(total), fits);
+        }
+        let saturated = sum::saturating_sum(&nums);
+        if nums.iter().all(|&n| n >= 0) {
+            prop_assert_eq!(saturated as i64, exact.min(i32::MAX as i64));
+        }
+    }
+
+    #[test]
+    fn small_integers_cross_lanes_and_blocks(nums in prop::collection::vec(any::<i8>(), 0..40)) {
+        let exact: i64 = nums.iter().map(|&n| n as i64).sum();
+        prop_assert_eq!(sum::chunked_sum(&nums), i8::try_from(exact).ok());
+    }
+
+    #[test]
+    fn compensated_sums_stay_within_bounds(terms in prop::collection::vec(dyadic(), 0..50)) {
+        let nums: Vec<f64> = terms.iter().map(|&(m, e)| m as f64 * 2f64.powi(e)).collect();
+        let exact_units: i128 = terms.iter().map(|&(m, e)| (m as i128) << (e + 20)).sum();
+        let exact = exact_units as f64 / 2f64.powi(20);
+        let magnitude: f64 = nums.iter().map(|n| n.abs()).sum();
+        let (u, n) = (f64::EPSILON / 2.0, nums.len() as f64);
+        let second_order = 4.0 * n * n * u * u * magnitude;
+        // Neumaier's error is bounded by the exact sum, Kahan's by the sum of magnitudes
+        let neumaier = sum::neumaier_sum(&nums);
+        prop_assert!((neumaier - exact).abs() <= 2.0 * u * exact.abs() + second_order);
+        let kahan = sum::kahan_sum(&nums);
+        prop_assert!((kahan - exact).abs() <= 2.0 * u * magnitude + second_order);
+    }
+}
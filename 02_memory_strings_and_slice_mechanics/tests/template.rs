@@ -0,0 +1,320 @@
+// Templates render what their tags say, report mistakes at the right line and column, and leave
+// text without tags exactly as it is.
+use memory_safety::template::{Context, Position, Template, TemplateError, Value};
+use proptest::prelude::*;
+
+fn render(source: &str, context: &Context) -> Result<String, TemplateError> {
+    Template::new(source)?.render(context)
+}
+
+fn at(line: usize, column: usize) -> Position {
+    Position { line, column }
+}
+
+// --- RENDERING ---
+#[test]
+fn substitutes_and_filters() {
+    let user = Value::map([
+        ("name", "  Ferris ".into()),
+        ("langs", vec!["Rust", "C"].into()),
+    ]);
+    let context = Context::new()
+        .with("user", user)
+        .with("count", 3)
+        .with("empty", "")
+        .with("title", "Ownership and borrowing");
+    let cases = [
+        ("Hi {{user.name}}!", "Hi   Ferris !"),
+        ("Hi {{ user.name | trim | upper }}!", "Hi FERRIS!"),
+        ("{{ user.name | trim | lower | length }}", "6"),
+        ("{{ title | truncate(12) }}", "Ownership a…"),
+        ("{{ title | truncate(100) }}", "Ownership and borrowing"),
+        (
+            "{{ user.langs | join(\", \") }} ({{ user.langs | length }})",
+            "Rust, C (2)",
+        ),
+        (
+            "{{ nickname | default(\"friend\") }}, {{ empty | default(\"-\") }}",
+            "friend, -",
+        ),
+        (
+            "{{ count }} {{ 42 }} {{ \"a \\\"quoted\\\" }}\" }}",
+            "3 42 a \"quoted\" }}",
+        ),
+        ("{{ \"नमस्ते\" | length }}", "3"), // Graphemes, not chars or bytes
+    ];
+    for (source, expected) in cases {
+        assert_eq!(render(source, &context).unwrap(), expected, "{source}");
+    }
+}
+
+#[test]
+fn branches_and_loops() {
+    let items = vec![
+        Value::map([("name", "pen".into()), ("stock", 0.into())]),
+        Value::map([("name", "ink".into()), ("stock", 4.into())]),
+        Value::map([("name", "nib".into()), ("stock", 1.into())]),
+    ];
+    let context = Context::new()
+        .with("items", items)
+        .with("admin", false)
+        .with("none", Vec::<&str>::new());
+    let list = "{% for item in items %}{{ loop.index }}. {{ item.name }}\
+                {% if not item.stock %} (sold out){% endif %}\
+                {% if not loop.last %}, {% endif %}{% endfor %}";
+    assert_eq!(
+        render(list, &context).unwrap(),
+        "1. pen (sold out), 2. ink, 3. nib"
+    );
+    let cases = [
+        ("{% if admin %}root{% else %}user{% endif %}", "user"),
+        ("{% if missing %}yes{% else %}no{% endif %}", "no"), // Missing values are false
+        (
+            "{% if items %}some{% endif %}{% if none %}none{% endif %}",
+            "some",
+        ),
+        ("[{% for x in none %}{{ x }}{% endfor %}]", "[]"),
+        // Nested loops: the inner `loop` is the inner one
+        (
+            "{% for a in none %}{% endfor %}{% for i in items %}{% for j in items %}\
+             {% if loop.first %}{{ i.name }}:{% endif %}{{ loop.index }}{% endfor %} {% endfor %}",
+            "pen:123 ink:123 nib:123 ",
+        ),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(render(source, &context).unwrap(), expected, "{source}");
+    }
+}
+
+#[test]
+fn trims_whitespace_and_drops_comments() {
+    let context = Context::new().with("langs", vec!["Rust", "Go"]);
+    let source =
+        "Languages:\n{% for lang in langs -%}\n  - {{ lang }}\n{%- endfor %}\n{# not shown #}done";
+    assert_eq!(
+        render(source, &context).unwrap(),
+        "Languages:\n- Rust- Go\ndone"
+    );
+    assert_eq!(render("a  {{- \"b\" -}}  c", &context).unwrap(), "abc");
+    assert_eq!(
+        render("{ not a tag } {#{{ x }}#}", &context).unwrap(),
+        "{ not a tag } "
+    );
+}
+
+#[test]
+fn renders_many_times_from_borrowed_data() {
+    let template = Template::new("{{ greeting }}, {{ name }}!").unwrap();
+    let names = [String::from("Ana"), String::from("Bo")];
+    let mut out = String::new();
+    for name in &names {
+        // The context borrows each name: nothing is copied to render it
+        let context = Context::new().with("greeting", "Hello").with("name", name);
+        template.render_to(&context, &mut out).unwrap();
+        out.push('\n');
+    }
+    assert_eq!(out, "Hello, Ana!\nHello, Bo!\n");
+    assert_eq!(template.source(), "{{ greeting }}, {{ name }}!");
+}
+
+// --- ERRORS ---
+#[test]
+fn compile_errors_point_into_the_template() {
+    let cases = [
+        ("Hi {{ name", TemplateError::UnclosedTag { at: at(1, 4) }),
+        (
+            "line one\n  {% if x %}never closed",
+            TemplateError::UnclosedBlock {
+                at: at(2, 6),
+                block: "if",
+            },
+        ),
+        (
+            "{% for x in xs %}{% endif %}",
+            TemplateError::UnexpectedTag {
+                at: at(1, 21),
+                tag: "endif".into(),
+            },
+        ),
+        (
+            "{% while x %}",
+            TemplateError::UnexpectedTag {
+                at: at(1, 4),
+                tag: "while".into(),
+            },
+        ),
+        (
+            "é\n{{ name | uper }}",
+            TemplateError::UnknownFilter {
+                at: at(2, 11),
+                name: "uper".into(),
+            },
+        ),
+        (
+            "{{ name | truncate(\"10\") }}",
+            TemplateError::BadArgument {
+                at: at(1, 11),
+                filter: "truncate".into(),
+            },
+        ),
+        (
+            "{{ name | truncate }}",
+            TemplateError::BadArgument {
+                at: at(1, 11),
+                filter: "truncate".into(),
+            },
+        ),
+        (
+            "{{ user. }}",
+            TemplateError::Unexpected {
+                at: at(1, 10),
+                found: "end of tag, expected a field name".into(),
+            },
+        ),
+        (
+            "{{ a b }}",
+            TemplateError::Unexpected {
+                at: at(1, 6),
+                found: "\"b\", expected the end of the tag".into(),
+            },
+        ),
+        (
+            "{% for x in xs | upper %}{% endfor %}",
+            TemplateError::NotList {
+                at: at(1, 13),
+                name: "xs | upper".into(),
+            },
+        ),
+        (
+            "{{ a ; }}",
+            TemplateError::Unexpected {
+                at: at(1, 6),
+                found: ";".into(),
+            },
+        ),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(Template::new(source), Err(expected), "{source}");
+    }
+}
+
+#[test]
+fn deep_nesting_is_an_error_not_a_stack_overflow() {
+    let nest = |open: &str, close: &str, n| open.repeat(n) + "x" + &close.repeat(n);
+    let deep = nest("{% if x %}", "{% endif %}", 100_000);
+    assert_eq!(
+        Template::new(&deep),
+        Err(TemplateError::TooDeep {
+            at: at(1, 324), // The 33rd {% if %}
+            block: "if",
+        })
+    );
+    let deep = nest("{% for x in xs %}\n", "{% endfor %}", 100_000);
+    assert_eq!(
+        Template::new(&deep),
+        Err(TemplateError::TooDeep {
+            at: at(33, 4),
+            block: "for",
+        })
+    );
+    assert_eq!(
+        Template::new(&deep).unwrap_err().to_string(),
+        "line 33, column 4: {% for %} is nested too deeply"
+    );
+    // Up to the limit is fine, and so is any number of blocks side by side
+    let context = Context::new().with("x", "yes");
+    let nested = nest("{% if x %}", "{% endif %}", 32);
+    assert_eq!(render(&nested, &context), Ok("x".to_string()));
+    let flat = "{% if x %}x{% else %}y{% endif %}".repeat(10_000);
+    assert_eq!(render(&flat, &context), Ok("x".repeat(10_000)));
+}
+
+#[test]
+fn render_errors_point_into_the_template() {
+    let context = Context::new()
+        .with("langs", vec!["Rust"])
+        .with("name", "Ferris");
+    let cases = [
+        (
+            "Dear {{ user.name }},",
+            TemplateError::Undefined {
+                at: at(1, 9),
+                name: "user.name".into(),
+            },
+        ),
+        (
+            "\n\n  {{ langs | upper }}",
+            TemplateError::NotText {
+                at: at(3, 6),
+                name: "langs".into(),
+            },
+        ),
+        (
+            "{% for c in name %}{% endfor %}",
+            TemplateError::NotList {
+                at: at(1, 13),
+                name: "name".into(),
+            },
+        ),
+        (
+            "{{ name | join(\",\") }}",
+            TemplateError::NotList {
+                at: at(1, 4),
+                name: "name".into(),
+            },
+        ),
+        (
+            "{% for l in langs %}{{ loop.size }}{% endfor %}",
+            TemplateError::Undefined {
+                at: at(1, 24),
+                name: "loop.size".into(),
+            },
+        ),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(render(source, &context), Err(expected), "{source}");
+    }
+    assert_eq!(
+        TemplateError::Undefined {
+            at: at(3, 6),
+            name: "user.name".into()
+        }
+        .to_string(),
+        "line 3, column 6: `user.name` is not defined"
+    );
+    assert_eq!(
+        TemplateError::UnclosedBlock {
+            at: at(1, 1),
+            block: "for"
+        }
+        .to_string(),
+        "line 1, column 1: {% for %} has no {% endfor %}"
+    );
+}
+
+// --- PROPERTIES ---
+proptest! {
+    // Text with no tag delimiters is its own template
+    #[test]
+    fn plain_text_renders_unchanged(text in "[a-z {}%#\n\u{e9}]{0,40}") {
+        prop_assume!(!["{{", "{%", "{#"].iter().any(|open| text.contains(open)));
+        prop_assert_eq!(render(&text, &Context::new()).unwrap(), text);
+    }
+
+    // Values are written out as they are, whatever they contain, and positions count lines and
+    // chars (not bytes) before the tag
+    #[test]
+    fn values_are_inserted_verbatim(value in "\\PC{0,20}", before in "[a\u{e9}\n]{0,10}") {
+        let context = Context::new().with("v", value.as_str());
+        let source = format!("{before}{{{{ v }}}}|{{{{ w }}}}");
+        let template = Template::new(&source).unwrap();
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap().chars().count() + 1;
+        prop_assert_eq!(
+            template.render(&context),
+            Err(TemplateError::Undefined { at: at(line, column + 11), name: "w".into() })
+        );
+        let context = context.with("w", "");
+        prop_assert_eq!(template.render(&context).unwrap(), format!("{before}{value}|"));
+    }
+}
@@ -0,0 +1,181 @@
+// The transforms in src/transform.rs promise to allocate only when the text changes. A counting
+// global allocator checks that promise: every unchanged path must make zero allocations.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+
+use memory_safety::transform;
+use proptest::prelude::*;
+
+mod common;
+
+// --- ALLOCATION COUNTING ---
+// Counts per thread, because the test harness runs tests on several threads at once
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// The result of `f` and how many allocations (and reallocations) it made
+fn counting<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+const HTML: &[(char, &str)] = &[('<', "&lt;"), ('>', "&gt;"), ('&', "&amp;")];
+
+// --- UNCHANGED TEXT: ZERO ALLOCATIONS ---
+#[test]
+fn unchanged_text_is_never_copied() {
+    let text = "Hello world, nothing to change here";
+    let (results, allocations) = counting(|| {
+        [
+            transform::trim(text),
+            transform::collapse_whitespace(text),
+            transform::replace(text, "goodbye", "hi"),
+            transform::escape(text, HTML),
+            transform::remove_chars(text, char::is_control),
+        ]
+    });
+    assert_eq!(allocations, 0);
+    for result in results {
+        assert!(matches!(result, Cow::Borrowed(s) if s == text));
+    }
+}
+
+#[test]
+fn trimming_and_collapsing_only_slice() {
+    let (result, allocations) = counting(|| transform::collapse_whitespace("  \tHello world\n"));
+    assert_eq!(allocations, 0);
+    assert!(matches!(result, Cow::Borrowed("Hello world")));
+}
+
+#[test]
+fn chains_of_unchanged_steps_stay_borrowed() {
+    let input = "  some <safe> text ";
+    let (result, allocations) = counting(|| {
+        let s = transform::trim(input);
+        let s = transform::chain(s, transform::collapse_whitespace);
+        let s = transform::chain(s, |s| transform::replace(s, "unsafe", "safe"));
+        transform::chain(s, |s| transform::remove_chars(s, char::is_control))
+    });
+    assert_eq!(allocations, 0);
+    assert!(matches!(result, Cow::Borrowed("some <safe> text")));
+}
+
+// An owned String is cut down in place by later borrowing steps, not copied
+#[test]
+fn owned_strings_are_trimmed_in_place() {
+    let owned = Cow::Owned(String::from("  padded  "));
+    let (result, allocations) = counting(|| transform::chain(owned, transform::trim));
+    assert_eq!(allocations, 0);
+    assert!(matches!(result, Cow::Owned(ref s) if s == "padded"));
+}
+
+// --- CHANGED TEXT ---
+type Call = fn() -> Cow<'static, str>;
+
+#[test]
+fn changed_text_is_allocated_once() {
+    let cases: [(Call, &str); 4] = [
+        (|| transform::collapse_whitespace(" a \t b\n\nc "), "a b c"),
+        (|| transform::replace("one two one", "one", "1"), "1 two 1"),
+        (
+            || transform::escape("a < b && c", HTML),
+            "a &lt; b &amp;&amp; c",
+        ),
+        (
+            || transform::remove_chars("bell\u{7}!", char::is_control),
+            "bell!",
+        ),
+    ];
+    for (f, expected) in cases {
+        let (result, allocations) = counting(f);
+        assert!(
+            matches!(result, Cow::Owned(ref s) if s == expected),
+            "{result:?}"
+        );
+        assert_eq!(allocations, 1, "{expected:?}");
+    }
+}
+
+#[test]
+fn replace_edge_cases() {
+    assert!(matches!(
+        transform::replace("abc", "", "x"),
+        Cow::Borrowed("abc")
+    ));
+    assert!(matches!(
+        transform::replace("abc", "b", "b"),
+        Cow::Borrowed("abc")
+    ));
+    assert_eq!(transform::replace("aaa", "aa", "b"), "ba"); // Non-overlapping, like str::replace
+    assert_eq!(transform::replace("🦀🦀", "🦀", "crab"), "crabcrab");
+}
+
+// --- AGAINST THE STANDARD LIBRARY ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &["a", "b", "ab", " ", "  ", "\t", "\n", "\u{3000}", "<", "&", "🦀", "é", "\u{7}"];
+
+fn text() -> impl Strategy<Value = String> {
+    common::concat_of(PIECES, 20)
+}
+
+proptest! {
+    // Same text as the obvious allocating version, and Borrowed exactly when nothing changed
+    #[test]
+    fn transforms_match_std(s in text(), from in text(), to in text()) {
+        let collapsed = transform::collapse_whitespace(&s);
+        prop_assert_eq!(&collapsed, &s.split_whitespace().collect::<Vec<_>>().join(" "));
+        prop_assert_eq!(matches!(collapsed, Cow::Borrowed(_)), collapsed == s.trim());
+
+        let replaced = transform::replace(&s, &from, &to);
+        if !from.is_empty() {
+            prop_assert_eq!(&replaced, &s.replace(&from, &to));
+        }
+        prop_assert_eq!(matches!(replaced, Cow::Borrowed(_)), replaced == s);
+
+        let escaped = transform::escape(&s, HTML);
+        let expected = s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        prop_assert_eq!(&escaped, &expected);
+        prop_assert_eq!(matches!(escaped, Cow::Borrowed(_)), escaped == s);
+
+        let removed = transform::remove_chars(&s, char::is_whitespace);
+        prop_assert_eq!(&removed, &s.chars().filter(|c| !c.is_whitespace()).collect::<String>());
+        prop_assert_eq!(matches!(removed, Cow::Borrowed(_)), removed == s);
+    }
+
+    // Chaining gives the same text whether the input is borrowed or owned
+    #[test]
+    fn chain_is_the_same_for_borrowed_and_owned(s in text()) {
+        let steps = |start: Cow<'_, str>| -> String {
+            let s = transform::chain(start, transform::trim);
+            let s = transform::chain(s, |s| transform::escape(s, HTML));
+            transform::chain(s, transform::collapse_whitespace).into_owned()
+        };
+        let expected = transform::collapse_whitespace(&transform::escape(s.trim(), HTML)).into_owned();
+        prop_assert_eq!(steps(Cow::Borrowed(&s)), expected.clone());
+        prop_assert_eq!(steps(Cow::Owned(s.clone())), expected);
+    }
+}
@@ -0,0 +1,245 @@
+// Chunked input must decode exactly like the whole input decoded at once by std, wherever the
+// chunk boundaries fall; WTF-8 must give back the UTF-16 it was made from.
+use memory_safety::utf8::{self, DecodeError, GraphemeDecoder, Mode, Utf8Decoder};
+use proptest::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Cuts `bytes` at the given offsets (taken modulo its length) and decodes the pieces in order
+fn decode_chunks(mode: Mode, bytes: &[u8], cuts: &[usize]) -> Result<String, DecodeError> {
+    let mut decoder = Utf8Decoder::new(mode);
+    let mut out = String::new();
+    for chunk in split(bytes, cuts) {
+        out.push_str(&decoder.decode(chunk)?);
+    }
+    out.push_str(decoder.finish()?);
+    Ok(out)
+}
+
+fn split<'b>(bytes: &'b [u8], cuts: &[usize]) -> Vec<&'b [u8]> {
+    let mut cuts: Vec<usize> = cuts.iter().map(|c| c % (bytes.len() + 1)).collect();
+    cuts.sort_unstable();
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    for cut in cuts.into_iter().chain([bytes.len()]) {
+        chunks.push(&bytes[start..cut]);
+        start = cut;
+    }
+    chunks
+}
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn decodes_chars_split_across_chunks() {
+    let text = "नमस्ते 🦀!";
+    for cut in 0..=text.len() {
+        let decoded = decode_chunks(Mode::Strict, text.as_bytes(), &[cut]).unwrap();
+        assert_eq!(decoded, text, "cut at {cut}");
+    }
+    // One byte per chunk: each char is only complete once its last byte arrives
+    let mut decoder = Utf8Decoder::new(Mode::Strict);
+    let decoded: Vec<String> = "🦀"
+        .as_bytes()
+        .iter()
+        .map(|b| {
+            decoder
+                .decode(std::slice::from_ref(b))
+                .unwrap()
+                .into_owned()
+        })
+        .collect();
+    assert_eq!(decoded, ["", "", "", "🦀"]);
+    assert_eq!(decoder.offset(), 4);
+}
+
+#[test]
+fn borrows_chunks_that_stand_alone() {
+    let mut decoder = Utf8Decoder::new(Mode::Lossy);
+    assert!(matches!(
+        decoder.decode("नमस्ते".as_bytes()),
+        Ok(std::borrow::Cow::Borrowed("नमस्ते"))
+    ));
+    // A chunk cut mid-char is borrowed up to the cut, and the next one has to copy
+    let crab = "a🦀".as_bytes();
+    assert!(matches!(
+        decoder.decode(&crab[..3]),
+        Ok(std::borrow::Cow::Borrowed("a"))
+    ));
+    assert!(matches!(decoder.decode(&crab[3..]), Ok(std::borrow::Cow::Owned(s)) if s == "🦀"));
+}
+
+#[test]
+fn reports_errors_with_stream_offsets() {
+    let mut decoder = Utf8Decoder::new(Mode::Strict);
+    assert_eq!(decoder.decode(b"caf").unwrap(), "caf");
+    assert_eq!(decoder.decode(b"\xC3").unwrap(), "");
+    // The é is never finished: 'x' is not a continuation byte
+    let err = DecodeError::Invalid { at: 3, len: 1 };
+    assert_eq!(decoder.decode(b"x"), Err(err.clone()));
+    assert_eq!(decoder.decode(b"more"), Err(err.clone())); // Stopped for good
+    assert_eq!(decoder.finish(), Err(err));
+
+    let mut decoder = Utf8Decoder::new(Mode::Strict);
+    assert_eq!(decoder.decode(b"ok \xF0\x9F").unwrap(), "ok ");
+    assert_eq!(decoder.finish(), Err(DecodeError::Truncated { at: 3 }));
+    assert_eq!(
+        DecodeError::Invalid { at: 7, len: 2 }.to_string(),
+        "invalid UTF-8 sequence of 2 byte(s) at offset 7"
+    );
+
+    // Lossy: one U+FFFD per invalid sequence, like String::from_utf8_lossy
+    let bytes = b"a\xF0\x9F\x80b\xFFc\xED\xA0\x80\xE2\x82";
+    assert_eq!(
+        decode_chunks(Mode::Lossy, bytes, &[2, 9]).unwrap(),
+        "a\u{FFFD}b\u{FFFD}c\u{FFFD}\u{FFFD}\u{FFFD}\u{FFFD}"
+    );
+}
+
+#[test]
+fn wtf8_keeps_lone_surrogates() {
+    // U+D83D alone (a lead surrogate without its trail) as WTF-8
+    let bytes = b"a\xED\xA0\xBDb";
+    assert_eq!(
+        decode_chunks(Mode::Strict, bytes, &[]),
+        Err(DecodeError::Invalid { at: 1, len: 1 })
+    );
+    assert_eq!(
+        decode_chunks(Mode::Wtf8, bytes, &[2]).unwrap(),
+        "a\u{FFFD}b"
+    );
+    let mut units = Vec::new();
+    let mut decoder = Utf8Decoder::new(Mode::Wtf8);
+    decoder.decode_utf16(&bytes[..2], &mut units).unwrap();
+    decoder.decode_utf16(&bytes[2..], &mut units).unwrap();
+    assert_eq!(units, [0x61, 0xD83D, 0x62]);
+    // A lead followed by a trail must be written as one 4-byte sequence
+    let pair = b"\xED\xA0\xBD\xED\xB8\x80";
+    assert_eq!(
+        decode_chunks(Mode::Wtf8, pair, &[4]),
+        Err(DecodeError::Invalid { at: 3, len: 3 })
+    );
+}
+
+#[test]
+fn graphemes_wait_for_the_next_chunk() {
+    let mut decoder = GraphemeDecoder::new(Mode::Strict);
+    // "e" could still get an accent, so it is held back
+    assert_eq!(
+        decoder.push(b"cafe").unwrap().collect::<Vec<_>>(),
+        ["c", "a", "f"]
+    );
+    assert_eq!(
+        decoder
+            .push("\u{301} 🇫".as_bytes())
+            .unwrap()
+            .collect::<Vec<_>>(),
+        ["e\u{301}", " "]
+    );
+    assert_eq!(
+        decoder.push("🇷!".as_bytes()).unwrap().collect::<Vec<_>>(),
+        ["🇫🇷"]
+    );
+    assert_eq!(decoder.finish().unwrap().collect::<Vec<_>>(), ["!"]);
+}
+
+#[test]
+fn validates_buffers() {
+    let long = "ascii ".repeat(100) + "नमस्ते" + &"🦀".repeat(10);
+    assert_eq!(utf8::validate(long.as_bytes()), Ok(()));
+    let mut bytes = long.into_bytes();
+    bytes[605] = b'!'; // Inside the म
+    assert_eq!(
+        utf8::validate(&bytes),
+        Err(DecodeError::Invalid { at: 603, len: 2 })
+    );
+    assert_eq!(
+        utf8::validate(b"abc\xE2\x82"),
+        Err(DecodeError::Truncated { at: 3 })
+    );
+    assert_eq!(utf8::validate(b""), Ok(()));
+}
+
+// --- AGAINST STD ---
+// Mostly ASCII with every kind of trouble mixed in: multi-byte chars, stray continuation
+// bytes, overlong forms, surrogates, truncated sequences
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+    #[rustfmt::skip]
+    let troubles: Vec<&[u8]> = vec![b"\x80", b"\xC0\xAF", b"\xED\xA0\x80", b"\xF4\x90\x80\x80", b"\xF0\x9F", b"\xE2\x82", b"\xFF"];
+    let piece = prop_oneof![
+        4 => prop::sample::select(vec!["a", "b", " "]).prop_map(|s| s.as_bytes().to_vec()),
+        2 => prop::sample::select(vec!["é", "न", "🦀", "\u{301}"]).prop_map(|s| s.as_bytes().to_vec()),
+        1 => prop::sample::select(troubles).prop_map(<[u8]>::to_vec),
+        1 => any::<u8>().prop_map(|b| vec![b]),
+    ];
+    prop::collection::vec(piece, 0..24).prop_map(|pieces| pieces.concat())
+}
+
+fn cuts() -> impl Strategy<Value = Vec<usize>> {
+    prop::collection::vec(any::<usize>(), 0..6)
+}
+
+// UTF-16 as WTF-8: surrogate pairs become one 4-byte sequence, lone surrogates 3 bytes
+fn wtf8(units: &[u16]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for c in char::decode_utf16(units.iter().copied()) {
+        match c {
+            Ok(c) => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Err(e) => {
+                let unit = e.unpaired_surrogate();
+                out.extend([
+                    0xED,
+                    0x80 | ((unit >> 6) & 0x3F) as u8,
+                    0x80 | (unit & 0x3F) as u8,
+                ]);
+            }
+        }
+    }
+    out
+}
+
+proptest! {
+    #[test]
+    fn lossy_matches_from_utf8_lossy(bytes in bytes(), cuts in cuts()) {
+        let expected = String::from_utf8_lossy(&bytes);
+        prop_assert_eq!(decode_chunks(Mode::Lossy, &bytes, &cuts).unwrap(), expected);
+    }
+
+    #[test]
+    fn strict_matches_from_utf8(bytes in bytes(), cuts in cuts()) {
+        let decoded = decode_chunks(Mode::Strict, &bytes, &cuts);
+        let expected = std::str::from_utf8(&bytes).map(str::to_string).map_err(|e| {
+            let at = e.valid_up_to() as u64;
+            match e.error_len() {
+                Some(len) => DecodeError::Invalid { at, len },
+                None => DecodeError::Truncated { at },
+            }
+        });
+        prop_assert_eq!(&decoded, &expected);
+        prop_assert_eq!(utf8::validate(&bytes), expected.map(|_| ()));
+    }
+
+    #[test]
+    fn wtf8_round_trips_utf16(units in prop::collection::vec(prop::sample::select(vec![0x61, 0xE9, 0x928, 0xD83D, 0xDE00, 0xDC00, 0xFFFF]), 0..16), cuts in cuts()) {
+        let bytes = wtf8(&units);
+        let mut decoder = Utf8Decoder::new(Mode::Wtf8);
+        let mut decoded = Vec::new();
+        for chunk in split(&bytes, &cuts) {
+            decoder.decode_utf16(chunk, &mut decoded).unwrap();
+        }
+        prop_assert_eq!(decoder.finish().unwrap(), "");
+        prop_assert_eq!(decoded, units.clone());
+        // The str view shows each lone surrogate as U+FFFD
+        let lossy = decode_chunks(Mode::Wtf8, &bytes, &cuts).unwrap();
+        prop_assert_eq!(lossy, String::from_utf16_lossy(&units));
+    }
+
+    #[test]
+    fn graphemes_match_the_whole_text(text in "[ae\u{301}\u{200d}🦀🇫🇷नस्त\r\n]{0,16}", cuts in cuts()) {
+        let mut decoder = GraphemeDecoder::new(Mode::Strict);
+        let mut graphemes: Vec<String> = Vec::new();
+        for chunk in split(text.as_bytes(), &cuts) {
+            graphemes.extend(decoder.push(chunk).unwrap().map(str::to_string));
+        }
+        graphemes.extend(decoder.finish().unwrap().map(str::to_string));
+        prop_assert_eq!(graphemes, text.graphemes(true).collect::<Vec<_>>());
+    }
+}
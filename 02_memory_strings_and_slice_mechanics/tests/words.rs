@@ -0,0 +1,174 @@
+// Words are UAX #29 segments borrowed from the input, the same ones from either end, and the
+// options only drop or split tokens, never move their offsets.
+use memory_safety::words::{self, Word, WordKind, WordOptions};
+use proptest::prelude::*;
+
+mod common;
+
+fn texts(words: impl Iterator<Item = Word<'static>>) -> Vec<&'static str> {
+    words.map(|w| w.text).collect()
+}
+
+const SPLIT: WordOptions = WordOptions {
+    punctuation: false,
+    numbers: true,
+    split_contractions: true,
+};
+
+const EVERYTHING: WordOptions = WordOptions {
+    punctuation: true,
+    numbers: true,
+    split_contractions: false,
+};
+
+// --- KNOWN ANSWERS ---
+#[test]
+fn splits_on_any_whitespace_and_punctuation() {
+    let text = "Hello,\tworld! It's 3.14\u{a0}km away";
+    assert_eq!(words::first_word(text), Some("Hello"));
+    assert_eq!(words::nth_word(text, 2), Some("It's"));
+    assert_eq!(words::nth_word(text, 6), None);
+    assert_eq!(words::last_word(text), Some("away"));
+    assert_eq!(words::word_count(text), 6);
+    assert_eq!(words::first_word(" \n\t "), None);
+    assert_eq!(words::last_word(""), None);
+
+    // UAX #29 keeps "e.g", "3.14" and "1,000" together, but not the final period
+    let text = "e.g. 1,000 (or 3.14)...";
+    assert_eq!(
+        texts(words::words_with(text, EVERYTHING)),
+        ["e.g", ".", "1,000", "(", "or", "3.14", ")", ".", ".", "."]
+    );
+    let kinds: Vec<WordKind> = words::words_with("a 1 !", EVERYTHING)
+        .map(|w| w.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [WordKind::Word, WordKind::Number, WordKind::Punctuation]
+    );
+    let no_numbers = WordOptions {
+        numbers: false,
+        ..WordOptions::default()
+    };
+    assert_eq!(
+        texts(words::words_with("3rd of 12", no_numbers)),
+        ["3rd", "of"]
+    );
+}
+
+#[test]
+fn contractions() {
+    assert_eq!(texts(words::words("don't stop")), ["don't", "stop"]);
+    assert_eq!(
+        texts(words::words_with("don't stop", SPLIT)),
+        ["don", "'t", "stop"]
+    );
+    // Typographic apostrophes and more than one of them
+    assert_eq!(texts(words::words_with("we’re", SPLIT)), ["we", "’re"]);
+    assert_eq!(
+        texts(words::words_with("rock'n'roll", SPLIT)),
+        ["rock", "'n", "'roll"]
+    );
+    // An apostrophe that is not between letters is not part of a word
+    assert_eq!(
+        texts(words::words_with("'tis dogs'", SPLIT)),
+        ["tis", "dogs"]
+    );
+    // Offsets point into the original text
+    let pieces: Vec<(usize, usize)> = words::words_with("I can't", SPLIT)
+        .map(|w| (w.start, w.end()))
+        .collect();
+    assert_eq!(pieces, [(0, 1), (2, 5), (5, 7)]);
+}
+
+#[test]
+fn reads_from_the_back() {
+    let text = "Hello,\tworld! It's 3.14\u{a0}km away";
+    let mut back = words::words(text).rev().map(|w| w.text);
+    assert_eq!(back.next(), Some("away"));
+    assert_eq!(back.next(), Some("km"));
+    assert_eq!(back.next(), Some("3.14"));
+    assert_eq!(
+        texts(words::words_with("rock'n'roll and don't", SPLIT).rev()),
+        ["'t", "don", "and", "'roll", "'n", "rock"]
+    );
+}
+
+#[test]
+fn both_ends_meet() {
+    // The two ends meet inside one segment, which both have started splitting
+    let mut iter = words::words_with("rock'n'roll", SPLIT);
+    assert_eq!(iter.next().map(|w| w.text), Some("rock"));
+    assert_eq!(iter.next_back().map(|w| w.text), Some("'roll"));
+    assert_eq!(iter.next().map(|w| w.text), Some("'n"));
+    assert_eq!(iter.next_back(), None);
+    assert_eq!(iter.next(), None);
+
+    let mut iter = words::words("one two three");
+    assert_eq!(iter.next_back().map(|w| w.text), Some("three"));
+    assert_eq!(iter.next().map(|w| w.text), Some("one"));
+    assert_eq!(iter.next_back().map(|w| w.text), Some("two"));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn scripts_without_spaces() {
+    // No dictionary: one token per ideograph, and kana runs stay together by script
+    assert_eq!(texts(words::words("我爱Rust")), ["我", "爱", "Rust"]);
+    assert_eq!(texts(words::words("カタカナ")), ["カタカナ"]);
+    assert_eq!(texts(words::words("ひらがな")), ["ひ", "ら", "が", "な"]);
+    assert_eq!(texts(words::words("नमस्ते दुनिया")), ["नमस्ते", "दुनिया"]);
+    // Emoji are punctuation, and a flag or a family is one token
+    assert_eq!(
+        texts(words::words_with("hi 👨‍👩‍👧 🇫🇷!", EVERYTHING)),
+        ["hi", "👨‍👩‍👧", "🇫🇷", "!"]
+    );
+    assert_eq!(texts(words::words("hi 👨‍👩‍👧 🇫🇷!")), ["hi"]);
+}
+
+// --- PROPERTIES ---
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "a", "don", "'", "’", "t", " ", "\t", "\u{a0}", ".", ",", "3", "!", "我", "カ", "नमस्ते", "🦀", "e\u{301}",
+];
+
+fn options() -> impl Strategy<Value = WordOptions> {
+    (any::<bool>(), any::<bool>(), any::<bool>()).prop_map(
+        |(punctuation, numbers, split_contractions)| WordOptions {
+            punctuation,
+            numbers,
+            split_contractions,
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn same_words_from_either_end(text in common::concat_of(PIECES, 16), options in options(), steps in prop::collection::vec(any::<bool>(), 0..20)) {
+        let forward: Vec<Word> = words::words_with(&text, options).collect();
+        let mut backward: Vec<Word> = words::words_with(&text, options).rev().collect();
+        backward.reverse();
+        prop_assert_eq!(&backward, &forward);
+        for word in &forward {
+            prop_assert_eq!(&text[word.start..word.end()], word.text);
+            prop_assert!(!word.text.is_empty() && !word.text.trim().is_empty());
+        }
+        prop_assert!(forward.windows(2).all(|w| w[0].end() <= w[1].start));
+
+        // Any mix of next and next_back takes words off the two ends of the same list
+        let mut iter = words::words_with(&text, options);
+        let (mut front, mut back) = (0, forward.len());
+        for from_back in steps {
+            if from_back {
+                let expected = (front < back).then(|| forward[back - 1]);
+                prop_assert_eq!(iter.next_back(), expected);
+                back -= usize::from(front < back);
+            } else {
+                let expected = (front < back).then(|| forward[front]);
+                prop_assert_eq!(iter.next(), expected);
+                front += usize::from(front < back);
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+// Arbitrary range file bodies and suffixes for the offline breached-password lookup
+#![no_main]
+
+use auth_service::password::breach;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (String, String)| {
+    let (range, password) = input;
+    let hash = breach::sha1_hex(&password);
+    assert_eq!(hash.len(), 40);
+    let _ = breach::count_in_range(&range, &hash[5..]);
+    let _ = breach::count_in_range(&range, &password);
+});
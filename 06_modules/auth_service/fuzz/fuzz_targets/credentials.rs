@@ -0,0 +1,50 @@
+// Arbitrary usernames and passwords through registration, login, password change and the strength checks.
+#![no_main]
+
+use auth_service::password::{self, PasswordPolicy};
+use auth_service::service::ServiceConfig;
+use auth_service::{AuthService, Credentials};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (String, String, String)| {
+    let (username, password, new_password) = input;
+    let _ = password::estimate(&password, &[&username]);
+    let _ = PasswordPolicy::default().check(&password, &[&username]);
+
+    let service = AuthService::new(ServiceConfig {
+        password_policy: PasswordPolicy {
+            min_chars: 0,
+            min_score: 0,
+            breach_list: None,
+        },
+        pbkdf2_rounds: 1,
+        shards: 2,
+        ..ServiceConfig::default()
+    });
+    let creds = Credentials {
+        username: username.clone(),
+        password: password.clone(),
+    };
+    service
+        .register(&creds)
+        .expect("any credentials register on an empty service");
+
+    // The same credentials must give the same answer every time
+    let token =
+        auth_service::authenticate(&service, &creds).expect("registered credentials must log in");
+    assert_eq!(service.validate(&token).as_deref(), Some(username.as_str()));
+    assert!(service.login(&creds).is_ok());
+    assert!(service.validate(&password).is_none() || password == token);
+
+    service
+        .change_password(&creds, &new_password)
+        .expect("permissive policy accepts any new password");
+    assert_eq!(service.validate(&token), None);
+    let new_creds = Credentials {
+        username,
+        password: new_password,
+    };
+    let token = service.login(&new_creds).expect("new password must log in");
+    assert!(service.logout(&token));
+    assert!(!service.logout(&token));
+});
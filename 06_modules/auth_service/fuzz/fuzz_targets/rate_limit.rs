@@ -0,0 +1,40 @@
+// Input: algorithm u8 | capacity u32 | refill f64 bits u64 | window ms u64 | key length u8 | key | timestamps u64...
+#![no_main]
+
+use std::time::Duration;
+
+use auth_service::rate_limit::{Algorithm, MemoryBackend, RateLimiter};
+use auth_service_fuzz::Reader;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Reader::new(data);
+    let selector = input.u8();
+    let capacity = input.u32();
+    let refill_per_sec = f64::from_bits(input.u64());
+    let window = Duration::from_millis(input.u64());
+    let key_len = input.u8() as usize;
+    let key = String::from_utf8_lossy(input.bytes(key_len)).into_owned();
+
+    let algorithm = if selector % 2 == 0 {
+        Algorithm::TokenBucket {
+            capacity,
+            refill_per_sec,
+        }
+    } else {
+        Algorithm::SlidingWindowLog {
+            limit: capacity % 1024, // Bounded so the log stays small
+            window,
+        }
+    };
+    let mut limiter = RateLimiter::new(algorithm, MemoryBackend::new());
+    for _ in 0..64 {
+        if input.is_empty() {
+            break;
+        }
+        let decision = limiter.check_at(&key, input.u64());
+        assert!(decision.remaining <= decision.limit);
+        assert_eq!(decision.allowed, decision.retry_after.is_none());
+        let _ = decision.headers();
+    }
+});
@@ -0,0 +1,25 @@
+// Arbitrary bytes as a persisted rate limiter state file: loading must fail cleanly or round-trip
+#![no_main]
+
+use std::fs;
+
+use auth_service::rate_limit::{Backend, FileBackend};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let path = std::env::temp_dir().join(format!("auth_service_fuzz_{}.txt", std::process::id()));
+    fs::write(&path, data).unwrap();
+    if let Ok(mut backend) = FileBackend::open(&path) {
+        let probe = backend.load("probe");
+        backend.store(
+            "fuzz",
+            auth_service::rate_limit::State::Bucket {
+                tokens: 1.0,
+                last_ms: 0,
+            },
+        );
+        let mut reopened = FileBackend::open(&path).expect("a file we just wrote must load");
+        assert_eq!(reopened.load("probe"), probe);
+    }
+    let _ = fs::remove_file(&path);
+});
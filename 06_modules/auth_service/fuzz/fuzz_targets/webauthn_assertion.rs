@@ -0,0 +1,62 @@
+// Input: u16 length | clientDataJSON | u16 length | authenticatorData | signature
+// A recorded packed registration is stored first, so mutations reach the signature and counter checks.
+#![no_main]
+
+use auth_service::webauthn::{
+    AssertionResponse, AuthenticationChallenge, CredentialStore, RegistrationResponse,
+};
+use auth_service_fuzz::{
+    CHALLENGE, REGISTRATION_ATTESTATION, REGISTRATION_CLIENT_DATA, registration_challenge,
+    relying_party, split_frames,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(frames) = split_frames(data, 3) else {
+        return;
+    };
+    let rp = relying_party();
+    let mut store = CredentialStore::new();
+    let registration = RegistrationResponse {
+        client_data_json: REGISTRATION_CLIENT_DATA.to_vec(),
+        attestation_object: REGISTRATION_ATTESTATION.to_vec(),
+    };
+    let credential = rp
+        .finish_registration(&registration_challenge(), &registration, &mut store)
+        .expect("recorded registration fixture must verify");
+
+    let challenge = AuthenticationChallenge {
+        challenge: CHALLENGE.to_vec(),
+        rp_id: rp.id.clone(),
+        username: credential.username.clone(),
+        allow_credentials: vec![credential.credential_id.clone()],
+        user_verification_required: false,
+    };
+    let response = AssertionResponse {
+        credential_id: credential.credential_id.clone(),
+        client_data_json: frames[0].to_vec(),
+        authenticator_data: frames[1].to_vec(),
+        signature: frames[2].to_vec(),
+        user_handle: None,
+    };
+    let before = store.get(&credential.credential_id).unwrap().sign_count;
+    match rp.finish_authentication(&challenge, &response, &mut store) {
+        Ok(result) => {
+            assert_eq!(
+                store.get(&credential.credential_id).unwrap().sign_count,
+                result.sign_count
+            );
+            // Replaying the exact same assertion must never succeed twice with a real counter
+            if result.sign_count > 0 {
+                assert!(
+                    rp.finish_authentication(&challenge, &response, &mut store)
+                        .is_err()
+                );
+            }
+        }
+        Err(_) => assert_eq!(
+            store.get(&credential.credential_id).unwrap().sign_count,
+            before
+        ),
+    }
+});
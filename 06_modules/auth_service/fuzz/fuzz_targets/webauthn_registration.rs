@@ -0,0 +1,25 @@
+// Input: u16 length | clientDataJSON | attestationObject (see the seed corpus for recorded examples)
+#![no_main]
+
+use auth_service::webauthn::attestation::{AttestationObject, AuthenticatorData};
+use auth_service::webauthn::{CredentialStore, RegistrationResponse};
+use auth_service_fuzz::{registration_challenge, relying_party, split_frames};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(frames) = split_frames(data, 2) else {
+        return;
+    };
+    let _ = AttestationObject::parse(frames[1]);
+    let _ = AuthenticatorData::parse(frames[1]);
+
+    let mut store = CredentialStore::new();
+    let response = RegistrationResponse {
+        client_data_json: frames[0].to_vec(),
+        attestation_object: frames[1].to_vec(),
+    };
+    match relying_party().finish_registration(&registration_challenge(), &response, &mut store) {
+        Ok(credential) => assert!(store.get(&credential.credential_id).is_some()),
+        Err(_) => assert!(store.is_empty()),
+    }
+});
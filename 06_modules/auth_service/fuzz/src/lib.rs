@@ -0,0 +1,84 @@
+// Helpers shared by the fuzz targets.
+use auth_service::webauthn::{RegistrationChallenge, RelyingParty};
+
+// Fixed challenge used by the recorded responses in fixtures/ and the seed corpus
+pub const CHALLENGE: [u8; 32] = [0x42; 32];
+
+pub const REGISTRATION_CLIENT_DATA: &[u8] =
+    include_bytes!("../fixtures/registration_client_data.json");
+pub const REGISTRATION_ATTESTATION: &[u8] =
+    include_bytes!("../fixtures/registration_packed_attestation.cbor");
+
+pub fn relying_party() -> RelyingParty {
+    RelyingParty::new("example.com", "Example", "https://example.com")
+}
+
+pub fn registration_challenge() -> RegistrationChallenge {
+    RegistrationChallenge {
+        challenge: CHALLENGE.to_vec(),
+        rp_id: "example.com".to_string(),
+        rp_name: "Example".to_string(),
+        user_handle: b"admin-handle".to_vec(),
+        username: "admin".to_string(),
+        user_verification_required: false,
+        exclude_credentials: Vec::new(),
+    }
+}
+
+// Splits `data` into `count` parts. Every part but the last is prefixed with a big endian u16
+// length, which lets the seed files hold real recorded responses the fuzzer can mutate.
+pub fn split_frames(mut data: &[u8], count: usize) -> Option<Vec<&[u8]>> {
+    let mut frames = Vec::with_capacity(count);
+    for _ in 1..count {
+        let (len, rest) = data.split_first_chunk::<2>()?;
+        let len = u16::from_be_bytes(*len) as usize;
+        if rest.len() < len {
+            return None;
+        }
+        let (frame, rest) = rest.split_at(len);
+        frames.push(frame);
+        data = rest;
+    }
+    frames.push(data);
+    Some(frames)
+}
+
+// Reads little endian integers off the front of the input; missing bytes read as zero
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data }
+    }
+
+    pub fn bytes(&mut self, n: usize) -> &'a [u8] {
+        let n = n.min(self.data.len());
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        head
+    }
+
+    pub fn u8(&mut self) -> u8 {
+        self.bytes(1).first().copied().unwrap_or(0)
+    }
+
+    pub fn u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        let head = self.bytes(4);
+        buf[..head.len()].copy_from_slice(head);
+        u32::from_le_bytes(buf)
+    }
+
+    pub fn u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        let head = self.bytes(8);
+        buf[..head.len()].copy_from_slice(head);
+        u64::from_le_bytes(buf)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
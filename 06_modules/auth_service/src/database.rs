@@ -3,7 +3,7 @@ pub enum Status {
     Interrupted,
 }
 pub fn connect_to_database() -> Status {
-    return Status::Connected;
+    Status::Connected
 }
 
 pub fn get_user() {
@@ -4,11 +4,77 @@ mod database; // This module handles database connections and user data retrieva
 
 mod auth_utils; // This module handles authentication utilities, including login and logout functions and the Credentials model. The auth_utils module is defined in a separate file, so we can use it here.
 
+pub mod password; // This module estimates password strength and checks passwords against an offline breach list.
+
+pub mod rate_limit; // This module provides token-bucket and sliding-window rate limiting keyed by IP, username or API key.
+
+pub mod service; // This module provides AuthService, a Send + Sync service object with sharded user and session stores.
+
+pub mod webauthn; // This module implements WebAuthn/passkey registration and assertion verification for users who need more than a password.
+
 pub use auth_utils::models::Credentials; // Re-exporting the Credentials struct for easier access in other modules.
-use database::Status;
-pub fn authenticate(creds: Credentials) {
-    if let status::Connected = database::connect_to_database() {
-        println!("Authenticated user: {}", creds.username);
-        auth_utils::login(creds);
-    }
+pub use service::AuthService; // Re-exporting the service object, which is what multithreaded servers share.
+
+// Checks the credentials against `service` and returns a session token. Servers share one
+// AuthService between their request threads (e.g. in an Arc) and call this from any of them.
+// The *_rate_limited helpers below take a SyncRateLimiter, which is shared the same way.
+pub fn authenticate(
+    service: &AuthService,
+    creds: &Credentials,
+) -> Result<String, service::AuthError> {
+    service.login(creds)
+}
+
+// Same as authenticate, but only if `client_key` (an IP, username, API key...) is still within its quota.
+// The returned Decision carries the remaining quota and retry-after for the HTTP layer's headers.
+pub fn authenticate_rate_limited(
+    service: &AuthService,
+    creds: &Credentials,
+    limiter: &rate_limit::SyncRateLimiter,
+    client_key: &str,
+) -> (rate_limit::Decision, Result<String, service::AuthError>) {
+    rate_limited(limiter, &format!("authenticate:{client_key}"), || {
+        authenticate(service, creds)
+    })
+}
+
+// Registers through `service` if `client_key` is still within its quota; limiting registrations per
+// IP slows down bulk account creation. A request over the limit fails with AuthError::RateLimited.
+pub fn register_rate_limited(
+    service: &AuthService,
+    creds: &Credentials,
+    limiter: &rate_limit::SyncRateLimiter,
+    client_key: &str,
+) -> (rate_limit::Decision, Result<(), service::AuthError>) {
+    rate_limited(limiter, &format!("register:{client_key}"), || {
+        service.register(creds)
+    })
+}
+
+// Starts a password reset for `username` if `client_key` is still within its quota, so nobody can
+// flood a user's inbox with reset mails. The token goes to `deliver`, and only for a known user.
+pub fn request_password_reset_rate_limited(
+    service: &AuthService,
+    username: &str,
+    deliver: impl FnOnce(&str),
+    limiter: &rate_limit::SyncRateLimiter,
+    client_key: &str,
+) -> (rate_limit::Decision, Result<(), service::AuthError>) {
+    rate_limited(limiter, &format!("password_reset:{client_key}"), || {
+        service.request_password_reset(username, deliver)
+    })
+}
+
+fn rate_limited<T>(
+    limiter: &rate_limit::SyncRateLimiter,
+    key: &str,
+    action: impl FnOnce() -> Result<T, service::AuthError>,
+) -> (rate_limit::Decision, Result<T, service::AuthError>) {
+    let decision = limiter.check(key);
+    let result = if decision.allowed {
+        action()
+    } else {
+        Err(service::AuthError::RateLimited)
+    };
+    (decision, result)
 }
@@ -0,0 +1,96 @@
+use std::fmt;
+
+pub mod breach; // Offline breached-password lookups against a local k-anonymity range set
+pub mod strength; // zxcvbn-style strength estimation with dictionary, keyboard, repeat and sequence detection
+
+pub use breach::BreachList;
+pub use strength::{Strength, estimate};
+
+// --- POLICY ---
+// What registration and password changes require of a new password
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_chars: usize,
+    pub min_score: u8, // 0..=4, see strength::Strength::score
+    pub breach_list: Option<BreachList>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> PasswordPolicy {
+        PasswordPolicy {
+            min_chars: 8,
+            min_score: 3,
+            breach_list: None,
+        }
+    }
+}
+
+// --- REJECTION ---
+// Why a password was refused, in sentences that can be shown to the user as they are
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordRejected {
+    pub reasons: Vec<String>,
+    pub suggestions: Vec<String>,
+    pub score: u8,
+}
+
+impl fmt::Display for PasswordRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reasons.join(" "))?;
+        if !self.suggestions.is_empty() {
+            write!(f, " {}", self.suggestions.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PasswordRejected {}
+
+impl PasswordPolicy {
+    // `user_inputs` should contain the username and anything else personal we know
+    pub fn check(
+        &self,
+        password: &str,
+        user_inputs: &[&str],
+    ) -> Result<Strength, PasswordRejected> {
+        let strength = estimate(password, user_inputs);
+        let mut reasons = Vec::new();
+
+        let chars = password.chars().count();
+        if chars < self.min_chars {
+            reasons.push(format!(
+                "Password must be at least {} characters long (it has {chars}).",
+                self.min_chars
+            ));
+        }
+        if strength.score < self.min_score {
+            reasons.push(match &strength.warning {
+                Some(warning) => format!("Password is too easy to guess: {warning}"),
+                None => "Password is too easy to guess.".to_string(),
+            });
+        }
+        if let Some(list) = &self.breach_list {
+            match list.times_seen(password) {
+                Ok(0) => {}
+                Ok(times) => reasons.push(format!(
+                    "This password has appeared in a data breach {times} time{} and must not be used.",
+                    if times == 1 { "" } else { "s" }
+                )),
+                // Failing closed: if we cannot check, we cannot say the password is safe
+                Err(e) => reasons.push(format!(
+                    "The breached password list could not be checked ({e}); please try again later."
+                )),
+            }
+        }
+
+        if reasons.is_empty() {
+            Ok(strength)
+        } else {
+            Err(PasswordRejected {
+                reasons,
+                suggestions: strength.suggestions,
+                score: strength.score,
+            })
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha1::{Digest, Sha1};
+
+// --- BREACHED PASSWORD LIST ---
+// An offline copy of a k-anonymity password range set (the Pwned Passwords layout): the SHA-1 of a
+// password is split into a 5 hex char prefix and a 35 char suffix, and each prefix has its own file
+// `<dir>/<PREFIX>.txt` with one `SUFFIX:COUNT` line per breached password. A lookup only reads the
+// one small range file for the prefix, so the full list never has to be loaded into memory.
+#[derive(Debug, Clone)]
+pub struct BreachList {
+    dir: PathBuf,
+}
+
+impl BreachList {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<BreachList> {
+        let dir = dir.as_ref().to_path_buf();
+        if !dir.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("breach list directory {} does not exist", dir.display()),
+            ));
+        }
+        Ok(BreachList { dir })
+    }
+
+    // How many times the password appears in the breach corpus, 0 if never
+    pub fn times_seen(&self, password: &str) -> io::Result<u64> {
+        let hash = sha1_hex(password);
+        let (prefix, suffix) = hash.split_at(5);
+        let range = match fs::read_to_string(self.dir.join(format!("{prefix}.txt"))) {
+            Ok(range) => range,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0), // No breached hash has this prefix
+            Err(e) => return Err(e),
+        };
+        Ok(count_in_range(&range, suffix))
+    }
+
+    pub fn is_breached(&self, password: &str) -> io::Result<bool> {
+        Ok(self.times_seen(password)? > 0)
+    }
+}
+
+// Uppercase hex SHA-1, which is what the range files use
+pub fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect()
+}
+
+// Finds `suffix` in a range file body. Lines look like "0018A45C4D1DEF81644B54AB7F969B88D65:21";
+// padding entries with a count of 0 (added to hide the real response size) count as not found.
+pub fn count_in_range(range: &str, suffix: &str) -> u64 {
+    range
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, count)| count.trim().parse().ok())
+        .unwrap_or(0)
+}
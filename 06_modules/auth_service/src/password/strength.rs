@@ -0,0 +1,547 @@
+use std::collections::HashMap;
+
+// A password strength estimator in the spirit of zxcvbn: instead of counting character classes it
+// looks for the patterns attackers try first (common passwords, keyboard walks, repeats, sequences,
+// years) and estimates how many guesses the cheapest way of covering the whole password would take.
+
+const MAX_CHARS: usize = 128; // Longer passwords are scored on their first 128 chars; they are strong anyway
+
+// Most common passwords and words, most common first. The rank is the number of guesses.
+#[rustfmt::skip]
+const COMMON: &[&str] = &[
+    "123456", "password", "12345678", "qwerty", "123456789", "12345", "1234", "111111", "1234567",
+    "dragon", "123123", "baseball", "abc123", "football", "monkey", "letmein", "696969", "shadow",
+    "master", "666666", "qwertyuiop", "123321", "mustang", "1234567890", "michael", "654321",
+    "superman", "1qaz2wsx", "7777777", "121212", "000000", "qazwsx", "123qwe", "killer", "trustno1",
+    "jordan", "jennifer", "zxcvbnm", "asdfgh", "hunter", "buster", "soccer", "harley", "batman",
+    "andrew", "tigger", "sunshine", "iloveyou", "2000", "charlie", "robert", "thomas", "hockey",
+    "ranger", "daniel", "starwars", "klaster", "112233", "george", "computer", "michelle",
+    "jessica", "pepper", "1111", "zxcvbn", "555555", "11111111", "131313", "freedom", "777777",
+    "pass", "maggie", "159753", "aaaaaa", "ginger", "princess", "joshua", "cheese", "amanda",
+    "summer", "love", "ashley", "nicole", "chelsea", "biteme", "matthew", "access", "yankees",
+    "987654321", "dallas", "austin", "thunder", "taylor", "matrix", "admin", "welcome", "login",
+    "passw0rd", "secret", "hello", "rust", "crab", "root", "user", "test", "guest", "changeme",
+    "default", "qwerty123", "password1", "abc", "letmein1", "winter", "spring", "autumn", "flower",
+    "dog", "cat", "house", "money", "friend", "family", "dream", "angel", "happy", "lucky", "god",
+    "orange", "apple", "banana", "purple", "blue", "green", "yellow", "black", "white", "red",
+    "silver", "golden", "diamond", "tiger", "eagle", "falcon", "phoenix", "wizard", "legend",
+];
+
+const KEYBOARD_ROWS: &[(&str, &str)] = &[
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+// --- RESULT TYPES ---
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    Dictionary {
+        word: String,
+        rank: usize,
+        reversed: bool,
+        l33t: bool,
+        user_input: bool,
+    },
+    Keyboard {
+        turns: usize,
+    },
+    Repeat {
+        base: String,
+        count: usize,
+    },
+    Sequence {
+        ascending: bool,
+    },
+    Year,
+    BruteForce,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub pattern: Pattern,
+    pub token: String,
+    pub start: usize, // Char offsets, not bytes, so "नमस्ते" counts as 6
+    pub end: usize,
+    pub guesses: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+    pub guesses: f64,
+    pub guesses_log10: f64,
+    pub score: u8, // 0 (trivially guessable) ..= 4 (very strong), the same scale as zxcvbn
+    pub sequence: Vec<Match>, // The cheapest way found to cover the password
+    pub warning: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+// --- ESTIMATOR ---
+// `user_inputs` are strings the attacker is likely to try first: username, email, site name...
+pub fn estimate(password: &str, user_inputs: &[&str]) -> Strength {
+    let chars: Vec<char> = password.chars().take(MAX_CHARS).collect();
+    let mut dictionary: HashMap<String, usize> = COMMON
+        .iter()
+        .enumerate()
+        .map(|(rank, word)| (word.to_string(), rank + 1))
+        .collect();
+    for input in user_inputs {
+        let input = input.to_lowercase();
+        if input.chars().count() >= 3 {
+            dictionary.insert(input, 1); // Treated as the very first guess
+        }
+    }
+
+    let mut matches = Vec::new();
+    dictionary_matches(&chars, &dictionary, user_inputs, &mut matches);
+    keyboard_matches(&chars, &mut matches);
+    repeat_matches(&chars, user_inputs, &mut matches);
+    sequence_matches(&chars, &mut matches);
+    year_matches(&chars, &mut matches);
+
+    let sequence = cheapest_sequence(&chars, matches);
+    let guesses = sequence.iter().map(|m| m.guesses).product::<f64>().max(1.0);
+    let guesses_log10 = guesses.log10();
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+    let (warning, suggestions) = feedback(&sequence, score);
+    Strength {
+        guesses,
+        guesses_log10,
+        score,
+        sequence,
+        warning,
+        suggestions,
+    }
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+// Undoes the usual "l33t" substitutions so p@ssw0rd is found as password
+fn unl33t(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' | '|' => 'i',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        '8' => 'b',
+        '9' => 'g',
+        '2' => 'z',
+        _ => c,
+    }
+}
+
+// Bruteforce alphabet size for a single character
+fn cardinality(c: char) -> f64 {
+    if c.is_ascii_lowercase() || c.is_ascii_uppercase() {
+        26.0
+    } else if c.is_ascii_digit() {
+        10.0
+    } else if c.is_ascii() {
+        33.0
+    } else {
+        100.0 // Non-ASCII letters: a rough guess, attackers rarely enumerate them
+    }
+}
+
+// --- DICTIONARY ---
+fn dictionary_matches(
+    chars: &[char],
+    dictionary: &HashMap<String, usize>,
+    user_inputs: &[&str],
+    matches: &mut Vec<Match>,
+) {
+    let is_user_input = |word: &str| user_inputs.iter().any(|u| u.to_lowercase() == word);
+    // No match can be longer than the longest word or user input, so longer tokens are skipped
+    let longest = dictionary
+        .keys()
+        .map(|w| w.chars().count())
+        .max()
+        .unwrap_or(0);
+    for i in 0..chars.len() {
+        for j in i + 3..=chars.len().min(i + longest) {
+            let token = &chars[i..j];
+            let plain: String = token.iter().map(|&c| lower(c)).collect();
+            let reversed: String = plain.chars().rev().collect();
+            let subbed: String = token.iter().map(|&c| unl33t(lower(c))).collect();
+
+            let mut candidates = vec![(plain.clone(), false, false), (reversed, true, false)];
+            if subbed != plain {
+                candidates.push((subbed, false, true));
+            }
+            for (word, is_reversed, is_l33t) in candidates {
+                if let Some(&rank) = dictionary.get(&word) {
+                    let mut guesses = rank as f64 * uppercase_variations(token);
+                    if is_reversed {
+                        guesses *= 2.0;
+                    }
+                    if is_l33t {
+                        guesses *= l33t_variations(token);
+                    }
+                    matches.push(Match {
+                        pattern: Pattern::Dictionary {
+                            user_input: is_user_input(&word),
+                            word,
+                            rank,
+                            reversed: is_reversed,
+                            l33t: is_l33t,
+                        },
+                        token: token.iter().collect(),
+                        start: i,
+                        end: j,
+                        guesses: guesses.max(1.0),
+                    });
+                }
+            }
+
+            // Any long enough piece of a user input counts too, e.g. most of the old password
+            if j - i >= 4 {
+                for input in user_inputs {
+                    let input = input.to_lowercase();
+                    if input != plain && input.contains(&plain) {
+                        matches.push(Match {
+                            pattern: Pattern::Dictionary {
+                                word: plain.clone(),
+                                rank: 1,
+                                reversed: false,
+                                l33t: false,
+                                user_input: true,
+                            },
+                            token: token.iter().collect(),
+                            start: i,
+                            end: j,
+                            // Which piece of the input was used has to be guessed too
+                            guesses: input.chars().count() as f64 * uppercase_variations(token),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+// "password" -> 1, "Password" / "PASSWORD" -> 2, anything else -> combinations of the mixed case letters
+fn uppercase_variations(token: &[char]) -> f64 {
+    let upper = token.iter().filter(|c| c.is_uppercase()).count();
+    let letters = token.iter().filter(|c| c.is_alphabetic()).count();
+    if upper == 0 {
+        1.0
+    } else if upper == letters || (upper == 1 && token[0].is_uppercase()) {
+        2.0
+    } else {
+        (1..=upper.min(letters - upper).max(1))
+            .map(|k| binomial(letters, k))
+            .sum::<f64>()
+            .max(2.0)
+    }
+}
+
+fn l33t_variations(token: &[char]) -> f64 {
+    let subbed = token
+        .iter()
+        .filter(|&&c| unl33t(lower(c)) != lower(c))
+        .count();
+    2f64.powi(subbed as i32).max(2.0)
+}
+
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+// --- KEYBOARD PATTERNS ---
+fn key_position(c: char) -> Option<(i32, i32)> {
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, (plain, shifted))| {
+            plain
+                .chars()
+                .position(|k| k == c)
+                .or_else(|| shifted.chars().position(|k| k == c))
+                .map(|col| (row as i32, col as i32))
+        })
+}
+
+// Rows are staggered: a key touches the keys at the same and next column in the row above
+fn adjacent_direction(a: char, b: char) -> Option<(i32, i32)> {
+    let (ra, ca) = key_position(a)?;
+    let (rb, cb) = key_position(b)?;
+    let direction = (rb - ra, cb - ca);
+    match direction {
+        (0, -1) | (0, 1) | (-1, 0) | (-1, 1) | (1, 0) | (1, -1) => Some(direction),
+        _ => None,
+    }
+}
+
+fn keyboard_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let mut i = 0;
+    while i < chars.len() {
+        let mut j = i + 1;
+        let mut turns = 0;
+        let mut last_direction = None;
+        while j < chars.len() {
+            match adjacent_direction(chars[j - 1], chars[j]) {
+                Some(direction) => {
+                    if last_direction.is_some_and(|d| d != direction) {
+                        turns += 1;
+                    }
+                    last_direction = Some(direction);
+                    j += 1;
+                }
+                None => break,
+            }
+        }
+        if j - i >= 3 {
+            // ~47 starting keys, ~4 neighbours per turn, and the length has to be guessed too
+            let guesses = 47.0 * 4f64.powi(turns as i32) * (j - i) as f64;
+            matches.push(Match {
+                pattern: Pattern::Keyboard { turns },
+                token: chars[i..j].iter().collect(),
+                start: i,
+                end: j,
+                guesses,
+            });
+        }
+        i = j.max(i + 1);
+    }
+}
+
+// --- REPEATS ---
+// "aaaa" (base "a" x4) and "abcabc" (base "abc" x2). At each position only the longest repeat is
+// kept and the scan jumps past it, which keeps the recursive estimate of the base cheap.
+fn repeat_matches(chars: &[char], user_inputs: &[&str], matches: &mut Vec<Match>) {
+    let mut i = 0;
+    while i < chars.len() {
+        let mut longest: Option<(usize, usize)> = None; // (base length, repeat count)
+        for base_len in 1..=(chars.len() - i) / 2 {
+            let base = &chars[i..i + base_len];
+            let mut count = 1;
+            while chars[i + count * base_len..].starts_with(base) {
+                count += 1;
+            }
+            let span = count * base_len;
+            if count >= 2 && span >= 3 && longest.is_none_or(|(b, c)| span > b * c) {
+                longest = Some((base_len, count));
+            }
+        }
+        match longest {
+            Some((base_len, count)) => {
+                let end = i + base_len * count;
+                let base: String = chars[i..i + base_len].iter().collect();
+                let base_guesses = estimate(&base, user_inputs).guesses;
+                matches.push(Match {
+                    pattern: Pattern::Repeat {
+                        base: base.clone(),
+                        count,
+                    },
+                    token: chars[i..end].iter().collect(),
+                    start: i,
+                    end,
+                    guesses: base_guesses * count as f64,
+                });
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+}
+
+// --- SEQUENCES ---
+// "abcd", "9876", "acegi": a constant step of 1 or 2 code points
+fn sequence_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let mut i = 0;
+    while i + 2 < chars.len() {
+        let step = chars[i + 1] as i64 - chars[i] as i64;
+        let mut j = i + 1;
+        if matches!(step.abs(), 1 | 2) {
+            while j < chars.len() && chars[j] as i64 - chars[j - 1] as i64 == step {
+                j += 1;
+            }
+        }
+        if j - i >= 3 {
+            let first = chars[i];
+            let start_guesses = if matches!(first, 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+                4.0 // Obvious starting points
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            let direction = if step > 0 { 1.0 } else { 2.0 };
+            matches.push(Match {
+                pattern: Pattern::Sequence {
+                    ascending: step > 0,
+                },
+                token: chars[i..j].iter().collect(),
+                start: i,
+                end: j,
+                guesses: start_guesses * direction * (j - i) as f64,
+            });
+            i = j - 1;
+        } else {
+            i += 1;
+        }
+    }
+}
+
+// --- YEARS ---
+fn year_matches(chars: &[char], matches: &mut Vec<Match>) {
+    for i in 0..chars.len().saturating_sub(3) {
+        let token: String = chars[i..i + 4].iter().collect();
+        if let Ok(year) = token.parse::<u32>()
+            && (1900..=2049).contains(&year)
+        {
+            matches.push(Match {
+                pattern: Pattern::Year,
+                token,
+                start: i,
+                end: i + 4,
+                guesses: 150.0, // Size of the range of years people actually use
+            });
+        }
+    }
+}
+
+// --- CHEAPEST COVER ---
+// Dynamic programming over char positions: best[j] is the fewest guesses needed for chars[..j],
+// either extending best[j - 1] by one brute-forced char or jumping over a whole match that ends at j.
+fn cheapest_sequence(chars: &[char], matches: Vec<Match>) -> Vec<Match> {
+    let n = chars.len();
+    let mut best = vec![f64::INFINITY; n + 1];
+    let mut step: Vec<Option<Match>> = vec![None; n + 1]; // How position j was reached (None = brute force)
+    best[0] = 1.0;
+    let mut by_end: Vec<Vec<Match>> = vec![Vec::new(); n + 1];
+    for m in matches {
+        by_end[m.end].push(m);
+    }
+    for j in 1..=n {
+        let brute = best[j - 1] * cardinality(chars[j - 1]);
+        best[j] = brute;
+        step[j] = None;
+        for m in &by_end[j] {
+            // Each extra pattern costs a little: the attacker must also guess how the pieces fit
+            let cost = best[m.start] * m.guesses * if m.start > 0 { 2.0 } else { 1.0 };
+            if cost < best[j] {
+                best[j] = cost;
+                step[j] = Some(m.clone());
+            }
+        }
+    }
+
+    // Walk back from the end, merging consecutive brute-forced chars into one match
+    let mut sequence = Vec::new();
+    let mut j = n;
+    while j > 0 {
+        match step[j].take() {
+            Some(m) => {
+                j = m.start;
+                sequence.push(m);
+            }
+            None => {
+                let end = j;
+                while j > 0 && step[j].is_none() {
+                    j -= 1;
+                }
+                let token = &chars[j..end];
+                sequence.push(Match {
+                    pattern: Pattern::BruteForce,
+                    token: token.iter().collect(),
+                    start: j,
+                    end,
+                    guesses: token.iter().map(|&c| cardinality(c)).product(),
+                });
+            }
+        }
+    }
+    sequence.reverse();
+    sequence
+}
+
+// --- FEEDBACK ---
+fn feedback(sequence: &[Match], score: u8) -> (Option<String>, Vec<String>) {
+    if sequence.is_empty() {
+        return (
+            None,
+            vec!["Use a few words, avoid common phrases.".to_string()],
+        );
+    }
+    if score > 2 {
+        return (None, Vec::new());
+    }
+    let mut suggestions = vec!["Add another word or two. Uncommon words are better.".to_string()];
+    // The longest pattern explains most of the weakness
+    let worst = sequence
+        .iter()
+        .filter(|m| m.pattern != Pattern::BruteForce)
+        .max_by_key(|m| m.end - m.start);
+    let warning = match worst.map(|m| &m.pattern) {
+        Some(Pattern::Dictionary {
+            word,
+            rank,
+            reversed,
+            l33t,
+            user_input,
+        }) => {
+            if *reversed {
+                suggestions.push("Reversed words aren't much harder to guess.".to_string());
+            }
+            if *l33t {
+                suggestions.push(
+                    "Predictable substitutions like '@' instead of 'a' don't help very much."
+                        .to_string(),
+                );
+            }
+            if worst.is_some_and(|m| m.token.chars().any(char::is_uppercase)) {
+                suggestions.push("Capitalization doesn't help very much.".to_string());
+            }
+            Some(if *user_input {
+                "Passwords containing your username or personal details are easy to guess."
+                    .to_string()
+            } else if sequence.len() == 1 && *rank <= 10 {
+                "This is a top-10 common password.".to_string()
+            } else if sequence.len() == 1 {
+                "This is a very common password.".to_string()
+            } else {
+                format!("\"{word}\" is a common word or password and easy to guess.")
+            })
+        }
+        Some(Pattern::Keyboard { turns }) => {
+            suggestions.push("Use a longer keyboard pattern with more turns.".to_string());
+            Some(if *turns == 0 {
+                "Straight rows of keys are easy to guess.".to_string()
+            } else {
+                "Short keyboard patterns are easy to guess.".to_string()
+            })
+        }
+        Some(Pattern::Repeat { base, .. }) => {
+            suggestions.push("Avoid repeated words and characters.".to_string());
+            Some(if base.chars().count() == 1 {
+                "Repeats like \"aaa\" are easy to guess.".to_string()
+            } else {
+                format!(
+                    "Repeats like \"{base}{base}\" are only slightly harder to guess than \"{base}\"."
+                )
+            })
+        }
+        Some(Pattern::Sequence { .. }) => {
+            suggestions.push("Avoid sequences.".to_string());
+            Some("Sequences like abc or 6543 are easy to guess.".to_string())
+        }
+        Some(Pattern::Year) => {
+            suggestions.push("Avoid years that are associated with you.".to_string());
+            Some("Years are easy to guess.".to_string())
+        }
+        Some(Pattern::BruteForce) | None => None,
+    };
+    (warning, suggestions)
+}
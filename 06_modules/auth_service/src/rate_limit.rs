@@ -0,0 +1,395 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub mod file_backend; // Backend that keeps limiter state in a file so it survives restarts
+
+pub use file_backend::FileBackend;
+
+use crate::service::sharded::ShardedMap;
+
+// The limiter drops idle keys after this many checks, so keys seen once do not pile up forever
+const SWEEP_EVERY: u32 = 1024;
+
+// --- ALGORITHMS ---
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    // Allows bursts of up to `capacity` requests, then `refill_per_sec` requests per second
+    TokenBucket { capacity: u32, refill_per_sec: f64 },
+    // Allows at most `limit` requests in any rolling `window`, remembering the time of each request
+    SlidingWindowLog { limit: u32, window: Duration },
+}
+
+// The per-key state a backend has to remember. Times are milliseconds since the UNIX epoch
+// (not Instant) so the state still makes sense after being written to disk and read back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum State {
+    Bucket { tokens: f64, last_ms: u64 },
+    Log { hits_ms: VecDeque<u64> },
+}
+
+// --- DECISION ---
+// The answer for one request, with everything an HTTP layer needs for RateLimit-* / Retry-After headers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    pub retry_after: Option<Duration>, // Only set when the request was rejected
+    pub reset_after: Duration,         // Until the quota is completely full again
+}
+
+impl Decision {
+    // Header names follow the IETF RateLimit header draft; values are whole seconds, rounded up
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![
+            ("RateLimit-Limit", self.limit.to_string()),
+            ("RateLimit-Remaining", self.remaining.to_string()),
+            ("RateLimit-Reset", ceil_secs(self.reset_after).to_string()),
+        ];
+        if let Some(retry) = self.retry_after {
+            headers.push(("Retry-After", ceil_secs(retry).to_string()));
+        }
+        headers
+    }
+}
+
+fn ceil_secs(d: Duration) -> u64 {
+    d.as_millis().div_ceil(1000) as u64
+}
+
+// --- BACKENDS ---
+// Where limiter state lives. Keys are arbitrary strings: an IP, a username, an API key...
+pub trait Backend {
+    fn load(&mut self, key: &str) -> Option<State>;
+    fn store(&mut self, key: &str, state: State);
+    fn remove(&mut self, key: &str);
+    // Keeps only the states `keep` returns true for
+    fn retain(&mut self, keep: &mut dyn FnMut(&str, &State) -> bool);
+}
+
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    states: HashMap<String, State>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> MemoryBackend {
+        MemoryBackend::default()
+    }
+
+    // Number of keys with state
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn load(&mut self, key: &str) -> Option<State> {
+        self.states.get(key).cloned()
+    }
+
+    fn store(&mut self, key: &str, state: State) {
+        self.states.insert(key.to_string(), state);
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.states.remove(key);
+    }
+
+    fn retain(&mut self, keep: &mut dyn FnMut(&str, &State) -> bool) {
+        self.states.retain(|key, state| keep(key, state));
+    }
+}
+
+// --- RATE LIMITER ---
+#[derive(Debug)]
+pub struct RateLimiter<B: Backend> {
+    algorithm: Algorithm,
+    backend: B,
+    checks_since_sweep: u32,
+}
+
+impl<B: Backend> RateLimiter<B> {
+    pub fn new(algorithm: Algorithm, backend: B) -> RateLimiter<B> {
+        RateLimiter {
+            algorithm,
+            backend,
+            checks_since_sweep: 0,
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    // Counts one request for `key` against the wall clock
+    pub fn check(&mut self, key: &str) -> Decision {
+        self.check_at(key, now_ms())
+    }
+
+    // Same as check, with the current time passed in (milliseconds since the UNIX epoch)
+    pub fn check_at(&mut self, key: &str, now_ms: u64) -> Decision {
+        let previous = self.backend.load(key);
+        let (decision, state) = decide(self.algorithm, previous, now_ms);
+        match state {
+            Some(state) => self.backend.store(key, state),
+            None => self.backend.remove(key),
+        }
+        self.checks_since_sweep += 1;
+        if self.checks_since_sweep >= SWEEP_EVERY {
+            self.sweep_at(now_ms);
+        }
+        decision
+    }
+
+    // Forgets a key, e.g. after a successful password reset
+    pub fn reset(&mut self, key: &str) {
+        self.backend.remove(key);
+    }
+
+    // Drops every idle key and returns how many there were. check_at does this on its own every
+    // SWEEP_EVERY checks; forgetting an idle key never changes a later decision.
+    pub fn sweep(&mut self) -> usize {
+        self.sweep_at(now_ms())
+    }
+
+    pub fn sweep_at(&mut self, now_ms: u64) -> usize {
+        let algorithm = self.algorithm;
+        let mut removed = 0;
+        self.backend.retain(&mut |_, state| {
+            let idle = is_idle(algorithm, state, now_ms);
+            removed += usize::from(idle);
+            !idle
+        });
+        self.checks_since_sweep = 0;
+        removed
+    }
+}
+
+// --- SHARED RATE LIMITER ---
+// A RateLimiter for many threads at once: check takes &self, so servers can share one limiter
+// (e.g. in an Arc) like they share the AuthService. State lives in memory in a ShardedMap, and each
+// check is a read-modify-write under its key's shard lock, so concurrent requests from one client
+// are never allowed more than the quota while other clients rarely wait.
+#[derive(Debug)]
+pub struct SyncRateLimiter {
+    algorithm: Algorithm,
+    states: ShardedMap<String, State>,
+    checks_since_sweep: AtomicU32,
+}
+
+impl SyncRateLimiter {
+    pub fn new(algorithm: Algorithm, shards: usize) -> SyncRateLimiter {
+        SyncRateLimiter {
+            algorithm,
+            states: ShardedMap::new(shards),
+            checks_since_sweep: AtomicU32::new(0),
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    // Number of keys with state
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    pub fn check(&self, key: &str) -> Decision {
+        self.check_at(key, now_ms())
+    }
+
+    pub fn check_at(&self, key: &str, now_ms: u64) -> Decision {
+        let algorithm = self.algorithm;
+        let decision = self.states.update(key.to_string(), |entry| match entry {
+            Entry::Occupied(mut entry) => {
+                let (decision, state) = decide(algorithm, Some(entry.get().clone()), now_ms);
+                match state {
+                    Some(state) => *entry.get_mut() = state,
+                    None => {
+                        entry.remove();
+                    }
+                }
+                decision
+            }
+            Entry::Vacant(entry) => {
+                let (decision, state) = decide(algorithm, None, now_ms);
+                if let Some(state) = state {
+                    entry.insert(state);
+                }
+                decision
+            }
+        });
+        // Sweeping outside the shard lock; two threads sweeping at once only do the work twice
+        if self.checks_since_sweep.fetch_add(1, Ordering::Relaxed) + 1 >= SWEEP_EVERY {
+            self.sweep_at(now_ms);
+        }
+        decision
+    }
+
+    pub fn reset(&self, key: &str) {
+        self.states.remove(&key.to_string());
+    }
+
+    pub fn sweep(&self) -> usize {
+        self.sweep_at(now_ms())
+    }
+
+    pub fn sweep_at(&self, now_ms: u64) -> usize {
+        self.checks_since_sweep.store(0, Ordering::Relaxed);
+        let algorithm = self.algorithm;
+        self.states
+            .retain(|_, state| !is_idle(algorithm, state, now_ms))
+    }
+}
+
+// Runs the algorithm for one request. The new state is None when it is back to where a new key
+// starts (full bucket, empty log), so it need not be kept.
+fn decide(algorithm: Algorithm, previous: Option<State>, now_ms: u64) -> (Decision, Option<State>) {
+    let (decision, state) = match algorithm {
+        Algorithm::TokenBucket {
+            capacity,
+            refill_per_sec,
+        } => token_bucket(capacity, refill_per_sec, previous, now_ms),
+        Algorithm::SlidingWindowLog { limit, window } => {
+            sliding_window_log(limit, window, previous, now_ms)
+        }
+    };
+    let idle = is_idle(algorithm, &state, now_ms);
+    (decision, (!idle).then_some(state))
+}
+
+// True if `state` gives the same decisions as no state at all: the bucket has refilled, or every
+// logged request has left the window
+fn is_idle(algorithm: Algorithm, state: &State, now_ms: u64) -> bool {
+    match (algorithm, state) {
+        (
+            Algorithm::TokenBucket {
+                capacity,
+                refill_per_sec,
+            },
+            State::Bucket { tokens, last_ms },
+        ) => {
+            let elapsed = now_ms.saturating_sub(*last_ms) as f64;
+            tokens + elapsed * refill_per_sec / 1000.0 >= capacity as f64
+        }
+        (Algorithm::SlidingWindowLog { window, .. }, State::Log { hits_ms }) => {
+            let window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX);
+            hits_ms
+                .back()
+                .is_none_or(|&newest| now_ms.saturating_sub(newest) >= window_ms)
+        }
+        _ => true, // Left over from a different algorithm, which check_at ignores anyway
+    }
+}
+
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn token_bucket(
+    capacity: u32,
+    refill_per_sec: f64,
+    previous: Option<State>,
+    now_ms: u64,
+) -> (Decision, State) {
+    let capacity_f = capacity as f64;
+    let refill_per_ms = refill_per_sec / 1000.0;
+    let mut tokens = match previous {
+        Some(State::Bucket { tokens, last_ms }) => {
+            // Clocks can go backwards (NTP), so never refill with a negative elapsed time
+            let elapsed = now_ms.saturating_sub(last_ms) as f64;
+            (tokens + elapsed * refill_per_ms).min(capacity_f)
+        }
+        _ => capacity_f, // New key, or state left over from a different algorithm
+    };
+
+    let allowed = tokens >= 1.0;
+    if allowed {
+        tokens -= 1.0;
+    }
+    // Time until `needed` more tokens have dripped in
+    let wait = |needed: f64| -> Duration {
+        if needed <= 0.0 {
+            Duration::ZERO
+        } else if refill_per_ms <= 0.0 {
+            Duration::MAX
+        } else {
+            Duration::from_millis((needed / refill_per_ms).ceil() as u64)
+        }
+    };
+    let decision = Decision {
+        allowed,
+        limit: capacity,
+        remaining: tokens.floor() as u32,
+        retry_after: (!allowed).then(|| wait(1.0 - tokens)),
+        reset_after: wait(capacity_f - tokens),
+    };
+    (
+        decision,
+        State::Bucket {
+            tokens,
+            last_ms: now_ms,
+        },
+    )
+}
+
+fn sliding_window_log(
+    limit: u32,
+    window: Duration,
+    previous: Option<State>,
+    now_ms: u64,
+) -> (Decision, State) {
+    let window_ms = u64::try_from(window.as_millis()).unwrap_or(u64::MAX);
+    let mut hits_ms = match previous {
+        Some(State::Log { hits_ms }) => hits_ms,
+        _ => VecDeque::new(),
+    };
+    // Drop requests that have slid out of the window
+    while let Some(&oldest) = hits_ms.front() {
+        if now_ms.saturating_sub(oldest) >= window_ms {
+            hits_ms.pop_front();
+        } else {
+            break;
+        }
+    }
+
+    let allowed = (hits_ms.len() as u32) < limit;
+    if allowed {
+        hits_ms.push_back(now_ms); // Rejected requests are not logged, or a client could lock itself out forever
+    }
+    // When the request at `index` leaves the window a slot frees up
+    let expires_in = |index: usize| -> Duration {
+        hits_ms
+            .get(index)
+            .map(|&t| Duration::from_millis(window_ms.saturating_sub(now_ms.saturating_sub(t))))
+            .unwrap_or(Duration::ZERO)
+    };
+    let used = hits_ms.len();
+    let decision = Decision {
+        allowed,
+        limit,
+        remaining: limit.saturating_sub(used as u32),
+        retry_after: (!allowed).then(|| expires_in(used - limit as usize)),
+        reset_after: expires_in(used.saturating_sub(1)),
+    };
+    (decision, State::Log { hits_ms })
+}
@@ -0,0 +1,215 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::{Backend, State};
+
+// Stale lines the file may collect beyond two per live key before it is compacted
+const COMPACT_SLACK: usize = 1024;
+
+// --- FILE BACKEND ---
+// Keeps every key's state in memory and appends one line to the file per change, so a request
+// costs one short write. Later lines for a key replace earlier ones:
+//   <hex key> bucket <tokens> <last_ms>
+//   <hex key> log <t1>,<t2>,...
+//   <hex key> removed
+// Keys are hex encoded so they can contain spaces, newlines or any other character.
+// The file is rewritten with one line per key (compacted) on the first write after opening, when
+// stale lines start to outnumber live ones, and after a sweep removed keys.
+#[derive(Debug)]
+pub struct FileBackend {
+    path: PathBuf,
+    states: HashMap<String, State>,
+    log: Option<File>, // Open for appending once the file has been compacted
+    lines: usize,      // Lines in the file, live or stale
+    last_error: Option<String>, // Backend methods cannot fail, so write errors are kept here
+}
+
+impl FileBackend {
+    // Opens (or creates on first write) the state file; a missing file just means no state yet
+    pub fn open(path: impl AsRef<Path>) -> io::Result<FileBackend> {
+        let path = path.as_ref().to_path_buf();
+        let states = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                parse(&contents).map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))?
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(FileBackend {
+            path,
+            states,
+            log: None,
+            lines: 0,
+            last_error: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // The error from the most recent failed write, if any
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    // Number of keys with state
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    // Records one change, which is already applied to `states`
+    fn append(&mut self, line: String) {
+        let stale = self.lines.saturating_sub(self.states.len());
+        let Some(log) = self
+            .log
+            .as_mut()
+            .filter(|_| stale < self.states.len() + COMPACT_SLACK)
+        else {
+            self.compact();
+            return;
+        };
+        match log.write_all(line.as_bytes()) {
+            Ok(()) => {
+                self.lines += 1;
+                self.last_error = None;
+            }
+            Err(e) => {
+                self.last_error = Some(e.to_string());
+                self.log = None; // The next write rewrites the file from memory
+            }
+        }
+    }
+
+    // Writes to a temporary file first and renames it, so a crash never leaves half a file behind
+    fn compact(&mut self) {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let result = fs::write(&tmp, serialize(&self.states))
+            .and_then(|_| fs::rename(&tmp, &self.path))
+            .and_then(|_| OpenOptions::new().append(true).open(&self.path));
+        self.lines = self.states.len();
+        self.last_error = result.as_ref().err().map(|e| e.to_string());
+        self.log = result.ok();
+    }
+}
+
+impl Backend for FileBackend {
+    fn load(&mut self, key: &str) -> Option<State> {
+        self.states.get(key).cloned()
+    }
+
+    fn store(&mut self, key: &str, state: State) {
+        let line = state_line(key, &state);
+        self.states.insert(key.to_string(), state);
+        self.append(line);
+    }
+
+    fn remove(&mut self, key: &str) {
+        if self.states.remove(key).is_some() {
+            self.append(format!("{} removed\n", hex_encode(key.as_bytes())));
+        }
+    }
+
+    fn retain(&mut self, keep: &mut dyn FnMut(&str, &State) -> bool) {
+        let before = self.states.len();
+        self.states.retain(|key, state| keep(key, state));
+        if self.states.len() != before {
+            self.compact();
+        }
+    }
+}
+
+fn state_line(key: &str, state: &State) -> String {
+    let key = hex_encode(key.as_bytes());
+    match state {
+        State::Bucket { tokens, last_ms } => format!("{key} bucket {tokens} {last_ms}\n"),
+        State::Log { hits_ms } => {
+            let hits: Vec<String> = hits_ms.iter().map(|t| t.to_string()).collect();
+            format!("{key} log {}\n", hits.join(","))
+        }
+    }
+}
+
+fn serialize(states: &HashMap<String, State>) -> String {
+    let mut keys: Vec<&String> = states.keys().collect();
+    keys.sort(); // Stable output makes the file diffable
+    keys.into_iter()
+        .map(|key| state_line(key, &states[key]))
+        .collect()
+}
+
+fn parse(contents: &str) -> Result<HashMap<String, State>, String> {
+    let mut states = HashMap::new();
+    let count = contents.lines().count();
+    for (number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(line) {
+            Ok((key, Some(state))) => {
+                states.insert(key, state);
+            }
+            Ok((key, None)) => {
+                states.remove(&key);
+            }
+            // A crash in the middle of an append leaves the last line cut short: it never happened
+            Err(_) if number + 1 == count && !contents.ends_with('\n') => {}
+            Err(what) => return Err(format!("line {}: {what}", number + 1)),
+        }
+    }
+    Ok(states)
+}
+
+// One line of the file: the key and its state, or None if the key was removed
+fn parse_line(line: &str) -> Result<(String, Option<State>), &'static str> {
+    let mut parts = line.split(' ');
+    let key = parts
+        .next()
+        .and_then(hex_decode)
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or("invalid key")?;
+    let state = match parts.next() {
+        Some("bucket") => {
+            let tokens = parts.next().and_then(|t| t.parse().ok());
+            let last_ms = parts.next().and_then(|t| t.parse().ok());
+            match (tokens, last_ms) {
+                (Some(tokens), Some(last_ms)) => Some(State::Bucket { tokens, last_ms }),
+                _ => return Err("invalid bucket state"),
+            }
+        }
+        Some("log") => {
+            let hits_ms = match parts.next() {
+                None | Some("") => VecDeque::new(),
+                Some(list) => list
+                    .split(',')
+                    .map(|t| t.parse().map_err(|_| "invalid timestamp"))
+                    .collect::<Result<VecDeque<u64>, _>>()?,
+            };
+            Some(State::Log { hits_ms })
+        }
+        Some("removed") => None,
+        _ => return Err("unknown state kind"),
+    };
+    Ok((key, state))
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,365 @@
+use std::collections::hash_map::Entry;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use sha2::Sha256;
+
+use crate::Credentials;
+use crate::password::{PasswordPolicy, PasswordRejected};
+
+pub mod sharded; // HashMap split into separately locked shards
+
+use sharded::ShardedMap;
+
+// How long a password reset token can be used
+const RESET_TOKEN_TTL: Duration = Duration::from_secs(30 * 60);
+
+// --- CONFIGURATION ---
+#[derive(Debug, Clone)]
+pub struct ServiceConfig {
+    pub password_policy: PasswordPolicy,
+    pub max_failed_attempts: u32, // Wrong passwords in a row before the account locks
+    pub pbkdf2_rounds: u32,       // PBKDF2-HMAC-SHA256 work factor for stored passwords
+    pub shards: usize,            // Lock shards per store; more shards = less contention
+}
+
+impl Default for ServiceConfig {
+    fn default() -> ServiceConfig {
+        ServiceConfig {
+            password_policy: PasswordPolicy::default(),
+            max_failed_attempts: 5,
+            pbkdf2_rounds: 600_000, // OWASP recommendation for PBKDF2-HMAC-SHA256
+            shards: 64,
+        }
+    }
+}
+
+// --- ERRORS ---
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    UserExists,
+    InvalidCredentials, // Same answer for unknown users and wrong passwords
+    Locked,
+    WeakPassword(PasswordRejected),
+    Randomness,
+    RateLimited, // Too many requests from one client; the rate limiter's Decision says when to retry
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::UserExists => write!(f, "a user with this name already exists"),
+            AuthError::InvalidCredentials => write!(f, "invalid username or password"),
+            AuthError::Locked => write!(f, "account locked after too many failed attempts"),
+            AuthError::WeakPassword(rejected) => write!(f, "{rejected}"),
+            AuthError::Randomness => write!(f, "could not generate random bytes"),
+            AuthError::RateLimited => write!(f, "too many requests, try again later"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl From<PasswordRejected> for AuthError {
+    fn from(rejected: PasswordRejected) -> AuthError {
+        AuthError::WeakPassword(rejected)
+    }
+}
+
+// --- STORED STATE ---
+#[derive(Debug, Clone)]
+struct UserRecord {
+    salt: [u8; 16],
+    hash: [u8; 32],
+    failed_attempts: u32,
+    // Bumped on revoke and password change; sessions from an older epoch are no longer valid,
+    // even one created by a login that raced with the revoke
+    session_epoch: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    username: String,
+    epoch: u64,
+}
+
+#[derive(Debug, Clone)]
+struct PasswordReset {
+    username: String,
+    epoch: u64, // Like sessions, a reset token dies when the user's epoch moves on
+    issued: Instant,
+}
+
+// --- AUTH SERVICE ---
+// One instance is shared by all request threads (e.g. in an Arc). Every method takes &self;
+// users and sessions live in sharded maps, and each read-modify-write of a user record (failure
+// counter, epoch, password) happens under that user's shard lock, so concurrent calls never lose updates.
+// Password hashing, the slow part, runs outside any lock.
+#[derive(Debug)]
+pub struct AuthService {
+    config: ServiceConfig,
+    users: ShardedMap<String, UserRecord>,
+    sessions: ShardedMap<String, Session>,
+    resets: ShardedMap<String, PasswordReset>,
+}
+
+// Compile-time proof that the service can be shared across threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<AuthService>();
+};
+
+impl AuthService {
+    pub fn new(config: ServiceConfig) -> AuthService {
+        AuthService {
+            users: ShardedMap::new(config.shards),
+            sessions: ShardedMap::new(config.shards),
+            resets: ShardedMap::new(config.shards),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &ServiceConfig {
+        &self.config
+    }
+
+    // --- REGISTRATION ---
+    pub fn register(&self, creds: &Credentials) -> Result<(), AuthError> {
+        self.config
+            .password_policy
+            .check(&creds.password, &[&creds.username])?;
+        let salt = random_bytes::<16>()?;
+        let hash = self.hash_password(&creds.password, &salt);
+        self.users
+            .update(creds.username.clone(), |entry| match entry {
+                Entry::Occupied(_) => Err(AuthError::UserExists),
+                Entry::Vacant(slot) => {
+                    slot.insert(UserRecord {
+                        salt,
+                        hash,
+                        failed_attempts: 0,
+                        session_epoch: 0,
+                    });
+                    Ok(())
+                }
+            })
+    }
+
+    // --- LOGIN / LOGOUT ---
+    // Returns a session token on success
+    pub fn login(&self, creds: &Credentials) -> Result<String, AuthError> {
+        let epoch = self.verify_password(&creds.username, &creds.password)?;
+        let token = URL_SAFE_NO_PAD.encode(random_bytes::<32>()?);
+        self.sessions.insert(
+            token.clone(),
+            Session {
+                username: creds.username.clone(),
+                epoch,
+            },
+        );
+        Ok(token)
+    }
+
+    // Returns false if the token was not (or no longer) a live session
+    pub fn logout(&self, token: &str) -> bool {
+        self.sessions.remove(&token.to_string()).is_some()
+    }
+
+    // The user a token belongs to, if the session is still valid
+    pub fn validate(&self, token: &str) -> Option<String> {
+        let session = self.sessions.get_cloned(&token.to_string())?;
+        let current_epoch = self
+            .users
+            .read_with(&session.username, |user| user.map(|u| u.session_epoch))?;
+        (session.epoch == current_epoch).then_some(session.username)
+    }
+
+    // Ends every session of a user and returns how many were removed
+    pub fn revoke(&self, username: &str) -> usize {
+        self.users.update(username.to_string(), |entry| {
+            if let Entry::Occupied(mut user) = entry {
+                user.get_mut().session_epoch += 1;
+            }
+        });
+        self.sessions
+            .retain(|_, session| session.username != username)
+    }
+
+    // --- PASSWORD CHANGE ---
+    // Needs the current password; all existing sessions are revoked afterwards
+    pub fn change_password(
+        &self,
+        creds: &Credentials,
+        new_password: &str,
+    ) -> Result<(), AuthError> {
+        self.verify_password(&creds.username, &creds.password)?;
+        self.config
+            .password_policy
+            .check(new_password, &[&creds.username, &creds.password])?;
+        let salt = random_bytes::<16>()?;
+        let hash = self.hash_password(new_password, &salt);
+        self.users
+            .update(creds.username.clone(), |entry| match entry {
+                Entry::Occupied(mut user) => {
+                    let user = user.get_mut();
+                    user.salt = salt;
+                    user.hash = hash;
+                    user.session_epoch += 1;
+                    Ok(())
+                }
+                // Removed between the check and now
+                Entry::Vacant(_) => Err(AuthError::InvalidCredentials),
+            })?;
+        self.sessions
+            .retain(|_, session| session.username != creds.username);
+        Ok(())
+    }
+
+    // --- PASSWORD RESET ---
+    // For users who forgot their password: the token goes to them out of band through `deliver`
+    // (e-mail, SMS...) and is good for one reset within RESET_TOKEN_TTL, unless the password
+    // changes first. Unknown usernames get the same answer after the same work, only nothing is
+    // delivered, so the result does not tell anyone which usernames exist.
+    pub fn request_password_reset(
+        &self,
+        username: &str,
+        deliver: impl FnOnce(&str),
+    ) -> Result<(), AuthError> {
+        let epoch = self
+            .users
+            .read_with(&username.to_string(), |user| user.map(|u| u.session_epoch));
+        let token = URL_SAFE_NO_PAD.encode(random_bytes::<32>()?);
+        self.resets
+            .retain(|_, reset| reset.issued.elapsed() < RESET_TOKEN_TTL); // Forget unused tokens
+        if let Some(epoch) = epoch {
+            self.resets.insert(
+                token.clone(),
+                PasswordReset {
+                    username: username.to_string(),
+                    epoch,
+                    issued: Instant::now(),
+                },
+            );
+            deliver(&token);
+        }
+        Ok(())
+    }
+
+    // Sets a new password with a reset token. Like a password change it revokes all sessions,
+    // and since the token proves who the user is it also unlocks the account.
+    pub fn reset_password(&self, token: &str, new_password: &str) -> Result<(), AuthError> {
+        let reset = self
+            .resets
+            .get_cloned(&token.to_string())
+            .filter(|reset| reset.issued.elapsed() < RESET_TOKEN_TTL)
+            .ok_or(AuthError::InvalidCredentials)?;
+        self.config
+            .password_policy
+            .check(new_password, &[&reset.username])?;
+        let salt = random_bytes::<16>()?;
+        let hash = self.hash_password(new_password, &salt);
+        self.users
+            .update(reset.username.clone(), |entry| match entry {
+                // A second use of the token, or a reset racing with it, sees the bumped epoch
+                Entry::Occupied(mut user) if user.get().session_epoch == reset.epoch => {
+                    let user = user.get_mut();
+                    user.salt = salt;
+                    user.hash = hash;
+                    user.failed_attempts = 0;
+                    user.session_epoch += 1;
+                    Ok(())
+                }
+                _ => Err(AuthError::InvalidCredentials),
+            })?;
+        self.resets
+            .retain(|_, other| other.username != reset.username);
+        self.sessions
+            .retain(|_, session| session.username != reset.username);
+        Ok(())
+    }
+
+    // --- LOCKOUT ---
+    pub fn failed_attempts(&self, username: &str) -> Option<u32> {
+        self.users.read_with(&username.to_string(), |user| {
+            user.map(|u| u.failed_attempts)
+        })
+    }
+
+    pub fn unlock(&self, username: &str) {
+        self.users.update(username.to_string(), |entry| {
+            if let Entry::Occupied(mut user) = entry {
+                user.get_mut().failed_attempts = 0;
+            }
+        });
+    }
+
+    pub fn user_count(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.len()
+    }
+
+    // Checks a password and returns the user's current session epoch. The hash is computed
+    // without holding a lock, then compared and the failure counter updated in one locked step.
+    fn verify_password(&self, username: &str, password: &str) -> Result<u64, AuthError> {
+        let snapshot = self.users.get_cloned(&username.to_string());
+        let Some(snapshot) = snapshot else {
+            // Hash anyway so unknown usernames take as long as wrong passwords
+            self.hash_password(password, &[0u8; 16]);
+            return Err(AuthError::InvalidCredentials);
+        };
+        if snapshot.failed_attempts >= self.config.max_failed_attempts {
+            return Err(AuthError::Locked);
+        }
+        let candidate = self.hash_password(password, &snapshot.salt);
+
+        let max_failed = self.config.max_failed_attempts;
+        self.users.update(username.to_string(), |entry| {
+            let Entry::Occupied(mut user) = entry else {
+                return Err(AuthError::InvalidCredentials);
+            };
+            let user = user.get_mut();
+            // Another thread may have locked the account meanwhile
+            if user.failed_attempts >= max_failed {
+                return Err(AuthError::Locked);
+            }
+            // A new salt means the candidate was hashed for a password that is gone: turn the
+            // login away, but it says nothing about whether the password was wrong
+            if user.salt != snapshot.salt {
+                return Err(AuthError::InvalidCredentials);
+            }
+            if constant_time_eq(&user.hash, &candidate) {
+                user.failed_attempts = 0;
+                Ok(user.session_epoch)
+            } else {
+                user.failed_attempts += 1;
+                Err(AuthError::InvalidCredentials)
+            }
+        })
+    }
+
+    fn hash_password(&self, password: &str, salt: &[u8]) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(
+            password.as_bytes(),
+            salt,
+            self.config.pbkdf2_rounds.max(1),
+            &mut hash,
+        );
+        hash
+    }
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], AuthError> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|_| AuthError::Randomness)?;
+    Ok(bytes)
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
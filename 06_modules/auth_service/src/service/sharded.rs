@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::hash::{Hash, Hasher};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// --- SHARDED MAP ---
+// A HashMap split into independently locked shards. Threads working on different keys almost
+// always hit different shards, so they do not wait for each other, while every update to a
+// single key still happens under that shard's write lock (no lost updates).
+#[derive(Debug)]
+pub struct ShardedMap<K, V> {
+    shards: Vec<RwLock<HashMap<K, V>>>,
+}
+
+impl<K: Hash + Eq, V> ShardedMap<K, V> {
+    pub fn new(shard_count: usize) -> ShardedMap<K, V> {
+        let shard_count = shard_count.max(1);
+        ShardedMap {
+            shards: (0..shard_count)
+                .map(|_| RwLock::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    fn shard_index(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    // A panic while holding a lock poisons it; the maps stay structurally valid, so keep going
+    fn read(&self, index: usize) -> RwLockReadGuard<'_, HashMap<K, V>> {
+        self.shards[index].read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self, index: usize) -> RwLockWriteGuard<'_, HashMap<K, V>> {
+        self.shards[index]
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Runs `f` on the value for `key` (if any) under a read lock
+    pub fn read_with<R>(&self, key: &K, f: impl FnOnce(Option<&V>) -> R) -> R {
+        let shard = self.read(self.shard_index(key));
+        f(shard.get(key))
+    }
+
+    // Runs `f` on the entry for `key` under the shard's write lock: the read-modify-write is atomic
+    pub fn update<R>(&self, key: K, f: impl FnOnce(Entry<'_, K, V>) -> R) -> R {
+        let mut shard = self.write(self.shard_index(&key));
+        f(shard.entry(key))
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let mut shard = self.write(self.shard_index(&key));
+        shard.insert(key, value)
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        let mut shard = self.write(self.shard_index(key));
+        shard.remove(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.read_with(key, |value| value.is_some())
+    }
+
+    // Keeps only the entries for which `keep` returns true and returns how many were removed.
+    // Shards are locked one at a time, never all at once.
+    pub fn retain(&self, mut keep: impl FnMut(&K, &mut V) -> bool) -> usize {
+        let mut removed = 0;
+        for index in 0..self.shards.len() {
+            let mut shard = self.write(index);
+            let before = shard.len();
+            shard.retain(|k, v| keep(k, v));
+            removed += before - shard.len();
+        }
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        (0..self.shards.len())
+            .map(|index| self.read(index).len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> ShardedMap<K, V> {
+    pub fn get_cloned(&self, key: &K) -> Option<V> {
+        self.read_with(key, |value| value.cloned())
+    }
+}
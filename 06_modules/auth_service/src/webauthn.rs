@@ -0,0 +1,355 @@
+use std::fmt;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use sha2::{Digest, Sha256};
+
+pub mod attestation; // Authenticator data, flags, COSE keys and the "none"/"packed" attestation formats
+mod cbor; // Minimal CBOR decoder used by the attestation module
+pub mod store; // Where registered credentials and their signature counters live
+
+pub use attestation::{AttestationFormat, AuthenticatorFlags};
+pub use store::{CredentialStore, StoredCredential};
+
+const CHALLENGE_LEN: usize = 32; // The spec asks for at least 16 random bytes
+
+// --- ERRORS ---
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebAuthnError {
+    Malformed(String),              // Input could not be parsed
+    UnsupportedAttestation(String), // Attestation format we do not handle
+    UnsupportedAlgorithm(i128),     // Only ES256 (-7) is supported
+    WrongType(String),              // clientDataJSON "type" was not what the ceremony expects
+    ChallengeMismatch,
+    OriginMismatch(String),
+    RpIdMismatch,
+    UserNotPresent,
+    UserNotVerified,
+    InvalidSignature,
+    UnknownCredential,
+    CredentialNotAllowed,
+    DuplicateCredential,
+    UserHandleMismatch,
+    CounterRegression { stored: u32, received: u32 }, // Possible cloned authenticator
+    Randomness,
+}
+
+impl fmt::Display for WebAuthnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebAuthnError::Malformed(msg) => write!(f, "malformed WebAuthn data: {msg}"),
+            WebAuthnError::UnsupportedAttestation(fmt) => {
+                write!(f, "unsupported attestation format: {fmt}")
+            }
+            WebAuthnError::UnsupportedAlgorithm(alg) => {
+                write!(f, "unsupported COSE algorithm {alg}")
+            }
+            WebAuthnError::WrongType(t) => write!(f, "unexpected client data type {t:?}"),
+            WebAuthnError::ChallengeMismatch => write!(f, "challenge does not match"),
+            WebAuthnError::OriginMismatch(o) => write!(f, "unexpected origin {o:?}"),
+            WebAuthnError::RpIdMismatch => write!(f, "relying party id hash does not match"),
+            WebAuthnError::UserNotPresent => write!(f, "user presence flag not set"),
+            WebAuthnError::UserNotVerified => {
+                write!(f, "user verification required but not performed")
+            }
+            WebAuthnError::InvalidSignature => write!(f, "signature verification failed"),
+            WebAuthnError::UnknownCredential => write!(f, "credential is not registered"),
+            WebAuthnError::CredentialNotAllowed => {
+                write!(f, "credential was not offered for this login")
+            }
+            WebAuthnError::DuplicateCredential => write!(f, "credential is already registered"),
+            WebAuthnError::UserHandleMismatch => {
+                write!(f, "user handle does not match the credential")
+            }
+            WebAuthnError::CounterRegression { stored, received } => write!(
+                f,
+                "signature counter went from {stored} to {received}, authenticator may be cloned"
+            ),
+            WebAuthnError::Randomness => write!(f, "could not generate a random challenge"),
+        }
+    }
+}
+
+impl std::error::Error for WebAuthnError {}
+
+// --- RELYING PARTY ---
+// Our side of the WebAuthn ceremonies: one per site (rp id + the origin pages are served from)
+#[derive(Debug, Clone)]
+pub struct RelyingParty {
+    pub id: String,                      // Usually the domain, e.g. "example.com"
+    pub name: String,                    // Shown to the user by the authenticator
+    pub origin: String,                  // e.g. "https://example.com"
+    pub require_user_verification: bool, // Admins should set this: a touch alone is not enough
+}
+
+// What the browser needs to call navigator.credentials.create(), kept server-side until the response arrives
+#[derive(Debug, Clone)]
+pub struct RegistrationChallenge {
+    pub challenge: Vec<u8>,
+    pub rp_id: String,
+    pub rp_name: String,
+    pub user_handle: Vec<u8>,
+    pub username: String,
+    pub user_verification_required: bool,
+    pub exclude_credentials: Vec<Vec<u8>>, // Credentials the user already has, so they are not registered twice
+}
+
+// The fields of the AuthenticatorAttestationResponse, already base64url-decoded
+#[derive(Debug, Clone)]
+pub struct RegistrationResponse {
+    pub client_data_json: Vec<u8>,
+    pub attestation_object: Vec<u8>,
+}
+
+// What the browser needs to call navigator.credentials.get()
+#[derive(Debug, Clone)]
+pub struct AuthenticationChallenge {
+    pub challenge: Vec<u8>,
+    pub rp_id: String,
+    pub username: String,
+    pub allow_credentials: Vec<Vec<u8>>,
+    pub user_verification_required: bool,
+}
+
+// The fields of the AuthenticatorAssertionResponse, already base64url-decoded
+#[derive(Debug, Clone)]
+pub struct AssertionResponse {
+    pub credential_id: Vec<u8>,
+    pub client_data_json: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub user_handle: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticationResult {
+    pub username: String,
+    pub credential_id: Vec<u8>,
+    pub sign_count: u32,
+    pub flags: AuthenticatorFlags,
+}
+
+impl RelyingParty {
+    pub fn new(id: &str, name: &str, origin: &str) -> RelyingParty {
+        RelyingParty {
+            id: id.to_string(),
+            name: name.to_string(),
+            origin: origin.to_string(),
+            require_user_verification: false,
+        }
+    }
+
+    // --- REGISTRATION ---
+    pub fn start_registration(
+        &self,
+        username: &str,
+        user_handle: &[u8],
+        store: &CredentialStore,
+    ) -> Result<RegistrationChallenge, WebAuthnError> {
+        Ok(RegistrationChallenge {
+            challenge: random_challenge()?,
+            rp_id: self.id.clone(),
+            rp_name: self.name.clone(),
+            user_handle: user_handle.to_vec(),
+            username: username.to_string(),
+            user_verification_required: self.require_user_verification,
+            exclude_credentials: store
+                .for_user(username)
+                .iter()
+                .map(|c| c.credential_id.clone())
+                .collect(),
+        })
+    }
+
+    // Verifies a registration response and stores the new credential
+    pub fn finish_registration(
+        &self,
+        challenge: &RegistrationChallenge,
+        response: &RegistrationResponse,
+        store: &mut CredentialStore,
+    ) -> Result<StoredCredential, WebAuthnError> {
+        self.check_client_data(
+            &response.client_data_json,
+            "webauthn.create",
+            &challenge.challenge,
+        )?;
+        let client_data_hash = Sha256::digest(&response.client_data_json);
+
+        let attestation = attestation::AttestationObject::parse(&response.attestation_object)?;
+        let auth_data = &attestation.auth_data;
+        self.check_flags_and_rp(
+            &auth_data.rp_id_hash,
+            auth_data.flags,
+            challenge.user_verification_required,
+        )?;
+        attestation.verify(&client_data_hash)?;
+
+        let credential = auth_data.attested_credential.as_ref().ok_or_else(|| {
+            WebAuthnError::Malformed("registration has no attested credential data".to_string())
+        })?;
+        let stored = StoredCredential {
+            credential_id: credential.credential_id.clone(),
+            user_handle: challenge.user_handle.clone(),
+            username: challenge.username.clone(),
+            public_key: credential.public_key.clone(),
+            sign_count: auth_data.sign_count,
+            aaguid: credential.aaguid,
+            user_verified_at_registration: auth_data.flags.user_verified,
+            backup_eligible: auth_data.flags.backup_eligible,
+        };
+        if !store.insert(stored.clone()) {
+            return Err(WebAuthnError::DuplicateCredential);
+        }
+        Ok(stored)
+    }
+
+    // --- AUTHENTICATION ---
+    pub fn start_authentication(
+        &self,
+        username: &str,
+        store: &CredentialStore,
+    ) -> Result<AuthenticationChallenge, WebAuthnError> {
+        Ok(AuthenticationChallenge {
+            challenge: random_challenge()?,
+            rp_id: self.id.clone(),
+            username: username.to_string(),
+            allow_credentials: store
+                .for_user(username)
+                .iter()
+                .map(|c| c.credential_id.clone())
+                .collect(),
+            user_verification_required: self.require_user_verification,
+        })
+    }
+
+    // Verifies an assertion and bumps the stored signature counter
+    pub fn finish_authentication(
+        &self,
+        challenge: &AuthenticationChallenge,
+        response: &AssertionResponse,
+        store: &mut CredentialStore,
+    ) -> Result<AuthenticationResult, WebAuthnError> {
+        if !challenge
+            .allow_credentials
+            .contains(&response.credential_id)
+        {
+            return Err(WebAuthnError::CredentialNotAllowed);
+        }
+        let stored = store
+            .get_mut(&response.credential_id)
+            .ok_or(WebAuthnError::UnknownCredential)?;
+        if stored.username != challenge.username {
+            return Err(WebAuthnError::CredentialNotAllowed);
+        }
+        if let Some(handle) = &response.user_handle
+            && *handle != stored.user_handle
+        {
+            return Err(WebAuthnError::UserHandleMismatch);
+        }
+
+        self.check_client_data(
+            &response.client_data_json,
+            "webauthn.get",
+            &challenge.challenge,
+        )?;
+        let auth_data = attestation::AuthenticatorData::parse(&response.authenticator_data)?;
+        self.check_flags_and_rp(
+            &auth_data.rp_id_hash,
+            auth_data.flags,
+            challenge.user_verification_required,
+        )?;
+
+        // The signature covers authenticatorData || SHA-256(clientDataJSON)
+        let mut signed = response.authenticator_data.clone();
+        signed.extend_from_slice(&Sha256::digest(&response.client_data_json));
+        stored.public_key.verify(&signed, &response.signature)?;
+
+        // Counters only mean something if the authenticator implements them (many passkeys send 0)
+        if (auth_data.sign_count != 0 || stored.sign_count != 0)
+            && auth_data.sign_count <= stored.sign_count
+        {
+            return Err(WebAuthnError::CounterRegression {
+                stored: stored.sign_count,
+                received: auth_data.sign_count,
+            });
+        }
+        stored.sign_count = auth_data.sign_count;
+
+        Ok(AuthenticationResult {
+            username: stored.username.clone(),
+            credential_id: stored.credential_id.clone(),
+            sign_count: stored.sign_count,
+            flags: auth_data.flags,
+        })
+    }
+
+    // --- SHARED CHECKS ---
+    // clientDataJSON is {"type": ..., "challenge": base64url, "origin": ..., ...}
+    fn check_client_data(
+        &self,
+        client_data_json: &[u8],
+        expected_type: &str,
+        expected_challenge: &[u8],
+    ) -> Result<(), WebAuthnError> {
+        let client_data: serde_json::Value = serde_json::from_slice(client_data_json)
+            .map_err(|e| WebAuthnError::Malformed(format!("clientDataJSON: {e}")))?;
+        let field = |name: &str| {
+            client_data
+                .get(name)
+                .and_then(|v| v.as_str())
+                .ok_or_else(|| WebAuthnError::Malformed(format!("clientDataJSON has no {name}")))
+        };
+
+        let kind = field("type")?;
+        if kind != expected_type {
+            return Err(WebAuthnError::WrongType(kind.to_string()));
+        }
+        let challenge = URL_SAFE_NO_PAD
+            .decode(field("challenge")?)
+            .map_err(|_| WebAuthnError::ChallengeMismatch)?;
+        if !constant_time_eq(&challenge, expected_challenge) {
+            return Err(WebAuthnError::ChallengeMismatch);
+        }
+        let origin = field("origin")?;
+        if origin != self.origin {
+            return Err(WebAuthnError::OriginMismatch(origin.to_string()));
+        }
+        Ok(())
+    }
+
+    // `user_verification_required` comes from the challenge, not from self: the ceremony is judged
+    // by what the browser was asked for, even if require_user_verification changed since
+    fn check_flags_and_rp(
+        &self,
+        rp_id_hash: &[u8; 32],
+        flags: AuthenticatorFlags,
+        user_verification_required: bool,
+    ) -> Result<(), WebAuthnError> {
+        if Sha256::digest(self.id.as_bytes())[..] != rp_id_hash[..] {
+            return Err(WebAuthnError::RpIdMismatch);
+        }
+        if !flags.user_present {
+            return Err(WebAuthnError::UserNotPresent);
+        }
+        if user_verification_required && !flags.user_verified {
+            return Err(WebAuthnError::UserNotVerified);
+        }
+        Ok(())
+    }
+}
+
+// Encodes a challenge or credential id the way browsers put it into clientDataJSON
+pub fn base64url(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+fn random_challenge() -> Result<Vec<u8>, WebAuthnError> {
+    let mut challenge = vec![0u8; CHALLENGE_LEN];
+    getrandom::getrandom(&mut challenge).map_err(|_| WebAuthnError::Randomness)?;
+    Ok(challenge)
+}
+
+// Compares without returning early so the time taken does not leak how many bytes matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
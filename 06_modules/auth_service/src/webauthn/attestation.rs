@@ -0,0 +1,261 @@
+use p256::EncodedPoint;
+use p256::ecdsa::signature::Verifier;
+use p256::ecdsa::{Signature, VerifyingKey};
+
+use super::WebAuthnError;
+use super::cbor::{self, Value};
+
+// COSE algorithm id for ECDSA with P-256 and SHA-256, the only algorithm we accept
+pub const COSE_ALG_ES256: i128 = -7;
+
+// --- AUTHENTICATOR FLAGS ---
+// The single flags byte inside authenticator data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticatorFlags {
+    pub user_present: bool,             // UP: someone touched the authenticator
+    pub user_verified: bool,            // UV: PIN, fingerprint or face check passed
+    pub backup_eligible: bool,          // BE: the credential may be synced (a passkey)
+    pub backed_up: bool,                // BS: the credential is currently synced
+    pub attested_credential_data: bool, // AT: a new credential follows (registration only)
+    pub extension_data: bool,           // ED: extension outputs follow
+}
+
+impl AuthenticatorFlags {
+    pub fn from_byte(byte: u8) -> AuthenticatorFlags {
+        AuthenticatorFlags {
+            user_present: byte & 0x01 != 0,
+            user_verified: byte & 0x04 != 0,
+            backup_eligible: byte & 0x08 != 0,
+            backed_up: byte & 0x10 != 0,
+            attested_credential_data: byte & 0x40 != 0,
+            extension_data: byte & 0x80 != 0,
+        }
+    }
+}
+
+// --- AUTHENTICATOR DATA ---
+// Layout: rpIdHash (32) | flags (1) | signCount (4, big endian) | [attested credential data] | [extensions]
+#[derive(Debug, Clone)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: AuthenticatorFlags,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+// Attested credential data: aaguid (16) | credentialIdLength (2) | credentialId | COSE public key
+#[derive(Debug, Clone)]
+pub struct AttestedCredential {
+    pub aaguid: [u8; 16],
+    pub credential_id: Vec<u8>,
+    pub public_key: CoseKey,
+}
+
+impl AuthenticatorData {
+    pub fn parse(data: &[u8]) -> Result<AuthenticatorData, WebAuthnError> {
+        if data.len() < 37 {
+            return Err(WebAuthnError::Malformed(format!(
+                "authenticator data is {} bytes, expected at least 37",
+                data.len()
+            )));
+        }
+        let rp_id_hash: [u8; 32] = data[0..32].try_into().unwrap();
+        let flags = AuthenticatorFlags::from_byte(data[32]);
+        let sign_count = u32::from_be_bytes(data[33..37].try_into().unwrap());
+        let mut rest = &data[37..];
+
+        let mut attested_credential = None;
+        if flags.attested_credential_data {
+            if rest.len() < 18 {
+                return Err(WebAuthnError::Malformed(
+                    "attested credential data is truncated".to_string(),
+                ));
+            }
+            let aaguid: [u8; 16] = rest[0..16].try_into().unwrap();
+            let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            rest = &rest[18..];
+            if rest.len() < id_len {
+                return Err(WebAuthnError::Malformed(
+                    "credential id is longer than the authenticator data".to_string(),
+                ));
+            }
+            let credential_id = rest[..id_len].to_vec();
+            rest = &rest[id_len..];
+            // The COSE key has no length prefix, so we need to know how much of the input it used
+            let (key, used) = cbor::decode_prefix(rest).map_err(WebAuthnError::Malformed)?;
+            rest = &rest[used..];
+            attested_credential = Some(AttestedCredential {
+                aaguid,
+                credential_id,
+                public_key: CoseKey::from_cbor(&key)?,
+            });
+        }
+
+        if flags.extension_data {
+            // We do not use any extensions, but the map must still be well formed
+            let (_, used) = cbor::decode_prefix(rest).map_err(WebAuthnError::Malformed)?;
+            rest = &rest[used..];
+        }
+        if !rest.is_empty() {
+            return Err(WebAuthnError::Malformed(format!(
+                "{} unexpected trailing bytes in authenticator data",
+                rest.len()
+            )));
+        }
+
+        Ok(AuthenticatorData {
+            rp_id_hash,
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+}
+
+// --- COSE PUBLIC KEY ---
+// An EC2 key on P-256: {1: 2 (kty EC2), 3: -7 (alg ES256), -1: 1 (crv P-256), -2: x, -3: y}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoseKey {
+    pub algorithm: i128,
+    pub sec1: Vec<u8>, // Uncompressed SEC1 point (0x04 | x | y), which is what we store
+}
+
+impl CoseKey {
+    pub fn from_cbor(key: &Value) -> Result<CoseKey, WebAuthnError> {
+        let field = |label: i128| {
+            key.get_int(label).ok_or_else(|| {
+                WebAuthnError::Malformed(format!("COSE key is missing label {label}"))
+            })
+        };
+        let kty = field(1)?.as_int();
+        let alg = field(3)?.as_int();
+        if kty != Some(2) || alg != Some(COSE_ALG_ES256) || field(-1)?.as_int() != Some(1) {
+            return Err(WebAuthnError::UnsupportedAlgorithm(alg.unwrap_or_default()));
+        }
+        let coordinate = |label: i128| -> Result<&[u8], WebAuthnError> {
+            field(label)?
+                .as_bytes()
+                .filter(|c| c.len() == 32)
+                .ok_or_else(|| {
+                    WebAuthnError::Malformed("COSE key coordinate is not 32 bytes".to_string())
+                })
+        };
+        let point = EncodedPoint::from_affine_coordinates(
+            coordinate(-2)?.into(),
+            coordinate(-3)?.into(),
+            false,
+        );
+        let cose_key = CoseKey {
+            algorithm: COSE_ALG_ES256,
+            sec1: point.as_bytes().to_vec(),
+        };
+        cose_key.verifying_key()?; // Reject points that are not on the curve right away
+        Ok(cose_key)
+    }
+
+    pub fn verifying_key(&self) -> Result<VerifyingKey, WebAuthnError> {
+        VerifyingKey::from_sec1_bytes(&self.sec1).map_err(|_| {
+            WebAuthnError::Malformed("public key is not a valid P-256 point".to_string())
+        })
+    }
+
+    // Checks an ASN.1 DER encoded ECDSA signature over `message` (hashed with SHA-256 internally)
+    pub fn verify(&self, message: &[u8], der_signature: &[u8]) -> Result<(), WebAuthnError> {
+        let signature =
+            Signature::from_der(der_signature).map_err(|_| WebAuthnError::InvalidSignature)?;
+        self.verifying_key()?
+            .verify(message, &signature)
+            .map_err(|_| WebAuthnError::InvalidSignature)
+    }
+}
+
+// --- ATTESTATION OBJECT ---
+// CBOR map {"fmt": text, "attStmt": map, "authData": bytes} returned by navigator.credentials.create()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttestationFormat {
+    None,
+    PackedSelf, // "packed" signed by the credential key itself, no certificate chain
+}
+
+pub struct AttestationObject {
+    pub format: AttestationFormat,
+    pub auth_data_raw: Vec<u8>,
+    pub auth_data: AuthenticatorData,
+    statement: Value,
+}
+
+impl AttestationObject {
+    pub fn parse(bytes: &[u8]) -> Result<AttestationObject, WebAuthnError> {
+        let object = cbor::decode(bytes).map_err(WebAuthnError::Malformed)?;
+        let fmt = object
+            .get("fmt")
+            .and_then(Value::as_text)
+            .ok_or_else(|| WebAuthnError::Malformed("attestation object has no fmt".to_string()))?;
+        let statement = object
+            .get("attStmt")
+            .filter(|s| matches!(s, Value::Map(_)))
+            .cloned()
+            .ok_or_else(|| {
+                WebAuthnError::Malformed("attestation object has no attStmt".to_string())
+            })?;
+        let auth_data_raw = object
+            .get("authData")
+            .and_then(Value::as_bytes)
+            .ok_or_else(|| {
+                WebAuthnError::Malformed("attestation object has no authData".to_string())
+            })?
+            .to_vec();
+
+        let format = match fmt {
+            "none" => AttestationFormat::None,
+            // Full packed attestation carries an x5c certificate chain, which we do not validate
+            "packed" if statement.get("x5c").is_some() => {
+                return Err(WebAuthnError::UnsupportedAttestation(
+                    "packed with x5c certificate chain".to_string(),
+                ));
+            }
+            "packed" => AttestationFormat::PackedSelf,
+            other => return Err(WebAuthnError::UnsupportedAttestation(other.to_string())),
+        };
+
+        let auth_data = AuthenticatorData::parse(&auth_data_raw)?;
+        Ok(AttestationObject {
+            format,
+            auth_data_raw,
+            auth_data,
+            statement,
+        })
+    }
+
+    // Checks the attestation statement against the hash of clientDataJSON
+    pub fn verify(&self, client_data_hash: &[u8]) -> Result<(), WebAuthnError> {
+        let credential = self.auth_data.attested_credential.as_ref().ok_or_else(|| {
+            WebAuthnError::Malformed("registration has no attested credential data".to_string())
+        })?;
+        match self.format {
+            AttestationFormat::None => match &self.statement {
+                Value::Map(entries) if entries.is_empty() => Ok(()),
+                _ => Err(WebAuthnError::Malformed(
+                    "\"none\" attestation must have an empty attStmt".to_string(),
+                )),
+            },
+            AttestationFormat::PackedSelf => {
+                // Self attestation: alg must match the credential key and sig is made with that key
+                let alg = self.statement.get("alg").and_then(Value::as_int);
+                if alg != Some(credential.public_key.algorithm) {
+                    return Err(WebAuthnError::UnsupportedAlgorithm(alg.unwrap_or_default()));
+                }
+                let sig = self
+                    .statement
+                    .get("sig")
+                    .and_then(Value::as_bytes)
+                    .ok_or_else(|| {
+                        WebAuthnError::Malformed("packed attStmt has no sig".to_string())
+                    })?;
+                let mut signed = self.auth_data_raw.clone();
+                signed.extend_from_slice(client_data_hash);
+                credential.public_key.verify(&signed, sig)
+            }
+        }
+    }
+}
@@ -0,0 +1,169 @@
+// A tiny CBOR (RFC 8949) reader: just enough to decode attestation objects and COSE keys.
+// Authenticators only send definite-length items, so indefinite lengths, floats and tags are rejected.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Unsigned(u64),
+    Negative(i128), // Stored as the real value, e.g. -7 for the ES256 algorithm id
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>), // Keeps the wire order; maps in WebAuthn are tiny so a Vec is fine
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    pub fn as_int(&self) -> Option<i128> {
+        match self {
+            Value::Unsigned(n) => Some(*n as i128),
+            Value::Negative(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    // Looks up a text key ("fmt", "sig", ...) in a map
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_text() == Some(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    // Looks up an integer key (COSE keys use 1, 3, -1, -2, -3) in a map
+    pub fn get_int(&self, key: i128) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.as_int() == Some(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+const MAX_DEPTH: usize = 16; // Nesting limit so hostile input cannot blow the stack
+
+// Decodes one item from the front of `input` and returns it along with the number of bytes it used.
+// WebAuthn needs the consumed length because the COSE key sits in the middle of authenticator data.
+pub fn decode_prefix(input: &[u8]) -> Result<(Value, usize), String> {
+    let mut reader = Reader { input, pos: 0 };
+    let value = reader.read_value(0)?;
+    Ok((value, reader.pos))
+}
+
+// Decodes exactly one item; trailing bytes are an error
+pub fn decode(input: &[u8]) -> Result<Value, String> {
+    let (value, used) = decode_prefix(input)?;
+    if used != input.len() {
+        return Err(format!(
+            "{} trailing bytes after CBOR item",
+            input.len() - used
+        ));
+    }
+    Ok(value)
+}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.input.len())
+            .ok_or_else(|| format!("unexpected end of CBOR input at byte {}", self.pos))?;
+        let bytes = &self.input[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    // Reads the argument that follows the initial byte (the "additional information" bits)
+    fn read_argument(&mut self, info: u8) -> Result<u64, String> {
+        match info {
+            0..=23 => Ok(info as u64),
+            24 => Ok(self.take(1)?[0] as u64),
+            25 => Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64),
+            26 => Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64),
+            27 => Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap())),
+            _ => Err(format!("unsupported CBOR length encoding {info}")),
+        }
+    }
+
+    fn read_len(&mut self, info: u8) -> Result<usize, String> {
+        let len = self.read_argument(info)?;
+        // A length can never be larger than what is left, which also stops huge allocations
+        if len > (self.input.len() - self.pos) as u64 {
+            return Err(format!("CBOR length {len} exceeds remaining input"));
+        }
+        Ok(len as usize)
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("CBOR nesting too deep".to_string());
+        }
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        match major {
+            0 => Ok(Value::Unsigned(self.read_argument(info)?)),
+            1 => Ok(Value::Negative(-1 - self.read_argument(info)? as i128)),
+            2 => {
+                let len = self.read_len(info)?;
+                Ok(Value::Bytes(self.take(len)?.to_vec()))
+            }
+            3 => {
+                let len = self.read_len(info)?;
+                let text = std::str::from_utf8(self.take(len)?)
+                    .map_err(|_| "CBOR text string is not valid UTF-8".to_string())?;
+                Ok(Value::Text(text.to_string()))
+            }
+            4 => {
+                let len = self.read_len(info)?;
+                let mut items = Vec::with_capacity(len);
+                for _ in 0..len {
+                    items.push(self.read_value(depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            5 => {
+                let len = self.read_len(info)?;
+                let mut entries = Vec::with_capacity(len);
+                for _ in 0..len {
+                    let key = self.read_value(depth + 1)?;
+                    let value = self.read_value(depth + 1)?;
+                    entries.push((key, value));
+                }
+                Ok(Value::Map(entries))
+            }
+            7 => match info {
+                20 => Ok(Value::Bool(false)),
+                21 => Ok(Value::Bool(true)),
+                22 => Ok(Value::Null),
+                _ => Err(format!("unsupported CBOR simple value {info}")),
+            },
+            _ => Err(format!("unsupported CBOR major type {major}")),
+        }
+    }
+}
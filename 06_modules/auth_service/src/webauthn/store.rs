@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+
+use super::attestation::CoseKey;
+
+// --- STORED CREDENTIAL ---
+// Everything we must remember about a registered authenticator to verify its assertions later
+#[derive(Debug, Clone)]
+pub struct StoredCredential {
+    pub credential_id: Vec<u8>,
+    pub user_handle: Vec<u8>,
+    pub username: String,
+    pub public_key: CoseKey,
+    pub sign_count: u32,
+    pub aaguid: [u8; 16],
+    pub user_verified_at_registration: bool,
+    pub backup_eligible: bool,
+}
+
+// --- CREDENTIAL STORE ---
+// In-memory store keyed by credential id, like the mock database module this is not persisted
+#[derive(Debug, Default)]
+pub struct CredentialStore {
+    credentials: HashMap<Vec<u8>, StoredCredential>,
+}
+
+impl CredentialStore {
+    pub fn new() -> CredentialStore {
+        CredentialStore::default()
+    }
+
+    // Returns false (and keeps the old entry) if the credential id is already registered
+    pub fn insert(&mut self, credential: StoredCredential) -> bool {
+        if self.credentials.contains_key(&credential.credential_id) {
+            return false;
+        }
+        self.credentials
+            .insert(credential.credential_id.clone(), credential);
+        true
+    }
+
+    pub fn get(&self, credential_id: &[u8]) -> Option<&StoredCredential> {
+        self.credentials.get(credential_id)
+    }
+
+    pub fn get_mut(&mut self, credential_id: &[u8]) -> Option<&mut StoredCredential> {
+        self.credentials.get_mut(credential_id)
+    }
+
+    pub fn remove(&mut self, credential_id: &[u8]) -> Option<StoredCredential> {
+        self.credentials.remove(credential_id)
+    }
+
+    // All credentials registered for a user, e.g. to build the allowCredentials list
+    pub fn for_user(&self, username: &str) -> Vec<&StoredCredential> {
+        self.credentials
+            .values()
+            .filter(|c| c.username == username)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.credentials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.credentials.is_empty()
+    }
+}
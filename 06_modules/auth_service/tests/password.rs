@@ -0,0 +1,206 @@
+// Weak passwords are scored low for the pattern that makes them weak, the policy says why it
+// refuses a password, and the breach list finds exactly the passwords in its range files.
+use std::fs;
+use std::path::PathBuf;
+
+use auth_service::password::breach::{count_in_range, sha1_hex};
+use auth_service::password::strength::Pattern;
+use auth_service::password::{BreachList, PasswordPolicy, estimate};
+
+// A breach list directory of its own for each test, removed when the test is done
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> TempDir {
+        let path = std::env::temp_dir().join(format!(
+            "auth_service_password_{name}_{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    // Adds `password` to the range file for its prefix, the way the Pwned Passwords files look
+    fn add(&self, password: &str, count: u64) {
+        let hash = sha1_hex(password);
+        let (prefix, suffix) = hash.split_at(5);
+        let path = self.0.join(format!("{prefix}.txt"));
+        let mut range = fs::read_to_string(&path).unwrap_or_default();
+        range.push_str(&format!("{suffix}:{count}\r\n"));
+        fs::write(path, range).unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// --- STRENGTH ---
+#[test]
+fn finds_the_pattern_that_makes_a_password_weak() {
+    let cases = [
+        ("password", "This is a top-10 common password."),
+        ("qwertyuiop", "This is a very common password."),
+        ("zxcvbnm,./", "Straight rows of keys are easy to guess."),
+        ("aaaaaaaaaa", "Repeats like \"aaa\" are easy to guess."),
+        (
+            "abcdefghij",
+            "Sequences like abc or 6543 are easy to guess.",
+        ),
+        ("1987", "Years are easy to guess."),
+    ];
+    for (password, warning) in cases {
+        let strength = estimate(password, &[]);
+        assert!(strength.score <= 1, "{password}: {strength:?}");
+        assert_eq!(strength.warning.as_deref(), Some(warning), "{password}");
+        assert!(!strength.suggestions.is_empty());
+    }
+
+    let strength = estimate("drowssap", &[]);
+    assert!(matches!(
+        strength.sequence[0].pattern,
+        Pattern::Dictionary { reversed: true, .. }
+    ));
+    let strength = estimate("P@ssw0rd", &[]);
+    assert!(strength.score <= 1);
+    assert!(matches!(
+        strength.sequence[0].pattern,
+        Pattern::Dictionary { l33t: true, .. }
+    ));
+    assert!(
+        strength
+            .suggestions
+            .iter()
+            .any(|s| s.contains("Capitalization"))
+    );
+}
+
+#[test]
+fn knows_about_the_user() {
+    let alone = estimate("ferrisferris", &[]);
+    let with_name = estimate("ferrisferris", &["Ferris"]);
+    assert!(with_name.guesses < alone.guesses);
+    assert_eq!(
+        estimate("ferris", &["ferris"]).warning.as_deref(),
+        Some("Passwords containing your username or personal details are easy to guess.")
+    );
+}
+
+#[test]
+fn scores_long_unpredictable_passwords_high() {
+    for password in [
+        "correct horse battery staple",
+        "t7#Vq9!mZ2$kLp",
+        "ржавый краб плавает",
+    ] {
+        let strength = estimate(password, &["admin"]);
+        assert_eq!(strength.score, 4, "{password}: {strength:?}");
+        assert_eq!(strength.warning, None);
+    }
+    // Offsets count chars, and the sequence covers the whole password
+    let strength = estimate("नमस्ते1234", &[]);
+    assert_eq!(strength.sequence.last().unwrap().end, 10);
+    assert_eq!(strength.sequence[0].start, 0);
+    assert_eq!(estimate("", &[]).score, 0);
+}
+
+// --- POLICY ---
+#[test]
+fn policy_explains_every_reason() {
+    let dir = TempDir::new("policy");
+    dir.add("qwerty", 3_946_737);
+    dir.add("Tr0ub4dor&3-horse", 1);
+    let policy = PasswordPolicy {
+        breach_list: Some(BreachList::open(&dir.0).unwrap()),
+        ..PasswordPolicy::default()
+    };
+
+    let rejected = policy.check("qwerty", &[]).unwrap_err();
+    assert_eq!(
+        rejected.reasons,
+        [
+            "Password must be at least 8 characters long (it has 6).",
+            "Password is too easy to guess: This is a top-10 common password.",
+            "This password has appeared in a data breach 3946737 times and must not be used.",
+        ]
+    );
+    assert!(rejected.score < 3);
+    assert!(
+        rejected
+            .to_string()
+            .starts_with(&rejected.reasons.join(" "))
+    );
+
+    // Strong, but breached once
+    let rejected = policy.check("Tr0ub4dor&3-horse", &[]).unwrap_err();
+    assert_eq!(
+        rejected.reasons,
+        ["This password has appeared in a data breach 1 time and must not be used."]
+    );
+
+    let rejected = policy.check("ferris-admin", &["ferris-admin"]).unwrap_err();
+    assert_eq!(
+        rejected.reasons,
+        [
+            "Password is too easy to guess: Passwords containing your username or personal details are easy to guess."
+        ]
+    );
+
+    let strength = policy.check("correct horse battery staple", &[]).unwrap();
+    assert_eq!(strength.score, 4);
+}
+
+#[test]
+fn policy_fails_closed_when_the_breach_list_cannot_be_read() {
+    let dir = TempDir::new("unreadable");
+    let policy = PasswordPolicy {
+        breach_list: Some(BreachList::open(&dir.0).unwrap()),
+        ..PasswordPolicy::default()
+    };
+    // A directory where the range file should be makes the lookup fail
+    let hash = sha1_hex("correct horse battery staple");
+    fs::create_dir(dir.0.join(format!("{}.txt", &hash[..5]))).unwrap();
+    let rejected = policy
+        .check("correct horse battery staple", &[])
+        .unwrap_err();
+    assert_eq!(rejected.reasons.len(), 1);
+    assert!(
+        rejected.reasons[0].starts_with("The breached password list could not be checked"),
+        "{:?}",
+        rejected.reasons
+    );
+}
+
+// --- BREACH LIST ---
+#[test]
+fn looks_up_passwords_by_hash_prefix() {
+    let dir = TempDir::new("lookup");
+    dir.add("password", 9_545_824);
+    dir.add("hunter2", 17_043);
+    let list = BreachList::open(&dir.0).unwrap();
+    assert_eq!(list.times_seen("password").unwrap(), 9_545_824);
+    assert!(list.is_breached("hunter2").unwrap());
+    assert!(!list.is_breached("hunter3").unwrap()); // No range file for its prefix
+    assert!(BreachList::open(dir.0.join("missing")).is_err());
+
+    assert_eq!(
+        sha1_hex("password"),
+        "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8"
+    );
+    let range = "1E4C9B93F3F0682250B6CF8331B7EE68FD8:3\n\
+                 0000000000000000000000000000000000A:0\n\
+                 00000000000000000000000000000000000:x";
+    // Suffixes match in any case; padding entries (count 0) and unreadable counts are not found
+    let lookups = [
+        ("1e4c9b93f3f0682250b6cf8331b7ee68fd8", 3),
+        ("0000000000000000000000000000000000A", 0),
+        ("00000000000000000000000000000000000", 0),
+    ];
+    for (suffix, count) in lookups {
+        assert_eq!(count_in_range(range, suffix), count, "{suffix}");
+    }
+    assert_eq!(count_in_range("", "1E4C9B93F3F0682250B6CF8331B7EE68FD8"), 0);
+}
@@ -0,0 +1,192 @@
+// Property tests: every public entry point that takes user-supplied data is fed arbitrary input.
+// Invariants: nothing panics, and the same input always produces the same outcome.
+use std::time::Duration;
+
+use auth_service::password::{self, PasswordPolicy};
+use auth_service::rate_limit::{
+    Algorithm, Backend, FileBackend, MemoryBackend, RateLimiter, State,
+};
+use auth_service::service::{AuthError, ServiceConfig};
+use auth_service::webauthn::attestation::{AttestationObject, AuthenticatorData};
+use auth_service::webauthn::{
+    AssertionResponse, CredentialStore, RegistrationResponse, RelyingParty,
+};
+use auth_service::{AuthService, Credentials};
+use proptest::prelude::*;
+
+fn creds(username: &str, password: &str) -> Credentials {
+    Credentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+}
+
+// Any password is accepted and hashing is cheap, so the properties are about the service logic
+fn permissive_service() -> AuthService {
+    AuthService::new(ServiceConfig {
+        password_policy: PasswordPolicy {
+            min_chars: 0,
+            min_score: 0,
+            breach_list: None,
+        },
+        max_failed_attempts: 3,
+        pbkdf2_rounds: 1,
+        shards: 4,
+    })
+}
+
+#[rustfmt::skip]
+const PIECES: &[&str] = &[
+    "a", "Z", "0", "9", " ", "\t", "\n", "\0", "é", "e\u{301}", "न", "म", "स्", "ते", "🦀",
+    "👩\u{200d}💻", "\u{202e}", "ß", "İ", "ﬃ", "p@ss", "qwerty", "1234",
+];
+
+// Strings mixing ASCII, combining marks, RTL text, emoji ZWJ sequences and control characters
+fn unicode_string() -> impl Strategy<Value = String> {
+    prop_oneof![
+        any::<String>(),
+        "\\PC{0,40}",
+        prop::collection::vec(prop::sample::select(PIECES.to_vec()), 0..20)
+            .prop_map(|parts| parts.concat()),
+    ]
+}
+
+proptest! {
+    // --- CREDENTIALS ---
+    #[test]
+    fn authenticate_never_panics(username in unicode_string(), password in unicode_string()) {
+        let service = permissive_service();
+        let result = auth_service::authenticate(&service, &creds(&username, &password));
+        prop_assert_eq!(result, Err(AuthError::InvalidCredentials));
+    }
+
+    #[test]
+    fn same_credentials_same_outcome(username in unicode_string(), password in unicode_string(), other in unicode_string()) {
+        let service = permissive_service();
+        let registered = service.register(&creds(&username, &password));
+        prop_assert!(registered.is_ok());
+        prop_assert_eq!(service.register(&creds(&username, &password)), Err(AuthError::UserExists));
+
+        // The right password works every time and resets the failure counter
+        for _ in 0..3 {
+            let token = service.login(&creds(&username, &password));
+            prop_assert!(token.is_ok());
+            prop_assert_eq!(service.validate(&token.unwrap()), Some(username.clone()));
+        }
+        // A different password fails the same way every time until the account locks
+        if other != password {
+            for _ in 0..3 {
+                prop_assert_eq!(service.login(&creds(&username, &other)), Err(AuthError::InvalidCredentials));
+            }
+            prop_assert_eq!(service.login(&creds(&username, &password)), Err(AuthError::Locked));
+        }
+    }
+
+    #[test]
+    fn unknown_user_is_invalid_credentials(username in unicode_string(), password in unicode_string()) {
+        let service = permissive_service();
+        prop_assert_eq!(service.login(&creds(&username, &password)), Err(AuthError::InvalidCredentials));
+        prop_assert_eq!(service.validate(&password), None);
+        prop_assert!(!service.logout(&password));
+        prop_assert_eq!(service.revoke(&username), 0);
+    }
+
+    #[test]
+    fn change_password_revokes_sessions(username in unicode_string(), old in unicode_string(), new in unicode_string()) {
+        let service = permissive_service();
+        service.register(&creds(&username, &old)).unwrap();
+        let token = service.login(&creds(&username, &old)).unwrap();
+        prop_assert!(service.change_password(&creds(&username, &old), &new).is_ok());
+        prop_assert_eq!(service.validate(&token), None);
+        prop_assert!(service.login(&creds(&username, &new)).is_ok());
+    }
+
+    // --- PASSWORD STRENGTH ---
+    #[test]
+    fn strength_is_deterministic_and_covers_password(pw in unicode_string(), input in unicode_string()) {
+        let first = password::estimate(&pw, &[&input]);
+        let second = password::estimate(&pw, &[&input]);
+        prop_assert_eq!(&first, &second);
+        prop_assert!(first.score <= 4);
+        prop_assert!(first.guesses >= 1.0);
+
+        // The chosen matches tile the (possibly truncated) password without gaps or overlaps
+        let mut position = 0;
+        for m in &first.sequence {
+            prop_assert_eq!(m.start, position);
+            prop_assert!(m.end > m.start);
+            position = m.end;
+        }
+        prop_assert_eq!(position, pw.chars().count().min(128));
+    }
+
+    #[test]
+    fn policy_check_never_panics(pw in unicode_string(), username in unicode_string()) {
+        let _ = PasswordPolicy::default().check(&pw, &[&username]);
+    }
+
+    #[test]
+    fn breach_range_parsing_never_panics(range in any::<String>(), suffix in any::<String>()) {
+        let _ = password::breach::count_in_range(&range, &suffix);
+    }
+
+    // --- WEBAUTHN ---
+    #[test]
+    fn attestation_parsing_never_panics(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = AttestationObject::parse(&bytes);
+        let _ = AuthenticatorData::parse(&bytes);
+    }
+
+    #[test]
+    fn registration_rejects_garbage(client_data in prop::collection::vec(any::<u8>(), 0..256), attestation in prop::collection::vec(any::<u8>(), 0..256)) {
+        let rp = RelyingParty::new("example.com", "Example", "https://example.com");
+        let mut store = CredentialStore::new();
+        let challenge = rp.start_registration("admin", b"handle", &store).unwrap();
+        let response = RegistrationResponse { client_data_json: client_data, attestation_object: attestation };
+        prop_assert!(rp.finish_registration(&challenge, &response, &mut store).is_err());
+        prop_assert!(store.is_empty());
+    }
+
+    #[test]
+    fn assertion_rejects_garbage(id in prop::collection::vec(any::<u8>(), 0..64), data in prop::collection::vec(any::<u8>(), 0..128), sig in prop::collection::vec(any::<u8>(), 0..80)) {
+        let rp = RelyingParty::new("example.com", "Example", "https://example.com");
+        let mut store = CredentialStore::new();
+        let mut challenge = rp.start_authentication("admin", &store).unwrap();
+        challenge.allow_credentials.push(id.clone());
+        let response = AssertionResponse {
+            credential_id: id,
+            client_data_json: data.clone(),
+            authenticator_data: data,
+            signature: sig,
+            user_handle: None,
+        };
+        prop_assert!(rp.finish_authentication(&challenge, &response, &mut store).is_err());
+    }
+
+    // --- RATE LIMITING ---
+    #[test]
+    fn rate_limiter_never_panics(key in unicode_string(), capacity in any::<u32>(), refill in any::<f64>(), window_ms in any::<u64>(), times in prop::collection::vec(any::<u64>(), 1..20)) {
+        let mut bucket = RateLimiter::new(Algorithm::TokenBucket { capacity, refill_per_sec: refill }, MemoryBackend::new());
+        let mut log = RateLimiter::new(Algorithm::SlidingWindowLog { limit: capacity % 50, window: Duration::from_millis(window_ms) }, MemoryBackend::new());
+        for now in times {
+            let decision = bucket.check_at(&key, now);
+            prop_assert!(decision.remaining <= decision.limit);
+            let decision = log.check_at(&key, now);
+            prop_assert!(decision.remaining <= decision.limit);
+            prop_assert_eq!(decision.allowed, decision.retry_after.is_none());
+        }
+    }
+
+    #[test]
+    fn file_backend_roundtrips_any_key(key in unicode_string(), hits in prop::collection::vec(any::<u64>(), 0..5)) {
+        let path = std::env::temp_dir().join(format!("auth_service_prop_{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let state = State::Log { hits_ms: hits.into_iter().collect() };
+        let mut backend = FileBackend::open(&path).unwrap();
+        backend.store(&key, state.clone());
+        prop_assert_eq!(backend.last_error(), None);
+        let mut reopened = FileBackend::open(&path).unwrap();
+        prop_assert_eq!(reopened.load(&key), Some(state));
+        let _ = std::fs::remove_file(&path);
+    }
+}
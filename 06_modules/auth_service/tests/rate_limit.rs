@@ -0,0 +1,383 @@
+// The rate limiter allows exactly as many requests as its algorithm says, whatever the window
+// or clock values, and never panics on them.
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use auth_service::rate_limit::{
+    Algorithm, Backend, Decision, FileBackend, MemoryBackend, RateLimiter, State, SyncRateLimiter,
+};
+use auth_service::service::{AuthError, ServiceConfig};
+use auth_service::{AuthService, Credentials, password::PasswordPolicy};
+
+fn bucket(capacity: u32, refill_per_sec: f64) -> RateLimiter<MemoryBackend> {
+    RateLimiter::new(
+        Algorithm::TokenBucket {
+            capacity,
+            refill_per_sec,
+        },
+        MemoryBackend::new(),
+    )
+}
+
+fn log(limit: u32, window_ms: u64) -> Algorithm {
+    Algorithm::SlidingWindowLog {
+        limit,
+        window: Duration::from_millis(window_ms),
+    }
+}
+
+// A state file of its own for each test, removed when the test is done
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> TempFile {
+        let path = std::env::temp_dir().join(format!(
+            "auth_service_rate_limit_{name}_{}.txt",
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        TempFile(path)
+    }
+
+    fn lines(&self) -> usize {
+        fs::read_to_string(&self.0).unwrap().lines().count()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// --- ALGORITHMS ---
+#[test]
+fn token_bucket_allows_bursts_then_refills() {
+    let mut limiter = bucket(3, 2.0); // One token every 500 ms
+    for remaining in [2, 1, 0] {
+        let decision = limiter.check_at("ip", 1_000);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, remaining);
+    }
+    let rejected = limiter.check_at("ip", 1_000);
+    assert_eq!(
+        rejected,
+        Decision {
+            allowed: false,
+            limit: 3,
+            remaining: 0,
+            retry_after: Some(Duration::from_millis(500)),
+            reset_after: Duration::from_millis(1_500),
+        }
+    );
+    assert_eq!(
+        rejected.headers(),
+        [
+            ("RateLimit-Limit", "3".to_string()),
+            ("RateLimit-Remaining", "0".to_string()),
+            ("RateLimit-Reset", "2".to_string()), // Rounded up to whole seconds
+            ("Retry-After", "1".to_string()),
+        ]
+    );
+    assert!(limiter.check_at("other ip", 1_000).allowed); // Keys are independent
+    assert!(!limiter.check_at("ip", 1_499).allowed);
+    assert!(limiter.check_at("ip", 1_500).allowed);
+    // A clock that jumps back never refills the bucket
+    assert!(!limiter.check_at("ip", 0).allowed);
+}
+
+#[test]
+fn sliding_window_counts_requests_in_the_window() {
+    let mut limiter = RateLimiter::new(log(2, 1_000), MemoryBackend::new());
+    assert!(limiter.check_at("user", 0).allowed);
+    assert!(limiter.check_at("user", 400).allowed);
+    let rejected = limiter.check_at("user", 900);
+    assert!(!rejected.allowed);
+    assert_eq!(rejected.retry_after, Some(Duration::from_millis(100)));
+    assert_eq!(rejected.reset_after, Duration::from_millis(500));
+    // Rejected requests are not logged, so the first slot frees up at 1000 ms
+    assert!(limiter.check_at("user", 1_000).allowed);
+    assert!(!limiter.check_at("user", 1_399).allowed);
+    assert!(limiter.check_at("user", 1_400).allowed);
+
+    limiter.reset("user");
+    assert_eq!(limiter.check_at("user", 1_400).remaining, 1);
+}
+
+// --- EVICTION ---
+#[test]
+fn sweeps_drop_idle_keys_only() {
+    let mut limiter = bucket(2, 1.0);
+    limiter.check_at("full again", 0);
+    limiter.check_at("busy", 0);
+    limiter.check_at("busy", 0);
+    assert_eq!(limiter.backend().len(), 2);
+    // After 1 s "full again" has its 2 tokens back; "busy" has only 1
+    assert_eq!(limiter.sweep_at(1_000), 1);
+    assert_eq!(limiter.backend().len(), 1);
+    assert!(limiter.check_at("busy", 1_000).allowed);
+    assert!(!limiter.check_at("busy", 1_000).allowed);
+
+    let mut limiter = RateLimiter::new(log(1, 100), MemoryBackend::new());
+    limiter.check_at("old", 0);
+    limiter.check_at("new", 50);
+    assert_eq!(limiter.sweep_at(120), 1);
+    assert!(!limiter.check_at("new", 120).allowed);
+}
+
+#[test]
+fn idle_keys_do_not_pile_up() {
+    // Every key is seen once, as with requests from many different IPs
+    let mut limiter = RateLimiter::new(log(5, 10), MemoryBackend::new());
+    for i in 0..10_000u64 {
+        assert!(limiter.check_at(&format!("ip {i}"), i).allowed);
+    }
+    assert!(
+        limiter.backend().len() < 2_000,
+        "{}",
+        limiter.backend().len()
+    );
+
+    // A state that is no different from a new key's is never stored at all
+    let mut limiter = RateLimiter::new(log(0, 1_000), MemoryBackend::new());
+    assert!(!limiter.check_at("key", 0).allowed);
+    assert!(limiter.backend().is_empty());
+}
+
+// --- FILE BACKEND ---
+#[test]
+fn file_backend_appends_one_line_per_change() {
+    let file = TempFile::new("appends");
+    let mut limiter = RateLimiter::new(log(10, 60_000), FileBackend::open(&file.0).unwrap());
+    limiter.check_at("a", 0);
+    limiter.check_at("b", 0);
+    limiter.check_at("a", 1);
+    limiter.reset("b");
+    assert_eq!(limiter.backend().last_error(), None);
+    assert_eq!(file.lines(), 4); // Not a rewrite of every key per request
+
+    let mut reopened = FileBackend::open(&file.0).unwrap();
+    assert_eq!(reopened.len(), 1);
+    assert_eq!(
+        reopened.load("a"),
+        Some(State::Log {
+            hits_ms: [0, 1].into()
+        })
+    );
+    assert_eq!(reopened.load("b"), None);
+
+    // A crash halfway through an append leaves a cut-off last line, which is ignored
+    let mut contents = fs::read_to_string(&file.0).unwrap();
+    contents.push_str("61 log 12,");
+    fs::write(&file.0, contents).unwrap();
+    assert_eq!(FileBackend::open(&file.0).unwrap().len(), 1);
+    fs::write(&file.0, "61 log 12,\n").unwrap();
+    assert!(FileBackend::open(&file.0).is_err());
+}
+
+#[test]
+fn file_backend_compacts_stale_lines() {
+    let file = TempFile::new("compacts");
+    let mut limiter = RateLimiter::new(
+        Algorithm::TokenBucket {
+            capacity: 10_000,
+            refill_per_sec: 0.0,
+        },
+        FileBackend::open(&file.0).unwrap(),
+    );
+    for now in 0..5_000 {
+        limiter.check_at("a", now);
+        limiter.check_at("b", now);
+    }
+    assert!(file.lines() <= 1_100, "{}", file.lines());
+    let mut reopened = FileBackend::open(&file.0).unwrap();
+    for key in ["a", "b"] {
+        assert_eq!(
+            reopened.load(key),
+            Some(State::Bucket {
+                tokens: 5_000.0,
+                last_ms: 4_999
+            })
+        );
+    }
+
+    // Sweeping rewrites the file without the idle keys
+    let mut limiter = RateLimiter::new(log(5, 100), FileBackend::open(&file.0).unwrap());
+    limiter.check_at("a", 0);
+    // "a" has left the window and "b" is a bucket, which this limiter would start afresh anyway
+    assert_eq!(limiter.sweep_at(1_000), 2);
+    assert_eq!(file.lines(), 0);
+}
+
+// --- SHARED LIMITER ---
+#[test]
+fn shared_limiter_allows_exactly_the_quota_across_threads() {
+    const THREADS: usize = 8;
+    let algorithm = Algorithm::TokenBucket {
+        capacity: 100,
+        refill_per_sec: 0.0,
+    };
+    let limiter = SyncRateLimiter::new(algorithm, 4); // Fewer shards than threads
+    // Every thread checks the shared key and a key of its own 50 times, all at the same instant
+    let allowed: Vec<(usize, usize)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let limiter = &limiter;
+                scope.spawn(move || {
+                    let own = format!("ip{t}");
+                    (0..50).fold((0, 0), |(shared, mine), _| {
+                        let shared =
+                            shared + usize::from(limiter.check_at("shared", 1_000).allowed);
+                        (
+                            shared,
+                            mine + usize::from(limiter.check_at(&own, 1_000).allowed),
+                        )
+                    })
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(
+        allowed.iter().map(|&(shared, _)| shared).sum::<usize>(),
+        100
+    );
+    assert!(allowed.iter().all(|&(_, mine)| mine == 50));
+    assert_eq!(limiter.len(), THREADS + 1);
+    let decision = limiter.check_at("shared", 1_000);
+    assert!(!decision.allowed && decision.remaining == 0);
+    limiter.reset("shared");
+    assert!(limiter.check_at("shared", 1_000).allowed);
+}
+
+#[test]
+fn shared_limiter_decides_like_the_single_threaded_one() {
+    let shared = SyncRateLimiter::new(log(3, 1_000), 2);
+    let mut single = RateLimiter::new(log(3, 1_000), MemoryBackend::new());
+    for (i, now) in [0, 10, 20, 30, 500, 1_005, 1_015, 2_100, 5_000]
+        .into_iter()
+        .enumerate()
+    {
+        let key = ["a", "b"][i % 2];
+        assert_eq!(
+            shared.check_at(key, now),
+            single.check_at(key, now),
+            "at {now}"
+        );
+    }
+    assert_eq!(shared.len(), single.backend().len());
+    assert_eq!(shared.sweep_at(10_000), single.sweep_at(10_000));
+    assert!(shared.is_empty());
+}
+
+// --- AUTH ENDPOINTS ---
+#[test]
+fn registration_and_password_reset_are_rate_limited() {
+    let service = AuthService::new(ServiceConfig {
+        password_policy: PasswordPolicy {
+            min_chars: 0,
+            min_score: 0,
+            breach_list: None,
+        },
+        max_failed_attempts: 3,
+        pbkdf2_rounds: 1,
+        shards: 4,
+    });
+    let limiter = SyncRateLimiter::new(log(2, 60_000), 4);
+    let creds = |username: &str| Credentials {
+        username: username.to_string(),
+        password: "correct horse".to_string(),
+    };
+
+    for username in ["ana", "bo"] {
+        let (decision, result) =
+            auth_service::register_rate_limited(&service, &creds(username), &limiter, "10.0.0.1");
+        assert!(decision.allowed);
+        assert_eq!(result, Ok(()));
+    }
+    let (decision, result) =
+        auth_service::register_rate_limited(&service, &creds("cy"), &limiter, "10.0.0.1");
+    assert!(!decision.allowed && decision.retry_after.is_some());
+    assert_eq!(result, Err(AuthError::RateLimited));
+    assert_eq!(service.user_count(), 2);
+    // Other clients, and the other endpoints, have quotas of their own
+    let (_, result) =
+        auth_service::register_rate_limited(&service, &creds("cy"), &limiter, "10.0.0.2");
+    assert_eq!(result, Ok(()));
+
+    for _ in 0..2 {
+        let mut sent = false;
+        let (_, result) = auth_service::request_password_reset_rate_limited(
+            &service,
+            "ana",
+            |_| sent = true,
+            &limiter,
+            "10.0.0.1",
+        );
+        assert_eq!(result, Ok(()));
+        assert!(sent);
+    }
+    let (decision, result) = auth_service::request_password_reset_rate_limited(
+        &service,
+        "ana",
+        |_| panic!("token sent over the limit"),
+        &limiter,
+        "10.0.0.1",
+    );
+    assert!(!decision.allowed);
+    assert_eq!(result, Err(AuthError::RateLimited));
+}
+
+#[test]
+fn endpoints_share_one_limiter_between_threads() {
+    let service = AuthService::new(ServiceConfig {
+        pbkdf2_rounds: 1,
+        ..ServiceConfig::default()
+    });
+    let limiter = SyncRateLimiter::new(log(5, 60_000), 4);
+    // 8 threads register 4 users each from the same IP; only 5 registrations get through
+    let registered: usize = thread::scope(|scope| {
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let (service, limiter) = (&service, &limiter);
+                scope.spawn(move || {
+                    (0..4)
+                        .filter(|i| {
+                            let creds = Credentials {
+                                username: format!("user{t}_{i}"),
+                                password: "correct horse battery staple".to_string(),
+                            };
+                            let (decision, result) = auth_service::register_rate_limited(
+                                service, &creds, limiter, "10.0.0.1",
+                            );
+                            assert_eq!(decision.allowed, result.is_ok());
+                            result.is_ok()
+                        })
+                        .count()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    assert_eq!(registered, 5);
+    assert_eq!(service.user_count(), 5);
+}
+
+// --- REGRESSIONS ---
+// A huge window or a timestamp near u64::MAX overflowed `hit + window` in the sliding window
+// log and panicked in debug builds.
+#[test]
+fn regression_sliding_window_overflow() {
+    let mut log = RateLimiter::new(
+        Algorithm::SlidingWindowLog {
+            limit: 1,
+            window: Duration::MAX,
+        },
+        MemoryBackend::new(),
+    );
+    assert!(log.check_at("key", u64::MAX).allowed);
+    assert!(!log.check_at("key", u64::MAX).allowed);
+    assert!(!log.check_at("key", 0).allowed);
+}
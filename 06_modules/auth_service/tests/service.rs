@@ -0,0 +1,134 @@
+// AuthService keeps its promises about passwords and sessions: a reset token works exactly once,
+// and only while the password it was issued for is still the current one, and asking for one
+// answers the same for unknown users.
+use auth_service::password::PasswordPolicy;
+use auth_service::service::{AuthError, ServiceConfig};
+use auth_service::{AuthService, Credentials};
+
+fn creds(username: &str, password: &str) -> Credentials {
+    Credentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+}
+
+// Passwords only need 8 characters and hashing is cheap
+fn service() -> AuthService {
+    AuthService::new(ServiceConfig {
+        password_policy: PasswordPolicy {
+            min_chars: 8,
+            min_score: 0,
+            breach_list: None,
+        },
+        max_failed_attempts: 3,
+        pbkdf2_rounds: 1,
+        shards: 4,
+    })
+}
+
+// Requests a reset and returns the token the service delivered, if it delivered one
+fn reset_token(service: &AuthService, username: &str) -> Option<String> {
+    let mut delivered = None;
+    let result = service.request_password_reset(username, |token| {
+        delivered = Some(token.to_string());
+    });
+    assert_eq!(result, Ok(())); // The same answer whether the user exists or not
+    delivered
+}
+
+// --- PASSWORD CHANGE ---
+#[test]
+fn change_password_needs_the_current_password() {
+    let service = service();
+    service.register(&creds("ana", "old password")).unwrap();
+    let session = service.login(&creds("ana", "old password")).unwrap();
+    assert_eq!(
+        service.change_password(&creds("ana", "wrong password"), "new password"),
+        Err(AuthError::InvalidCredentials)
+    );
+    assert_eq!(service.failed_attempts("ana"), Some(1)); // Counts towards the lockout
+    assert_eq!(service.validate(&session).as_deref(), Some("ana"));
+    assert!(service.login(&creds("ana", "old password")).is_ok());
+
+    assert!(matches!(
+        service.change_password(&creds("ana", "old password"), "short"),
+        Err(AuthError::WeakPassword(_))
+    ));
+    service
+        .change_password(&creds("ana", "old password"), "new password")
+        .unwrap();
+    assert_eq!(service.validate(&session), None);
+    assert_eq!(
+        service.login(&creds("ana", "old password")),
+        Err(AuthError::InvalidCredentials)
+    );
+    assert!(service.login(&creds("ana", "new password")).is_ok());
+}
+
+// --- PASSWORD RESET ---
+#[test]
+fn reset_sets_a_new_password_once() {
+    let service = service();
+    service.register(&creds("ana", "old password")).unwrap();
+    let session = service.login(&creds("ana", "old password")).unwrap();
+    for _ in 0..3 {
+        let _ = service.login(&creds("ana", "guess"));
+    }
+    assert_eq!(
+        service.login(&creds("ana", "old password")),
+        Err(AuthError::Locked)
+    );
+
+    let token = reset_token(&service, "ana").unwrap();
+    // A rejected password leaves the token usable
+    assert!(matches!(
+        service.reset_password(&token, "short"),
+        Err(AuthError::WeakPassword(_))
+    ));
+    service.reset_password(&token, "new password").unwrap();
+    assert_eq!(
+        service.reset_password(&token, "newer password"),
+        Err(AuthError::InvalidCredentials)
+    );
+
+    // The reset unlocked the account and ended the old sessions
+    assert_eq!(service.failed_attempts("ana"), Some(0));
+    assert_eq!(service.validate(&session), None);
+    assert_eq!(
+        service.login(&creds("ana", "old password")),
+        Err(AuthError::InvalidCredentials)
+    );
+    assert!(service.login(&creds("ana", "new password")).is_ok());
+}
+
+#[test]
+fn reset_tokens_die_with_the_password() {
+    let service = service();
+    service.register(&creds("bo", "first password")).unwrap();
+    let first = reset_token(&service, "bo").unwrap();
+    let second = reset_token(&service, "bo").unwrap();
+    service
+        .change_password(&creds("bo", "first password"), "second password")
+        .unwrap();
+    assert_eq!(
+        service.reset_password(&first, "third password"),
+        Err(AuthError::InvalidCredentials)
+    );
+
+    // Using one token also ends the others of the same user
+    let third = reset_token(&service, "bo").unwrap();
+    let fourth = reset_token(&service, "bo").unwrap();
+    service.reset_password(&third, "third password").unwrap();
+    for token in [second, fourth] {
+        assert_eq!(
+            service.reset_password(&token, "fourth password"),
+            Err(AuthError::InvalidCredentials)
+        );
+    }
+
+    assert_eq!(reset_token(&service, "nobody"), None);
+    assert_eq!(
+        service.reset_password("not a token", "some password"),
+        Err(AuthError::InvalidCredentials)
+    );
+}
@@ -0,0 +1,154 @@
+// Many threads hammering one shared AuthService lose no update: failure counters add up, every
+// session a thread keeps is still there, no session outlives a revoke, and a login racing with a
+// password change is not counted as a failure.
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use auth_service::password::PasswordPolicy;
+use auth_service::service::{AuthError, ServiceConfig};
+use auth_service::{AuthService, Credentials};
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 50;
+const PASSWORD: &str = "correct horse battery staple";
+
+fn creds(username: &str, password: &str) -> Credentials {
+    Credentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+}
+
+// "shared" plus one user per thread, "user0" to "user7"
+fn service() -> AuthService {
+    let service = AuthService::new(ServiceConfig {
+        max_failed_attempts: u32::MAX, // These tests count failures, they do not want lockouts
+        pbkdf2_rounds: 1,              // Hashing speed is not what is being tested
+        shards: 4,                     // Fewer shards than threads, so threads do contend
+        ..ServiceConfig::default()
+    });
+    service.register(&creds("shared", PASSWORD)).unwrap();
+    for t in 0..THREADS {
+        service
+            .register(&creds(&format!("user{t}"), PASSWORD))
+            .unwrap();
+    }
+    service
+}
+
+#[test]
+fn failure_counters_lose_no_updates() {
+    // Every thread fails on the same account; the counter must end at exactly THREADS * ROUNDS
+    let service = service();
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|| {
+                for _ in 0..ROUNDS {
+                    let result = service.login(&creds("shared", "wrong password"));
+                    assert_eq!(result, Err(AuthError::InvalidCredentials));
+                }
+            });
+        }
+    });
+    assert_eq!(
+        service.failed_attempts("shared"),
+        Some((THREADS * ROUNDS) as u32)
+    );
+}
+
+#[test]
+fn sessions_survive_concurrent_login_and_logout() {
+    // Each thread logs its own user in and out; every session it keeps must still be there
+    let service = service();
+    let kept = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let (service, kept) = (&service, &kept);
+            scope.spawn(move || {
+                let username = format!("user{t}");
+                for round in 0..ROUNDS {
+                    let token = service.login(&creds(&username, PASSWORD)).unwrap();
+                    assert_eq!(service.validate(&token).as_deref(), Some(username.as_str()));
+                    if round % 2 == 0 {
+                        assert!(service.logout(&token), "session vanished before logout");
+                        assert!(!service.logout(&token), "session logged out twice");
+                    } else {
+                        kept.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(service.session_count(), kept.into_inner());
+}
+
+#[test]
+fn no_session_outlives_a_revoke() {
+    // Revokes race with logins on the same account; once a final revoke has run, no token from
+    // the race may still validate and the session table must be empty
+    let service = service();
+    let race_tokens = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let (service, race_tokens) = (&service, &race_tokens);
+            scope.spawn(move || {
+                for _ in 0..ROUNDS {
+                    if t % 4 == 0 {
+                        service.revoke("shared");
+                    } else if let Ok(token) = service.login(&creds("shared", PASSWORD)) {
+                        race_tokens.lock().unwrap().push(token);
+                    }
+                }
+            });
+        }
+    });
+    service.revoke("shared");
+    for token in race_tokens.into_inner().unwrap() {
+        assert_eq!(
+            service.validate(&token),
+            None,
+            "revoked session still valid"
+        );
+    }
+    assert_eq!(service.session_count(), 0);
+}
+
+#[test]
+fn password_changes_do_not_count_as_failures() {
+    // One thread keeps changing the password to itself (a new salt each time) while the others
+    // log in with it. A login that hashed with the old salt is turned away but is not a wrong
+    // password: with max_failed_attempts 1, counting it would lock everyone out.
+    let service = AuthService::new(ServiceConfig {
+        password_policy: PasswordPolicy {
+            min_chars: 0,
+            min_score: 0, // Changing a password to itself is fine here
+            breach_list: None,
+        },
+        max_failed_attempts: 1,
+        pbkdf2_rounds: 1_000, // Slow enough that changes land between the hash and the check
+        shards: 4,
+    });
+    service.register(&creds("shared", PASSWORD)).unwrap();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let service = &service;
+            scope.spawn(move || {
+                for _ in 0..ROUNDS {
+                    if t == 0 {
+                        let result = service.change_password(&creds("shared", PASSWORD), PASSWORD);
+                        assert_eq!(result, Ok(()));
+                    } else {
+                        let result = service.login(&creds("shared", PASSWORD));
+                        assert!(
+                            matches!(result, Ok(_) | Err(AuthError::InvalidCredentials)),
+                            "login failed with {result:?}"
+                        );
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(service.failed_attempts("shared"), Some(0));
+    assert!(service.login(&creds("shared", PASSWORD)).is_ok());
+}
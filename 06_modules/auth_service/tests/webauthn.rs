@@ -0,0 +1,333 @@
+// Recorded authenticator responses (fuzz/fixtures) verify against the relying party they were made
+// for, and every check of the ceremony rejects them once the one thing it looks at is changed.
+use auth_service::webauthn::attestation::AttestationObject;
+use auth_service::webauthn::{
+    AssertionResponse, AttestationFormat, AuthenticationChallenge, CredentialStore,
+    RegistrationChallenge, RegistrationResponse, RelyingParty, StoredCredential, WebAuthnError,
+};
+
+// All fixtures were recorded for rp id "example.com", origin "https://example.com" and a
+// challenge of 32 bytes of 0x42
+const CHALLENGE: [u8; 32] = [0x42; 32];
+const REGISTRATION_CLIENT_DATA: &[u8] =
+    include_bytes!("../fuzz/fixtures/registration_client_data.json");
+const NONE_ATTESTATION: &[u8] =
+    include_bytes!("../fuzz/fixtures/registration_none_attestation.cbor");
+const PACKED_ATTESTATION: &[u8] =
+    include_bytes!("../fuzz/fixtures/registration_packed_attestation.cbor");
+const ASSERTION_CLIENT_DATA: &[u8] = include_bytes!("../fuzz/fixtures/assertion_client_data.json");
+// Counter 1, user present and verified
+const UV_AUTHENTICATOR_DATA: &[u8] =
+    include_bytes!("../fuzz/fixtures/assertion_uv_authenticator_data.bin");
+const UV_SIGNATURE: &[u8] = include_bytes!("../fuzz/fixtures/assertion_uv_signature.der");
+// Counter 2, user present only
+const UP_ONLY_AUTHENTICATOR_DATA: &[u8] =
+    include_bytes!("../fuzz/fixtures/assertion_up_only_authenticator_data.bin");
+const UP_ONLY_SIGNATURE: &[u8] = include_bytes!("../fuzz/fixtures/assertion_up_only_signature.der");
+
+fn relying_party() -> RelyingParty {
+    RelyingParty::new("example.com", "Example", "https://example.com")
+}
+
+fn registration_challenge() -> RegistrationChallenge {
+    RegistrationChallenge {
+        challenge: CHALLENGE.to_vec(),
+        rp_id: "example.com".to_string(),
+        rp_name: "Example".to_string(),
+        user_handle: b"admin-handle".to_vec(),
+        username: "admin".to_string(),
+        user_verification_required: false,
+        exclude_credentials: Vec::new(),
+    }
+}
+
+fn registration(client_data_json: &[u8], attestation_object: &[u8]) -> RegistrationResponse {
+    RegistrationResponse {
+        client_data_json: client_data_json.to_vec(),
+        attestation_object: attestation_object.to_vec(),
+    }
+}
+
+// A store holding the credential from the packed registration fixture
+fn registered(rp: &RelyingParty) -> (CredentialStore, StoredCredential) {
+    let mut store = CredentialStore::new();
+    let credential = rp
+        .finish_registration(
+            &registration_challenge(),
+            &registration(REGISTRATION_CLIENT_DATA, PACKED_ATTESTATION),
+            &mut store,
+        )
+        .unwrap();
+    (store, credential)
+}
+
+fn authentication_challenge(credential: &StoredCredential) -> AuthenticationChallenge {
+    AuthenticationChallenge {
+        challenge: CHALLENGE.to_vec(),
+        rp_id: "example.com".to_string(),
+        username: credential.username.clone(),
+        allow_credentials: vec![credential.credential_id.clone()],
+        user_verification_required: false,
+    }
+}
+
+fn assertion(
+    credential: &StoredCredential,
+    authenticator_data: &[u8],
+    signature: &[u8],
+) -> AssertionResponse {
+    AssertionResponse {
+        credential_id: credential.credential_id.clone(),
+        client_data_json: ASSERTION_CLIENT_DATA.to_vec(),
+        authenticator_data: authenticator_data.to_vec(),
+        signature: signature.to_vec(),
+        user_handle: Some(b"admin-handle".to_vec()),
+    }
+}
+
+// Swaps one piece of recorded clientDataJSON for another
+fn edit(client_data_json: &[u8], from: &str, to: &str) -> Vec<u8> {
+    let json = std::str::from_utf8(client_data_json).unwrap();
+    assert!(json.contains(from), "{from} is not in {json}");
+    json.replace(from, to).into_bytes()
+}
+
+// --- REGISTRATION ---
+#[test]
+fn registers_none_and_packed_attestation() {
+    let rp = relying_party();
+    for (attestation, format) in [
+        (NONE_ATTESTATION, AttestationFormat::None),
+        (PACKED_ATTESTATION, AttestationFormat::PackedSelf),
+    ] {
+        let parsed = AttestationObject::parse(attestation).unwrap();
+        assert_eq!(parsed.format, format);
+
+        let mut store = CredentialStore::new();
+        let response = registration(REGISTRATION_CLIENT_DATA, attestation);
+        let credential = rp
+            .finish_registration(&registration_challenge(), &response, &mut store)
+            .unwrap();
+        assert_eq!(credential.credential_id, b"fuzz-credential-1");
+        assert_eq!(credential.username, "admin");
+        assert_eq!(credential.sign_count, 0);
+        assert!(credential.user_verified_at_registration);
+        assert!(store.get(b"fuzz-credential-1").is_some());
+
+        // The same authenticator cannot be registered twice
+        assert_eq!(
+            rp.finish_registration(&registration_challenge(), &response, &mut store)
+                .err(),
+            Some(WebAuthnError::DuplicateCredential)
+        );
+        assert_eq!(store.len(), 1);
+    }
+}
+
+#[test]
+fn rejects_tampered_registrations() {
+    let rp = relying_party();
+    let challenge = registration_challenge();
+    let mut wrong_challenge = registration_challenge();
+    wrong_challenge.challenge = vec![0x43; 32];
+    let other_origin = edit(
+        REGISTRATION_CLIENT_DATA,
+        "https://example.com",
+        "https://evil.example",
+    );
+    let get_type = edit(REGISTRATION_CLIENT_DATA, "webauthn.create", "webauthn.get");
+    // Passes the client data checks, but the attestation was signed over the original's hash
+    let cross_origin = edit(
+        REGISTRATION_CLIENT_DATA,
+        "\"crossOrigin\":false",
+        "\"crossOrigin\":true",
+    );
+    let other_rp = RelyingParty::new("other.com", "Other", "https://example.com");
+
+    let cases = [
+        (
+            &rp,
+            &wrong_challenge,
+            registration(REGISTRATION_CLIENT_DATA, PACKED_ATTESTATION),
+            WebAuthnError::ChallengeMismatch,
+        ),
+        (
+            &rp,
+            &challenge,
+            registration(&other_origin, PACKED_ATTESTATION),
+            WebAuthnError::OriginMismatch("https://evil.example".to_string()),
+        ),
+        (
+            &rp,
+            &challenge,
+            registration(&get_type, NONE_ATTESTATION),
+            WebAuthnError::WrongType("webauthn.get".to_string()),
+        ),
+        (
+            &other_rp,
+            &challenge,
+            registration(REGISTRATION_CLIENT_DATA, NONE_ATTESTATION),
+            WebAuthnError::RpIdMismatch,
+        ),
+        (
+            &rp,
+            &challenge,
+            registration(&cross_origin, PACKED_ATTESTATION),
+            WebAuthnError::InvalidSignature,
+        ),
+    ];
+    for (rp, challenge, response, expected) in cases {
+        let mut store = CredentialStore::new();
+        assert_eq!(
+            rp.finish_registration(challenge, &response, &mut store)
+                .err(),
+            Some(expected)
+        );
+        assert!(store.is_empty());
+    }
+}
+
+// --- AUTHENTICATION ---
+#[test]
+fn accepts_recorded_assertions() {
+    let rp = relying_party();
+    let (mut store, credential) = registered(&rp);
+    let challenge = authentication_challenge(&credential);
+
+    let result = rp
+        .finish_authentication(
+            &challenge,
+            &assertion(&credential, UV_AUTHENTICATOR_DATA, UV_SIGNATURE),
+            &mut store,
+        )
+        .unwrap();
+    assert_eq!(result.username, "admin");
+    assert_eq!(result.sign_count, 1);
+    assert!(result.flags.user_present && result.flags.user_verified);
+
+    let result = rp
+        .finish_authentication(
+            &challenge,
+            &assertion(&credential, UP_ONLY_AUTHENTICATOR_DATA, UP_ONLY_SIGNATURE),
+            &mut store,
+        )
+        .unwrap();
+    assert_eq!(result.sign_count, 2);
+    assert!(result.flags.user_present && !result.flags.user_verified);
+    assert_eq!(store.get(&credential.credential_id).unwrap().sign_count, 2);
+}
+
+#[test]
+fn rejects_counter_regression() {
+    let rp = relying_party();
+    let (mut store, credential) = registered(&rp);
+    let challenge = authentication_challenge(&credential);
+    let newer = assertion(&credential, UP_ONLY_AUTHENTICATOR_DATA, UP_ONLY_SIGNATURE);
+    let older = assertion(&credential, UV_AUTHENTICATOR_DATA, UV_SIGNATURE);
+
+    rp.finish_authentication(&challenge, &newer, &mut store)
+        .unwrap();
+    // An older counter, or the same one replayed, means another copy of the key is in use
+    for (response, received) in [(&older, 1), (&newer, 2)] {
+        assert_eq!(
+            rp.finish_authentication(&challenge, response, &mut store),
+            Err(WebAuthnError::CounterRegression {
+                stored: 2,
+                received
+            })
+        );
+    }
+    assert_eq!(store.get(&credential.credential_id).unwrap().sign_count, 2);
+}
+
+#[test]
+fn requires_user_verification_when_the_challenge_asked_for_it() {
+    let mut rp = relying_party();
+    let (mut store, credential) = registered(&rp); // The packed registration was verified
+    let strict = AuthenticationChallenge {
+        user_verification_required: true,
+        ..authentication_challenge(&credential)
+    };
+    let relaxed = authentication_challenge(&credential);
+
+    assert_eq!(
+        rp.finish_authentication(
+            &strict,
+            &assertion(&credential, UP_ONLY_AUTHENTICATOR_DATA, UP_ONLY_SIGNATURE),
+            &mut store,
+        ),
+        Err(WebAuthnError::UserNotVerified)
+    );
+    assert_eq!(store.get(&credential.credential_id).unwrap().sign_count, 0);
+    rp.finish_authentication(
+        &strict,
+        &assertion(&credential, UV_AUTHENTICATOR_DATA, UV_SIGNATURE),
+        &mut store,
+    )
+    .unwrap();
+
+    // Turning the setting on affects new challenges, not one already handed to the browser
+    rp.require_user_verification = true;
+    assert!(
+        rp.start_authentication("admin", &store)
+            .unwrap()
+            .user_verification_required
+    );
+    rp.finish_authentication(
+        &relaxed,
+        &assertion(&credential, UP_ONLY_AUTHENTICATOR_DATA, UP_ONLY_SIGNATURE),
+        &mut store,
+    )
+    .unwrap();
+    assert_eq!(store.get(&credential.credential_id).unwrap().sign_count, 2);
+}
+
+#[test]
+fn rejects_tampered_assertions() {
+    let rp = relying_party();
+    let (_, credential) = registered(&rp);
+    let challenge = authentication_challenge(&credential);
+    let mut wrong_challenge = authentication_challenge(&credential);
+    wrong_challenge.challenge = vec![0x43; 32];
+    let valid = assertion(&credential, UV_AUTHENTICATOR_DATA, UV_SIGNATURE);
+
+    let mut other_origin = valid.clone();
+    other_origin.client_data_json = edit(
+        ASSERTION_CLIENT_DATA,
+        "https://example.com",
+        "https://example.com.evil",
+    );
+    let mut other_rp_hash = valid.clone();
+    other_rp_hash.authenticator_data[0] ^= 1;
+    let mut other_counter = valid.clone();
+    other_counter.authenticator_data[36] = 9; // Signed over counter 1
+    let mut other_user = valid.clone();
+    other_user.user_handle = Some(b"someone-else".to_vec());
+    let mut not_offered = challenge.clone();
+    not_offered.allow_credentials.clear();
+
+    let cases = [
+        (
+            &wrong_challenge,
+            valid.clone(),
+            WebAuthnError::ChallengeMismatch,
+        ),
+        (
+            &challenge,
+            other_origin,
+            WebAuthnError::OriginMismatch("https://example.com.evil".to_string()),
+        ),
+        (&challenge, other_rp_hash, WebAuthnError::RpIdMismatch),
+        (&challenge, other_counter, WebAuthnError::InvalidSignature),
+        (&challenge, other_user, WebAuthnError::UserHandleMismatch),
+        (&not_offered, valid, WebAuthnError::CredentialNotAllowed),
+    ];
+    for (challenge, response, expected) in cases {
+        let (mut store, _) = registered(&rp);
+        assert_eq!(
+            rp.finish_authentication(challenge, &response, &mut store),
+            Err(expected)
+        );
+        assert_eq!(store.get(&credential.credential_id).unwrap().sign_count, 0);
+    }
+}